@@ -62,6 +62,9 @@ ensogl::read_args! {
     preferred_engine_version: Option<semver::Version>,
     project: Option<String>,
     project_manager: Option<String>,
+    /// Record the sessions with the Language Server, so they can be replayed in tests. See the
+    /// `transport::recording` module of the IDE.
+    record_session: bool,
     shaders_url: String,
     skip_min_version_check: bool,
     /// When profiling the application (e.g. with the `./run profile` command), this argument
@@ -9,6 +9,7 @@ pub mod client;
 pub mod connection;
 pub mod message;
 pub mod serialization;
+pub mod transfer;
 pub mod uuid;
 
 pub use client::Client;
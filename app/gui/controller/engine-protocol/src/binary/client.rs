@@ -4,6 +4,7 @@
 use crate::prelude::*;
 
 use crate::binary::message::ErrorPayload;
+use crate::binary::message::FileSegment;
 use crate::binary::message::FromServerPayloadOwned;
 use crate::binary::message::MessageFromServerOwned;
 use crate::binary::message::MessageToServerRef;
@@ -55,6 +56,21 @@ pub type Event = crate::common::event::Event<Notification>;
 
 
 
+// ===========================
+// === FileSegmentContents ===
+// ===========================
+
+/// The contents of a file segment, as returned by [`API::read_bytes`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileSegmentContents {
+    /// The bytes read. There may be less of them than requested if the segment exceeds the file.
+    pub bytes:    Vec<u8>,
+    /// The checksum of the bytes read, computed by the server.
+    pub checksum: Sha3_224,
+}
+
+
+
 // ===========
 // === API ===
 // ===========
@@ -80,6 +96,15 @@ pub trait API {
         bytes: &[u8],
     ) -> StaticBoxFuture<FallibleResult<Sha3_224>>;
 
+    /// Reads the specified segment of the file.
+    fn read_bytes(
+        &self,
+        segment: &FileSegment,
+    ) -> StaticBoxFuture<FallibleResult<FileSegmentContents>>;
+
+    /// Computes the checksum of the specified segment of the file.
+    fn checksum_bytes(&self, segment: &FileSegment) -> StaticBoxFuture<FallibleResult<Sha3_224>>;
+
     /// Asynchronous event stream with notification and errors.
     ///
     /// On a repeated call, previous stream is closed.
@@ -231,6 +256,39 @@ impl API for Client {
         })
     }
 
+    fn read_bytes(
+        &self,
+        segment: &FileSegment,
+    ) -> StaticBoxFuture<FallibleResult<FileSegmentContents>> {
+        info!(
+            "Reading {} bytes of {} at offset {}.",
+            segment.length, segment.path, segment.byte_offset
+        );
+        let payload = ToServerPayload::ReadBytes { segment };
+        self.make_request(payload, move |result| {
+            if let FromServerPayloadOwned::ReadBytesReply { checksum, bytes } = result {
+                Ok(FileSegmentContents { bytes, checksum: checksum.into() })
+            } else {
+                Err(RpcError::MismatchedResponseType.into())
+            }
+        })
+    }
+
+    fn checksum_bytes(&self, segment: &FileSegment) -> StaticBoxFuture<FallibleResult<Sha3_224>> {
+        info!(
+            "Computing checksum of {} bytes of {} at offset {}.",
+            segment.length, segment.path, segment.byte_offset
+        );
+        let payload = ToServerPayload::ChecksumBytes { segment };
+        self.make_request(payload, move |result| {
+            if let FromServerPayloadOwned::ChecksumBytesReply { checksum } = result {
+                Ok(checksum.into())
+            } else {
+                Err(RpcError::MismatchedResponseType.into())
+            }
+        })
+    }
+
     fn event_stream(&self) -> StaticBoxStream<Event> {
         self.handler.event_stream().boxed_local()
     }
@@ -246,6 +304,7 @@ impl API for Client {
 mod tests {
     use super::*;
 
+    use crate::binary::message::EnsoDigest;
     use crate::binary::message::MessageFromServer;
     use crate::binary::message::MessageToServerOwned;
     use crate::binary::message::ToServerPayloadOwned;
@@ -361,6 +420,38 @@ mod tests {
     }
 
 
+    #[test]
+    fn test_read_bytes() {
+        let root_id = Uuid::new_v4();
+        let path = Path::new(root_id, &["Main.enso"]);
+        let segment = FileSegment { path, byte_offset: 2, length: 3 };
+        let bytes = Vec::from("llo".as_bytes());
+        let checksum = Sha3_224::new(&bytes);
+        let digest = EnsoDigest { bytes: hex::decode(checksum.as_str()).unwrap() };
+        test_request(
+            |client| client.read_bytes(&segment),
+            FileSegmentContents { bytes: bytes.clone(), checksum },
+            ToServerPayloadOwned::ReadBytes { segment: segment.clone() },
+            FromServerPayloadOwned::ReadBytesReply { checksum: digest, bytes },
+        );
+    }
+
+    #[test]
+    fn test_checksum_bytes() {
+        let root_id = Uuid::new_v4();
+        let path = Path::new(root_id, &["Main.enso"]);
+        let segment = FileSegment { path, byte_offset: 0, length: 5 };
+        let checksum = Sha3_224::new(b"hello");
+        let digest = EnsoDigest { bytes: hex::decode(checksum.as_str()).unwrap() };
+        test_request(
+            |client| client.checksum_bytes(&segment),
+            checksum.clone(),
+            ToServerPayloadOwned::ChecksumBytes { segment: segment.clone() },
+            FromServerPayloadOwned::ChecksumBytesReply { checksum: digest },
+        );
+    }
+
+
 
     // =============================
     // === Testing Notifications ===
@@ -42,6 +42,14 @@ impl Connection {
         Ok(Connection { client_id, client })
     }
 
+    /// Restores the session after the connection has been re-established, by initializing the
+    /// protocol again with the same client ID.
+    pub async fn restore_session(&self) -> FallibleResult {
+        let init_response = self.client.init(self.client_id).await;
+        init_response.map_err(FailedToInitializeProtocol)?;
+        Ok(())
+    }
+
     /// Creates a connection which wraps a mock client.
     pub fn new_mock(client: MockClient) -> Connection {
         Connection { client: Box::new(client), client_id: default() }
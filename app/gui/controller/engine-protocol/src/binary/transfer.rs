@@ -0,0 +1,496 @@
+//! Chunked, resumable and checksum-verified file transfers over the binary protocol.
+//!
+//! The files are transferred in segments, and the checksum of every segment is compared with the
+//! one computed by the server. Once the transfer is finished, the digest of the whole transferred
+//! data is verified as well. An interrupted transfer can be resumed: the checksum of the already
+//! transferred beginning of the file is compared with the local one, so the transfer continues
+//! only if both sides still agree on the file contents.
+
+use crate::prelude::*;
+
+use crate::binary::client::FileSegmentContents;
+use crate::binary::client::RpcError;
+use crate::binary::message::ErrorPayload;
+use crate::binary::message::FileSegment;
+use crate::language_server::Path;
+use crate::types::Sha3_224;
+
+use sha3::Digest;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The default size of the transferred chunks, in bytes.
+pub const DEFAULT_CHUNK_SIZE: u64 = 1024 * 1024;
+
+
+
+// ==============
+// === Errors ===
+// ==============
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(
+    display = "Checksum of {} bytes of {} at offset {} is {}, while {} was expected.",
+    length, path, byte_offset, remote, local
+)]
+pub struct ChecksumMismatch {
+    pub path:        Path,
+    pub byte_offset: u64,
+    pub length:      u64,
+    pub remote:      Sha3_224,
+    pub local:       Sha3_224,
+}
+
+impl ChecksumMismatch {
+    /// Fail with `ChecksumMismatch` unless the checksums of the segment are equal.
+    fn check(segment: &FileSegment, remote: Sha3_224, local: Sha3_224) -> FallibleResult {
+        if remote == local {
+            Ok(())
+        } else {
+            let path = segment.path.clone();
+            let byte_offset = segment.byte_offset;
+            let length = segment.length;
+            Err(Self { path, byte_offset, length, remote, local }.into())
+        }
+    }
+}
+
+/// If the error signals that the read segment starts beyond the end of the file, return the
+/// length of the file.
+pub fn read_out_of_bounds(error: &failure::Error) -> Option<u64> {
+    match error.downcast_ref::<RpcError>() {
+        Some(RpcError::RemoteError(json_rpc::messages::Error {
+            data: Some(ErrorPayload::ReadOOB { file_length }),
+            ..
+        })) => Some(*file_length),
+        _ => None,
+    }
+}
+
+
+
+// ===================
+// === ChunkReader ===
+// ===================
+
+/// Reads a file chunk by chunk, verifying the checksum of every chunk and, once the whole file has
+/// been read, of the whole contents.
+#[derive(Clone, Debug)]
+pub struct ChunkReader {
+    path:       Path,
+    chunk_size: u64,
+    bytes_read: u64,
+    digest:     sha3::Sha3_224,
+    finished:   bool,
+}
+
+impl ChunkReader {
+    /// Start reading the file from the beginning.
+    pub fn new(path: Path, chunk_size: u64) -> Self {
+        let digest = sha3::Sha3_224::new();
+        Self { path, chunk_size, bytes_read: 0, digest, finished: false }
+    }
+
+    /// Resume reading the file, given its beginning read before the interruption.
+    ///
+    /// If the beginning no longer matches the file contents (e.g. because the file has been
+    /// modified in the meantime), the reading starts over. It can be checked with `bytes_read`.
+    pub async fn resume(
+        client: &dyn API,
+        path: Path,
+        chunk_size: u64,
+        read_before: &[u8],
+    ) -> FallibleResult<Self> {
+        let mut reader = Self::new(path, chunk_size);
+        if !read_before.is_empty() {
+            let length = read_before.len() as u64;
+            let segment = reader.segment(0, length);
+            let remote = match client.checksum_bytes(&segment).await {
+                Ok(checksum) => Some(checksum),
+                Err(error) if read_out_of_bounds(&error).is_some() => None,
+                Err(error) => return Err(error),
+            };
+            if remote == Some(Sha3_224::new(read_before)) {
+                reader.digest.input(read_before);
+                reader.bytes_read = length;
+            } else {
+                warn!("The file {} has changed, reading it from the beginning.", reader.path);
+            }
+        }
+        Ok(reader)
+    }
+
+    /// The number of bytes read so far.
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    /// Check if the whole file has been read.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Read the next chunk of the file. Returns `None` once the whole file has been read.
+    ///
+    /// If reading fails, e.g. because the connection has been interrupted, the call may be
+    /// repeated, as the reader state is updated only after the chunk has been read and verified.
+    pub async fn next_chunk(&mut self, client: &dyn API) -> FallibleResult<Option<Vec<u8>>> {
+        if self.finished {
+            return Ok(None);
+        }
+        let segment = self.segment(self.bytes_read, self.chunk_size);
+        let bytes = match client.read_bytes(&segment).await {
+            Ok(FileSegmentContents { bytes, checksum }) => {
+                let read_segment = self.segment(self.bytes_read, bytes.len() as u64);
+                ChecksumMismatch::check(&read_segment, checksum, Sha3_224::new(&bytes))?;
+                bytes
+            }
+            Err(error) if read_out_of_bounds(&error) == Some(self.bytes_read) => default(),
+            Err(error) => return Err(error),
+        };
+        let mut digest = self.digest.clone();
+        digest.input(&bytes);
+        let bytes_read = self.bytes_read + bytes.len() as u64;
+        let finished = (bytes.len() as u64) < self.chunk_size;
+        if finished && bytes_read > 0 {
+            let segment = self.segment(0, bytes_read);
+            let remote = client.checksum_bytes(&segment).await?;
+            ChecksumMismatch::check(&segment, remote, digest.clone().into())?;
+        }
+        self.digest = digest;
+        self.bytes_read = bytes_read;
+        self.finished = finished;
+        Ok(if bytes.is_empty() { None } else { Some(bytes) })
+    }
+
+    /// Return a stream of the subsequent chunks of the file. The stream ends after the first
+    /// error.
+    pub fn into_stream(self, client: &dyn API) -> impl Stream<Item = FallibleResult<Vec<u8>>> + '_ {
+        futures::stream::unfold(Some(self), move |reader| async move {
+            let mut reader = reader?;
+            match reader.next_chunk(client).await {
+                Ok(Some(chunk)) => Some((Ok(chunk), Some(reader))),
+                Ok(None) => None,
+                Err(error) => Some((Err(error), None)),
+            }
+        })
+    }
+
+    fn segment(&self, byte_offset: u64, length: u64) -> FileSegment {
+        FileSegment { path: self.path.clone(), byte_offset, length }
+    }
+}
+
+/// Read the file in chunks of `chunk_size` bytes. See [`ChunkReader`].
+pub fn read_chunks(
+    client: &dyn API,
+    path: Path,
+    chunk_size: u64,
+) -> impl Stream<Item = FallibleResult<Vec<u8>>> + '_ {
+    ChunkReader::new(path, chunk_size).into_stream(client)
+}
+
+
+
+// ===================
+// === ChunkWriter ===
+// ===================
+
+/// Writes a file chunk by chunk, verifying the checksum of every chunk and, once finished, of the
+/// whole written contents.
+#[derive(Clone, Debug)]
+pub struct ChunkWriter {
+    path:          Path,
+    bytes_written: u64,
+    digest:        sha3::Sha3_224,
+    /// Set after resuming, as the chunk written before the interruption might have been written
+    /// without us receiving the reply.
+    overwrite:     bool,
+}
+
+impl ChunkWriter {
+    /// Start writing the file from the beginning.
+    pub fn new(path: Path) -> Self {
+        let digest = sha3::Sha3_224::new();
+        Self { path, bytes_written: 0, digest, overwrite: false }
+    }
+
+    /// The number of bytes written so far.
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// Resume the interrupted writing. Fails with [`ChecksumMismatch`] if the server does not
+    /// hold the data written so far.
+    pub async fn resume(&mut self, client: &dyn API) -> FallibleResult {
+        if self.bytes_written > 0 {
+            let segment = self.written_segment();
+            let remote = client.checksum_bytes(&segment).await?;
+            ChecksumMismatch::check(&segment, remote, self.digest.clone().into())?;
+        }
+        self.overwrite = true;
+        Ok(())
+    }
+
+    /// Append the chunk to the file.
+    ///
+    /// If writing fails, e.g. because the connection has been interrupted, the writer should be
+    /// resumed before writing the chunk again.
+    pub async fn write_chunk(&mut self, client: &dyn API, chunk: &[u8]) -> FallibleResult {
+        let offset = self.bytes_written;
+        let remote = client.write_bytes(&self.path, offset, self.overwrite, chunk).await?;
+        let length = chunk.len() as u64;
+        let segment = FileSegment { path: self.path.clone(), byte_offset: offset, length };
+        ChecksumMismatch::check(&segment, remote, Sha3_224::new(chunk))?;
+        self.digest.input(chunk);
+        self.bytes_written += chunk.len() as u64;
+        self.overwrite = false;
+        Ok(())
+    }
+
+    /// Finish writing and verify the checksum of the whole written contents. If no bytes have
+    /// been written, an empty file is created. Returns the checksum of the file.
+    pub async fn finish(&self, client: &dyn API) -> FallibleResult<Sha3_224> {
+        let local: Sha3_224 = self.digest.clone().into();
+        if self.bytes_written == 0 {
+            client.write_file(&self.path, &[]).await?;
+        } else {
+            let segment = self.written_segment();
+            let remote = client.checksum_bytes(&segment).await?;
+            ChecksumMismatch::check(&segment, remote, local.clone())?;
+        }
+        Ok(local)
+    }
+
+    fn written_segment(&self) -> FileSegment {
+        FileSegment {
+            path:        self.path.clone(),
+            byte_offset: 0,
+            length:      self.bytes_written,
+        }
+    }
+}
+
+/// Write the chunks to the file. Returns the checksum of the written file. See [`ChunkWriter`].
+pub async fn write_chunks(
+    client: &dyn API,
+    path: Path,
+    chunks: impl Stream<Item = FallibleResult<Vec<u8>>>,
+) -> FallibleResult<Sha3_224> {
+    let mut writer = ChunkWriter::new(path);
+    let mut chunks = Box::pin(chunks);
+    while let Some(chunk) = chunks.next().await {
+        writer.write_chunk(client, &chunk?).await?;
+    }
+    writer.finish(client).await
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::binary::Event;
+
+    use futures::executor::block_on;
+    use futures::future::ready;
+
+    /// A client serving a single file kept in memory.
+    #[derive(Debug, Default)]
+    struct FileServer {
+        contents:       RefCell<Vec<u8>>,
+        /// The number of the subsequent requests which will be dropped as if the connection was
+        /// interrupted. The write requests are performed nevertheless.
+        interrupt_next: Cell<usize>,
+    }
+
+    impl FileServer {
+        fn new(contents: &[u8]) -> Self {
+            Self { contents: RefCell::new(contents.to_vec()), ..default() }
+        }
+
+        fn reply<T: 'static>(&self, result: T) -> StaticBoxFuture<FallibleResult<T>> {
+            if self.interrupt_next.get() > 0 {
+                self.interrupt_next.set(self.interrupt_next.get() - 1);
+                ready(Err(RpcError::ConnectionInterrupted.into())).boxed_local()
+            } else {
+                ready(Ok(result)).boxed_local()
+            }
+        }
+
+        fn segment(&self, segment: &FileSegment) -> FallibleResult<Vec<u8>> {
+            let contents = self.contents.borrow();
+            let file_length = contents.len() as u64;
+            if segment.byte_offset >= file_length && segment.length > 0 {
+                let data = Some(ErrorPayload::ReadOOB { file_length });
+                let error = json_rpc::messages::Error { code: 1009, message: default(), data };
+                Err(RpcError::RemoteError(error).into())
+            } else {
+                let end = (segment.byte_offset + segment.length).min(file_length);
+                Ok(contents[segment.byte_offset as usize..end as usize].to_vec())
+            }
+        }
+    }
+
+    impl API for FileServer {
+        fn init(&self, _client_id: Uuid) -> StaticBoxFuture<FallibleResult> {
+            self.reply(())
+        }
+
+        fn write_file(&self, _path: &Path, contents: &[u8]) -> StaticBoxFuture<FallibleResult> {
+            *self.contents.borrow_mut() = contents.to_vec();
+            self.reply(())
+        }
+
+        fn read_file(&self, _path: &Path) -> StaticBoxFuture<FallibleResult<Vec<u8>>> {
+            self.reply(self.contents.borrow().clone())
+        }
+
+        fn write_bytes(
+            &self,
+            _path: &Path,
+            byte_offset: u64,
+            overwrite: bool,
+            bytes: &[u8],
+        ) -> StaticBoxFuture<FallibleResult<Sha3_224>> {
+            let mut contents = self.contents.borrow_mut();
+            let offset = byte_offset as usize;
+            if offset < contents.len() && !overwrite {
+                return ready(Err(failure::err_msg("Cannot overwrite."))).boxed_local();
+            }
+            contents.resize(offset, 0);
+            contents.extend_from_slice(bytes);
+            drop(contents);
+            self.reply(Sha3_224::new(bytes))
+        }
+
+        fn read_bytes(
+            &self,
+            segment: &FileSegment,
+        ) -> StaticBoxFuture<FallibleResult<FileSegmentContents>> {
+            match self.segment(segment) {
+                Ok(bytes) => {
+                    let checksum = Sha3_224::new(&bytes);
+                    self.reply(FileSegmentContents { bytes, checksum })
+                }
+                Err(error) => ready(Err(error)).boxed_local(),
+            }
+        }
+
+        fn checksum_bytes(
+            &self,
+            segment: &FileSegment,
+        ) -> StaticBoxFuture<FallibleResult<Sha3_224>> {
+            match self.segment(segment) {
+                Ok(bytes) => self.reply(Sha3_224::new(&bytes)),
+                Err(error) => ready(Err(error)).boxed_local(),
+            }
+        }
+
+        fn event_stream(&self) -> StaticBoxStream<Event> {
+            futures::stream::empty().boxed_local()
+        }
+    }
+
+    fn path() -> Path {
+        Path::new(Uuid::default(), &["data", "file"])
+    }
+
+    fn collect_chunks(stream: impl Stream<Item = FallibleResult<Vec<u8>>>) -> Vec<Vec<u8>> {
+        block_on(stream.map(|chunk| chunk.unwrap()).collect())
+    }
+
+    #[test]
+    fn reading_chunks() {
+        let server = FileServer::new(b"Hello, world!");
+        let chunks = collect_chunks(read_chunks(&server, path(), 5));
+        assert_eq!(chunks, vec![b"Hello".to_vec(), b", wor".to_vec(), b"ld!".to_vec()]);
+
+        // The file length is a multiple of the chunk size.
+        let server = FileServer::new(b"0123456789");
+        let chunks = collect_chunks(read_chunks(&server, path(), 5));
+        assert_eq!(chunks, vec![b"01234".to_vec(), b"56789".to_vec()]);
+
+        let server = FileServer::new(b"");
+        assert!(collect_chunks(read_chunks(&server, path(), 5)).is_empty());
+    }
+
+    #[test]
+    fn resuming_reading() {
+        let server = FileServer::new(b"Hello, world!");
+        let mut reader = ChunkReader::new(path(), 5);
+        assert_eq!(block_on(reader.next_chunk(&server)).unwrap(), Some(b"Hello".to_vec()));
+        server.interrupt_next.set(1);
+        assert!(block_on(reader.next_chunk(&server)).is_err());
+        assert_eq!(reader.bytes_read(), 5);
+
+        let resumed = block_on(ChunkReader::resume(&server, path(), 5, b"Hello")).unwrap();
+        assert_eq!(resumed.bytes_read(), 5);
+        assert_eq!(collect_chunks(resumed.into_stream(&server)), vec![
+            b", wor".to_vec(),
+            b"ld!".to_vec()
+        ]);
+
+        // The file has changed since the interruption.
+        let resumed = block_on(ChunkReader::resume(&server, path(), 5, b"Howdy")).unwrap();
+        assert_eq!(resumed.bytes_read(), 0);
+        let chunks = collect_chunks(resumed.into_stream(&server));
+        assert_eq!(chunks.into_iter().flatten().collect_vec(), b"Hello, world!");
+    }
+
+    #[test]
+    fn writing_chunks() {
+        let server = FileServer::default();
+        let chunks = vec![Ok(b"Hello".to_vec()), Ok(b", world!".to_vec())];
+        let checksum = block_on(write_chunks(&server, path(), futures::stream::iter(chunks)));
+        assert_eq!(checksum.unwrap(), Sha3_224::new(b"Hello, world!"));
+        assert_eq!(*server.contents.borrow(), b"Hello, world!");
+
+        let server = FileServer::new(b"old");
+        let checksum = block_on(write_chunks(&server, path(), futures::stream::empty()));
+        assert_eq!(checksum.unwrap(), Sha3_224::new(b""));
+        assert!(server.contents.borrow().is_empty());
+    }
+
+    #[test]
+    fn resuming_writing() {
+        let server = FileServer::default();
+        let mut writer = ChunkWriter::new(path());
+        block_on(writer.write_chunk(&server, b"Hello")).unwrap();
+        // The chunk is written, but the reply is lost.
+        server.interrupt_next.set(1);
+        assert!(block_on(writer.write_chunk(&server, b", wor")).is_err());
+        assert_eq!(writer.bytes_written(), 5);
+
+        block_on(writer.resume(&server)).unwrap();
+        block_on(writer.write_chunk(&server, b", wor")).unwrap();
+        block_on(writer.write_chunk(&server, b"ld!")).unwrap();
+        assert_eq!(block_on(writer.finish(&server)).unwrap(), Sha3_224::new(b"Hello, world!"));
+        assert_eq!(*server.contents.borrow(), b"Hello, world!");
+
+        // The file has been modified by someone else during the interruption.
+        server.contents.borrow_mut()[0] = b'J';
+        let error = block_on(writer.resume(&server)).unwrap_err();
+        assert!(error.downcast_ref::<ChecksumMismatch>().is_some());
+    }
+
+    #[test]
+    fn detecting_corrupted_contents() {
+        let server = FileServer::default();
+        let mut writer = ChunkWriter::new(path());
+        block_on(writer.write_chunk(&server, b"Hello")).unwrap();
+        server.contents.borrow_mut()[1] = b'a';
+        let error = block_on(writer.finish(&server)).unwrap_err();
+        assert!(error.downcast_ref::<ChecksumMismatch>().is_some());
+    }
+}
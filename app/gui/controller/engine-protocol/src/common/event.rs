@@ -7,6 +7,10 @@
 pub enum Event<N> {
     /// The handler's transport has been closed.
     Closed,
+    /// The handler's transport has lost the connection and is trying to re-establish it.
+    Reconnecting,
+    /// The handler's transport has re-established the connection. The session should be restored.
+    Reconnected,
     /// An error has occurred.
     Error(failure::Error),
     /// A notification has been received.
@@ -5,9 +5,15 @@ use crate::prelude::*;
 use crate::common::error::NoSuchRequest;
 
 use futures::channel::oneshot;
+use json_rpc::reconnecting::NotConnected;
+use json_rpc::RpcError;
 
 
 
+/// Completes a request with the peer's reply, or fails it with an error that is not a reply from
+/// the peer.
+pub type Completer<Reply> = oneshot::Sender<FallibleResult<Reply>>;
+
 /// Stores active requests, i.e. the requests that were sent to the peer but are still awaiting
 /// their answer.
 /// `Id` identifies the request.
@@ -16,7 +22,7 @@ use futures::channel::oneshot;
 #[derivative(Default(bound = ""))]
 pub struct OngoingCalls<Id, Reply>
 where Id: Hash + Eq {
-    ongoing_calls: HashMap<Id, oneshot::Sender<Reply>>,
+    ongoing_calls: HashMap<Id, Completer<Reply>>,
 }
 
 impl<Id, Reply> OngoingCalls<Id, Reply>
@@ -29,7 +35,7 @@ where Id: Copy + Debug + Display + Hash + Eq + Send + Sync + 'static
 
     /// Removes the request from the storage and returns it (if present).
     /// The removed request can be used to either feed the reply or cancel the future result.
-    pub fn remove_request(&mut self, id: &Id) -> Option<oneshot::Sender<Reply>> {
+    pub fn remove_request(&mut self, id: &Id) -> Option<Completer<Reply>> {
         let ret = self.ongoing_calls.remove(id);
         if ret.is_some() {
             info!("Removing request {id}");
@@ -41,7 +47,7 @@ where Id: Copy + Debug + Display + Hash + Eq + Send + Sync + 'static
 
     /// Inserts a new request with given id and completer (i.e. the channel capable of accepting
     /// the peer's reply and completing the request).
-    pub fn insert_request(&mut self, id: Id, completer: oneshot::Sender<Reply>) {
+    pub fn insert_request(&mut self, id: Id, completer: Completer<Reply>) {
         info!("Storing a new request {id}");
         // There will be no previous request, since Ids are assumed to be unique.
         // Still, if there was, we can just safely drop it.
@@ -60,28 +66,51 @@ where Id: Copy + Debug + Display + Hash + Eq + Send + Sync + 'static
     where
         F: FnOnce(Reply) -> FallibleResult<R>,
     {
-        let (sender, receiver) = oneshot::channel::<Reply>();
+        let (sender, receiver) = oneshot::channel::<FallibleResult<Reply>>();
         let ret = receiver.map(move |result_or_cancel| {
-            let result = result_or_cancel?;
+            let result = result_or_cancel??;
             f(result)
         });
         self.insert_request(id, sender);
         ret
     }
 
+    /// Removes the request that could not be sent to the peer due to the `error`. Its future fails
+    /// with `RpcError::ConnectionInterrupted` if the transport is re-establishing the connection,
+    /// or signals cancellation otherwise.
+    pub fn abort_request(&mut self, id: &Id, error: &failure::Error) {
+        if let Some(request) = self.remove_request(id) {
+            if error.downcast_ref::<NotConnected>().is_some() {
+                let error: RpcError = RpcError::ConnectionInterrupted;
+                let _ = request.send(Err(error.into()));
+            }
+        }
+    }
+
     /// Removes all awaiting requests. Their futures will signal cancellation.
     pub fn clear(&mut self) {
         info!("Clearing all the requests.");
         self.ongoing_calls.clear()
     }
 
+    /// Removes all awaiting requests. Their futures will fail with
+    /// `RpcError::ConnectionInterrupted`, as the connection has been lost while awaiting replies.
+    pub fn interrupt(&mut self) {
+        info!("Interrupting all the requests.");
+        for (_, request) in self.ongoing_calls.drain() {
+            let error: RpcError = RpcError::ConnectionInterrupted;
+            // The other side might have already dropped the future, which is fine.
+            let _ = request.send(Err(error.into()));
+        }
+    }
+
     /// Passes peer's `reply` to complete request with given `id`.
     /// Fails, if such request was not present in the storage.
     pub fn complete_request(&mut self, id: Id, reply: Reply) -> FallibleResult {
         if let Some(request) = self.remove_request(&id) {
             // Explicitly ignore error. Can happen only if the other side already dropped future
             // with the call result. In such case no one needs to be notified and we are fine.
-            let _ = request.send(reply);
+            let _ = request.send(Ok(reply));
             Ok(())
         } else {
             Err(NoSuchRequest(id).into())
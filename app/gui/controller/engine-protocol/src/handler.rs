@@ -147,6 +147,12 @@ where
                 }
                 TransportEvent::Opened => {}
                 TransportEvent::Closed => self.emit_event(Event::Closed),
+                TransportEvent::Reconnecting => {
+                    // The replies will never come, as the peer will not remember the requests.
+                    self.ongoing_calls.interrupt();
+                    self.emit_event(Event::Reconnecting);
+                }
+                TransportEvent::Reconnected => self.emit_event(Event::Reconnected),
             }
         });
     }
@@ -164,10 +170,10 @@ where
             let ret = self.ongoing_calls.open_new_request(id, f);
             debug!("Sending message {message:?}");
             let sending_result = message.send(self.transport.as_mut());
-            if sending_result.is_err() {
+            if let Err(error) = sending_result {
                 // If we failed to send the request, it should be immediately removed.
                 // This will result in the returned future immediately yielding error.
-                self.ongoing_calls.remove_request(&id);
+                self.ongoing_calls.abort_request(&id, &error);
             }
             ret
         })
@@ -314,4 +320,44 @@ mod tests {
         assert!(matches!(event, Event::Closed), "Event was: {:?}", event);
         events.expect_pending();
     }
+
+    #[derive(Debug)]
+    struct MockRequest(i32);
+
+    impl IsRequest for MockRequest {
+        type Id = i32;
+
+        fn send(&self, transport: &mut dyn Transport) -> FallibleResult {
+            transport.send_text(&self.0.to_string())
+        }
+
+        fn id(&self) -> Self::Id {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_interrupted_request() {
+        let logger = Logger::new("RPC_Handler_Test");
+        let mut transport = MockTransport::new();
+        let processor = |msg| panic!("Must never be called in this test, but got {:?}!", msg);
+        let handler = Handler::<i32, (), ()>::new(transport.clone_ref(), logger, processor);
+        let mut runner = handler.runner().boxed_local();
+        let mut events = handler.event_stream().boxed_local();
+        let mut request = handler.make_request(&MockRequest(1), Ok).boxed_local();
+        request.expect_pending();
+        transport.mock_connection_interrupted();
+        runner.expect_pending();
+
+        let error = request.expect_err();
+        let error = error.downcast_ref::<json_rpc::RpcError>();
+        assert!(matches!(error, Some(json_rpc::RpcError::ConnectionInterrupted)));
+        let event = events.expect_next();
+        assert!(matches!(event, Event::Reconnecting), "Event was: {:?}", event);
+
+        transport.mock_connection_restored();
+        runner.expect_pending();
+        let event = events.expect_next();
+        assert!(matches!(event, Event::Reconnected), "Event was: {:?}", event);
+    }
 }
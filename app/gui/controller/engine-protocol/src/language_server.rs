@@ -29,12 +29,9 @@ use crate::prelude::*;
 use crate::types::Sha3_224;
 use crate::types::UTCDateTime;
 
-use json_rpc::api::Result;
-use json_rpc::make_rpc_methods;
-use json_rpc::Handler;
+use schemars::JsonSchema;
 use serde::Deserialize;
 use serde::Serialize;
-use std::future::Future;
 use uuid::Uuid;
 
 
@@ -43,162 +40,312 @@ use uuid::Uuid;
 // === API & Client ===
 // ====================
 
-make_rpc_methods! {
+#[json_rpc::rpc_api(title = "Enso Language Server")]
 /// An interface containing all the available file management operations.
-trait API {
+pub trait API {
     /// Initialize the connection used to send the textual protocol messages. This initialisation
     /// is important such that the client identifier can be correlated between the textual and data
     /// connections.
-    #[MethodInput=InitProtocolInput, rpc_name="session/initProtocolConnection"]
-    fn init_protocol_connection(&self, client_id: Uuid) -> response::InitProtocolConnection;
+    #[rpc(name = "session/initProtocolConnection")]
+    fn init_protocol_connection(&self, client_id: &Uuid) -> response::InitProtocolConnection;
 
     /// Copy a specified file system object to another location.
-    #[MethodInput=CopyFileInput, rpc_name="file/copy"]
-    fn copy_file(&self, from: Path, to: Path) -> ();
+    #[rpc(name = "file/copy")]
+    fn copy_file(&self, from: &Path, to: &Path);
 
     /// Delete the specified file system object.
-    #[MethodInput=DeleteFileInput, rpc_name="file/delete"]
-    fn delete_file(&self, path: Path) -> ();
+    #[rpc(name = "file/delete")]
+    fn delete_file(&self, path: &Path);
 
     /// Check if file system object exists.
-    #[MethodInput=FileExistsInput, rpc_name="file/exists"]
-    fn file_exists(&self, path: Path) -> response::FileExists;
+    #[rpc(name = "file/exists")]
+    fn file_exists(&self, path: &Path) -> response::FileExists;
 
     /// List all file-system objects in the specified path.
-    #[MethodInput=FileListInput, rpc_name="file/list"]
-    fn file_list(&self, path: Path) -> response::FileList;
+    #[rpc(name = "file/list")]
+    fn file_list(&self, path: &Path) -> response::FileList;
 
     /// Move file system object to another location.
-    #[MethodInput=MoveFileInput, rpc_name="file/move"]
-    fn move_file(&self, from: Path, to: Path) -> ();
+    #[rpc(name = "file/move")]
+    fn move_file(&self, from: &Path, to: &Path);
 
     /// Reads file's content as a String.
-    #[MethodInput=ReadFileInput, rpc_name="file/read"]
-    fn read_file(&self, path: Path) -> response::Read;
+    #[rpc(name = "file/read")]
+    fn read_file(&self, path: &Path) -> response::Read;
 
     /// Gets file system object's attributes information.
-    #[MethodInput=FileInfoInput, rpc_name="file/info"]
-    fn file_info(&self, path: Path) -> response::FileInfo;
+    #[rpc(name = "file/info")]
+    fn file_info(&self, path: &Path) -> response::FileInfo;
 
     /// Requests that the language server provide the checksum of the provided file.
-    #[MethodInput=FileChecksumInput, rpc_name="file/checksum"]
-    fn file_checksum(&self, path: Path) -> response::FileChecksum;
+    #[rpc(name = "file/checksum")]
+    fn file_checksum(&self, path: &Path) -> response::FileChecksum;
 
     /// Creates the specified file system object.
-    #[MethodInput=CreateInput, rpc_name="file/create"]
-    fn create_file(&self, object: FileSystemObject) -> ();
+    #[rpc(name = "file/create")]
+    fn create_file(&self, object: &FileSystemObject);
 
     /// Writes String contents to a file in the specified path.
-    #[MethodInput=FileWriteInput, rpc_name="file/write"]
-    fn write_file(&self, path: Path, contents: String) -> ();
+    #[rpc(name = "file/write")]
+    fn write_file(&self, path: &Path, contents: &String);
 
     /// Acquire capability permission.
-    #[MethodInput=AcquireCapabilityInput, rpc_name="capability/acquire"]
-    fn acquire_capability(&self, method: String, register_options: RegisterOptions) -> ();
+    #[rpc(name = "capability/acquire")]
+    fn acquire_capability(&self, method: &String, register_options: &RegisterOptions);
+
+    /// Release the capability previously acquired with `acquire_capability`.
+    #[rpc(name = "capability/release")]
+    fn release_capability(&self, registration: &CapabilityRegistration);
 
     /// Open the specified file. If no user has write lock on the opened file, the write lock
     /// capability is granted to the caller.
-    #[MethodInput=OpenTextFileInput, rpc_name="text/openFile"]
-    fn open_text_file(&self, path: Path) -> response::OpenTextFile;
+    #[rpc(name = "text/openFile")]
+    fn open_text_file(&self, path: &Path) -> response::OpenTextFile;
 
     /// Informs the language server that a client has closed the specified file.
-    #[MethodInput=CloseTextFileInput, rpc_name="text/closeFile"]
-    fn close_text_file(&self, path: Path) -> ();
+    #[rpc(name = "text/closeFile")]
+    fn close_text_file(&self, path: &Path);
 
     /// Save the specified file. It may fail if the user does not have permission to edit that file.
-    #[MethodInput=SaveTextFileInput, rpc_name="text/save"]
-    fn save_text_file(&self, path: Path, current_version:Sha3_224) -> ();
+    #[rpc(name = "text/save")]
+    fn save_text_file(&self, path: &Path, current_version: &Sha3_224);
 
     /// Apply edits to the specified text file. This operation may fail if the user does not
     /// have permission to edit the resources for which edits are sent. This failure may be partial,
     /// in that some edits are applied and others are not.
-    #[MethodInput=ApplyTextFileEditInput, rpc_name="text/applyEdit"]
-    fn apply_text_file_edit(&self, edit: FileEdit) -> ();
+    #[rpc(name = "text/applyEdit")]
+    fn apply_text_file_edit(&self, edit: &FileEdit);
+
+    /// Set the expression to a new value, e.g. change a literal. Unlike `apply_text_file_edit`,
+    /// the edit must not change the syntax tree, so the engine can swap the value without
+    /// recompiling the module.
+    #[rpc(name = "text/applyExpressionValue")]
+    fn apply_expression_value(
+        &self,
+        expression_id: &ExpressionId,
+        path: &Path,
+        edit: &TextEdit,
+        old_version: &Sha3_224,
+        new_version: &Sha3_224,
+    );
 
     /// Create a new execution context. Return capabilities executionContext/canModify and
-    /// executionContext/receivesUpdates containing freshly created ContextId
-    #[MethodInput=CreateExecutionContextInput, rpc_name="executionContext/create"]
-    fn create_execution_context(&self) -> response::CreateExecutionContext;
+    /// executionContext/receivesUpdates containing freshly created ContextId. The `context_id` may
+    /// be given to create the context with a specific id, e.g. to restore the context after the
+    /// connection has been re-established.
+    #[rpc(name = "executionContext/create")]
+    fn create_execution_context(
+        &self,
+        context_id: Option<&ContextId>,
+    ) -> response::CreateExecutionContext;
 
     /// Destroy an execution context and free its resources.
-    #[MethodInput=DestroyExecutionContextInput, rpc_name="executionContext/destroy"]
-    fn destroy_execution_context(&self, context_id: ContextId) -> ();
+    #[rpc(name = "executionContext/destroy")]
+    fn destroy_execution_context(&self, context_id: &ContextId);
 
     /// Move the execution context to a new location deeper down the stack.
-    #[MethodInput=PushToExecutionContextInput, rpc_name="executionContext/push"]
-    fn push_to_execution_context(&self, context_id: ContextId, stack_item: StackItem) -> ();
+    #[rpc(name = "executionContext/push")]
+    fn push_to_execution_context(&self, context_id: &ContextId, stack_item: &StackItem);
 
     /// Move the execution context up the stack.
-    #[MethodInput=PopFromExecutionContextInput, rpc_name="executionContext/pop"]
-    fn pop_from_execution_context(&self, context_id: ContextId) -> ();
+    #[rpc(name = "executionContext/pop")]
+    fn pop_from_execution_context(&self, context_id: &ContextId);
 
     /// Attach a visualisation, potentially preprocessed by some arbitrary Enso code, to a given
     /// node in the program.
-    #[MethodInput=AttachVisualisationInput, rpc_name="executionContext/attachVisualisation"]
-    fn attach_visualisation
-    ( &self
-    , visualisation_id     : Uuid
-    , expression_id        : Uuid
-    , visualisation_config : VisualisationConfiguration) -> ();
+    #[rpc(name = "executionContext/attachVisualisation")]
+    fn attach_visualisation(
+        &self,
+        visualisation_id: &Uuid,
+        expression_id: &Uuid,
+        visualisation_config: &VisualisationConfiguration,
+    );
 
     /// Detach a visualisation from the executing code.
-    #[MethodInput=DetachVisualisationInput, rpc_name="executionContext/detachVisualisation"]
-    fn detach_visualisation
-    (&self, context_id: Uuid, visualisation_id: Uuid, expression_id: Uuid) -> ();
+    #[rpc(name = "executionContext/detachVisualisation")]
+    fn detach_visualisation(
+        &self,
+        context_id: &Uuid,
+        visualisation_id: &Uuid,
+        expression_id: &Uuid,
+    );
 
     /// Modify the configuration for an existing visualisation.
-    #[MethodInput=ModifyVisualisationInput, rpc_name="executionContext/modifyVisualisation"]
-    fn modify_visualisation
-    (&self, visualisation_id: Uuid, visualisation_config: VisualisationConfiguration) -> ();
+    #[rpc(name = "executionContext/modifyVisualisation")]
+    fn modify_visualisation(
+        &self,
+        visualisation_id: &Uuid,
+        visualisation_config: &VisualisationConfiguration,
+    );
+
+    /// Execute the visualisation expression on the given node once. The result is sent like an
+    /// update of an attached visualisation.
+    #[rpc(name = "executionContext/executeExpression")]
+    fn execute_expression(
+        &self,
+        visualisation_id: &Uuid,
+        expression_id: &Uuid,
+        visualisation_config: &VisualisationConfiguration,
+    );
 
     /// Interrupt the program execution.
-    #[MethodInput=InterruptInput, rpc_name="executionContext/interrupt"]
-    fn interrupt(&self, context_id: ContextId) -> ();
+    #[rpc(name = "executionContext/interrupt")]
+    fn interrupt(&self, context_id: &ContextId);
 
     /// Restart the program execution.
-    #[MethodInput=RecomputeInput, rpc_name="executionContext/recompute"]
-    fn recompute(&self, context_id: ContextId, invalidated_expressions: InvalidatedExpressions) -> ();
+    #[rpc(name = "executionContext/recompute")]
+    fn recompute(&self, context_id: &ContextId, invalidated_expressions: &InvalidatedExpressions);
 
     /// Obtain the full suggestions database.
-    #[MethodInput=GetSuggestionsDatabaseInput, rpc_name="search/getSuggestionsDatabase"]
+    #[rpc(name = "search/getSuggestionsDatabase")]
     fn get_suggestions_database(&self) -> response::GetSuggestionDatabase;
 
     /// Receive the current version of the suggestions database.
-    #[MethodInput=GetSuggestionsDatabaseVersionInput,
-        rpc_name="search/getSuggestionsDatabaseVersion"]
+    #[rpc(name = "search/getSuggestionsDatabaseVersion")]
     fn get_suggestions_database_version(&self) -> response::GetSuggestionDatabaseVersion;
 
     /// Receive the autocomplete suggestion.
-    #[MethodInput=CompletionInput,rpc_name="search/completion"]
-    fn completion
-    ( &self
-    , file        : Path
-    , position    : Position
-    , self_type   : Option<String>
-    , return_type : Option<String>
-    , tags        : Option<Vec<SuggestionEntryType>>
+    #[rpc(name = "search/completion")]
+    fn completion(
+        &self,
+        file: &Path,
+        position: &Position,
+        self_type: &Option<String>,
+        return_type: &Option<String>,
+        tags: &Option<Vec<SuggestionEntryType>>,
     ) -> response::Completion;
 
     /// Get the list of component groups available in runtime.
-    #[MethodInput=GetComponentGroups, rpc_name="executionContext/getComponentGroups"]
-    fn get_component_groups(&self, context_id: ContextId) -> response::GetComponentGroups;
+    #[rpc(name = "executionContext/getComponentGroups")]
+    fn get_component_groups(&self, context_id: &ContextId) -> response::GetComponentGroups;
 
     /// Initialize VCS at the specified root.
-    #[MethodInput=VcsInitInput, rpc_name="vcs/init"]
-    fn init_vcs(&self, root: Path) -> ();
+    #[rpc(name = "vcs/init")]
+    fn init_vcs(&self, root: &Path);
 
     /// Save project to VCS at the specified root.
-    #[MethodInput=VcsWriteInput, rpc_name="vcs/save"]
-    fn save_vcs(&self, root: Path, name: Option<String>) -> response::SaveVcs;
+    #[rpc(name = "vcs/save")]
+    fn save_vcs(&self, root: &Path, name: &Option<String>) -> response::SaveVcs;
 
     /// Return a list of all project states that are saved to the VCS.
-    #[MethodInput=VcsListInput, rpc_name="vcs/list"]
-    fn list_vcs(&self, root: Path, limit: Option<usize>) -> response::ListVcs;
+    #[rpc(name = "vcs/list")]
+    fn list_vcs(&self, root: &Path, limit: &Option<usize>) -> response::ListVcs;
 
     /// Returns the current status of the changes made to the project.
-    #[MethodInput=VcsStatusInput, rpc_name="vcs/status"]
-    fn vcs_status(&self, root: Path) -> response::VcsStatus;
-}}
+    #[rpc(name = "vcs/status")]
+    fn vcs_status(&self, root: &Path) -> response::VcsStatus;
+
+    /// Restore the project to the state saved in the VCS under `commit_id`, or to the last save if
+    /// `commit_id` is not given. All unsaved changes are lost.
+    #[rpc(name = "vcs/restore")]
+    fn restore_vcs(&self, root: &Path, commit_id: &Option<String>) -> response::RestoreVcs;
+
+    /// Refactor the project name in the interpreter runtime. Normally sent by the Project
+    /// Manager when the project is renamed.
+    #[rpc(name = "refactoring/renameProject")]
+    fn rename_project(&self, namespace: &String, old_name: &String, new_name: &String);
+
+    /// List the editions available on the system. If `update` is set, the new editions are
+    /// downloaded from the repositories first.
+    #[rpc(name = "editions/listAvailable")]
+    fn list_available_editions(&self, update: &bool) -> response::ListAvailableEditions;
+
+    /// Resolve the engine version implied by the edition.
+    #[rpc(name = "editions/resolve")]
+    fn resolve_edition(&self, edition: &EditionReference) -> response::ResolveEdition;
+
+    /// Get the edition-related settings of the project.
+    #[rpc(name = "editions/getProjectSettings")]
+    fn get_project_edition_settings(&self) -> response::ProjectEditionSettings;
+
+    /// Set the parent edition of the project. The project needs to be re-opened for the change to
+    /// take effect.
+    #[rpc(name = "editions/setProjectParentEdition")]
+    fn set_project_parent_edition(
+        &self,
+        new_edition_name: &String,
+    ) -> response::EditionSettingsUpdate;
+
+    /// Set whether the local libraries should take precedence over the ones defined in the
+    /// project's edition.
+    #[rpc(name = "editions/setProjectLocalLibrariesPreference")]
+    fn set_project_local_libraries_preference(
+        &self,
+        prefer_local_libraries: &bool,
+    ) -> response::EditionSettingsUpdate;
+
+    /// List the libraries defined in the edition or any of its parents.
+    #[rpc(name = "editions/listDefinedLibraries")]
+    fn list_defined_libraries(&self, edition: &EditionReference) -> response::ListDefinedLibraries;
+
+    /// List the component groups defined in the edition.
+    #[rpc(name = "editions/listDefinedComponents")]
+    fn list_defined_components(
+        &self,
+        edition: &EditionReference,
+    ) -> response::ListDefinedComponents;
+
+    /// List the local libraries available in the system.
+    #[rpc(name = "library/listLocal")]
+    fn list_local_libraries(&self) -> response::ListLocalLibraries;
+
+    /// Create a new library in the local libraries directory. The library inherits the edition
+    /// settings of the current project.
+    #[rpc(name = "library/create")]
+    fn create_library(
+        &self,
+        namespace: &String,
+        name: &String,
+        authors: &Vec<Contact>,
+        maintainers: &Vec<Contact>,
+        license: &String,
+    );
+
+    /// Get the metadata of the given library version.
+    #[rpc(name = "library/getMetadata")]
+    fn get_library_metadata(
+        &self,
+        namespace: &String,
+        name: &String,
+        version: &LibraryVersion,
+    ) -> response::LibraryMetadata;
+
+    /// Set the metadata of the local library, used when publishing it. The fields which are not
+    /// given are removed from the metadata.
+    #[rpc(name = "library/setMetadata")]
+    fn set_library_metadata(
+        &self,
+        namespace: &String,
+        name: &String,
+        description: &Option<String>,
+        tag_line: &Option<String>,
+    );
+
+    /// Get the package config of the given library version.
+    #[rpc(name = "library/getPackage")]
+    fn get_library_package(
+        &self,
+        namespace: &String,
+        name: &String,
+        version: &LibraryVersion,
+    ) -> response::LibraryPackage;
+
+    /// Publish the local library to the library repository accepting uploads at `upload_url`.
+    #[rpc(name = "library/publish")]
+    fn publish_library(
+        &self,
+        namespace: &String,
+        name: &String,
+        auth_token: &String,
+        upload_url: &String,
+        bump_version_after_publish: &Option<bool>,
+    );
+
+    /// Download the library and all its dependencies, at versions resolved by the project's
+    /// edition, so it can be imported without further downloads.
+    #[rpc(name = "library/preinstall")]
+    fn preinstall_library(&self, namespace: &String, name: &String);
+}
 
 
 
@@ -63,6 +63,17 @@ impl Connection {
         Ok(content_roots.drain(index..=index).next().unwrap())
     }
 
+    /// Restores the session after the connection has been re-established, by initializing the
+    /// protocol again with the same client ID.
+    ///
+    /// The capabilities, opened files and execution contexts of the previous session are not
+    /// restored, it is the responsibility of their owners.
+    pub async fn restore_session(&self) -> FallibleResult {
+        let init_response = self.client.init_protocol_connection(&self.client_id).await;
+        init_response.map_err(|e| FailedToInitializeProtocol(e.into()))?;
+        Ok(())
+    }
+
     /// Creates a connection which wraps a mock client.
     pub fn new_mock(client: MockClient) -> Connection {
         Connection {
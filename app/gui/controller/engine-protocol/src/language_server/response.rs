@@ -5,13 +5,14 @@ use crate::prelude::*;
 
 use crate::types::Sha3_224;
 
+use schemars::JsonSchema;
 use serde::Deserialize;
 use serde::Serialize;
 
 
 
 /// Response of `init_protocol_connection` method.
-#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct InitProtocolConnection {
     /// List of Root IDs.
@@ -19,42 +20,42 @@ pub struct InitProtocolConnection {
 }
 
 /// Response of `file_read` method.
-#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct Read {
     #[allow(missing_docs)]
     pub contents: String,
 }
 
 /// Response of `file_exists` method.
-#[derive(Hash, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct FileExists {
     #[allow(missing_docs)]
     pub exists: bool,
 }
 
 /// Response of `file_lst` method.
-#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct FileList {
     #[allow(missing_docs)]
     pub paths: Vec<FileSystemObject>,
 }
 
 /// Response of `file_info` method.
-#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct FileInfo {
     #[allow(missing_docs)]
     pub attributes: FileAttributes,
 }
 
 /// Response of `file_checksum` method.
-#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct FileChecksum {
     #[allow(missing_docs)]
     pub checksum: Sha3_224,
 }
 
 /// Response of `open_text_file` method.
-#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 #[allow(missing_docs)]
 pub struct OpenTextFile {
@@ -64,7 +65,7 @@ pub struct OpenTextFile {
 }
 
 /// Response of `create_execution_context` method.
-#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 #[allow(missing_docs)]
 pub struct CreateExecutionContext {
@@ -74,7 +75,7 @@ pub struct CreateExecutionContext {
 }
 
 /// Response of `get_suggestions_database` method.
-#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 #[allow(missing_docs)]
 pub struct GetSuggestionDatabase {
@@ -83,7 +84,7 @@ pub struct GetSuggestionDatabase {
 }
 
 /// Response of `get_suggestions_database_version` method.
-#[derive(Hash, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 #[allow(missing_docs)]
 pub struct GetSuggestionDatabaseVersion {
@@ -91,7 +92,7 @@ pub struct GetSuggestionDatabaseVersion {
 }
 
 /// Response of `completion` method.
-#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 #[allow(missing_docs)]
 pub struct Completion {
@@ -100,7 +101,7 @@ pub struct Completion {
 }
 
 /// Response of `get_component_groups` method.
-#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 #[allow(missing_docs)]
 pub struct GetComponentGroups {
@@ -108,7 +109,7 @@ pub struct GetComponentGroups {
 }
 
 /// Response of `save_vcs` method.
-#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 #[allow(missing_docs)]
 pub struct SaveVcs {
@@ -117,7 +118,7 @@ pub struct SaveVcs {
 }
 
 /// Response of `list_vcs` method.
-#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 #[allow(missing_docs)]
 pub struct ListVcs {
@@ -125,7 +126,7 @@ pub struct ListVcs {
 }
 
 /// Response of `vcs_status` method.
-#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 #[allow(missing_docs)]
 pub struct VcsStatus {
@@ -133,3 +134,94 @@ pub struct VcsStatus {
     pub changed:   Vec<Path>,
     pub last_save: SaveVcs,
 }
+
+/// Response of `restore_vcs` method.
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreVcs {
+    /// The files modified by restoring the project.
+    pub changed: Vec<Path>,
+}
+
+/// Response of `list_available_editions` method.
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+#[allow(missing_docs)]
+pub struct ListAvailableEditions {
+    pub edition_names: Vec<String>,
+}
+
+/// Response of `resolve_edition` method.
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+#[allow(missing_docs)]
+pub struct ResolveEdition {
+    pub engine_version: String,
+}
+
+/// Response of `get_project_edition_settings` method.
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectEditionSettings {
+    /// The parent edition of the project. May be missing if not set in the project's config.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_edition:         Option<String>,
+    /// Whether the local libraries take precedence over the ones defined in the edition.
+    pub prefer_local_libraries: bool,
+}
+
+/// Response of `set_project_parent_edition` and `set_project_local_libraries_preference` methods.
+#[derive(Hash, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EditionSettingsUpdate {
+    /// Whether the Language Server needs to be restarted for the change to take effect. Missing
+    /// value should be treated as `false`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub needs_restart: Option<bool>,
+}
+
+/// Response of `list_defined_libraries` method.
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+#[allow(missing_docs)]
+pub struct ListDefinedLibraries {
+    pub available_libraries: Vec<LibraryEntry>,
+}
+
+/// Response of `list_defined_components` method.
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+#[allow(missing_docs)]
+pub struct ListDefinedComponents {
+    pub available_components: Vec<LibraryComponentGroup>,
+}
+
+/// Response of `list_local_libraries` method.
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+#[allow(missing_docs)]
+pub struct ListLocalLibraries {
+    pub local_libraries: Vec<LibraryEntry>,
+}
+
+/// Response of `get_library_metadata` method. The fields are missing if not set for the library.
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+#[allow(missing_docs)]
+pub struct LibraryMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag_line:    Option<String>,
+}
+
+/// Response of `get_library_package` method. The fields are missing if not set for the library.
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+#[allow(missing_docs)]
+pub struct LibraryPackage {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license:          Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub component_groups: Option<LibraryComponentGroups>,
+}
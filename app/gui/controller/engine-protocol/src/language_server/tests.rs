@@ -2,9 +2,11 @@ use crate::language_server::*;
 use enso_prelude::*;
 
 use futures::task::LocalSpawnExt;
+use json_rpc::api::Result;
 use json_rpc::messages::Message;
 use json_rpc::messages::RequestMessage;
 use json_rpc::test_util::transport::mock::MockTransport;
+use serde::de::DeserializeOwned;
 use serde_json::json;
 use serde_json::Value;
 use std::future::Future;
@@ -64,6 +66,8 @@ fn test_file_event_notification() {
     fixture.executor.run_until_stalled();
 
     if let Event::Notification(n) = events.expect_next() {
+        let method: &'static str = (&n).into();
+        assert_eq!(method, "file/event");
         assert_eq!(n, Notification::FileEvent(expected_event));
     } else {
         panic!("expected notification event");
@@ -373,7 +377,7 @@ fn test_execution_context() {
     let create_execution_context_response =
         response::CreateExecutionContext { context_id, can_modify, receives_updates };
     test_request(
-        |client| client.create_execution_context(),
+        |client| client.create_execution_context(None),
         "executionContext/create",
         json!({}),
         json!({
@@ -603,3 +607,324 @@ fn test_execution_context() {
         (),
     );
 }
+
+/// Check that `value` serializes to `json` and that `json` deserializes back to `value`.
+fn test_round_trip<T>(value: T, json: Value)
+where T: Debug + PartialEq + Serialize + DeserializeOwned {
+    assert_eq!(serde_json::to_value(&value).unwrap(), json);
+    assert_eq!(serde_json::from_value::<T>(json).unwrap(), value);
+}
+
+#[test]
+fn test_library_types_round_trip() {
+    test_round_trip(LibraryVersion::LocalLibraryVersion, json!({"type":"LocalLibraryVersion"}));
+    let version = "1.0.0".to_owned();
+    let repository_url = "https://libraries.release.enso.org/libraries".to_owned();
+    test_round_trip(
+        LibraryVersion::PublishedLibraryVersion { version, repository_url },
+        json!({
+            "type"          : "PublishedLibraryVersion",
+            "version"       : "1.0.0",
+            "repositoryUrl" : "https://libraries.release.enso.org/libraries"
+        }),
+    );
+    test_round_trip(
+        EditionReference::CurrentProjectEdition,
+        json!({"type":"CurrentProjectEdition"}),
+    );
+    let edition_name = "2022.1.1".to_owned();
+    test_round_trip(
+        EditionReference::NamedEdition { edition_name },
+        json!({"type":"NamedEdition", "editionName":"2022.1.1"}),
+    );
+    let contact = Contact { name: Some("Bob".to_owned()), email: None };
+    test_round_trip(contact, json!({"name":"Bob"}));
+    let library = LibraryComponentGroup {
+        library: "Standard.Base".to_owned(),
+        name:    "Input".to_owned(),
+        color:   None,
+        icon:    None,
+        exports: vec![],
+    };
+    let groups = LibraryComponentGroups { new_groups: vec![library], extended_groups: vec![] };
+    let package =
+        response::LibraryPackage { license: None, component_groups: Some(groups) };
+    test_round_trip(
+        package,
+        json!({"componentGroups":{"newGroups":[{
+            "library" : "Standard.Base",
+            "name"    : "Input",
+            "color"   : null,
+            "icon"    : null,
+            "exports" : []
+        }]}}),
+    );
+    test_round_trip(response::EditionSettingsUpdate::default(), json!({}));
+    test_round_trip(response::LibraryMetadata::default(), json!({}));
+}
+
+#[test]
+fn test_library_requests() {
+    let unit_json = json!(null);
+    let namespace = "local".to_owned();
+    let name = "Lib".to_owned();
+    let local_entry = LibraryEntry {
+        namespace: namespace.clone(),
+        name:      name.clone(),
+        version:   LibraryVersion::LocalLibraryVersion,
+        is_cached: true,
+    };
+    let local_entry_json = json!({
+        "namespace" : "local",
+        "name"      : "Lib",
+        "version"   : {"type":"LocalLibraryVersion"},
+        "isCached"  : true
+    });
+
+    test_request(
+        |client| client.list_local_libraries(),
+        "library/listLocal",
+        json!({}),
+        json!({"localLibraries":[local_entry_json.clone()]}),
+        response::ListLocalLibraries { local_libraries: vec![local_entry.clone()] },
+    );
+    let author = Contact { name: Some("Bob".to_owned()), email: Some("bob@enso.org".to_owned()) };
+    let license = "MIT".to_owned();
+    test_request(
+        |client| client.create_library(&namespace, &name, &vec![author], &vec![], &license),
+        "library/create",
+        json!({
+            "namespace"   : "local",
+            "name"        : "Lib",
+            "authors"     : [{"name":"Bob","email":"bob@enso.org"}],
+            "maintainers" : [],
+            "license"     : "MIT"
+        }),
+        unit_json.clone(),
+        (),
+    );
+    let version = LibraryVersion::LocalLibraryVersion;
+    test_request(
+        |client| client.get_library_metadata(&namespace, &name, &version),
+        "library/getMetadata",
+        json!({"namespace":"local", "name":"Lib", "version":{"type":"LocalLibraryVersion"}}),
+        json!({"tagLine":"A library."}),
+        response::LibraryMetadata { description: None, tag_line: Some("A library.".to_owned()) },
+    );
+    let description = Some("The description.".to_owned());
+    test_request(
+        |client| client.set_library_metadata(&namespace, &name, &description, &None),
+        "library/setMetadata",
+        json!({"namespace":"local", "name":"Lib", "description":"The description."}),
+        unit_json.clone(),
+        (),
+    );
+    test_request(
+        |client| client.get_library_package(&namespace, &name, &version),
+        "library/getPackage",
+        json!({"namespace":"local", "name":"Lib", "version":{"type":"LocalLibraryVersion"}}),
+        json!({"license":"MIT"}),
+        response::LibraryPackage {
+            license:          Some("MIT".to_owned()),
+            component_groups: None,
+        },
+    );
+    let auth_token = "token".to_owned();
+    let upload_url = "https://publish.enso.org/".to_owned();
+    test_request(
+        |client| client.publish_library(&namespace, &name, &auth_token, &upload_url, &Some(true)),
+        "library/publish",
+        json!({
+            "namespace"               : "local",
+            "name"                    : "Lib",
+            "authToken"               : "token",
+            "uploadUrl"               : "https://publish.enso.org/",
+            "bumpVersionAfterPublish" : true
+        }),
+        unit_json.clone(),
+        (),
+    );
+    test_request(
+        |client| client.preinstall_library(&namespace, &name),
+        "library/preinstall",
+        json!({"namespace":"local", "name":"Lib"}),
+        unit_json,
+        (),
+    );
+
+    let edition = EditionReference::NamedEdition { edition_name: "2022.1.1".to_owned() };
+    let edition_json = json!({"type":"NamedEdition", "editionName":"2022.1.1"});
+    test_request(
+        |client| client.list_available_editions(&false),
+        "editions/listAvailable",
+        json!({"update":false}),
+        json!({"editionNames":["2022.1.1"]}),
+        response::ListAvailableEditions { edition_names: vec!["2022.1.1".to_owned()] },
+    );
+    test_request(
+        |client| client.resolve_edition(&edition),
+        "editions/resolve",
+        json!({"edition":edition_json.clone()}),
+        json!({"engineVersion":"0.2.32"}),
+        response::ResolveEdition { engine_version: "0.2.32".to_owned() },
+    );
+    test_request(
+        |client| client.get_project_edition_settings(),
+        "editions/getProjectSettings",
+        json!({}),
+        json!({"preferLocalLibraries":false}),
+        response::ProjectEditionSettings {
+            parent_edition:         None,
+            prefer_local_libraries: false,
+        },
+    );
+    test_request(
+        |client| client.set_project_parent_edition(&"2022.1.1".to_owned()),
+        "editions/setProjectParentEdition",
+        json!({"newEditionName":"2022.1.1"}),
+        json!({"needsRestart":true}),
+        response::EditionSettingsUpdate { needs_restart: Some(true) },
+    );
+    test_request(
+        |client| client.set_project_local_libraries_preference(&true),
+        "editions/setProjectLocalLibrariesPreference",
+        json!({"preferLocalLibraries":true}),
+        json!({}),
+        response::EditionSettingsUpdate { needs_restart: None },
+    );
+    test_request(
+        |client| client.list_defined_libraries(&edition),
+        "editions/listDefinedLibraries",
+        json!({"edition":edition_json.clone()}),
+        json!({ "availableLibraries": [local_entry_json] }),
+        response::ListDefinedLibraries { available_libraries: vec![local_entry] },
+    );
+    test_request(
+        |client| client.list_defined_components(&edition),
+        "editions/listDefinedComponents",
+        json!({ "edition": edition_json }),
+        json!({"availableComponents":[]}),
+        response::ListDefinedComponents { available_components: vec![] },
+    );
+}
+
+#[test]
+fn test_other_requests() {
+    let root_id = Uuid::parse_str("00000000-0000-0000-0000-000000000000");
+    let root_id = root_id.expect("Couldn't parse uuid.");
+    let main = Path { root_id, segments: vec!["Main.txt".into()] };
+    let main_json =
+        json!({"rootId":"00000000-0000-0000-0000-000000000000", "segments":["Main.txt"]});
+    let unit_json = json!(null);
+
+    test_request(
+        |client| client.restore_vcs(&main, &Some("a1b2".to_owned())),
+        "vcs/restore",
+        json!({"root":main_json.clone(), "commitId":"a1b2"}),
+        json!({"changed":[main_json.clone()]}),
+        response::RestoreVcs { changed: vec![main.clone()] },
+    );
+    test_request(
+        |client| client.restore_vcs(&main, &None),
+        "vcs/restore",
+        json!({"root":main_json.clone()}),
+        json!({"changed":[]}),
+        response::RestoreVcs::default(),
+    );
+    let registration = CapabilityRegistration::create_can_edit_text_file(main.clone());
+    test_request(
+        |client| client.release_capability(&registration),
+        "capability/release",
+        json!({"registration" : {
+            "method"          : "text/canEdit",
+            "registerOptions" : {"path":main_json.clone()}
+        }}),
+        unit_json.clone(),
+        (),
+    );
+    let namespace = "local".to_owned();
+    test_request(
+        |client| client.rename_project(&namespace, &"Old".to_owned(), &"New".to_owned()),
+        "refactoring/renameProject",
+        json!({"namespace":"local", "oldName":"Old", "newName":"New"}),
+        unit_json.clone(),
+        (),
+    );
+    let expression_id = Uuid::default();
+    let position = Position { line: 0, character: 5 };
+    let range = TextRange { start: position, end: position };
+    let edit = TextEdit { range, text: ",".to_owned() };
+    let old_version = Sha3_224::new(b"Hello world!");
+    let new_version = Sha3_224::new(b"Hello, world!");
+    test_request(
+        |client| {
+            client.apply_expression_value(&expression_id, &main, &edit, &old_version, &new_version)
+        },
+        "text/applyExpressionValue",
+        json!({
+            "expressionId" : "00000000-0000-0000-0000-000000000000",
+            "path"         : main_json,
+            "edit"         : {
+                "range" : {"start":{"line":0, "character":5}, "end":{"line":0, "character":5}},
+                "text"  : ","
+            },
+            "oldVersion"   : "d3ee9b1ba1990fecfd794d2f30e0207aaa7be5d37d463073096d86f8",
+            "newVersion"   : "6a33e22f20f16642697e8bd549ff7b759252ad56c05a1b0acc31dc69"
+        }),
+        unit_json.clone(),
+        (),
+    );
+    let visualisation_id = Uuid::default();
+    let expression = MethodPointer {
+        module:          "Foo.Bar".to_owned(),
+        defined_on_type: "Foo.Bar".to_owned(),
+        name:            "foo".to_owned(),
+    };
+    let visualisation_config = VisualisationConfiguration {
+        execution_context_id: Uuid::default(),
+        expression,
+        positional_arguments_expressions: vec![],
+    };
+    test_request(
+        |client| {
+            client.execute_expression(&visualisation_id, &expression_id, &visualisation_config)
+        },
+        "executionContext/executeExpression",
+        json!({
+            "visualisationId"     : "00000000-0000-0000-0000-000000000000",
+            "expressionId"        : "00000000-0000-0000-0000-000000000000",
+            "visualisationConfig" : {
+                "executionContextId"  : "00000000-0000-0000-0000-000000000000",
+                "expression"          : {
+                    "module"        : "Foo.Bar",
+                    "definedOnType" : "Foo.Bar",
+                    "name"          : "foo"
+                },
+                "positionalArgumentsExpressions" : []
+            }
+        }),
+        unit_json,
+        (),
+    );
+}
+
+#[test]
+fn test_open_rpc_document() {
+    let document = open_rpc_document();
+    assert_eq!(document["info"]["title"], "Enso Language Server");
+    let methods = document["methods"].as_array().expect("Methods should be an array.");
+    let names = methods.iter().map(|method| method["name"].as_str().unwrap()).collect_vec();
+    assert_eq!(names.iter().unique().count(), names.len());
+    assert!(methods.iter().all(|method| !method["description"].as_str().unwrap().is_empty()));
+
+    let copy_file = methods.iter().find(|method| method["name"] == "file/copy").unwrap();
+    let params = copy_file["params"].as_array().unwrap();
+    let param_names = params.iter().map(|param| &param["name"]).collect_vec();
+    assert_eq!(param_names, vec!["from", "to"]);
+    assert_eq!(params[0]["schema"]["$ref"], "#/components/schemas/Path");
+    assert!(document["components"]["schemas"]["Path"]["properties"]["segments"].is_object());
+    let create_context =
+        methods.iter().find(|method| method["name"] == "executionContext/create").unwrap();
+    assert_eq!(create_context["params"][0]["name"], "contextId");
+    assert_eq!(create_context["params"][0]["required"], false);
+}
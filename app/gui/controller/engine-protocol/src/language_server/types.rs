@@ -21,7 +21,7 @@ pub type Event = json_rpc::handler::Event<Notification>;
 // ============
 
 /// A path is a representation of a path relative to a specified content root.
-#[derive(Clone, Debug, Serialize, Deserialize, Hash, PartialEq, Eq)]
+#[derive(Clone, Debug, Serialize, Deserialize, Hash, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Path {
     /// Path's root id.
@@ -107,67 +107,78 @@ impl Path {
 // === Notification ===
 // ====================
 
-/// Notification generated by the Language Server.
-#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, IntoStaticStr)]
+/// Notification generated by the Language Server. Converts into its JSON-RPC method name.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, IntoStaticStr, JsonSchema)]
 #[serde(tag = "method", content = "params")]
 pub enum Notification {
     /// Filesystem event occurred for a watched path.
     #[serde(rename = "file/event")]
+    #[strum(serialize = "file/event")]
     FileEvent(FileEvent),
 
     /// This is a notification sent from the server to the clients to inform them of any successful
     /// auto-save action.
     #[serde(rename = "text/autoSave")]
+    #[strum(serialize = "text/autoSave")]
     TextAutoSave(TextAutoSave),
 
     /// Sent from the server to the client to inform about new information for certain expressions
     /// becoming available. This notification is superseded by executionContext/expressionUpdates.
     #[serde(rename = "executionContext/expressionValuesComputed")]
+    #[strum(serialize = "executionContext/expressionValuesComputed")]
     ExpressionValuesComputed(serde_json::Value),
 
     /// Sent from the server to the client to inform about new information for certain expressions
     /// becoming available.
     #[serde(rename = "executionContext/expressionUpdates")]
+    #[strum(serialize = "executionContext/expressionUpdates")]
     ExpressionUpdates(ExpressionUpdates),
 
     /// Sent from the server to the client to inform about a failure during execution of an
     /// execution context.
     #[serde(rename = "executionContext/executionFailed")]
+    #[strum(serialize = "executionContext/executionFailed")]
     ExecutionFailed(ExecutionFailed),
 
     /// Sent from the server to the client to inform about the successful execution of a context.
     #[serde(rename = "executionContext/executionComplete")]
+    #[strum(serialize = "executionContext/executionComplete")]
     #[serde(rename_all = "camelCase")]
     #[allow(missing_docs)]
     ExecutionComplete { context_id: ContextId },
 
     /// Sent from the server to the client to inform about a status of execution.
     #[serde(rename = "executionContext/executionStatus")]
+    #[strum(serialize = "executionContext/executionStatus")]
     ExecutionStatus(ExecutionStatus),
 
     /// Sent from server to the client to inform about the change in the suggestions database.
     #[serde(rename = "search/suggestionsDatabaseUpdates")]
+    #[strum(serialize = "search/suggestionsDatabaseUpdates")]
     SuggestionDatabaseUpdates(SuggestionDatabaseUpdatesEvent),
 
     /// Sent from server to the client to inform that a content root has been added.
     #[allow(missing_docs)]
     #[serde(rename = "file/rootAdded")]
+    #[strum(serialize = "file/rootAdded")]
     ContentRootAdded { root: ContentRoot },
 
     /// Sent from server to the client to inform that a content root has been removed.
     #[allow(missing_docs)]
     #[serde(rename = "file/rootRemoved")]
+    #[strum(serialize = "file/rootRemoved")]
     ContentRootRemoved { id: Uuid },
 
     /// Sent from server to the client to inform about a failure during execution of a
     /// visualisation.
     #[serde(rename = "executionContext/visualisationEvaluationFailed")]
+    #[strum(serialize = "executionContext/visualisationEvaluationFailed")]
     VisualisationEvaluationFailed(VisualisationEvaluationFailed),
 }
 
 /// Sent from the server to the client to inform about a failure during execution of an execution
 /// context.
-#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, Eq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, Eq, JsonSchema)]
 #[allow(missing_docs)]
 #[serde(rename_all = "camelCase")]
 pub struct ExecutionFailed {
@@ -176,7 +187,7 @@ pub struct ExecutionFailed {
 }
 
 /// Sent from server to the client to inform about a failure during execution of a visualisation.
-#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, Eq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, Eq, JsonSchema)]
 #[allow(missing_docs)]
 #[serde(rename_all = "camelCase")]
 pub struct VisualisationEvaluationFailed {
@@ -195,7 +206,7 @@ pub struct VisualisationEvaluationFailed {
 
 /// Sent from the server to the client to inform about new information for certain expressions
 /// becoming available.
-#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, JsonSchema)]
 #[allow(missing_docs)]
 #[serde(rename_all = "camelCase")]
 pub struct ExpressionUpdates {
@@ -204,7 +215,7 @@ pub struct ExpressionUpdates {
 }
 
 /// An update about the computed expression.
-#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, JsonSchema)]
 #[allow(missing_docs)]
 #[serde(rename_all = "camelCase")]
 pub struct ExpressionUpdate {
@@ -219,7 +230,7 @@ pub struct ExpressionUpdate {
 
 /// Profiling information on an executed expression. It is implemented as a union as additional
 /// types of information will be added in the future.
-#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, Eq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, Eq, JsonSchema)]
 #[allow(missing_docs)]
 // Not sure what the future variants will be, and implementing Copy is not essential for this.
 #[allow(missing_copy_implementations)]
@@ -228,7 +239,7 @@ pub enum ProfilingInfo {
     ExecutionTime { nano_time: u64 },
 }
 
-#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, JsonSchema)]
 #[allow(missing_docs)]
 #[serde(tag = "type")]
 pub enum ExpressionUpdatePayload {
@@ -256,7 +267,7 @@ pub enum ExpressionUpdatePayload {
 // =======================
 
 /// Sent from the server to the client to inform about a status of execution.
-#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, Eq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, Eq, JsonSchema)]
 #[allow(missing_docs)]
 #[serde(rename_all = "camelCase")]
 pub struct ExecutionStatus {
@@ -265,7 +276,7 @@ pub struct ExecutionStatus {
 }
 
 /// The type of diagnostic message.
-#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize, Eq)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize, Eq, JsonSchema)]
 #[allow(missing_docs)]
 pub enum DiagnosticType {
     Error,
@@ -281,7 +292,7 @@ pub enum DiagnosticType {
 // a builtin node. Then, to locate the error in the code, you can use the stack field with a stack
 // trace to find the first element with non-empty location (as the head of the stack will point to
 // the builtin element).
-#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, Eq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, Eq, JsonSchema)]
 #[allow(missing_docs)]
 #[serde(rename_all = "camelCase")]
 pub struct Diagnostic {
@@ -295,7 +306,7 @@ pub struct Diagnostic {
 
 /// The frame of the stack trace. If the error refer to a builtin node, the path and location fields
 /// will be empty.
-#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, Eq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, Eq, JsonSchema)]
 #[allow(missing_docs)]
 #[serde(rename_all = "camelCase")]
 pub struct StackTraceElement {
@@ -314,7 +325,7 @@ pub struct StackTraceElement {
 
 /// The `file/event` notification parameters.
 #[derive(Clone, Debug, PartialEq, Eq)]
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 #[allow(missing_docs)]
 pub struct FileEvent {
     pub path: Path,
@@ -323,7 +334,7 @@ pub struct FileEvent {
 
 /// Describes kind of filesystem event (was the file created or deleted, etc.)
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 #[allow(missing_docs)]
 pub enum FileEventKind {
     Added,
@@ -339,7 +350,7 @@ pub enum FileEventKind {
 
 /// The `text/autoSave` notification parameters.
 #[derive(Clone, Debug, PartialEq, Eq)]
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 #[allow(missing_docs)]
 pub struct TextAutoSave {
     pub path: Path,
@@ -353,7 +364,7 @@ pub struct TextAutoSave {
 
 /// Attributes of the file in the filesystem.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct FileAttributes {
     /// When the file was created.
@@ -370,7 +381,7 @@ pub struct FileAttributes {
 }
 
 /// A representation of what kind of type a filesystem object can be.
-#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type")]
 #[allow(missing_docs)]
 pub enum FileSystemObject {
@@ -451,7 +462,7 @@ impl FileSystemObject {
 /// A content root represents a location on a real file-system that has been virtualized for use in
 /// the Cloud.
 #[allow(missing_docs)]
-#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize, JsonSchema)]
 #[serde(tag = "type")]
 pub enum ContentRoot {
     /// Points to the project home.
@@ -493,7 +504,7 @@ impl ContentRoot {
 // ================
 
 /// A representation of a position in a text file.
-#[derive(Hash, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[allow(missing_docs)]
 pub struct Position {
     pub line:      usize,
@@ -521,7 +532,7 @@ impls! { From + &From <Position> for enso_text::Location<Utf16CodeUnit> { |posit
 // =================
 
 /// A representation of a range of text in a text file.
-#[derive(Hash, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[allow(missing_docs)]
 pub struct TextRange {
     pub start: Position,
@@ -546,7 +557,7 @@ impls! { From + &From <TextRange> for enso_text::Range<enso_text::Location<Utf16
 // ================
 
 /// A representation of a change to a text file at a given position.
-#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 #[allow(missing_docs)]
 pub struct TextEdit {
@@ -656,7 +667,7 @@ impl TextEdit {
 // ================
 
 /// A versioned representation of batch edits to a file.
-#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 #[allow(missing_docs)]
 pub struct FileEdit {
@@ -678,7 +689,7 @@ pub type ContextId = Uuid;
 pub type ExpressionId = Uuid;
 
 /// A configuration object for properties of the visualisation.
-#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 #[allow(missing_docs)]
 pub struct VisualisationConfiguration {
@@ -692,7 +703,7 @@ pub struct VisualisationConfiguration {
 
 /// Used to enter deeper in the execution context stack. In general, all consequent stack items
 /// should be `LocalCall`s.
-#[derive(Hash, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 #[allow(missing_docs)]
 pub struct LocalCall {
@@ -700,7 +711,7 @@ pub struct LocalCall {
 }
 
 /// Points to a method definition.
-#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 #[allow(missing_docs)]
 pub struct MethodPointer {
@@ -712,7 +723,7 @@ pub struct MethodPointer {
 
 /// Used for entering a method. The first item on the execution context stack should always be
 /// an `ExplicitCall`.
-#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 #[allow(missing_docs)]
 pub struct ExplicitCall {
@@ -722,7 +733,7 @@ pub struct ExplicitCall {
 }
 
 /// A representation of an executable position in code, used by the context execution methods.
-#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type")]
 #[allow(missing_docs)]
 pub enum StackItem {
@@ -731,7 +742,7 @@ pub enum StackItem {
 }
 
 /// Expressions to invalidate when restarting the program.
-#[derive(Hash, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 #[allow(missing_docs)]
 pub enum InvalidatedExpressions {
@@ -743,7 +754,7 @@ pub enum InvalidatedExpressions {
 // ==============================
 
 /// `CapabilityRegistration` is used to keep track of permissions granting.
-#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CapabilityRegistration {
     /// Method is the name of the capability listed in
@@ -801,7 +812,7 @@ impl CapabilityRegistration {
 //TODO[ao] we cannot have one variant for each cabability due to `untagged` attribute.
 // The best solution is make CapabilityRegistration an enum and write serialization and
 // deserialization by hand.
-#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(untagged, rename_all = "camelCase")]
 #[allow(missing_docs)]
 pub enum RegisterOptions {
@@ -825,7 +836,7 @@ pub enum RegisterOptions {
 pub type HtmlString = String;
 
 /// Documentation section mark.
-#[derive(Hash, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[allow(missing_docs)]
 pub enum Mark {
     Important,
@@ -834,7 +845,7 @@ pub enum Mark {
 }
 
 /// A single section of the documentation.
-#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[allow(missing_docs)]
 #[serde(tag = "type")]
 #[serde(rename_all = "camelCase")]
@@ -884,7 +895,7 @@ pub type SuggestionId = usize;
 pub type SuggestionsDatabaseVersion = usize;
 
 /// The argument of an atom, method or function suggestion.
-#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SuggestionEntryArgument {
     /// The argument name.
@@ -920,7 +931,7 @@ impl SuggestionEntryArgument {
 }
 
 /// The definition scope. The start and end are chars indices.
-#[derive(Hash, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 #[allow(missing_docs)]
 pub struct SuggestionEntryScope {
@@ -940,7 +951,7 @@ impls! { From + &From <SuggestionEntryScope> for RangeInclusive<enso_text::Locat
 }}
 
 /// A type of suggestion entry.
-#[derive(Hash, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 #[allow(missing_docs)]
 pub enum SuggestionEntryType {
@@ -951,7 +962,7 @@ pub enum SuggestionEntryType {
 }
 
 /// A Suggestion Entry.
-#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[allow(missing_docs)]
 #[serde(tag = "type")]
 #[serde(rename_all = "camelCase")]
@@ -1048,7 +1059,7 @@ impl SuggestionEntry {
 }
 
 /// The entry in the suggestions database.
-#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 #[allow(missing_docs)]
 pub struct SuggestionsDatabaseEntry {
@@ -1059,14 +1070,14 @@ pub struct SuggestionsDatabaseEntry {
 
 // === Suggestion Database Updates ===
 
-#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize, JsonSchema)]
 #[allow(missing_docs)]
 pub enum FieldAction {
     Remove,
     Set,
 }
 
-#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 #[allow(missing_docs)]
 pub struct FieldUpdate<T> {
@@ -1100,7 +1111,7 @@ impl<T> FieldUpdate<T> {
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 #[allow(missing_docs)]
 pub enum SuggestionArgumentUpdate {
@@ -1120,7 +1131,7 @@ pub enum SuggestionArgumentUpdate {
 }
 
 /// The kind of the suggestions database update.
-#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize, JsonSchema)]
 #[allow(missing_docs)]
 pub enum SuggestionsDatabaseUpdateKind {
     Add,
@@ -1129,7 +1140,7 @@ pub enum SuggestionsDatabaseUpdateKind {
 }
 
 /// The update of the suggestions database.
-#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[allow(missing_docs)]
 #[serde(tag = "type")]
 pub enum SuggestionsDatabaseUpdate {
@@ -1147,7 +1158,7 @@ pub enum SuggestionsDatabaseUpdate {
 }
 
 /// The modification of suggestion database entry.
-#[derive(Hash, Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[allow(missing_docs)]
 #[serde(rename_all = "camelCase")]
 pub struct SuggestionsDatabaseModification {
@@ -1163,7 +1174,7 @@ pub struct SuggestionsDatabaseModification {
 }
 
 /// Notification about change in the suggestions database.
-#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 #[allow(missing_docs)]
 pub struct SuggestionDatabaseUpdatesEvent {
@@ -1178,7 +1189,7 @@ pub struct SuggestionDatabaseUpdatesEvent {
 // =============================
 
 /// A single component of a [`LibraryComponentGroup`].
-#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 #[allow(missing_docs)]
 pub struct LibraryComponent {
@@ -1187,7 +1198,7 @@ pub struct LibraryComponent {
 }
 
 /// The component group provided by a library.
-#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 #[allow(missing_docs)]
 pub struct LibraryComponentGroup {
@@ -1203,6 +1214,76 @@ pub struct LibraryComponentGroup {
     pub exports: Vec<LibraryComponent>,
 }
 
+/// The description of component groups provided by a library package.
+#[derive(Hash, Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryComponentGroups {
+    /// The component groups provided by the package.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub new_groups:      Vec<LibraryComponentGroup>,
+    /// The component groups that this package extends.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extended_groups: Vec<LibraryComponentGroup>,
+}
+
+
+
+// ==========================
+// === Library Management ===
+// ==========================
+
+/// A version of a library.
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type")]
+pub enum LibraryVersion {
+    /// A locally editable version of the library, located in the local libraries directory.
+    LocalLibraryVersion,
+    /// A version of the library published in some repository.
+    #[serde(rename_all = "camelCase")]
+    PublishedLibraryVersion {
+        /// A semver-compliant version of the library.
+        version:        String,
+        /// URL of the repository that this library will be downloaded from.
+        repository_url: String,
+    },
+}
+
+/// A library available in a resolved edition.
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+#[allow(missing_docs)]
+pub struct LibraryEntry {
+    pub namespace: String,
+    pub name:      String,
+    pub version:   LibraryVersion,
+    pub is_cached: bool,
+}
+
+/// Contact information of library authors or maintainers. At least one of the fields should be
+/// defined.
+#[derive(Hash, Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[allow(missing_docs)]
+pub struct Contact {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name:  Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+}
+
+/// A reference to an edition.
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type")]
+pub enum EditionReference {
+    /// The edition associated with the currently open project, with all of its overrides.
+    CurrentProjectEdition,
+    /// An edition stored under the given name.
+    #[serde(rename_all = "camelCase")]
+    NamedEdition {
+        #[allow(missing_docs)]
+        edition_name: String,
+    },
+}
+
 
 // ======================
 // === Test Utilities ===
@@ -10,12 +10,9 @@ use crate::prelude::*;
 
 use crate::types::UTCDateTime;
 
-use json_rpc::api::Result;
-use json_rpc::make_rpc_methods;
-use json_rpc::Handler;
+use schemars::JsonSchema;
 use serde::Deserialize;
 use serde::Serialize;
-use std::future::Future;
 use uuid::Uuid;
 
 
@@ -36,52 +33,54 @@ pub type Event = json_rpc::handler::Event<Notification>;
 // === RPC Methods ===
 // ===================
 
-make_rpc_methods! {
+#[json_rpc::rpc_api(title = "Enso Project Manager")]
 /// An interface containing all the available project management operations.
-trait API {
+pub trait API {
     /// Request the project manager to open a specified project. This operation also
     /// includes spawning an instance of the language server open on the specified project.
     ///
     /// If the opened project uses Enso version not installed yet, this method outcome is defined
     /// by `missing_component_action` argument.
-    #[MethodInput=OpenProjectInput,rpc_name="project/open"]
-    fn open_project
-    (&self, project_id:Uuid, missing_component_action:MissingComponentAction)
-    -> response::OpenProject;
+    #[rpc(name = "project/open")]
+    fn open_project(
+        &self,
+        project_id: &Uuid,
+        missing_component_action: &MissingComponentAction,
+    ) -> response::OpenProject;
 
     /// Request the project manager to close a specified project. This operation
     /// includes shutting down the language server gracefully so that it can persist state to disk
     /// as needed.
-    #[MethodInput=CloseProjectInput,rpc_name="project/close"]
-    fn close_project(&self, project_id:Uuid) -> ();
+    #[rpc(name = "project/close")]
+    fn close_project(&self, project_id: &Uuid);
 
     /// Request the project manager to lists all user's projects. The list of projects is sorted by
     /// the open time.
-    #[MethodInput=ListRecentProjectsInput,rpc_name="project/list"]
-    fn list_projects(&self, number_of_projects:Option<u32>) -> response::ProjectList;
+    #[rpc(name = "project/list")]
+    fn list_projects(&self, number_of_projects: &Option<u32>) -> response::ProjectList;
 
     /// Request the creation of a new project.
-    #[MethodInput=CreateProjectInput,rpc_name="project/create"]
-    fn create_project
-    ( &self
-    , name                     : ProjectName
-    , project_template         : Option<String>
-    , version                  : Option<String>
-    , missing_component_action : MissingComponentAction
+    #[rpc(name = "project/create")]
+    fn create_project(
+        &self,
+        name: &ProjectName,
+        project_template: &Option<String>,
+        version: &Option<String>,
+        missing_component_action: &MissingComponentAction,
     ) -> response::CreateProject;
 
     /// Request project renaming.
-    #[MethodInput=RenameProject,rpc_name="project/rename"]
-    fn rename_project(&self, project_id:Uuid, name:ProjectName) -> ();
+    #[rpc(name = "project/rename")]
+    fn rename_project(&self, project_id: &Uuid, name: &ProjectName);
 
     /// Request the deletion of a project.
-    #[MethodInput=DeleteProjectInput,rpc_name="project/delete"]
-    fn delete_project(&self, project_id:Uuid) -> ();
+    #[rpc(name = "project/delete")]
+    fn delete_project(&self, project_id: &Uuid);
 
     /// Request a list of sample projects that are available to the user.
-    #[MethodInput=ListSamplesInput,rpc_name="project/listSample"]
-    fn list_samples(&self, num_projects:u32) -> response::ProjectList;
-}}
+    #[rpc(name = "project/listSample")]
+    fn list_samples(&self, num_projects: &u32) -> response::ProjectList;
+}
 
 
 
@@ -90,7 +89,7 @@ trait API {
 // =============
 
 /// Address consisting of host and port.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 pub struct IpWithSocket {
     /// Host name.
     pub host: String,
@@ -105,7 +104,7 @@ impl Display for IpWithSocket {
 }
 
 /// Project name.
-#[derive(Clone, Debug, Deserialize, Display, Eq, From, Hash, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, Display, Eq, From, Hash, PartialEq, Serialize, JsonSchema)]
 pub struct ProjectName(String);
 
 impl ProjectName {
@@ -137,7 +136,7 @@ impl From<ProjectName> for ImString {
 }
 
 /// Project information, such as name, its id and last time it was opened.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ProjectMetadata {
     /// Project's name.
@@ -154,7 +153,7 @@ pub struct ProjectMetadata {
 
 /// This type specifies what action should be taken if an Engine's component required to complete
 /// the Project Manager operation (like project/open) is missing.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, JsonSchema)]
 pub enum MissingComponentAction {
     /// Will make the operation fail if any components are missing.
     Fail,
@@ -170,14 +169,14 @@ pub mod response {
     use super::*;
 
     /// Response of `list_projects` and `list_samples`.
-    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
     pub struct ProjectList {
         /// List of projects.
         pub projects: Vec<ProjectMetadata>,
     }
 
     /// Response of `create_project`.
-    #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
     #[serde(rename_all = "camelCase")]
     pub struct CreateProject {
         /// Created project uuid.
@@ -185,7 +184,7 @@ pub mod response {
     }
 
     /// Response of `open_project`.
-    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
     #[serde(rename_all = "camelCase")]
     pub struct OpenProject {
         /// The version of the started language server represented by a semver version string.
@@ -341,6 +340,7 @@ mod remote_client_tests {
 
     use chrono::DateTime;
     use futures::task::LocalSpawnExt;
+    use json_rpc::api::Result;
     use json_rpc::messages::Message;
     use json_rpc::messages::RequestMessage;
     use json_rpc::test_util::transport::mock::MockTransport;
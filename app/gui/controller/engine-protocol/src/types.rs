@@ -4,6 +4,7 @@ use crate::prelude::*;
 
 use crate::binary;
 
+use schemars::JsonSchema;
 use serde::Deserialize;
 use serde::Serialize;
 
@@ -23,7 +24,8 @@ pub type UTCDateTime = chrono::DateTime<chrono::FixedOffset>;
 // ================
 
 /// SHA3-224 hash digest.
-#[derive(Hash, Debug, Display, Clone, PartialEq, Eq, Serialize, Deserialize, Shrinkwrap)]
+#[derive(Hash, Debug, Display, Clone, PartialEq, Eq, Shrinkwrap)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct Sha3_224(String);
 
 impl Sha3_224 {
@@ -0,0 +1,60 @@
+//! Tests checking that the committed OpenRPC documents of the Enso services are up to date.
+//!
+//! The documents are generated from the `API` traits of the clients. After changing the API, run
+//! these tests with the `UPDATE_OPENRPC` environment variable set to regenerate them.
+
+// === Non-Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+
+use engine_protocol::language_server;
+use engine_protocol::project_manager;
+use serde_json::Value;
+
+
+
+// =================
+// === Utilities ===
+// =================
+
+/// The environment variable which makes the tests regenerate the documents instead of checking
+/// them.
+const UPDATE_VARIABLE: &str = "UPDATE_OPENRPC";
+
+/// Check that the `document` is the same as the committed one, given by its `path` relative to the
+/// crate root and its `committed` contents. Writes the `document` to `path` instead if the
+/// `UPDATE_VARIABLE` is set.
+fn check_document(document: Value, path: &str, committed: &str) {
+    if std::env::var_os(UPDATE_VARIABLE).is_some() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(path);
+        let contents = serde_json::to_string_pretty(&document).unwrap() + "\n";
+        std::fs::write(path, contents).expect("Failed to write the OpenRPC document.");
+    } else {
+        let committed: Value = serde_json::from_str(committed).expect("Invalid OpenRPC document.");
+        assert!(
+            document == committed,
+            "The OpenRPC document {path} is stale. Run the tests with {UPDATE_VARIABLE}=1 to \
+            regenerate it."
+        );
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn language_server_document() {
+    let committed = include_str!("../openrpc/language-server.json");
+    let document = language_server::open_rpc_document();
+    check_document(document, "openrpc/language-server.json", committed);
+}
+
+#[test]
+fn project_manager_document() {
+    let committed = include_str!("../openrpc/project-manager.json");
+    let document = project_manager::open_rpc_document();
+    check_document(document, "openrpc/project-manager.json", committed);
+}
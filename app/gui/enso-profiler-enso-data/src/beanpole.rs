@@ -64,6 +64,8 @@ impl<'a> Diagram<'a> {
                 Metadata::RpcEvent(message) => dia.message(ls, frontend, time, message),
                 Metadata::RpcRequest(message) =>
                     dia.message(frontend, ls, time, message.to_string()),
+                Metadata::RpcResponse(id) =>
+                    dia.message(ls, frontend, time, format!("Response {id}")),
                 _ => {}
             }
         }
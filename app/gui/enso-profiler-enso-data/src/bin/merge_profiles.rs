@@ -0,0 +1,86 @@
+//! Merge the profiles of Enso processes, aligning their clocks.
+//!
+//! # Interface
+//!
+//! Reads from stdin a multi-process profile file, e.g. containing information logged by the IDE
+//! and by the language server. Writes to stdout a multi-process profile file in which the
+//! timestamps of every process are converted to the clock of the first process; the estimated
+//! offset and drift of each process's clock are written to stderr.
+//!
+//! The clocks are aligned based on the messages exchanged by the processes; see
+//! [`enso_profiler_enso_data::clock`]. The profile of a process that did not exchange messages
+//! with the first process in both directions is left unchanged.
+//!
+//! # Usage example
+//!
+//! ```console
+//! # Convert the language server messages to the enso_profiler format.
+//! profiler/enso_data $ cargo run --bin api_events_to_profile < ~/messages.csv > ~/messages.json
+//! # Merge the IDE profile and the language server messages profile.
+//! profiler/enso_data $ cat ~/profile.json ~/messages.json | cargo run --bin merge_profiles \
+//!     > ~/fullprofile.json
+//! # Render an SVG diagram of the message timings.
+//! profiler/enso_data $ cargo run --bin message_beanpoles < ~/fullprofile.json > ~/diagram.svg
+//! ```
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+// === Non-Standard Linter Configuration ===
+#![deny(unconditional_recursion)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+
+use enso_profiler_data as data;
+use enso_profiler_enso_data as enso_data;
+use enso_profiler_enso_data::clock;
+
+
+
+// ============
+// === Main ===
+// ============
+
+fn main() {
+    use std::io::Read;
+
+    let mut input = String::new();
+    std::io::stdin().read_to_string(&mut input).unwrap();
+    let raw_parse_error = "Cannot parse input as sequence of JSON values!";
+    let logs =
+        serde_json::Deserializer::from_str(&input).into_iter::<&serde_json::value::RawValue>();
+    let logs: Vec<_> = logs.map(|log| log.expect(raw_parse_error).get()).collect();
+    let profiles: Vec<data::Profile<enso_data::Metadata>> = logs
+        .iter()
+        .map(|log| match log.parse() {
+            Ok(profile) => profile,
+            Err(data::Error::RecoverableFormatError { with_missing_data, .. }) => with_missing_data,
+            Err(e) => panic!("{}", e),
+        })
+        .collect();
+    let Some(reference) = profiles.first() else { return };
+    let reference_messages = clock::messages(reference);
+    println!("{}", logs[0]);
+    for (index, (log, profile)) in logs.iter().zip(&profiles).enumerate().skip(1) {
+        let name = profile.headers.process.clone().unwrap_or_else(|| format!("Process {index}"));
+        let origin = profile.headers.time_offset.map_or(0.0, |offset| offset.into_ms());
+        let messages = clock::messages(profile);
+        match clock::Alignment::estimate(&messages, &reference_messages, origin) {
+            Some(alignment) => {
+                let clock::Alignment { offset, drift, .. } = alignment;
+                eprintln!("{name}: offset {offset:.3}ms, drift {:.3}ms/s", drift * 1000.0);
+                println!("{}", clock::rebase(log, alignment).unwrap());
+            }
+            None => {
+                eprintln!("{name}: no messages exchanged with the first process; left unaligned.");
+                println!("{log}");
+            }
+        }
+    }
+}
@@ -0,0 +1,428 @@
+//! Alignment of the clocks of different processes.
+//!
+//! Every process timestamps its profile with its own clock. The clocks of different processes
+//! (possibly running on different machines) are offset from each other, and may run at slightly
+//! different rates; so the time offsets in the profiles' headers are not sufficient to relate the
+//! events logged by different processes.
+//!
+//! The relation between two clocks is estimated from the messages exchanged by the processes, like
+//! in NTP: A message from one process and a message back to it form a round trip. Assuming both
+//! messages took equally long to deliver, the offset between the clocks is the difference between
+//! the midpoints of the round trip as observed by each process; the error of the estimate is at
+//! most half of the round trip's delay. The offset is estimated in several windows of time, each
+//! from its round trip with the least delay; the drift of the clocks is the trend of the estimates.
+
+use crate::backend::Direction;
+use crate::Metadata;
+
+use enso_profiler::format;
+use enso_profiler_data as data;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The number of windows of time the round trips are divided into. The offset between the clocks
+/// is estimated in each window from its round trip with the least delay.
+const WINDOWS: usize = 8;
+
+
+
+// ===============
+// === Message ===
+// ===============
+
+/// A message sent or received by a process.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Message {
+    /// Identifies whether the process is the sender or receiver of the message.
+    pub direction:  Direction,
+    /// Used to associate requests and responses, if the process logged it.
+    pub request_id: Option<String>,
+    /// Identifies an RPC method. Empty if the process did not log it.
+    pub endpoint:   String,
+    /// When the message was sent or received, in ms, on the clock of the process.
+    pub time:       f64,
+}
+
+/// Return the messages logged in the profile, in chronological order. The times include the
+/// profile's time offset, if it has one.
+pub fn messages(profile: &data::Profile<Metadata>) -> Vec<Message> {
+    let time_offset = profile.headers.time_offset.map_or(0.0, |offset| offset.into_ms());
+    let mut messages: Vec<_> = profile
+        .metadata()
+        .filter_map(|metadata| {
+            let time = metadata.time.into_ms() + time_offset;
+            let (direction, request_id, endpoint) = match &metadata.data {
+                Metadata::RpcRequest(method) => (Direction::Request, None, method.to_string()),
+                Metadata::RpcResponse(id) =>
+                    (Direction::Response, Some(id.to_string()), String::new()),
+                Metadata::RpcEvent(method) => (Direction::Response, None, method.clone()),
+                Metadata::BackendMessage(message) =>
+                    (message.direction, message.request_id.clone(), message.endpoint.clone()),
+                Metadata::RenderStats(_) => return None,
+            };
+            Some(Message { direction, request_id, endpoint, time })
+        })
+        .collect();
+    messages.sort_by(|a, b| a.time.total_cmp(&b.time));
+    messages
+}
+
+
+// === Delivery ===
+
+/// A message sent by one process, and received by another.
+#[derive(Clone, Copy, Debug)]
+struct Delivery<'a> {
+    request_id: Option<&'a str>,
+    /// When the message was sent, on the clock of the sender.
+    sent:       f64,
+    /// When the message was received, on the clock of the recipient.
+    received:   f64,
+}
+
+/// Match the messages sent by one process with the messages received by the other. The messages
+/// logged with the same request ID by both processes are matched by it, whatever their endpoints;
+/// e.g. the IDE logs the responses it receives only with the IDs of their requests. The remaining
+/// messages of every endpoint are matched in order, as the messages between two processes are
+/// delivered in order.
+fn deliveries<'a>(sender: &'a [Message], recipient: &'a [Message]) -> Vec<Delivery<'a>> {
+    let request_id = |message: &&'a Message| message.request_id.as_deref();
+    let sent = sender.iter().filter(|message| message.direction == Direction::Request);
+    let received = recipient.iter().filter(|message| message.direction == Direction::Response);
+    let received_by_id: HashMap<_, _> =
+        received.clone().filter_map(|message| Some((request_id(&message)?, message))).collect();
+    let mut matched_ids = HashSet::new();
+    let mut by_endpoint: HashMap<&str, (Vec<&Message>, Vec<&Message>)> = HashMap::new();
+    let mut deliveries = vec![];
+    for message in sent {
+        let id = request_id(&message);
+        match id.and_then(|id| received_by_id.get(id)) {
+            Some(received) => {
+                matched_ids.extend(id);
+                deliveries.push(Delivery {
+                    request_id: id,
+                    sent:       message.time,
+                    received:   received.time,
+                });
+            }
+            None => by_endpoint.entry(&message.endpoint).or_default().0.push(message),
+        }
+    }
+    for message in received {
+        if !request_id(&message).map_or(false, |id| matched_ids.contains(id)) {
+            by_endpoint.entry(&message.endpoint).or_default().1.push(message);
+        }
+    }
+    for (sent, received) in by_endpoint.into_values() {
+        deliveries.extend(sent.iter().zip(received).map(|(sent, received)| Delivery {
+            request_id: request_id(sent).or_else(|| request_id(&received)),
+            sent:       sent.time,
+            received:   received.time,
+        }));
+    }
+    deliveries
+}
+
+
+
+// ==============
+// === Sample ===
+// ==============
+
+/// An estimate of the offset between two clocks, from a round trip of messages.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Sample {
+    /// The midpoint of the round trip, on the clock of the process.
+    time:   f64,
+    /// The estimated offset from the clock of the process to the clock of the reference process.
+    offset: f64,
+    /// The total time the messages took to deliver.
+    delay:  f64,
+}
+
+impl Sample {
+    /// Estimate the offset from a message sent by the process, and a message received by it.
+    fn new(outbound: &Delivery, inbound: &Delivery) -> Self {
+        // A message cannot be received before it was sent, which bounds the offset between the
+        // clocks from both sides. The width of the bounds is the sum of the delivery times.
+        let upper = outbound.received - outbound.sent;
+        let lower = inbound.sent - inbound.received;
+        let time = (outbound.sent + inbound.received) / 2.0;
+        Self { time, offset: (upper + lower) / 2.0, delay: upper - lower }
+    }
+}
+
+/// Form round trips from the messages between the processes: Each message is paired with the
+/// message in the other direction sharing its request ID, or else with the closest one in time.
+///
+/// The outbound messages must be sorted by their send times, and the inbound messages by their
+/// receive times.
+fn samples(outbound: &[Delivery], inbound: &[Delivery]) -> Vec<Sample> {
+    let sent: Vec<_> = outbound.iter().map(|delivery| delivery.sent).collect();
+    let received: Vec<_> = inbound.iter().map(|delivery| delivery.received).collect();
+    let responses: HashMap<_, _> = inbound
+        .iter()
+        .enumerate()
+        .filter_map(|(i, delivery)| Some((delivery.request_id?, i)))
+        .collect();
+    let requests: HashMap<_, _> = outbound
+        .iter()
+        .enumerate()
+        .filter_map(|(o, delivery)| Some((delivery.request_id?, o)))
+        .collect();
+    let mut pairs = BTreeSet::new();
+    for (o, delivery) in outbound.iter().enumerate() {
+        let response = delivery.request_id.and_then(|id| responses.get(id).copied());
+        if let Some(i) = response.or_else(|| nearest(&received, delivery.sent)) {
+            pairs.insert((o, i));
+        }
+    }
+    for (i, delivery) in inbound.iter().enumerate() {
+        let request = delivery.request_id.and_then(|id| requests.get(id).copied());
+        if let Some(o) = request.or_else(|| nearest(&sent, delivery.received)) {
+            pairs.insert((o, i));
+        }
+    }
+    pairs.into_iter().map(|(o, i)| Sample::new(&outbound[o], &inbound[i])).collect()
+}
+
+/// Return the index of the time closest to the given one, in a sorted slice.
+fn nearest(times: &[f64], time: f64) -> Option<usize> {
+    let next = times.partition_point(|t| *t < time);
+    let candidates = next.checked_sub(1).into_iter().chain((next < times.len()).then_some(next));
+    let distance = |i: &usize| (times[*i] - time).abs();
+    candidates.min_by(|a, b| distance(a).total_cmp(&distance(b)))
+}
+
+/// Select the sample with the least delay from each window of time.
+fn best_samples(samples: &[Sample]) -> Vec<Sample> {
+    let start = samples.iter().map(|sample| sample.time).fold(f64::INFINITY, f64::min);
+    let end = samples.iter().map(|sample| sample.time).fold(f64::NEG_INFINITY, f64::max);
+    let width = (end - start) / WINDOWS as f64;
+    let mut best: Vec<Option<Sample>> = vec![None; WINDOWS];
+    // A negative delay would indicate that the messages were matched incorrectly.
+    for &sample in samples.iter().filter(|sample| sample.delay >= 0.0) {
+        let window = match width > 0.0 {
+            true => (((sample.time - start) / width) as usize).min(WINDOWS - 1),
+            false => 0,
+        };
+        let best = &mut best[window];
+        if best.map_or(true, |best| sample.delay < best.delay) {
+            *best = Some(sample);
+        }
+    }
+    best.into_iter().flatten().collect()
+}
+
+
+
+// =================
+// === Alignment ===
+// =================
+
+/// A mapping from the clock of a process to the clock of the reference process.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Alignment {
+    /// A time on the clock of the process, at which the offset is given.
+    pub origin: f64,
+    /// The offset between the clocks at the origin, in ms.
+    pub offset: f64,
+    /// The change of the offset per ms of the process's clock.
+    pub drift:  f64,
+}
+
+impl Alignment {
+    /// Estimate the alignment of a process's clock from the messages it exchanged with the
+    /// reference process. The offset is given at the specified origin, e.g. the process's time
+    /// offset. Returns `None` if the processes did not exchange messages in both directions.
+    pub fn estimate(process: &[Message], reference: &[Message], origin: f64) -> Option<Self> {
+        let mut outbound = deliveries(process, reference);
+        let mut inbound = deliveries(reference, process);
+        outbound.sort_by(|a, b| a.sent.total_cmp(&b.sent));
+        inbound.sort_by(|a, b| a.received.total_cmp(&b.received));
+        let samples = best_samples(&samples(&outbound, &inbound));
+        if samples.is_empty() {
+            return None;
+        }
+        let count = samples.len() as f64;
+        let mean_time = samples.iter().map(|sample| sample.time).sum::<f64>() / count;
+        let mean_offset = samples.iter().map(|sample| sample.offset).sum::<f64>() / count;
+        let deviation = |sample: &Sample| (sample.time - mean_time, sample.offset - mean_offset);
+        let variance: f64 = samples.iter().map(deviation).map(|(time, _)| time * time).sum();
+        let covariance: f64 =
+            samples.iter().map(deviation).map(|(time, offset)| time * offset).sum();
+        let drift = if variance > 0.0 { covariance / variance } else { 0.0 };
+        let offset = mean_offset + drift * (origin - mean_time);
+        Some(Self { origin, offset, drift })
+    }
+
+    /// Convert a time on the clock of the process to the clock of the reference process.
+    pub fn apply(&self, time: f64) -> f64 {
+        time + self.offset + self.drift * (time - self.origin)
+    }
+}
+
+
+
+// ================
+// === Rebasing ===
+// ================
+
+/// Convert the timestamps of a process's event log to the clock of the reference process.
+///
+/// If the log has a time offset, it is converted, and the timestamps relative to it are scaled by
+/// the drift. Otherwise, the timestamps are converted; any that would precede the clock's epoch are
+/// clamped to it.
+pub fn rebase(log: &str, alignment: Alignment) -> serde_json::Result<String> {
+    let mut events: Vec<format::Event> = serde_json::from_str(log)?;
+    let time_offset = |event: &format::Event| match event {
+        format::Event::Metadata(metadata) => match serde_json::from_str(metadata.data.get()) {
+            Ok(format::Header::TimeOffset(offset)) => Some(offset),
+            _ => None,
+        },
+        _ => None,
+    };
+    let origin = events.iter().find_map(time_offset).map(|offset| offset.into_ms());
+    let rebased_origin = origin.map(|origin| alignment.apply(origin));
+    let origin = origin.unwrap_or_default();
+    let convert = |time: &mut format::Timestamp| {
+        let rebased = alignment.apply(time.into_ms() + origin) - rebased_origin.unwrap_or_default();
+        *time = format::Timestamp::from_ms(rebased);
+    };
+    for event in &mut events {
+        if time_offset(event).is_some() {
+            let offset = format::Timestamp::from_ms(rebased_origin.unwrap_or_default());
+            let header = serde_json::value::to_raw_value(&format::Header::TimeOffset(offset))?;
+            if let format::Event::Metadata(metadata) = event {
+                metadata.data = header;
+            }
+            continue;
+        }
+        match event {
+            format::Event::Create(start) => start.start.iter_mut().for_each(convert),
+            format::Event::Start { timestamp, .. }
+            | format::Event::End { timestamp, .. }
+            | format::Event::Pause { timestamp, .. } => convert(timestamp),
+            format::Event::Metadata(metadata) => convert(&mut metadata.time),
+            format::Event::Label { .. } => (),
+        }
+    }
+    serde_json::to_string(&events)
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::backend;
+
+    /// Messages between a client and a server, whose clock is ahead by 1000ms at the client's time
+    /// 0, and gains 1ms per second. The deliveries take between 2 and 12ms.
+    fn exchange() -> (Vec<Message>, Vec<Message>) {
+        let server_time = |time: f64| time + 1000.0 + time / 1000.0;
+        let mut client = vec![];
+        let mut server = vec![];
+        for i in 0..100 {
+            let sent = i as f64 * 100.0;
+            let request_delay = 2.0 + (i % 5) as f64;
+            let response_delay = 2.0 + (i % 3) as f64 * 5.0;
+            let received = sent + request_delay;
+            let responded = received + 1.0;
+            let request_id = Some(i.to_string());
+            let message = |direction, time| Message {
+                direction,
+                request_id: request_id.clone(),
+                endpoint: "executionContext/push".to_owned(),
+                time,
+            };
+            client.push(message(Direction::Request, sent));
+            server.push(message(Direction::Response, server_time(received)));
+            server.push(message(Direction::Request, server_time(responded)));
+            client.push(message(Direction::Response, responded + response_delay));
+        }
+        (client, server)
+    }
+
+    #[test]
+    fn estimate_offset_and_drift() {
+        let (client, server) = exchange();
+        let alignment = Alignment::estimate(&server, &client, 1000.0).unwrap();
+        // The error is at most half of the least delay of the round trips, i.e. 2ms.
+        assert!((alignment.offset + 1000.0).abs() <= 2.0, "{alignment:?}");
+        assert!((alignment.drift + 0.001).abs() < 0.0002, "{alignment:?}");
+        let client_time = alignment.apply(1000.0 + 5000.0 + 5.0);
+        assert!((client_time - 5000.0).abs() <= 2.0, "{client_time}");
+    }
+
+    /// The profiles of the IDE and the Language Server, as logged by them, and converted by
+    /// `api_events_to_profile`. The Language Server's clock is ahead by 1000ms. The IDE makes
+    /// requests, and is notified after each of them; every message takes 3ms to deliver.
+    fn profiles() -> (data::Profile<Metadata>, data::Profile<Metadata>) {
+        let mut ide = format::Builder::new();
+        ide.time_offset(format::Timestamp::from_ms(5000.0));
+        ide.process("Ide");
+        let mut language_server = format::Builder::new();
+        language_server.time_offset(format::Timestamp::from_ms(0.0));
+        language_server.process("LanguageServer");
+        let mut log_backend_message = |time: f64, direction, request_id: Option<i64>, endpoint| {
+            let request_id = request_id.map(|id| id.to_string());
+            let endpoint = String::from(endpoint);
+            let message = backend::Message { direction, request_id, endpoint };
+            let time = format::Timestamp::from_ms(5000.0 + time + 1000.0);
+            language_server.metadata(time, "BackendMessage", message);
+        };
+        for id in 0..20 {
+            let time = id as f64 * 100.0;
+            let push = "executionContext/push";
+            let complete = "executionContext/executionComplete";
+            ide.metadata(format::Timestamp::from_ms(time), "RpcRequest", push);
+            log_backend_message(time + 3.0, Direction::Response, Some(id), push);
+            log_backend_message(time + 4.0, Direction::Request, Some(id), push);
+            let id = json_rpc::messages::Id(id);
+            ide.metadata(format::Timestamp::from_ms(time + 7.0), "RpcResponse", id);
+            log_backend_message(time + 50.0, Direction::Request, None, complete);
+            ide.metadata(format::Timestamp::from_ms(time + 53.0), "RpcEvent", complete);
+        }
+        let ide = ide.build_string().parse().unwrap();
+        let language_server = language_server.build_string().parse().unwrap();
+        (ide, language_server)
+    }
+
+    #[test]
+    fn estimate_from_logged_messages() {
+        let (ide, language_server) = profiles();
+        let ide = messages(&ide);
+        let language_server = messages(&language_server);
+        let alignment = Alignment::estimate(&language_server, &ide, 0.0).unwrap();
+        assert!((alignment.offset + 1000.0).abs() < 1e-6, "{alignment:?}");
+        assert!(alignment.drift.abs() < 1e-9, "{alignment:?}");
+    }
+
+    #[test]
+    fn rebase_log() {
+        let mut log = format::Builder::new();
+        log.time_offset(format::Timestamp::from_ms(1000.0));
+        log.process("LanguageServer");
+        log.metadata(format::Timestamp::from_ms(2000.0), "RpcEvent", "executionComplete");
+        let log = log.build_string();
+        let alignment = Alignment { origin: 1000.0, offset: -500.0, drift: 0.5 };
+        let rebased = rebase(&log, alignment).unwrap();
+        let profile: data::Profile<data::OpaqueMetadata> = rebased.parse().unwrap();
+        assert_eq!(profile.headers.time_offset, Some(format::Timestamp::from_ms(500.0)));
+        let times: Vec<_> = profile.metadata().map(|metadata| metadata.time.into_ms()).collect();
+        assert_eq!(times, [3000.0]);
+    }
+}
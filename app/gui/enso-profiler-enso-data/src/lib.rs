@@ -27,6 +27,7 @@ use std::fmt::Formatter;
 
 pub mod backend;
 pub mod beanpole;
+pub mod clock;
 
 
 
@@ -41,6 +42,8 @@ pub enum Metadata {
     RpcEvent(String),
     /// A message sent from the IDE to the Language Server.
     RpcRequest(json_rpc::log::RpcRequest),
+    /// A response received by the IDE from the Language Server, identified by its request's ID.
+    RpcResponse(json_rpc::messages::Id),
     /// A message between the Language Server and the Engine.
     BackendMessage(backend::Message),
     /// Performance stats gathered from the EnsoGL rendering engine.
@@ -52,6 +55,7 @@ impl Display for Metadata {
         match self {
             Metadata::RpcEvent(name) => f.collect_str(name),
             Metadata::RpcRequest(method) => f.collect_str(&method.to_string()),
+            Metadata::RpcResponse(id) => f.collect_str(&format!("Response {id}")),
             Metadata::BackendMessage(backend::Message { endpoint, .. }) => f.collect_str(endpoint),
             Metadata::RenderStats(stats) => f.collect_str(&format!("{:#?}", stats)),
         }
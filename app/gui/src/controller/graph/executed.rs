@@ -455,6 +455,100 @@ pub mod tests {
         notifications.expect_pending();
     }
 
+    // Test that the updates received when executing the graph in the recorded session are
+    // available through the executed graph.
+    #[wasm_bindgen_test]
+    fn replaying_recorded_execution() {
+        use crate::executor::test_utils::TestWithLocalPoolExecutor;
+        use crate::model::execution_context::synchronized::Notification as ExecutionUpdate;
+        use crate::test::replay;
+        use double_representation::name::project;
+        use engine_protocol::language_server;
+        use std::task::Poll;
+
+        let mut executor = TestWithLocalPoolExecutor::set_up();
+        let mut session = replay::Session::new(&mut executor, replay::EXECUTION_SESSION);
+        let logger = Logger::new("Test");
+        let parser = parser_scala::Parser::new_or_panic();
+        let repository = Rc::new(model::undo_redo::Repository::new(&logger));
+        let path = model::module::Path::from_name_segments(session.root_id(), &["Main"]).unwrap();
+        let source = parser.parse_with_metadata(replay::MAIN_MODULE.to_owned()).unwrap();
+        let module = model::module::Plain::new(path, source.ast, source.metadata, repository);
+        let method = MethodPointer {
+            module:          "local.Test.Main".into(),
+            defined_on_type: "local.Test.Main".into(),
+            name:            "main".into(),
+        };
+        let connection = session.connection.clone_ref();
+        let context = model::execution_context::Synchronized::create(
+            logger.clone_ref(),
+            connection,
+            method.clone(),
+        );
+        let context = Rc::new(replay::run(&mut executor, context).unwrap());
+
+        let mut project = model::project::MockAPI::new();
+        let project_name = project::QualifiedName::new("local", "Test");
+        model::project::test::expect_qualified_name(&mut project, &project_name);
+        model::project::test::expect_parser(&mut project, &parser);
+        model::project::test::expect_module(&mut project, Rc::new(module));
+        model::project::test::expect_execution_ctx(&mut project, context.clone_ref());
+        model::project::test::expect_root_id(&mut project, session.root_id());
+        let suggestion_db = Rc::new(model::SuggestionDatabase::new_empty());
+        model::project::test::expect_suggestion_db(&mut project, suggestion_db);
+        let project = Rc::new(project);
+        let executed_graph = replay::run(&mut executor, Handle::new(logger, project, method));
+        let executed_graph = executed_graph.unwrap();
+        let mut notifications = executed_graph.subscribe().boxed_local();
+
+        // Routing the notifications to the execution context, like the project model does.
+        let mut events = session.take_events();
+        let weak_context = Rc::downgrade(&context);
+        executor::global::spawn(async move {
+            while let Some(event) = events.next().await {
+                let update = match event {
+                    language_server::Event::Notification(
+                        language_server::Notification::ExpressionUpdates(updates),
+                    ) => ExecutionUpdate::ExpressionUpdates(updates.updates),
+                    language_server::Event::Notification(
+                        language_server::Notification::ExecutionComplete { .. },
+                    ) => ExecutionUpdate::Completed,
+                    _ => continue,
+                };
+                if let Some(context) = weak_context.upgrade() {
+                    context.handle_notification(update).unwrap();
+                }
+            }
+        });
+        drop(context);
+        executor.run_until_stalled();
+
+        let expected_ids =
+            ["6b5b2e1e-6a2e-4b53-9d5c-2b0f4c9a7a01", "6b5b2e1e-6a2e-4b53-9d5c-2b0f4c9a7a02"];
+        let expected_ids = expected_ids.map(|id| ExpressionId::parse_str(id).unwrap());
+        let nodes = executed_graph.graph().nodes().unwrap();
+        let node_ids = nodes.iter().map(|node| node.info.id()).collect_vec();
+        assert_eq!(node_ids, expected_ids);
+        let mut computed_ids = vec![];
+        while let Poll::Ready(Some(notification)) = notifications.manual_poll_next() {
+            if let Notification::ComputedValueInfo(updated_ids) = notification {
+                computed_ids.extend(updated_ids);
+            }
+        }
+        assert_eq!(computed_ids, expected_ids);
+        let registry = executed_graph.computed_value_info_registry();
+        for id in expected_ids {
+            let typename = registry.get(&id).unwrap().typename.clone();
+            assert_eq!(typename, Some(ImString::new("Standard.Base.Data.Numbers.Integer")));
+        }
+        assert_eq!(executed_graph.when_ready().boxed_local().expect_ready(), Some(()));
+
+        // Dropping the executed graph destroys the execution context.
+        drop(executed_graph);
+        executor.run_until_stalled();
+        session.expect_finished();
+    }
+
     #[wasm_bindgen_test]
     fn span_tree_context() {
         use crate::test::assert_call_info;
@@ -11,27 +11,14 @@ use crate::model::undo_redo;
 use crate::model::undo_redo::Repository;
 
 use engine_protocol::binary;
+use engine_protocol::binary::transfer::ChunkWriter;
 use engine_protocol::common::error::code;
 use engine_protocol::language_server;
 use engine_protocol::language_server::FileSystemObject;
 use engine_protocol::language_server::Path;
-use engine_protocol::types::Sha3_224;
+use ensogl::system::web::sleep;
 use json_rpc::error::RpcError;
-use sha3::Digest;
-
-
-
-// ==============
-// === Errors ===
-// ==============
-
-#[allow(missing_docs)]
-#[derive(Clone, Debug, Fail)]
-#[fail(display = "Wrong checksum of uploaded file: {}, local checksum is {}.", remote, local)]
-pub struct ChecksumMismatch {
-    pub remote: Sha3_224,
-    pub local:  Sha3_224,
-}
+use std::time::Duration;
 
 
 
@@ -41,6 +28,13 @@ pub struct ChecksumMismatch {
 
 const DATA_DIR_NAME: &str = "data";
 
+/// How many times the uploading is resumed after being interrupted, e.g. by the connection loss,
+/// before giving up.
+const MAX_UPLOAD_RESUME_ATTEMPTS: usize = 10;
+
+/// The delay before resuming the interrupted uploading.
+const UPLOAD_RESUME_DELAY: Duration = Duration::from_secs(1);
+
 
 
 // ====================
@@ -72,16 +66,22 @@ pub struct FileToUpload<DataProvider> {
     pub data: DataProvider,
 }
 
-/// The handler of uploading a given file to the specific location using the Language Server's file
-/// API.
+/// The handler of uploading a given file to the specific location using the Language Server's
+/// binary file API.
+///
+/// The file is uploaded chunk by chunk with [`ChunkWriter`], so the checksum of every chunk and of
+/// the whole uploaded file is verified. If uploading a chunk fails, e.g. because the connection
+/// has been interrupted, the next [`FileUploadProcess::upload_chunk`] call resumes the uploading
+/// and retries the failed chunk.
 #[derive(Clone, Debug)]
 pub struct FileUploadProcess<DataProvider> {
-    bin_connection:  Rc<binary::Connection>,
-    json_connection: Rc<language_server::Connection>,
-    file:            FileToUpload<DataProvider>,
-    remote_path:     Path,
-    bytes_uploaded:  u64,
-    checksum:        sha3::Sha3_224,
+    bin_connection: Rc<binary::Connection>,
+    file:           FileToUpload<DataProvider>,
+    remote_path:    Path,
+    bytes_uploaded: u64,
+    writer:         ChunkWriter,
+    /// The chunk which failed to upload, to be uploaded again once the uploading is resumed.
+    failed_chunk:   Option<Vec<u8>>,
 }
 
 /// The information if the uploading is finished or not, returned from
@@ -98,12 +98,12 @@ impl<DP: DataProvider> FileUploadProcess<DP> {
     pub fn new(
         file: FileToUpload<DP>,
         bin_connection: Rc<binary::Connection>,
-        json_connection: Rc<language_server::Connection>,
         remote_path: Path,
     ) -> Self {
         let bytes_uploaded = 0;
-        let checksum = sha3::Sha3_224::new();
-        Self { bin_connection, json_connection, file, remote_path, bytes_uploaded, checksum }
+        let writer = ChunkWriter::new(remote_path.clone());
+        let failed_chunk = None;
+        Self { bin_connection, file, remote_path, bytes_uploaded, writer, failed_chunk }
     }
 
     /// Upload next chunk. Returns information if all data has been uploaded.
@@ -111,11 +111,26 @@ impl<DP: DataProvider> FileUploadProcess<DP> {
     /// After uploading, the checksum of the uploaded file is compared with the file content digest,
     /// and an error is returned if they do not match.
     ///
+    /// If the previous call failed, the uploading is resumed and the chunk which failed to upload
+    /// is uploaded again.
+    ///
     /// The outcome of this function when uploading is finished (the `upload_chunk` have returned
     /// [`UploadingState::Finished`] before) is undefined.
     pub async fn upload_chunk(&mut self) -> FallibleResult<UploadingState> {
-        match self.file.data.next_chunk().await {
-            Ok(Some(data)) => {
+        let client = self.bin_connection.client.as_ref();
+        let chunk = match self.failed_chunk.take() {
+            Some(chunk) => {
+                info!("Resuming uploading {} to {}.", self.file.name, self.remote_path);
+                if let Err(error) = self.writer.resume(client).await {
+                    self.failed_chunk = Some(chunk);
+                    return Err(error);
+                }
+                Some(chunk)
+            }
+            None => self.file.data.next_chunk().await?,
+        };
+        match chunk {
+            Some(data) => {
                 debug!(
                     "Received chunk of {} of size {} uploading to {:?}: {:?}",
                     self.file.name,
@@ -123,17 +138,15 @@ impl<DP: DataProvider> FileUploadProcess<DP> {
                     self.remote_path,
                     data
                 );
-                let offset = self.bytes_uploaded;
-                self.bin_connection.write_bytes(&self.remote_path, offset, false, &data).await?;
-                self.checksum.input(&data);
-                self.bytes_uploaded += data.len() as u64;
+                if let Err(error) = self.writer.write_chunk(client, &data).await {
+                    self.failed_chunk = Some(data);
+                    return Err(error);
+                }
+                self.bytes_uploaded = self.writer.bytes_written();
                 Ok(UploadingState::NotFinished)
             }
-            Ok(None) => {
-                // If we haven't got any content, we need to create the file.
-                if self.bytes_uploaded == 0 {
-                    self.bin_connection.write_file(&self.remote_path, &[]).await?;
-                }
+            None => {
+                self.writer.finish(client).await?;
                 if self.bytes_uploaded != self.file.size {
                     error!(
                         "The promised file size ({}) and uploaded data length ({}) do not match. \
@@ -142,20 +155,8 @@ impl<DP: DataProvider> FileUploadProcess<DP> {
                     );
                     self.bytes_uploaded = self.file.size;
                 }
-                self.check_checksum().await?;
                 Ok(UploadingState::Finished)
             }
-            Err(err) => Err(err),
-        }
-    }
-
-    async fn check_checksum(&mut self) -> FallibleResult {
-        let remote = self.json_connection.file_checksum(&self.remote_path).await?.checksum;
-        let local = Into::<Sha3_224>::into(std::mem::take(&mut self.checksum));
-        if remote != local {
-            Err(ChecksumMismatch { remote, local }.into())
-        } else {
-            Ok(())
         }
     }
 }
@@ -241,12 +242,22 @@ impl NodeFromDroppedFileHandler {
         self.update_expression(node, Self::uploading_node_expression(&remote_name))?;
         let remote_path = self.data_path().append_im(&remote_name);
         let bin_connection = self.project.binary_rpc();
-        let json_connection = self.project.json_rpc();
-        let mut process =
-            FileUploadProcess::new(file, bin_connection, json_connection, remote_path);
-
-        while process.upload_chunk().await? == UploadingState::NotFinished {
-            self.update_metadata(node, |md| md.bytes_uploaded = process.bytes_uploaded);
+        let mut process = FileUploadProcess::new(file, bin_connection, remote_path);
+        let mut resume_attempts = 0;
+        loop {
+            match process.upload_chunk().await {
+                Ok(UploadingState::Finished) => break,
+                Ok(UploadingState::NotFinished) => {
+                    resume_attempts = 0;
+                    self.update_metadata(node, |md| md.bytes_uploaded = process.bytes_uploaded);
+                }
+                Err(err) if is_transient(&err) && resume_attempts < MAX_UPLOAD_RESUME_ATTEMPTS => {
+                    resume_attempts += 1;
+                    warning!(self.logger, "Uploading interrupted, will be resumed: {err}");
+                    sleep(UPLOAD_RESUME_DELAY).await;
+                }
+                Err(err) => return Err(err),
+            }
         }
         self.update_expression(node, Self::uploaded_node_expression(&remote_name))?;
         if let Err(err) =
@@ -334,6 +345,14 @@ impl undo_redo::Aware for NodeFromDroppedFileHandler {
 
 
 
+/// Check if the error is a transient one, e.g. caused by the connection interruption, so the
+/// failed operation may be retried.
+fn is_transient(error: &failure::Error) -> bool {
+    error.downcast_ref::<RpcError>().map_or(false, RpcError::is_transient)
+}
+
+
+
 // ======================================
 // === File Name Collisions Resolving ===
 // ======================================
@@ -374,8 +393,10 @@ mod test {
     use crate::executor::test_utils::TestWithLocalPoolExecutor;
     use crate::test::mock;
 
+    use engine_protocol::binary::message::FileSegment;
     use engine_protocol::language_server::response;
     use engine_protocol::language_server::FileAttributes;
+    use engine_protocol::types::Sha3_224;
     use engine_protocol::types::UTCDateTime;
     use futures::future;
     use futures::SinkExt;
@@ -436,11 +457,7 @@ mod test {
             Self::new_with_file_name(chunks, TEST_FILE.to_owned(), TEST_FILE.to_owned())
         }
 
-        fn setup_uploading_expectations(
-            &self,
-            json_client: &language_server::MockClient,
-            binary_client: &mut binary::MockClient,
-        ) {
+        fn setup_uploading_expectations(&self, binary_client: &mut binary::MockClient) {
             let mut write_seq = Sequence::new();
             let mut offset = 0;
             for chunk in self.chunks.iter().cloned() {
@@ -457,10 +474,14 @@ mod test {
                 offset += chunk_len as u64;
             }
             let checksum = self.checksum.clone();
-            json_client.expect.file_checksum(enclose!((self.path => path) move |p| {
-                assert_eq!(*p,path);
-                Ok(response::FileChecksum{checksum})
-            }));
+            let segment =
+                FileSegment { path: self.path.clone(), byte_offset: 0, length: offset };
+            binary_client
+                .expect_checksum_bytes()
+                .withf(move |s| *s == segment)
+                .times(1)
+                .in_sequence(&mut write_seq)
+                .returning(move |_| future::ready(Ok(checksum.clone())).boxed_local());
         }
 
         fn file_to_upload(&self) -> FileToUpload<TestProvider> {
@@ -495,17 +516,18 @@ mod test {
     impl UploadingFixture {
         fn new(data: TestData) -> Self {
             let mut binary_cli = binary::MockClient::new();
-            let json_cli = language_server::MockClient::default();
-            json_cli.require_all_calls();
-            data.setup_uploading_expectations(&json_cli, &mut binary_cli);
+            data.setup_uploading_expectations(&mut binary_cli);
+            Self::new_with_client(data, binary_cli)
+        }
+
+        fn new_with_client(data: TestData, binary_cli: binary::MockClient) -> Self {
             let (file, provider_sink) = data.file_to_upload_async();
             let bin_con = Rc::new(binary::Connection::new_mock(binary_cli));
-            let json_con = Rc::new(language_server::Connection::new_mock(json_cli));
 
             Self {
                 test:          TestWithLocalPoolExecutor::set_up(),
                 chunks:        data.chunks.into_iter(),
-                process:       FileUploadProcess::new(file, bin_con, json_con, data.path),
+                process:       FileUploadProcess::new(file, bin_con, data.path),
                 provider_sink: Some(provider_sink),
             }
         }
@@ -549,6 +571,78 @@ mod test {
         assert!(test.next_chunk_result().is_err());
     }
 
+    #[test]
+    fn resuming_interrupted_uploading() {
+        fn expect_write(
+            client: &mut binary::MockClient,
+            seq: &mut Sequence,
+            path: &Path,
+            (offset, overwrite): (u64, bool),
+            chunk: &[u8],
+            interrupted: bool,
+        ) {
+            let path = path.clone();
+            let chunk = chunk.to_vec();
+            let checksum = Sha3_224::new(&chunk);
+            client
+                .expect_write_bytes()
+                .withf(move |p, off, ow, ch| {
+                    *p == path && ch == chunk && *off == offset && *ow == overwrite
+                })
+                .times(1)
+                .in_sequence(seq)
+                .returning(move |_, _, _, _| {
+                    let result = if interrupted {
+                        Err(RpcError::ConnectionInterrupted.into())
+                    } else {
+                        Ok(checksum.clone())
+                    };
+                    future::ready(result).boxed_local()
+                });
+        }
+
+        fn expect_checksum(
+            client: &mut binary::MockClient,
+            seq: &mut Sequence,
+            path: &Path,
+            contents: &[u8],
+        ) {
+            let length = contents.len() as u64;
+            let segment = FileSegment { path: path.clone(), byte_offset: 0, length };
+            let checksum = Sha3_224::new(contents);
+            client
+                .expect_checksum_bytes()
+                .withf(move |s| *s == segment)
+                .times(1)
+                .in_sequence(seq)
+                .returning(move |_| future::ready(Ok(checksum.clone())).boxed_local());
+        }
+
+        let data = TestData::new(vec![vec![1, 2, 3], vec![4, 5]]);
+        let path = data.path.clone();
+        let mut client = binary::MockClient::new();
+        let mut seq = Sequence::new();
+        expect_write(&mut client, &mut seq, &path, (0, false), &[1, 2, 3], false);
+        expect_write(&mut client, &mut seq, &path, (3, false), &[4, 5], true);
+        expect_checksum(&mut client, &mut seq, &path, &[1, 2, 3]);
+        expect_write(&mut client, &mut seq, &path, (3, true), &[4, 5], false);
+        expect_checksum(&mut client, &mut seq, &path, &[1, 2, 3, 4, 5]);
+        let mut test = UploadingFixture::new_with_client(data, client);
+
+        assert_eq!(test.next_chunk_result().unwrap(), UploadingState::NotFinished);
+        let error = test.next_chunk_result().unwrap_err();
+        assert!(is_transient(&error));
+        assert_eq!(test.process.bytes_uploaded, 3);
+
+        // The resumed uploading retries the interrupted chunk instead of reading the next one.
+        let mut resumed = test.process.upload_chunk().boxed_local();
+        test.test.run_until_stalled();
+        assert_eq!(resumed.expect_ready().unwrap(), UploadingState::NotFinished);
+        drop(resumed);
+        assert_eq!(test.process.bytes_uploaded, 5);
+        assert_eq!(test.next_chunk_result().unwrap(), UploadingState::Finished);
+    }
+
 
     // === NodeFromDroppedFileHandler Tests ===
 
@@ -567,7 +661,7 @@ mod test {
                     FileSystemObject::File { name: "other".to_owned(), path: data_path() };
                 Ok(response::FileList { paths: vec![other_file] })
             });
-            data.setup_uploading_expectations(json_rpc, binary_rpc);
+            data.setup_uploading_expectations(binary_rpc);
         });
 
         let handler = NodeFromDroppedFileHandler::new(logger, fixture.project, fixture.graph);
@@ -646,7 +740,7 @@ mod test {
                             let file_2 = File { name: other_collision_name, path: data_path() };
                             Ok(response::FileList { paths: vec![file_1, file_2] })
                         });
-                        data.setup_uploading_expectations(json_rpc, binary_rpc);
+                        data.setup_uploading_expectations(binary_rpc);
                     });
 
                 let project = fixture.project;
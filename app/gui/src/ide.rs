@@ -28,6 +28,14 @@ pub use initializer::Initializer;
 pub const BACKEND_DISCONNECTED_MESSAGE: &str =
     "Connection to the backend has been lost. Please try restarting IDE.";
 
+/// Text that shows up in the statusbar when any of the backend connections is interrupted and the
+/// IDE tries to re-establish it.
+pub const BACKEND_RECONNECTING_MESSAGE: &str =
+    "Connection to the backend has been interrupted. Reconnecting...";
+
+/// Text that shows up in the statusbar when the interrupted backend connection is restored.
+pub const BACKEND_RECONNECTED_MESSAGE: &str = "Connection to the backend has been restored.";
+
 const ALIVE_LOG_INTERVAL_SEC: u64 = 60;
 
 
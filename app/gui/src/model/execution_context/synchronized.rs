@@ -70,7 +70,7 @@ impl ExecutionContext {
     ) -> impl Future<Output = FallibleResult<Self>> {
         async move {
             info!("Creating.");
-            let id = language_server.client.create_execution_context().await?.context_id;
+            let id = language_server.client.create_execution_context(None).await?.context_id;
             let logger = Logger::new_sub(&parent, iformat! {"ExecutionContext {id}"});
             let model = model::execution_context::Plain::new(root_definition);
             info!("Created. Id: {id}.");
@@ -81,6 +81,27 @@ impl ExecutionContext {
         }
     }
 
+    /// Create the execution context in the LanguageServer again, e.g. after the connection has
+    /// been re-established. The context keeps its id, and its stack and attached visualizations
+    /// are restored.
+    #[profile(Debug)]
+    pub async fn restore(&self) -> FallibleResult {
+        info!("Restoring.");
+        self.language_server.client.create_execution_context(Some(&self.id)).await?;
+        self.push_root_frame().await?;
+        for stack_item in self.model.stack_items().collect_vec() {
+            let call = language_server::LocalCall { expression_id: stack_item.call };
+            let frame = language_server::StackItem::LocalCall(call);
+            self.language_server.push_to_execution_context(&self.id, &frame).await?;
+        }
+        for vis in self.model.all_visualizations_info() {
+            let config = vis.config(self.id);
+            self.language_server.attach_visualisation(&vis.id, &vis.expression_id, &config).await?;
+        }
+        info!("Restored.");
+        Ok(())
+    }
+
     fn push_root_frame(&self) -> impl Future<Output = FallibleResult> {
         let method_pointer = self.model.entry_point.clone();
         let this_argument_expression = default();
@@ -395,7 +416,7 @@ pub mod test {
         fn mock_create_destroy_calls(data: &MockData, ls: &mut language_server::MockClient) {
             let id = data.context_id;
             let result = Self::expected_creation_response(data);
-            expect_call!(ls.create_execution_context()    => Ok(result));
+            expect_call!(ls.create_execution_context(context_id = None) => Ok(result));
             expect_call!(ls.destroy_execution_context(id) => Ok(()));
         }
 
@@ -511,6 +532,54 @@ pub mod test {
         });
     }
 
+    #[test]
+    fn restoring_context() {
+        let method_pointer = QualifiedMethodPointer::module_method(
+            MockData::new().module_qualified_name(),
+            Identifier::from_text("foo").unwrap(),
+        );
+        let vis = Visualization {
+            id: model::execution_context::VisualizationId::new_v4(),
+            expression_id: model::execution_context::ExpressionId::new_v4(),
+            method_pointer,
+            arguments: vec![],
+        };
+        let expression_id = model::execution_context::ExpressionId::new_v4();
+        let Fixture { data, mut test, context } = Fixture::new_customized(|ls, data| {
+            let id = data.context_id;
+            let vis_id = vis.id;
+            let ast_id = vis.expression_id;
+            let config = vis.config(id);
+            let call_frame = language_server::LocalCall { expression_id };
+            let stack_item = language_server::StackItem::LocalCall(call_frame);
+            let root_frame = language_server::ExplicitCall {
+                method_pointer:                   data.main_method_pointer(),
+                this_argument_expression:         None,
+                positional_arguments_expressions: vec![],
+            };
+            let root_item = language_server::StackItem::ExplicitCall(root_frame);
+            let creation_response = Fixture::expected_creation_response(data);
+            let pushed_item = stack_item.clone();
+            let attached_config = config.clone();
+            expect_call!(ls.push_to_execution_context(id,pushed_item) => Ok(()));
+            expect_call!(ls.attach_visualisation(vis_id,ast_id,attached_config) => Ok(()));
+            // Restoring the context.
+            expect_call!(ls.create_execution_context(context_id = Some(id)) => Ok(creation_response));
+            expect_call!(ls.push_to_execution_context(id,root_item) => Ok(()));
+            expect_call!(ls.push_to_execution_context(id,stack_item) => Ok(()));
+            expect_call!(ls.attach_visualisation(vis_id,ast_id,config) => Ok(()));
+        });
+        test.run_task(async move {
+            let item =
+                LocalCall { call: expression_id, definition: data.main_method_pointer() };
+            context.push(item.clone()).await.unwrap();
+            let _ = context.attach_visualization(vis.clone()).await.unwrap();
+            context.restore().await.unwrap();
+            assert_eq!((item,), context.model.stack_items().expect_tuple());
+            assert_eq!(vec![vis.id], context.model.active_visualizations());
+        });
+    }
+
     // TODO [mwu]
     //   The test below has been disabled as shaky, see https://github.com/enso-org/ide/issues/637
     #[ignore]
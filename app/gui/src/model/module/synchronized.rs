@@ -18,12 +18,15 @@ use double_representation::definition::DefinitionInfo;
 use double_representation::graph::Id;
 use double_representation::import;
 use engine_protocol::language_server;
+use engine_protocol::language_server::response;
 use engine_protocol::language_server::TextEdit;
 use engine_protocol::types::Sha3_224;
 use enso_text::text;
 use enso_text::Location;
 use enso_text::Range;
 use flo_stream::Subscriber;
+use futures::channel::mpsc::UnboundedReceiver;
+use futures::channel::mpsc::UnboundedSender;
 use parser_scala::api::SourceFile;
 use parser_scala::Parser;
 
@@ -50,6 +53,14 @@ impl ContentSummary {
         let end_of_file = text.utf16_code_unit_location_of_location(end_location_bytes);
         Self { digest: Sha3_224::from_parts(parts), end_of_file }
     }
+
+    /// Get summary of the file content, as opened in the Language Server.
+    fn from_opened(opened: &response::OpenTextFile) -> Self {
+        let content: text::Rope = (&opened.content).into();
+        let end_of_file_byte = content.last_line_end_location();
+        let end_of_file = content.utf16_code_unit_location_of_location(end_of_file_byte);
+        Self { digest: opened.current_version.clone(), end_of_file }
+    }
 }
 
 /// The information about module's content. In addition to minimal summery defined in
@@ -133,6 +144,21 @@ impl LanguageServerContent {
 
 
 
+// ===================
+// === RunnerEvent ===
+// ===================
+
+/// Event processed by the task synchronizing the module with the Language Server.
+#[derive(Debug)]
+enum RunnerEvent {
+    /// The module has been changed.
+    Notification(Notification),
+    /// The module has been reopened in the Language Server, which has the given content.
+    Reopened(ContentSummary),
+}
+
+
+
 // ===========================
 // === Synchronized Module ===
 // ===========================
@@ -148,6 +174,8 @@ impl LanguageServerContent {
 pub struct Module {
     model:           model::module::Plain,
     language_server: Rc<language_server::Connection>,
+    /// Passes the Language Server content of the reopened module to the `runner`.
+    reopened:        UnboundedSender<ContentSummary>,
 }
 
 
@@ -168,30 +196,42 @@ impl Module {
         let file_path = path.file_path().clone();
         info!("Opening module {file_path}");
         let opened = language_server.client.open_text_file(&file_path).await?;
-        let content: text::Rope = (&opened.content).into();
         info!("Read content of the module {path}, digest is {:?}", opened.current_version);
-        let end_of_file_byte = content.last_line_end_location();
-        let end_of_file = content.utf16_code_unit_location_of_location(end_of_file_byte);
+        let summary = ContentSummary::from_opened(&opened);
         // TODO[ao] We should not fail here when metadata are malformed, but discard them and set
         //  default instead.
         let source = parser.parse_with_metadata(opened.content)?;
-        let digest = opened.current_version;
-        let summary = ContentSummary { digest, end_of_file };
         let model = model::module::Plain::new(path, source.ast, source.metadata, repository);
-        let this = Rc::new(Module { model, language_server });
+        let (reopened, reopened_receiver) = futures::channel::mpsc::unbounded();
+        let this = Rc::new(Module { model, language_server, reopened });
         let content = this.model.serialized_content()?;
         let first_invalidation = this.full_invalidation(&summary, content);
-        executor::global::spawn(Self::runner(this.clone_ref(), summary, first_invalidation));
+        let runner = Self::runner(this.clone_ref(), summary, first_invalidation, reopened_receiver);
+        executor::global::spawn(runner);
         Ok(this)
     }
 
+    /// Open the module in the Language Server again, e.g. after the connection has been
+    /// re-established. The content in the Language Server is then replaced with the module's
+    /// state.
+    #[profile(Detail)]
+    pub async fn reopen(&self) -> FallibleResult {
+        let file_path = self.path().file_path().clone();
+        info!("Reopening module {file_path}");
+        let opened = self.language_server.client.open_text_file(&file_path).await?;
+        info!("Read content of the module {file_path}, digest is {:?}", opened.current_version);
+        channel::emit(&self.reopened, ContentSummary::from_opened(&opened));
+        Ok(())
+    }
+
     /// Create a module mock.
     pub fn mock(model: model::module::Plain) -> Rc<Self> {
         let client = language_server::MockClient::default();
         client.expect.close_text_file(|_| Ok(()));
         // We don't expect any other call, because we don't execute `runner()`.
         let language_server = language_server::Connection::new_mock_rc(client);
-        Rc::new(Module { model, language_server })
+        let (reopened, _) = futures::channel::mpsc::unbounded();
+        Rc::new(Module { model, language_server, reopened })
     }
 }
 
@@ -295,12 +335,17 @@ impl API for Module {
 impl Module {
     /// Returns the asynchronous task which listens for all module changes and sends proper updates
     /// to Language Server.
+    ///
+    /// When the module is reopened, its whole content is sent to the Language Server.
     fn runner(
         self: Rc<Self>,
         initial_ls_content: ContentSummary,
         first_invalidation: impl Future<Output = FallibleResult<ParsedContentSummary>>,
+        reopened: UnboundedReceiver<ContentSummary>,
     ) -> impl Future<Output = ()> {
-        let mut subscriber = self.model.subscribe();
+        let notifications = self.model.subscribe().map(RunnerEvent::Notification);
+        let reopened = reopened.map(RunnerEvent::Reopened);
+        let mut events = futures::stream::select(notifications, reopened);
 
         async move {
             let first_invalidation = first_invalidation.await;
@@ -309,14 +354,22 @@ impl Module {
             drop(self);
 
             loop {
-                let notification = subscriber.next().await;
+                let event = events.next().await;
                 let this = weak.upgrade();
-                match (notification, this) {
-                    (Some(notification), Some(this)) => {
+                match (event, this) {
+                    (Some(RunnerEvent::Notification(notification)), Some(this)) => {
                         debug!("Processing a notification: {notification:?}");
                         let result = this.handle_notification(&ls_content, notification).await;
                         ls_content = this.new_ls_content_info(ls_content.summary().clone(), result)
                     }
+                    (Some(RunnerEvent::Reopened(reopened_content)), Some(this)) => {
+                        debug!("Synchronizing the reopened module: {reopened_content:?}");
+                        let result = match this.model.serialized_content() {
+                            Ok(content) => this.full_invalidation(&reopened_content, content).await,
+                            Err(err) => Err(err),
+                        };
+                        ls_content = this.new_ls_content_info(reopened_content, result)
+                    }
                     _ => break,
                 }
             }
@@ -773,6 +826,68 @@ pub mod test {
         Runner::run(test);
     }
 
+    #[wasm_bindgen_test]
+    fn reopening_module() {
+        let initial_code = "main =\n    println \"Hello World!\"";
+        let mut data = crate::test::mock::Unified::new();
+        data.set_code(initial_code);
+
+        let test = |runner: &mut Runner| {
+            let edit_handler = LsClientSetup::new_for_mock_data(&data);
+            let mut fixture = data.fixture_customize(|data, client, _| {
+                data.expect_opening_module(client);
+                data.expect_closing_module(client);
+                // Opening module and metadata generation.
+                edit_handler.expect_full_invalidation(client);
+                // Reopening module, the Language Server has the initial content again.
+                data.expect_opening_module(client);
+                let initial_version = Sha3_224::new(initial_code.as_bytes());
+                client.expect.apply_text_file_edit(move |edits| {
+                    let (edit,) = edits.edits.iter().expect_tuple();
+                    assert_eq!(edits.old_version, initial_version);
+                    assert!(edit.text.starts_with(initial_code));
+                    Ok(())
+                });
+            });
+
+            let module = fixture.synchronized_module();
+            runner.perhaps_run_until_stalled(&mut fixture);
+            // Synchronous in test conditions, like opening the module.
+            module.reopen().boxed_local().expect_ok();
+            runner.perhaps_run_until_stalled(&mut fixture);
+        };
+        Runner::run(test);
+    }
+
+    #[wasm_bindgen_test]
+    fn replaying_recorded_session() {
+        use crate::executor::test_utils::TestWithLocalPoolExecutor;
+        use crate::test::replay;
+
+        let mut executor = TestWithLocalPoolExecutor::set_up();
+        let session = replay::Session::new(&mut executor, replay::MODULE_SESSION);
+        // The content sent by the IDE contains the randomly generated ids of the AST nodes, so
+        // neither the full invalidation, the id map updates, nor the versions match the recording.
+        session.transport.ignore_param("text/applyEdit", "/edit/edits/0");
+        session.transport.ignore_param("text/applyEdit", "/edit/oldVersion");
+        session.transport.ignore_param("text/applyEdit", "/edit/newVersion");
+
+        let parser = Parser::new_or_panic();
+        let path = Path::from_name_segments(session.root_id(), &["Main"]).unwrap();
+        let logger = Logger::new("Test");
+        let repository = Rc::new(model::undo_redo::Repository::new(&logger));
+        let connection = session.connection.clone_ref();
+        let module = Module::open(path, connection, parser.clone_ref(), repository);
+        let module = replay::run(&mut executor, module).unwrap();
+        // Replacing `42` with `43`.
+        let change = TextChange { range: (51..53).into(), text: "43".to_string() };
+        module.apply_code_change(change, &parser, default()).unwrap();
+        executor.run_until_stalled();
+        drop(module);
+        executor.run_until_stalled();
+        session.expect_finished();
+    }
+
     #[test]
     fn handle_insertion_edits_bug180558676() {
         let source = text::Rope::from("from Standard.Base import all\n\nmain =\n    operator1 = 0.up_to 100 . to_vector . map .noise\n    operator1.sort\n");
@@ -168,6 +168,10 @@ pub type Synchronized = synchronized::Project;
 pub enum Notification {
     /// One of the backend connections has been lost.
     ConnectionLost(BackendConnection),
+    /// One of the backend connections has been interrupted and is being re-established.
+    ConnectionInterrupted(BackendConnection),
+    /// The interrupted backend connection has been re-established and its session restored.
+    ConnectionRestored(BackendConnection),
     /// Indicates that the project VCS status has changed.
     VcsStatusChanged(VcsStatus),
 }
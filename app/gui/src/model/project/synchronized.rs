@@ -8,6 +8,7 @@ use crate::model::execution_context::synchronized::Notification as ExecutionUpda
 use crate::model::execution_context::VisualizationUpdateData;
 use crate::model::module;
 use crate::model::SuggestionDatabase;
+use crate::transport::recording;
 use crate::transport::web::WebSocket;
 
 use double_representation::name::project;
@@ -25,7 +26,11 @@ use engine_protocol::project_manager::MissingComponentAction;
 use engine_protocol::project_manager::ProjectName;
 use flo_stream::Subscriber;
 use json_rpc::error::RpcError;
+use json_rpc::ReconnectingTransport;
+use json_rpc::RetryPolicy;
+use json_rpc::Transport;
 use parser_scala::Parser;
+use std::time::Duration;
 
 
 
@@ -101,6 +106,11 @@ impl ExecutionContextsRegistry {
     pub fn insert(&self, context: Rc<execution_context::Synchronized>) {
         self.0.borrow_mut().insert(context.id(), context);
     }
+
+    /// Get handles to all registered execution contexts which are still alive.
+    pub fn all(&self) -> Vec<Rc<execution_context::Synchronized>> {
+        self.0.borrow().values().collect()
+    }
 }
 
 
@@ -195,6 +205,72 @@ async fn check_vcs_status_and_notify(
 
 
 
+// ==============================
+// === Connection Restoration ===
+// ==============================
+
+/// The upper bound of the delay between the attempts of re-establishing a lost connection with
+/// the Language Server.
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(10);
+
+/// Wrap the socket connected to `url` into a transport re-establishing the connection whenever it
+/// is lost. The reconnecting attempts are never given up.
+fn reconnecting_transport(
+    parent: impl AnyLogger,
+    socket: WebSocket,
+    url: String,
+) -> ReconnectingTransport {
+    let logger = Logger::new_sub(parent, "ReconnectingTransport");
+    socket.disable_auto_reconnect();
+    let connector = move || {
+        let logger = logger.clone_ref();
+        let url = url.clone();
+        async move {
+            let socket = WebSocket::new_opened(&logger, &url).await?;
+            socket.disable_auto_reconnect();
+            let transport: Box<dyn Transport> = Box::new(socket);
+            Ok(transport)
+        }
+        .boxed_local()
+    };
+    let policy = RetryPolicy::new(usize::MAX).max_backoff(MAX_RECONNECT_BACKOFF);
+    let transport = ReconnectingTransport::new(socket, connector, policy);
+    executor::global::spawn(transport.runner());
+    transport
+}
+
+/// Restore the Language Server session after the JSON-RPC connection has been re-established.
+///
+/// The Language Server does not remember anything about the previous connection, so the protocol
+/// connection is initialized again, the capabilities re-acquired, the modules re-opened and the
+/// execution contexts re-created together with their visualizations. Failing to restore a single
+/// module or execution context is logged and does not stop restoring the others.
+#[profile(Detail)]
+async fn restore_json_rpc_session(
+    language_server: Rc<language_server::Connection>,
+    module_registry: Weak<model::registry::Registry<module::Path, module::Synchronized>>,
+    execution_contexts: Weak<ExecutionContextsRegistry>,
+) -> FallibleResult {
+    language_server.restore_session().await?;
+    let capability = CapabilityRegistration::create_receives_suggestions_database_updates();
+    language_server.acquire_capability(&capability.method, &capability.register_options).await?;
+    let modules = module_registry.upgrade().map(|registry| registry.loaded()).unwrap_or_default();
+    for module in modules {
+        if let Err(err) = module.reopen().await {
+            error!("Failed to re-open module {}: {err}", module.path());
+        }
+    }
+    let contexts = execution_contexts.upgrade().map(|registry| registry.all()).unwrap_or_default();
+    for context in contexts {
+        if let Err(err) = context.restore().await {
+            error!("Failed to restore execution context {}: {err}", context.id());
+        }
+    }
+    Ok(())
+}
+
+
+
 // =============
 // === Model ===
 // =============
@@ -352,8 +428,18 @@ impl Project {
         let client_id = Uuid::new_v4();
         let json_ws = WebSocket::new_opened(&parent, &language_server_rpc).await?;
         let binary_ws = WebSocket::new_opened(&parent, &language_server_bin).await?;
-        let client_json = language_server::Client::new(json_ws);
-        let client_binary = binary::Client::new(&parent, binary_ws);
+        let json_transport = reconnecting_transport(&parent, json_ws, language_server_rpc);
+        let binary_transport = reconnecting_transport(&parent, binary_ws, language_server_bin);
+        let (client_json, client_binary) = if enso_config::ARGS.record_session {
+            let json_transport = recording::record(json_transport, recording::LANGUAGE_SERVER_JSON);
+            let binary_transport =
+                recording::record(binary_transport, recording::LANGUAGE_SERVER_BINARY);
+            let client_json = language_server::Client::new(json_transport);
+            (client_json, binary::Client::new(&parent, binary_transport))
+        } else {
+            let client_json = language_server::Client::new(json_transport);
+            (client_json, binary::Client::new(&parent, binary_transport))
+        };
         crate::executor::global::spawn(client_json.runner());
         crate::executor::global::spawn(client_binary.runner());
         let connection_json =
@@ -405,6 +491,7 @@ impl Project {
     ) -> impl Fn(engine_protocol::binary::Event) -> futures::future::Ready<()> {
         let publisher = self.notifications.clone_ref();
         let weak_execution_contexts = Rc::downgrade(&self.execution_contexts);
+        let weak_connection = Rc::downgrade(&self.language_server_bin);
         move |event| {
             debug!("Received an event from the binary protocol: {event:?}");
             use engine_protocol::binary::client::Event;
@@ -430,10 +517,28 @@ impl Project {
                     let which = model::project::BackendConnection::LanguageServerBinary;
                     let notification = model::project::Notification::ConnectionLost(which);
                     publisher.notify(notification);
-                    // TODO [wmu]
-                    //   The connection should be reestablished, see
-                    //   https://github.com/enso-org/ide/issues/145
                 }
+                Event::Reconnecting => {
+                    warn!("Lost binary connection with the Language Server, reconnecting.");
+                    let which = model::project::BackendConnection::LanguageServerBinary;
+                    let notification = model::project::Notification::ConnectionInterrupted(which);
+                    publisher.notify(notification);
+                }
+                Event::Reconnected =>
+                    if let Some(connection) = weak_connection.upgrade() {
+                        let publisher = publisher.clone_ref();
+                        executor::global::spawn(async move {
+                            if let Err(err) = connection.restore_session().await {
+                                error!("Failed to restore the binary protocol session: {err}");
+                            } else {
+                                info!("Binary connection with the Language Server restored.");
+                                let which = model::project::BackendConnection::LanguageServerBinary;
+                                let notification =
+                                    model::project::Notification::ConnectionRestored(which);
+                                publisher.notify(notification);
+                            }
+                        });
+                    },
                 Event::Error(error) => {
                     error!("Error emitted by the binary data connection: {error}.");
                 }
@@ -481,6 +586,8 @@ impl Project {
         let language_server = self.json_rpc().clone_ref();
         let weak_suggestion_db = Rc::downgrade(&self.suggestion_db);
         let weak_content_roots = Rc::downgrade(&self.content_roots);
+        let weak_module_registry = Rc::downgrade(&self.module_registry);
+        let weak_execution_contexts = Rc::downgrade(&self.execution_contexts);
         let execution_update_handler = self.execution_update_handler();
         move |event| {
             debug!("Received an event from the json-rpc protocol: {event:?}");
@@ -489,8 +596,8 @@ impl Project {
 
             // Profiler logging
             if let Event::Notification(notification) = &event {
-                let name: &'static str = notification.into();
-                log_rpc_event(name);
+                let method: &'static str = notification.into();
+                log_rpc_event(method);
             }
 
             // Event Handling
@@ -557,9 +664,31 @@ impl Project {
                     let which = model::project::BackendConnection::LanguageServerJson;
                     let notification = model::project::Notification::ConnectionLost(which);
                     publisher.notify(notification);
-                    // TODO [wmu]
-                    //  The connection should be reestablished,
-                    //  see https://github.com/enso-org/ide/issues/145
+                }
+                Event::Reconnecting => {
+                    warn!("Lost JSON-RPC connection with the Language Server, reconnecting.");
+                    let which = model::project::BackendConnection::LanguageServerJson;
+                    let notification = model::project::Notification::ConnectionInterrupted(which);
+                    publisher.notify(notification);
+                }
+                Event::Reconnected => {
+                    let publisher = publisher.clone_ref();
+                    let restoring = restore_json_rpc_session(
+                        language_server.clone_ref(),
+                        weak_module_registry.clone(),
+                        weak_execution_contexts.clone(),
+                    );
+                    executor::global::spawn(async move {
+                        if let Err(err) = restoring.await {
+                            error!("Failed to restore the Language Server session: {err}");
+                        } else {
+                            info!("JSON-RPC connection with the Language Server restored.");
+                            let which = model::project::BackendConnection::LanguageServerJson;
+                            let notification =
+                                model::project::Notification::ConnectionRestored(which);
+                            publisher.notify(notification);
+                        }
+                    });
                 }
                 Event::Error(error) => {
                     error!("Error emitted by the JSON-RPC data connection: {error}.");
@@ -810,6 +939,14 @@ mod test {
         run(Notification::ConnectionLost(LanguageServerJson), |f| {
             f.json_events_sender.send(json_rpc::Event::Closed).boxed_local().expect_ok();
         });
+
+        run(Notification::ConnectionInterrupted(LanguageServerBinary), |f| {
+            f.binary_events_sender.send(binary::Event::Reconnecting).boxed_local().expect_ok();
+        });
+
+        run(Notification::ConnectionInterrupted(LanguageServerJson), |f| {
+            f.json_events_sender.send(json_rpc::Event::Reconnecting).boxed_local().expect_ok();
+        });
     }
 
     #[wasm_bindgen_test]
@@ -110,6 +110,19 @@ where K: Clone + Eq + Hash
         }
     }
 
+    /// Get handles to all items which are loaded at the moment. The items being loaded are
+    /// skipped.
+    pub fn loaded(&self) -> Vec<Rc<V>> {
+        let registry = self.registry.borrow();
+        let entries = registry.values();
+        entries
+            .filter_map(|entry| match entry {
+                Entry::Loaded(handle) => Some(handle),
+                Entry::Loading(_) => None,
+            })
+            .collect()
+    }
+
     async fn get(&self, key: &K) -> Result<Option<Rc<V>>, LoadingError> {
         loop {
             let entry = self.registry.borrow_mut().get(key);
@@ -317,6 +317,16 @@ impl Project {
                     let message = view::status_bar::event::Label::from(message);
                     model.status_bar.add_event(message);
                 }
+                Notification::ConnectionInterrupted(_) => {
+                    let message = crate::BACKEND_RECONNECTING_MESSAGE;
+                    let message = view::status_bar::event::Label::from(message);
+                    model.status_bar.add_event(message);
+                }
+                Notification::ConnectionRestored(_) => {
+                    let message = crate::BACKEND_RECONNECTED_MESSAGE;
+                    let message = view::status_bar::event::Label::from(message);
+                    model.status_bar.add_event(message);
+                }
                 Notification::VcsStatusChanged(VcsStatus::Dirty) => {
                     model.set_project_changed(true);
                 }
@@ -536,3 +536,99 @@ impl Runner {
         Self::run_with(BitField32 { raw: n }, test)
     }
 }
+
+
+
+// ==============
+// === Replay ===
+// ==============
+
+/// Utilities for replaying the sessions with the Language Server recorded by
+/// `json_rpc::RecordingTransport`. The recordings are stored in the `tests/recordings` directory
+/// of this crate.
+pub mod replay {
+    use super::*;
+
+    use engine_protocol::language_server::API as _;
+    use futures::stream::LocalBoxStream;
+    use json_rpc::test_util::transport::replay::ReplayTransport;
+
+    /// The id of the client in the recorded sessions.
+    pub const CLIENT_ID: Uuid = Uuid::from_u128(0xC11E47);
+
+    /// The session opening the `Main` module, replacing `42` with `43` in its code and closing it.
+    pub const MODULE_SESSION: &str = include_str!("../tests/recordings/module.jsonl");
+
+    /// The session executing the `main` method of the `Main` module.
+    pub const EXECUTION_SESSION: &str = include_str!("../tests/recordings/execution.jsonl");
+
+    /// The contents of the `Main` module of the recorded project.
+    pub const MAIN_MODULE: &str = r#"from Standard.Base import all
+
+main =
+    number = 42
+    number + 1
+
+
+
+
+#### METADATA ####
+[[{"index":{"value":42},"size":{"value":11}},"6b5b2e1e-6a2e-4b53-9d5c-2b0f4c9a7a01"],[{"index":{"value":58},"size":{"value":10}},"6b5b2e1e-6a2e-4b53-9d5c-2b0f4c9a7a02"]]
+{}
+"#;
+
+    /// Run the future on the executor until it completes. Panics if it gets stalled before.
+    pub fn run<R: 'static>(
+        executor: &mut TestWithLocalPoolExecutor,
+        future: impl Future<Output = R> + 'static,
+    ) -> R {
+        let result = Rc::new(RefCell::new(None));
+        let sink = result.clone_ref();
+        executor.run_task(async move { *sink.borrow_mut() = Some(future.await) });
+        executor.run_until_stalled();
+        let result = result.borrow_mut().take();
+        result.expect("The future has stalled.")
+    }
+
+    /// A Language Server connection replaying a recorded session.
+    #[derive(Derivative)]
+    #[derivative(Debug)]
+    pub struct Session {
+        /// The transport serving the recording.
+        pub transport:  ReplayTransport,
+        /// The connection initialized over the `transport`.
+        pub connection: Rc<language_server::Connection>,
+        #[derivative(Debug = "ignore")]
+        events:         Option<LocalBoxStream<'static, language_server::Event>>,
+    }
+
+    impl Session {
+        /// Replay the `recording`, initializing the connection as the recorded client.
+        pub fn new(executor: &mut TestWithLocalPoolExecutor, recording: &str) -> Self {
+            let transport = ReplayTransport::from_recording(recording).unwrap();
+            let client = language_server::Client::new(transport.clone_ref());
+            executor::global::spawn(client.runner());
+            let events = Some(client.events());
+            let connection = language_server::Connection::new(client, CLIENT_ID);
+            let connection = Rc::new(run(executor, connection).unwrap());
+            Self { transport, connection, events }
+        }
+
+        /// The id of the project content root.
+        pub fn root_id(&self) -> Uuid {
+            self.connection.project_root().id()
+        }
+
+        /// Take the stream of the Language Server client events, including the notifications.
+        /// Panics if it has already been taken.
+        pub fn take_events(&mut self) -> LocalBoxStream<'static, language_server::Event> {
+            self.events.take().expect("The events have already been taken.")
+        }
+
+        /// Check that the whole recorded session has been replayed.
+        pub fn expect_finished(&self) {
+            let pending = self.transport.pending_frames();
+            assert!(pending.is_empty(), "The session has not been replayed: {pending:?}");
+        }
+    }
+}
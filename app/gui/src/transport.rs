@@ -5,6 +5,7 @@
 // === Export ===
 // ==============
 
+pub mod recording;
 #[cfg(test)]
 pub mod test_utils;
 pub mod web;
@@ -0,0 +1,75 @@
+//! Recording the sessions with the backend, enabled by the `record_session` argument.
+//!
+//! The frames are passed to the Electron, which writes them to a JSON-lines file per connection
+//! (see the `--record-session` option of the client). Such recordings can be served back in tests
+//! by `json_rpc::test_util::transport::replay::ReplayTransport`.
+
+use crate::prelude::*;
+
+use enso_debug_api as debug_api;
+use json_rpc::RecordingTransport;
+use json_rpc::Transport;
+use std::io::Write;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The name of the recorded Language Server JSON-RPC connection.
+pub const LANGUAGE_SERVER_JSON: &str = "language-server-json";
+
+/// The name of the recorded Language Server binary connection.
+pub const LANGUAGE_SERVER_BINARY: &str = "language-server-binary";
+
+
+
+// ====================
+// === ElectronSink ===
+// ====================
+
+/// A sink passing each complete line written to it to the Electron, as a frame of the named
+/// connection.
+#[derive(Debug)]
+struct ElectronSink {
+    connection: String,
+    buffer:     Vec<u8>,
+}
+
+impl ElectronSink {
+    fn new(connection: impl Into<String>) -> Self {
+        Self { connection: connection.into(), buffer: default() }
+    }
+}
+
+impl Write for ElectronSink {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        while let Some(end) = self.buffer.iter().position(|byte| *byte == b'\n') {
+            let line = self.buffer.drain(..=end).collect_vec();
+            let line = String::from_utf8_lossy(&line[..end]);
+            debug_api::record_session_frame(&self.connection, &line);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+
+
+// ==============
+// === Public ===
+// ==============
+
+/// Record the frames transferred by `transport` as the named connection. The recording runner is
+/// spawned on the global executor.
+pub fn record<T: Transport>(transport: T, connection: &str) -> RecordingTransport<T> {
+    info!("Recording the {connection} connection.");
+    let mut transport = RecordingTransport::new(transport, ElectronSink::new(connection));
+    executor::global::spawn(transport.runner());
+    transport
+}
@@ -343,6 +343,16 @@ impl WebSocket {
         with(self.model.borrow_mut(), |mut model| f(model.deref_mut()))
     }
 
+    /// Disable the automatic reconnecting of the underlying JS socket. Should be used when the
+    /// socket is wrapped by a transport that handles the reconnecting on its own, like
+    /// `json_rpc::ReconnectingTransport`.
+    pub fn disable_auto_reconnect(&self) {
+        with(self.model.borrow_mut(), |mut model| {
+            model.auto_reconnect = false;
+            model.on_close_internal.clear_callback();
+        });
+    }
+
     /// Sets callback for the `close` event.
     pub fn set_on_close(&mut self, f: impl FnMut(web_sys::CloseEvent) + 'static) {
         self.with_borrow_mut_model(move |model| {
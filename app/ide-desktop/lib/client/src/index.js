@@ -169,6 +169,14 @@ optParser.options('save-profile', {
     type: `string`,
 })
 
+optParser.options('record-session', {
+    group: debugOptionsGroup,
+    describe:
+        'Record the messages exchanged with the Language Server and write them to JSON-lines files (one per connection) in the given directory. The recordings can be replayed in tests.',
+    requiresArg: true,
+    type: `string`,
+})
+
 optParser.options('workflow', {
     group: debugOptionsGroup,
     describe: 'Specify a workflow for profiling. Must be used with --entry-point=profile.',
@@ -602,6 +610,21 @@ function createWindow() {
             fss.writeFileSync(args.saveProfile, data)
         })
     }
+    if (args.recordSession) {
+        urlCfg.record_session = true
+        fss.mkdirSync(args.recordSession, { recursive: true })
+        // The recording of each connection is started anew in each run.
+        const recordedConnections = new Set()
+        Electron.ipcMain.on('record-session', (event, connection, frame) => {
+            const file = path.join(args.recordSession, `${connection}.jsonl`)
+            if (recordedConnections.has(connection)) {
+                fss.appendFileSync(file, frame + '\n')
+            } else {
+                recordedConnections.add(connection)
+                fss.writeFileSync(file, frame + '\n')
+            }
+        })
+    }
     if (args.workflow) {
         urlCfg.test_workflow = args.workflow
     }
@@ -46,6 +46,12 @@ contextBridge.exposeInMainWorld('enso_profiling_data', {
     },
 })
 
+// Record the sessions with the backend.
+contextBridge.exposeInMainWorld('enso_session_recording', {
+    // Delivers a single frame of the session transferred through the named connection.
+    record: (connection, frame) => ipcRenderer.send('record-session', connection, frame),
+})
+
 // Access to the system console that Electron was run from.
 contextBridge.exposeInMainWorld('enso_console', {
     // Print an error message with `console.error`.
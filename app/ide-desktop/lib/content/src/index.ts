@@ -159,6 +159,12 @@ class Config {
         'When profiling the application (e.g. with the `./run profile` command), this argument ' +
             'chooses what is profiled.'
     )
+    recordSession: config.Param<boolean> = new config.Param(
+        false,
+        'Controls whether the sessions with the Language Server should be recorded, so they can ' +
+            'be replayed in tests. The recordings are written by the Electron client (see its ' +
+            '`--record-session` option).'
+    )
     skipMinVersionCheck: config.Param<boolean> = new config.Param(
         Version.isDev(),
         'Controls whether the minimum engine version check should be performed. It is set to ' +
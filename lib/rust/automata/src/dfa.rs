@@ -9,6 +9,8 @@ use crate::nfa::Nfa;
 use crate::state;
 use crate::symbol::Symbol;
 
+use std::collections::BTreeSet;
+
 
 
 // =============
@@ -69,6 +71,46 @@ impl Dfa {
         self.links.safe_index(current_state.id(), ix).unwrap_or_default()
     }
 
+    /// The rule accepted in the given state, if any.
+    ///
+    /// The `rules` are the NFA states returned by [`Nfa::new_pattern`] for the rules' patterns, in
+    /// the order of priority: if several rules accept in the same state, the first of them is
+    /// returned.
+    pub fn accepted_rule(&self, state: State, rules: &[nfa::State]) -> Option<nfa::State> {
+        let sources = self.sources.get(state.id())?;
+        rules.iter().find(|rule| sources.contains(rule)).copied()
+    }
+
+    /// Check if the whole `input` is matched by any of the `rules` and return the accepting rule.
+    /// See [`Dfa::accepted_rule`] for the meaning of `rules`.
+    pub fn matches(&self, input: &str, rules: &[nfa::State]) -> Option<nfa::State> {
+        let mut state = Self::START_STATE;
+        for char in input.chars() {
+            state = self.next_state(state, &Symbol::new(char as u64));
+            if state.is_invalid() {
+                return None;
+            }
+        }
+        self.accepted_rule(state, rules)
+    }
+
+    /// Find the longest prefix of `input` matched by any of the `rules`. See
+    /// [`Dfa::accepted_rule`] for the meaning of `rules`.
+    pub fn longest_match(&self, input: &str, rules: &[nfa::State]) -> Option<Match> {
+        let mut state = Self::START_STATE;
+        let mut result = self.accepted_rule(state, rules).map(|rule| Match { rule, length: 0 });
+        for (index, char) in input.chars().enumerate() {
+            state = self.next_state(state, &Symbol::new(char as u64));
+            if state.is_invalid() {
+                break;
+            }
+            if let Some(rule) = self.accepted_rule(state, rules) {
+                result = Some(Match { rule, length: index + 1 });
+            }
+        }
+        result
+    }
+
     /// Convert the automata to GraphViz Dot code for the deubgging purposes.
     pub fn as_graphviz_code(&self) -> FallibleResult<String> {
         use std::fmt::Write;
@@ -90,6 +132,18 @@ impl Dfa {
 }
 
 
+// === Match ===
+
+/// The result of [`Dfa::longest_match`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Match {
+    /// The accepting rule, see [`Dfa::accepted_rule`].
+    pub rule:   nfa::State,
+    /// The number of matched characters.
+    pub length: usize,
+}
+
+
 // === Trait Impls ===
 
 impl From<Vec<Vec<usize>>> for Matrix<State> {
@@ -165,6 +219,157 @@ impl From<&Nfa> for Dfa {
 
 
 
+// ====================
+// === Minimisation ===
+// ====================
+
+impl Dfa {
+    /// Minimise the automaton using the
+    /// [Hopcroft's algorithm](https://en.wikipedia.org/wiki/DFA_minimization#Hopcroft's_algorithm).
+    ///
+    /// Two states are considered equivalent if they accept the same subset of `rules` (see
+    /// [`Dfa::accepted_rule`]) and their transitions lead to equivalent states. Therefore, the
+    /// `sources` of the minimised automaton contain only the rule states. The states from which no
+    /// rule can be reached are removed, and the transitions to them lead to [`State::INVALID`].
+    /// The asymptotic complexity is `O(n·m·log(n))` for `n` states and `m` alphabet divisions.
+    pub fn minimize(&self, rules: &[nfa::State]) -> Dfa {
+        let state_count = self.links.rows;
+        let symbol_count = self.links.columns;
+        // The implicit state which all the invalid transitions lead to.
+        let dead = state_count;
+        let target = |state: usize, symbol: usize| {
+            let target = if state == dead { State::INVALID } else { self.links[(state, symbol)] };
+            if target.is_invalid() {
+                dead
+            } else {
+                target.id()
+            }
+        };
+        let accepted_rules = |state: usize| -> Vec<nfa::State> {
+            let sources = self.sources.get(state).map_or(&[][..], |sources| sources.as_slice());
+            rules.iter().filter(|rule| sources.contains(rule)).copied().collect()
+        };
+
+        let mut sources_of = vec![vec![vec![]; state_count + 1]; symbol_count];
+        for state in 0..=state_count {
+            for (symbol, sources_by_target) in sources_of.iter_mut().enumerate() {
+                sources_by_target[target(state, symbol)].push(state);
+            }
+        }
+
+        let mut partition = Partition::new(state_count + 1, accepted_rules);
+        let mut worklist = (0..partition.blocks.len()).collect_vec();
+        let mut in_worklist = vec![true; partition.blocks.len()];
+        while let Some(splitter) = worklist.pop() {
+            in_worklist[splitter] = false;
+            let splitter_states = partition.blocks[splitter].clone();
+            for sources_by_target in &sources_of {
+                let predecessors =
+                    splitter_states.iter().flat_map(|&t| &sources_by_target[t]).copied();
+                for (block, new_block) in partition.split(predecessors) {
+                    in_worklist.push(false);
+                    let to_add = if in_worklist[block] {
+                        new_block
+                    } else if partition.blocks[block].len() <= partition.blocks[new_block].len() {
+                        block
+                    } else {
+                        new_block
+                    };
+                    worklist.push(to_add);
+                    in_worklist[to_add] = true;
+                }
+            }
+        }
+        partition.into_dfa(self, dead, target, accepted_rules)
+    }
+}
+
+/// A partition of the DFA states into blocks, used by the [`Dfa::minimize`].
+#[derive(Clone, Debug)]
+struct Partition {
+    blocks:   Vec<Vec<usize>>,
+    block_of: Vec<usize>,
+}
+
+impl Partition {
+    /// Create the initial partition, where states are in the same block iff they have the same
+    /// `key`.
+    fn new<K: Ord>(state_count: usize, key: impl Fn(usize) -> K) -> Self {
+        let mut block_of_key = BTreeMap::<K, usize>::new();
+        let mut blocks: Vec<Vec<usize>> = default();
+        let mut block_of = Vec::with_capacity(state_count);
+        for state in 0..state_count {
+            let block = *block_of_key.entry(key(state)).or_insert_with(|| {
+                blocks.push(default());
+                blocks.len() - 1
+            });
+            blocks[block].push(state);
+            block_of.push(block);
+        }
+        Self { blocks, block_of }
+    }
+
+    /// Split every block into the states contained in `states` and the rest. Returns pairs of the
+    /// original block and the newly created one for every block which was actually split.
+    fn split(&mut self, states: impl Iterator<Item = usize>) -> Vec<(usize, usize)> {
+        let mut touched = BTreeMap::<usize, BTreeSet<usize>>::new();
+        for state in states {
+            touched.entry(self.block_of[state]).or_default().insert(state);
+        }
+        let mut splits = vec![];
+        for (block, inside) in touched {
+            if inside.len() < self.blocks[block].len() {
+                let new_block = self.blocks.len();
+                self.blocks[block].retain(|state| !inside.contains(state));
+                for &state in &inside {
+                    self.block_of[state] = new_block;
+                }
+                self.blocks.push(inside.into_iter().collect());
+                splits.push((block, new_block));
+            }
+        }
+        splits
+    }
+
+    /// Build the automaton having one state per block. The blocks are ordered by their smallest
+    /// state, so the block containing the start state becomes the new start state.
+    fn into_dfa(
+        self,
+        dfa: &Dfa,
+        dead: usize,
+        target: impl Fn(usize, usize) -> usize,
+        accepted_rules: impl Fn(usize) -> Vec<nfa::State>,
+    ) -> Dfa {
+        let dead_block = self.block_of[dead];
+        let start_block = self.block_of[Dfa::START_STATE.id()];
+        let alphabet = dfa.alphabet.clone();
+        if start_block == dead_block {
+            let links = Matrix::new(1, dfa.links.columns);
+            return Dfa { alphabet, links, sources: vec![default()] };
+        }
+        let mut live_blocks =
+            (0..self.blocks.len()).filter(|&block| block != dead_block).collect_vec();
+        live_blocks.sort_by_key(|&block| self.blocks[block].iter().min().copied());
+        let mut new_state_of_block = vec![State::INVALID; self.blocks.len()];
+        for (index, &block) in live_blocks.iter().enumerate() {
+            new_state_of_block[block] = State::new(index);
+        }
+        let mut links = Matrix::new(live_blocks.len(), dfa.links.columns);
+        let mut sources = Vec::with_capacity(live_blocks.len());
+        for (index, &block) in live_blocks.iter().enumerate() {
+            let representative = self.blocks[block][0];
+            for symbol in 0..dfa.links.columns {
+                let target_block = self.block_of[target(representative, symbol)];
+                links[(index, symbol)] = new_state_of_block[target_block];
+            }
+            sources.push(accepted_rules(representative));
+        }
+        Dfa { alphabet, links, sources }
+    }
+}
+
+
+
 // =============
 // === Tests ===
 // =============
@@ -1,5 +1,7 @@
 //! Implementation of Finite State Automata in both Nondeterministic and Deterministic forms,
-//! together with a set of conversions, processing, analysis, and visualization utilities.
+//! together with a set of conversions, processing, analysis, and visualization utilities. The
+//! patterns may be built with combinators or parsed from a textual regex subset, and the resulting
+//! DFA may be minimised and run over the input.
 
 // === Features ===
 #![feature(test)]
@@ -27,6 +29,7 @@ pub mod data;
 pub mod dfa;
 pub mod nfa;
 pub mod pattern;
+pub mod regex;
 pub mod state;
 pub mod symbol;
 
@@ -193,6 +193,11 @@ impl Pattern {
     pub fn repeat_between(pat: &Pattern, min: usize, max: usize) -> Self {
         (min..max).fold(Self::never(), |p, n| p | Self::repeat(pat, n))
     }
+
+    /// Parse the pattern from a textual regex. See [`crate::regex`] for the supported syntax.
+    pub fn regex(regex: &str) -> Result<Self, crate::regex::ParseError> {
+        crate::regex::parse(regex)
+    }
 }
 
 
@@ -0,0 +1,496 @@
+//! A parser of a textual regex subset producing [`Pattern`]s.
+//!
+//! The supported syntax is:
+//! - literal characters, and `.` matching any character but the newline;
+//! - character classes like `[abc]`, `[a-z_]` or negated `[^0-9]`;
+//! - escapes: `\n`, `\r`, `\t`, escaped special characters (like `\.` or `\[`), and the ASCII
+//!   classes `\d`, `\w`, `\s` with their negations `\D`, `\W`, `\S`;
+//! - grouping with `(...)`, alternation with `|`;
+//! - repetitions: `*`, `+`, `?`, `{n}`, `{n,}` and `{n,m}`.
+//!
+//! The pattern always matches the whole input, so there are no anchors (`^` and `$`).
+
+use crate::prelude::*;
+
+use crate::pattern::Pattern;
+use crate::symbol::Symbol;
+
+use std::ops::RangeInclusive;
+
+
+
+// =============
+// === Error ===
+// =============
+
+/// An error of parsing the regex. The positions are indices of characters in the regex.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, Eq, Fail, PartialEq)]
+pub enum ParseError {
+    #[fail(display = "Unexpected end of the regex.")]
+    UnexpectedEnd,
+    #[fail(display = "Unexpected character '{}' at position {}.", character, position)]
+    UnexpectedCharacter { character: char, position: usize },
+    #[fail(display = "Unknown escape sequence '\\{}' at position {}.", character, position)]
+    UnknownEscape { character: char, position: usize },
+    #[fail(display = "Invalid character range at position {}.", position)]
+    InvalidRange { position: usize },
+    #[fail(display = "Invalid repetition bounds at position {}.", position)]
+    InvalidRepetition { position: usize },
+}
+
+
+
+// =============
+// === Parse ===
+// =============
+
+/// The largest symbol representing a character.
+const MAX_CHAR: u64 = char::MAX as u64;
+
+/// Characters which must be escaped to be matched literally.
+const SPECIAL_CHARACTERS: &str = "\\.*+?()[]{}|^$-";
+
+/// Parse the `regex` into a [`Pattern`]. See the module docs for the supported syntax.
+pub fn parse(regex: &str) -> Result<Pattern, ParseError> {
+    let mut parser = Parser { chars: regex.chars().collect(), position: 0 };
+    let pattern = parser.alternative()?;
+    match parser.peek() {
+        None => Ok(pattern),
+        Some(character) => Err(parser.unexpected(character)),
+    }
+}
+
+/// A set of characters, as produced by character classes and escapes.
+type CharRanges = Vec<RangeInclusive<u64>>;
+
+/// A result of parsing an escape sequence.
+#[derive(Clone, Debug)]
+enum Escape {
+    Char(char),
+    Class(CharRanges),
+}
+
+/// A recursive-descent parser of the regex.
+#[derive(Clone, Debug)]
+struct Parser {
+    chars:    Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn peek_second(&self) -> Option<char> {
+        self.chars.get(self.position + 1).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let next = self.peek();
+        if next.is_some() {
+            self.position += 1;
+        }
+        next
+    }
+
+    fn next_or_err(&mut self) -> Result<char, ParseError> {
+        self.next().ok_or(ParseError::UnexpectedEnd)
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        let matches = self.peek() == Some(expected);
+        if matches {
+            self.position += 1;
+        }
+        matches
+    }
+
+    fn unexpected(&self, character: char) -> ParseError {
+        ParseError::UnexpectedCharacter { character, position: self.position }
+    }
+
+    /// `alternative := sequence ('|' sequence)*`
+    fn alternative(&mut self) -> Result<Pattern, ParseError> {
+        let mut pattern = self.sequence()?;
+        while self.eat('|') {
+            pattern = pattern | self.sequence()?;
+        }
+        Ok(pattern)
+    }
+
+    /// `sequence := repetition*`
+    fn sequence(&mut self) -> Result<Pattern, ParseError> {
+        let mut pattern = None;
+        while !matches!(self.peek(), None | Some('|') | Some(')')) {
+            let item = self.repetition()?;
+            pattern = Some(match pattern {
+                Some(pattern) => pattern >> item,
+                None => item,
+            });
+        }
+        Ok(pattern.unwrap_or(Pattern::Always))
+    }
+
+    /// `repetition := atom ('*' | '+' | '?' | bounds)*`
+    fn repetition(&mut self) -> Result<Pattern, ParseError> {
+        let mut pattern = self.atom()?;
+        loop {
+            pattern = match self.peek() {
+                Some('*') => {
+                    self.next();
+                    pattern.many()
+                }
+                Some('+') => {
+                    self.next();
+                    pattern.many1()
+                }
+                Some('?') => {
+                    self.next();
+                    pattern.opt()
+                }
+                Some('{') => self.bounds(&pattern)?,
+                _ => return Ok(pattern),
+            }
+        }
+    }
+
+    /// `bounds := '{' number (',' number?)? '}'`
+    fn bounds(&mut self, pattern: &Pattern) -> Result<Pattern, ParseError> {
+        let position = self.position;
+        let invalid = ParseError::InvalidRepetition { position };
+        self.next();
+        let min = self.number().ok_or(invalid)?;
+        let max = if self.eat(',') { self.number() } else { Some(min) };
+        if !self.eat('}') {
+            return Err(invalid);
+        }
+        match max {
+            Some(max) if max < min => Err(invalid),
+            Some(max) => Ok(Pattern::repeat_between(pattern, min, max + 1)),
+            None => Ok(Pattern::repeat(pattern, min) >> pattern.many()),
+        }
+    }
+
+    fn number(&mut self) -> Option<usize> {
+        let start = self.position;
+        while self.peek().map_or(false, |character| character.is_ascii_digit()) {
+            self.position += 1;
+        }
+        self.chars[start..self.position].iter().collect::<String>().parse().ok()
+    }
+
+    /// `atom := '(' alternative ')' | '[' class ']' | '.' | '\' escape | character`
+    fn atom(&mut self) -> Result<Pattern, ParseError> {
+        let position = self.position;
+        match self.next_or_err()? {
+            '(' => {
+                let pattern = self.alternative()?;
+                match self.next() {
+                    Some(')') => Ok(pattern),
+                    Some(character) => Err(ParseError::UnexpectedCharacter { character, position }),
+                    None => Err(ParseError::UnexpectedEnd),
+                }
+            }
+            '[' => self.class(),
+            '.' => Ok(ranges_to_pattern(complement(vec![newline()]))),
+            '\\' => Ok(match self.escape()? {
+                Escape::Char(character) => Pattern::char(character),
+                Escape::Class(ranges) => ranges_to_pattern(ranges),
+            }),
+            character if SPECIAL_CHARACTERS.contains(character) && character != '-' =>
+                Err(ParseError::UnexpectedCharacter { character, position }),
+            character => Ok(Pattern::char(character)),
+        }
+    }
+
+    /// `class := '^'? (item | item '-' item)+` where the `]` is allowed as the first item.
+    fn class(&mut self) -> Result<Pattern, ParseError> {
+        let negated = self.eat('^');
+        let mut ranges = CharRanges::new();
+        let mut first = true;
+        while first || !self.eat(']') {
+            first = false;
+            let position = self.position;
+            let start = match self.class_item()? {
+                Escape::Char(character) => character,
+                Escape::Class(class) => {
+                    ranges.extend(class);
+                    continue;
+                }
+            };
+            let is_range = self.peek() == Some('-') && !matches!(self.peek_second(), Some(']'));
+            if is_range {
+                self.next();
+                let end = match self.class_item()? {
+                    Escape::Char(character) if character >= start => character,
+                    _ => return Err(ParseError::InvalidRange { position }),
+                };
+                ranges.push(start as u64..=end as u64);
+            } else {
+                ranges.push(start as u64..=start as u64);
+            }
+        }
+        let ranges = if negated { complement(ranges) } else { ranges };
+        Ok(ranges_to_pattern(ranges))
+    }
+
+    fn class_item(&mut self) -> Result<Escape, ParseError> {
+        match self.next_or_err()? {
+            '\\' => self.escape(),
+            character => Ok(Escape::Char(character)),
+        }
+    }
+
+    /// Parse the escape sequence. The backslash must have already been consumed.
+    fn escape(&mut self) -> Result<Escape, ParseError> {
+        let position = self.position - 1;
+        let digit = || vec!['0' as u64..='9' as u64];
+        let word = || {
+            let ranges = [('0', '9'), ('A', 'Z'), ('_', '_'), ('a', 'z')];
+            ranges.iter().map(|(start, end)| *start as u64..=*end as u64).collect_vec()
+        };
+        let space = || {
+            let spaces = ['\t', '\n', '\u{b}', '\u{c}', '\r', ' '];
+            spaces.iter().map(|c| *c as u64..=*c as u64).collect_vec()
+        };
+        Ok(match self.next_or_err()? {
+            'n' => Escape::Char('\n'),
+            'r' => Escape::Char('\r'),
+            't' => Escape::Char('\t'),
+            'd' => Escape::Class(digit()),
+            'w' => Escape::Class(word()),
+            's' => Escape::Class(space()),
+            'D' => Escape::Class(complement(digit())),
+            'W' => Escape::Class(complement(word())),
+            'S' => Escape::Class(complement(space())),
+            character if SPECIAL_CHARACTERS.contains(character) => Escape::Char(character),
+            character => return Err(ParseError::UnknownEscape { character, position }),
+        })
+    }
+}
+
+
+
+// =================
+// === Utilities ===
+// =================
+
+fn newline() -> RangeInclusive<u64> {
+    '\n' as u64..='\n' as u64
+}
+
+/// Sort the ranges and merge the overlapping and adjacent ones.
+fn normalize(mut ranges: CharRanges) -> CharRanges {
+    ranges.sort_by_key(|range| *range.start());
+    let mut result: CharRanges = vec![];
+    for range in ranges {
+        match result.last_mut() {
+            Some(last) if *range.start() <= last.end().saturating_add(1) => {
+                let end = *last.end().max(range.end());
+                *last = *last.start()..=end;
+            }
+            _ => result.push(range),
+        }
+    }
+    result
+}
+
+/// All the characters not contained in `ranges`.
+fn complement(ranges: CharRanges) -> CharRanges {
+    let mut result = vec![];
+    let mut next_start = 0;
+    for range in normalize(ranges) {
+        if *range.start() > next_start {
+            result.push(next_start..=range.start() - 1);
+        }
+        next_start = range.end() + 1;
+    }
+    if next_start <= MAX_CHAR {
+        result.push(next_start..=MAX_CHAR);
+    }
+    result
+}
+
+fn ranges_to_pattern(ranges: CharRanges) -> Pattern {
+    let to_pattern = |range: RangeInclusive<u64>| {
+        Pattern::symbols(Symbol::new(*range.start())..=Symbol::new(*range.end()))
+    };
+    let mut patterns = normalize(ranges).into_iter().map(to_pattern).collect_vec();
+    match patterns.len() {
+        0 => Pattern::Never,
+        1 => patterns.remove(0),
+        _ => Pattern::Or(patterns),
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::dfa::Dfa;
+    use crate::nfa;
+    use crate::nfa::Nfa;
+
+    use ::regex::Regex as ReferenceRegex;
+    use rand::seq::SliceRandom;
+    use rand::Rng;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+
+    // === Utilities ===
+
+    fn range(start: char, end: char) -> Pattern {
+        Pattern::range(start..=end)
+    }
+
+    /// Build a DFA with a single rule for each of `patterns`, in the same order.
+    fn compile(patterns: &[Pattern]) -> (Dfa, Vec<nfa::State>) {
+        let mut nfa = Nfa::new();
+        let rules = patterns.iter().map(|pattern| nfa.new_pattern(nfa.start, pattern)).collect();
+        (Dfa::from(&nfa), rules)
+    }
+
+    /// Generate a random regex in the supported syntax, which is also accepted by the reference
+    /// regex engine.
+    fn random_regex(rng: &mut impl Rng, depth: usize) -> String {
+        const ATOMS: &[&str] = &["a", "b", "c", ".", "[ab]", "[^a]", "[a-c_]", "\\d", "\\w", "\\s"];
+        if depth == 0 {
+            return ATOMS.choose(rng).unwrap().to_string();
+        }
+        match rng.gen_range(0..8) {
+            0 | 1 => (0..rng.gen_range(2..4)).map(|_| random_regex(rng, depth - 1)).join(""),
+            2 => (0..rng.gen_range(2..4)).map(|_| random_regex(rng, depth - 1)).join("|"),
+            3 => format!("({})*", random_regex(rng, depth - 1)),
+            4 => format!("({})+", random_regex(rng, depth - 1)),
+            5 => format!("({})?", random_regex(rng, depth - 1)),
+            6 => {
+                let min = rng.gen_range(0..3);
+                let max = rng.gen_range(min..4);
+                format!("({}){{{},{}}}", random_regex(rng, depth - 1), min, max)
+            }
+            _ => format!("({})", random_regex(rng, depth - 1)),
+        }
+    }
+
+    /// Convert the regex to the syntax of the reference engine, where the `\d`, `\w` and `\s`
+    /// classes are not limited to ASCII.
+    fn to_reference(regex: &str) -> String {
+        let regex = regex.replace("\\d", "[0-9]").replace("\\w", "[0-9A-Za-z_]");
+        regex.replace("\\s", "[\\t\\n\\x0B\\x0C\\r ]")
+    }
+
+    fn random_input(rng: &mut impl Rng) -> String {
+        const CHARS: &[char] = &['a', 'b', 'c', '_', '0', ' ', '\n', 'ł'];
+        (0..rng.gen_range(0..8)).map(|_| *CHARS.choose(rng).unwrap()).collect()
+    }
+
+
+    // === The Tests ===
+
+    #[test]
+    fn parsing() {
+        let a = || Pattern::char('a');
+        let b = || Pattern::char('b');
+        assert_eq!(parse(""), Ok(Pattern::Always));
+        assert_eq!(parse("ab"), Ok(Pattern::Seq(vec![a(), b()])));
+        assert_eq!(parse("a|b|"), Ok(Pattern::Or(vec![a(), b(), Pattern::Always])));
+        assert_eq!(parse("(a|b)*"), Ok((a() | b()).many()));
+        assert_eq!(parse("a+b?"), Ok(a().many1() >> b().opt()));
+        assert_eq!(parse("a{2}"), Ok(Pattern::repeat_between(&a(), 2, 3)));
+        assert_eq!(parse("a{1,}"), Ok(Pattern::repeat(&a(), 1) >> a().many()));
+        assert_eq!(parse("[a-c_]"), Ok(Pattern::Or(vec![range('_', '_'), range('a', 'c')])));
+        assert_eq!(parse("[]a-]"), Ok(Pattern::Or(vec![range('-', '-'), range(']', ']'), a()])));
+        assert_eq!(parse("\\d"), Ok(range('0', '9')));
+        assert_eq!(
+            parse("\\.\\n"),
+            Ok(Pattern::Seq(vec![Pattern::char('.'), Pattern::char('\n')]))
+        );
+        let not_newline = Pattern::Or(vec![
+            Pattern::symbols(Symbol::new(0)..=Symbol::new('\n' as u64 - 1)),
+            Pattern::symbols(Symbol::new('\n' as u64 + 1)..=Symbol::new(MAX_CHAR)),
+        ]);
+        assert_eq!(parse("."), Ok(not_newline.clone()));
+        assert_eq!(parse("[^\\n]"), Ok(not_newline));
+    }
+
+    #[test]
+    fn parsing_errors() {
+        let unexpected =
+            |character, position| ParseError::UnexpectedCharacter { character, position };
+        assert_eq!(parse("(a"), Err(ParseError::UnexpectedEnd));
+        assert_eq!(parse("[ab"), Err(ParseError::UnexpectedEnd));
+        assert_eq!(parse("a)"), Err(unexpected(')', 1)));
+        assert_eq!(parse("*a"), Err(unexpected('*', 0)));
+        assert_eq!(parse("a|?"), Err(unexpected('?', 2)));
+        assert_eq!(parse("\\q"), Err(ParseError::UnknownEscape { character: 'q', position: 0 }));
+        assert_eq!(parse("a[z-a]"), Err(ParseError::InvalidRange { position: 2 }));
+        assert_eq!(parse("a{3,2}"), Err(ParseError::InvalidRepetition { position: 1 }));
+        assert_eq!(parse("a{x}"), Err(ParseError::InvalidRepetition { position: 1 }));
+    }
+
+    #[test]
+    fn longest_match_reports_rule() {
+        let keyword = parse("if").unwrap();
+        let identifier = parse("[a-z_][a-z_0-9]*").unwrap();
+        let number = parse("\\d+(\\.\\d+)?").unwrap();
+        let (dfa, rules) = compile(&[keyword, identifier, number]);
+        let dfa = dfa.minimize(&rules);
+        let longest_match = |input| dfa.longest_match(input, &rules).map(|m| (m.rule, m.length));
+        assert_eq!(longest_match("if x"), Some((rules[0], 2)));
+        assert_eq!(longest_match("iffy = 1"), Some((rules[1], 4)));
+        assert_eq!(longest_match("12.5e3"), Some((rules[2], 4)));
+        assert_eq!(longest_match("12.e3"), Some((rules[2], 2)));
+        assert_eq!(longest_match("+1"), None);
+        assert_eq!(dfa.matches("if", &rules), Some(rules[0]));
+        assert_eq!(dfa.matches("i", &rules), Some(rules[1]));
+        assert_eq!(dfa.matches("1.", &rules), None);
+    }
+
+    #[test]
+    fn minimization_reduces_states() {
+        let (dfa, rules) = compile(&[parse("(a|b)*abb").unwrap()]);
+        let minimal = dfa.minimize(&rules);
+        assert_eq!(minimal.links.rows, 4);
+        assert!(minimal.links.rows < dfa.links.rows);
+        assert_eq!(minimal.minimize(&rules), minimal);
+        let (never, rules) = compile(&[Pattern::Never]);
+        assert_eq!(never.minimize(&rules).links.rows, 1);
+    }
+
+    /// Check the parser, the NFA to DFA conversion, the minimisation and the matching runtime
+    /// against the reference regex engine.
+    #[test]
+    fn equivalence_with_reference_engine() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        for _ in 0..300 {
+            let regex = random_regex(&mut rng, 3);
+            let reference =
+                ReferenceRegex::new(&format!("^(?:{})$", to_reference(&regex))).unwrap();
+            let (dfa, rules) = compile(&[parse(&regex).unwrap()]);
+            let minimal = dfa.minimize(&rules);
+            assert!(minimal.links.rows <= dfa.links.rows);
+            for _ in 0..30 {
+                let input = random_input(&mut rng);
+                let expected = reference.is_match(&input).then_some(rules[0]);
+                assert_eq!(dfa.matches(&input, &rules), expected, "{regex:?} on {input:?}");
+                assert_eq!(minimal.matches(&input, &rules), expected, "{regex:?} on {input:?}");
+                let prefixes =
+                    input.char_indices().map(|(ix, _)| ix).chain(iter::once(input.len()));
+                let lengths =
+                    prefixes.enumerate().filter(|(_, end)| reference.is_match(&input[..*end]));
+                let expected_length = lengths.map(|(length, _)| length).last();
+                let longest_match = minimal.longest_match(&input, &rules).map(|m| m.length);
+                assert_eq!(longest_match, expected_length, "{regex:?} on {input:?}");
+            }
+        }
+    }
+}
@@ -93,6 +93,23 @@ pub fn load_profiles() -> Option<impl Future<Output = Vec<String>>> {
 
 
 
+// ==========================
+// === Recording sessions ===
+// ==========================
+
+/// Record a frame of the session with the backend. The `connection` names the connection the frame
+/// has been transferred through, the `frame` is a single line of the recording.
+///
+/// If the Electron API is not available, the frame is printed to the console.
+pub fn record_session_frame(connection: &str, frame: &str) {
+    match session_recording_api() {
+        Some(api) => api.record(connection, frame),
+        None => web_sys::console::log_2(&connection.into(), &frame.into()),
+    }
+}
+
+
+
 // ===========
 // === FFI ===
 // ===========
@@ -131,6 +148,20 @@ pub mod js {
         }
     }
 
+    /// Enso Session Recording API
+    pub mod session_recording {
+        use wasm_bindgen::prelude::*;
+
+        #[wasm_bindgen]
+        extern "C" {
+            pub type SessionRecording;
+
+            #[wasm_bindgen(method, js_name = record)]
+            #[allow(unsafe_code)]
+            pub fn record(this: &SessionRecording, connection: &str, frame: &str);
+        }
+    }
+
     /// Enso Console API
     pub mod console {
         use wasm_bindgen::prelude::*;
@@ -165,3 +196,4 @@ macro_rules! window_prop_getter {
 window_prop_getter!("enso_console"; console -> js::console::Console);
 window_prop_getter!("enso_lifecycle"; lifecycle_controller -> js::lifecycle::Lifecycle);
 window_prop_getter!("enso_profiling_data"; profiling_data_api -> js::profiling_data::ProfilingData);
+window_prop_getter!("enso_session_recording"; session_recording_api -> js::session_recording::SessionRecording);
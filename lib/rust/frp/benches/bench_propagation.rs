@@ -0,0 +1,97 @@
+//! This file contains benchmarks comparing the overhead of the depth-first and the transactional
+//! event propagation modes.
+
+use criterion::black_box;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::Criterion;
+use enso_frp::Network;
+use enso_frp::Propagation;
+use enso_frp::Source;
+use enso_frp::Stream;
+use std::time::Duration;
+
+
+
+// =================
+// === Utilities ===
+// =================
+
+/// The number of nodes in the benchmarked chains and the number of branches in the benchmarked
+/// diamonds.
+const SIZE: usize = 100;
+
+/// The base configuration for the benchmarks.
+fn bench_config() -> Criterion {
+    Criterion::default()
+        .measurement_time(Duration::from_secs(10))
+        .warm_up_time(Duration::from_secs(3))
+        .sample_size(25)
+}
+
+/// Both propagation modes, labeled for benchmark names.
+const MODES: [(&str, Propagation); 2] =
+    [("Depth-First", Propagation::DepthFirst), ("Transactional", Propagation::Transactional)];
+
+/// Create a network of `source -> map -> map -> ...` with `length` map nodes.
+fn gen_chain(propagation: Propagation, length: usize) -> (Network, Source<usize>) {
+    let network = Network::new_with_propagation("chain", propagation);
+    let source = network.source::<usize>("source");
+    let mut last: Stream<usize> = source.clone().into();
+    for _ in 0..length {
+        last = network.map("map", &last, |t| t + 1);
+    }
+    network.sampler::<_, usize>("sink", &last);
+    (network, source)
+}
+
+/// Create a network with `width` map nodes derived from a single source, and joined back with the
+/// source into a single stream by a chain of `all_with` nodes. In the depth-first mode, the number
+/// of evaluated `all_with` nodes grows quadratically with `width`.
+fn gen_diamond(propagation: Propagation, width: usize) -> (Network, Source<usize>) {
+    let network = Network::new_with_propagation("diamond", propagation);
+    let source = network.source::<usize>("source");
+    let mut joined: Stream<usize> = source.clone().into();
+    for i in 0..width {
+        let branch = network.map("branch", &source, move |t| t + i);
+        joined = network.all_with("join", &joined, &branch, |a, b| a + b);
+    }
+    network.sampler::<_, usize>("sink", &joined);
+    (network, source)
+}
+
+
+
+// ==================
+// === Benchmarks ===
+// ==================
+
+/// Emit events into a long chain of nodes. Shows the per-node overhead of each mode.
+fn chain(c: &mut Criterion) {
+    for (name, propagation) in MODES {
+        let (_network, source) = gen_chain(propagation, SIZE);
+        c.bench_function(&format!("Chain, {name}"), |b| b.iter(|| source.emit(black_box(1))));
+    }
+}
+
+/// Emit events into a wide diamond. Shows the cost of the glitches in the depth-first mode.
+fn diamond(c: &mut Criterion) {
+    for (name, propagation) in MODES {
+        let (_network, source) = gen_diamond(propagation, SIZE);
+        c.bench_function(&format!("Diamond, {name}"), |b| b.iter(|| source.emit(black_box(1))));
+    }
+}
+
+criterion_group! {
+    name    = propagation_benchmarks;
+    config  = bench_config();
+    targets = chain,diamond
+}
+
+
+
+// ==============
+// === Runner ===
+// ==============
+
+criterion_main!(propagation_benchmarks);
@@ -0,0 +1,406 @@
+//! Clocks driving the time-based FRP nodes, like [`crate::Network::debounce`].
+//!
+//! A [`Clock`] measures time and calls callbacks after requested delays. The nodes do not depend on
+//! a particular clock, so the same network can be driven by the browser timers ([`BrowserClock`]),
+//! by the `tokio` runtime in native applications ([`TokioClock`]), or, in tests, by a clock which
+//! is advanced manually ([`VirtualClock`]).
+
+use crate::prelude::*;
+
+use enso_web::traits::*;
+use enso_web::window;
+use enso_web::Closure;
+use std::time::Duration;
+
+
+
+// =============
+// === Clock ===
+// =============
+
+/// Identifier of a callback scheduled with [`Clock::schedule`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct TimerId(u64);
+
+/// A source of time, able to call callbacks after a delay.
+///
+/// The callbacks are called on the thread using the clock, and never from within
+/// [`Clock::schedule`] or [`Clock::cancel`], so a callback can schedule and cancel other callbacks.
+pub trait Clock: Debug {
+    /// The time elapsed since the clock was created.
+    fn now(&self) -> Duration;
+    /// Call the callback once, after the delay. Callbacks with equal deadlines are called in the
+    /// order they were scheduled in.
+    fn schedule(&self, delay: Duration, callback: Box<dyn FnOnce()>) -> TimerId;
+    /// Cancel a scheduled callback. Does nothing if the callback was already called or cancelled.
+    fn cancel(&self, timer: TimerId);
+}
+
+
+
+// ==================
+// === TimerQueue ===
+// ==================
+
+/// Scheduled callbacks, keyed by their deadlines, and identifiers for scheduling order.
+type Timers = BTreeMap<(Duration, TimerId), Box<dyn FnOnce()>>;
+
+/// Scheduled callbacks, ordered by their deadlines.
+#[derive(Default)]
+struct TimerQueue {
+    next_id: Cell<u64>,
+    timers:  RefCell<Timers>,
+}
+
+impl Debug for TimerQueue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.timers.borrow().keys()).finish()
+    }
+}
+
+impl TimerQueue {
+    fn insert(&self, deadline: Duration, callback: Box<dyn FnOnce()>) -> TimerId {
+        let id = TimerId(self.next_id.get());
+        self.next_id.set(id.0 + 1);
+        self.timers.borrow_mut().insert((deadline, id), callback);
+        id
+    }
+
+    fn remove(&self, timer: TimerId) {
+        self.timers.borrow_mut().retain(|(_, id), _| *id != timer);
+    }
+
+    fn next_deadline(&self) -> Option<Duration> {
+        self.timers.borrow().keys().next().map(|(deadline, _)| *deadline)
+    }
+
+    /// Remove the callback with the earliest deadline, if the deadline is not after the given time.
+    fn pop_due(&self, time: Duration) -> Option<(Duration, Box<dyn FnOnce()>)> {
+        let mut timers = self.timers.borrow_mut();
+        let key = *timers.keys().next()?;
+        let (deadline, _) = key;
+        if deadline > time {
+            return None;
+        }
+        timers.remove(&key).map(|callback| (deadline, callback))
+    }
+
+    /// Call the callbacks with deadlines not after the given time, including the ones scheduled by
+    /// the called callbacks.
+    fn fire_due(&self, time: Duration) {
+        while let Some((_, callback)) = self.pop_due(time) {
+            callback();
+        }
+    }
+}
+
+
+
+// ====================
+// === VirtualClock ===
+// ====================
+
+/// A clock whose time passes only when [`VirtualClock::advance`] is called. Intended for testing
+/// time-dependent networks natively and deterministically.
+#[derive(Clone, CloneRef, Debug, Default)]
+pub struct VirtualClock {
+    data: Rc<VirtualClockData>,
+}
+
+#[derive(Debug, Default)]
+struct VirtualClockData {
+    now:   Cell<Duration>,
+    queue: TimerQueue,
+}
+
+impl VirtualClock {
+    /// Constructor. The time of the clock starts at zero.
+    pub fn new() -> Self {
+        default()
+    }
+
+    /// Advance the time by the given duration. The callbacks which become due are called in the
+    /// order of their deadlines; while a callback is running, the time of the clock is equal to its
+    /// deadline.
+    pub fn advance(&self, duration: Duration) {
+        let target = self.data.now.get() + duration;
+        while let Some((deadline, callback)) = self.data.queue.pop_due(target) {
+            self.data.now.set(deadline.max(self.data.now.get()));
+            callback();
+        }
+        self.data.now.set(target);
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> Duration {
+        self.data.now.get()
+    }
+
+    fn schedule(&self, delay: Duration, callback: Box<dyn FnOnce()>) -> TimerId {
+        self.data.queue.insert(self.now() + delay, callback)
+    }
+
+    fn cancel(&self, timer: TimerId) {
+        self.data.queue.remove(timer)
+    }
+}
+
+
+
+// ====================
+// === BrowserClock ===
+// ====================
+
+/// Closure type alias for use in `setTimeout` call.
+type TimerClosure = Closure<dyn FnMut()>;
+
+/// A clock based on the `performance.now()` and `setTimeout` browser APIs.
+///
+/// A single browser timer is kept running, set to the earliest deadline. As with the other browser
+/// timers, the callbacks may be called later than requested if the browser event loop is busy.
+#[derive(Clone, CloneRef, Debug)]
+pub struct BrowserClock {
+    data: Rc<BrowserClockData>,
+}
+
+#[derive(Debug)]
+struct BrowserClockData {
+    /// The value of [`enso_web::time_from_start`] when the clock was created, in milliseconds.
+    origin:  f64,
+    queue:   TimerQueue,
+    /// The deadline and the handle of the running browser timer.
+    armed:   Cell<Option<(Duration, i32)>>,
+    closure: TimerClosure,
+}
+
+impl BrowserClock {
+    /// Constructor. The time of the clock starts at zero.
+    pub fn new() -> Self {
+        let data = Rc::new_cyclic(|data: &Weak<BrowserClockData>| {
+            let data = data.clone();
+            let closure: TimerClosure = Closure::new(move || {
+                if let Some(data) = data.upgrade() {
+                    data.on_timeout()
+                }
+            });
+            let origin = enso_web::time_from_start();
+            BrowserClockData { origin, queue: default(), armed: default(), closure }
+        });
+        Self { data }
+    }
+}
+
+impl Default for BrowserClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BrowserClockData {
+    fn now(&self) -> Duration {
+        let elapsed_ms = (enso_web::time_from_start() - self.origin).max(0.0);
+        Duration::from_secs_f64(elapsed_ms / 1000.0)
+    }
+
+    fn on_timeout(&self) {
+        let deadline = self.armed.take().map(|(deadline, _)| deadline);
+        // The browser timer may be rounded to a coarser resolution than the clock.
+        self.queue.fire_due(self.now().max(deadline.unwrap_or_default()));
+        self.rearm();
+    }
+
+    /// Set the browser timer to the earliest deadline, if it is not set to it already.
+    fn rearm(&self) {
+        let deadline = self.queue.next_deadline();
+        let armed = self.armed.get();
+        if deadline != armed.map(|(deadline, _)| deadline) {
+            if let Some((_, handle)) = armed {
+                window.clear_timeout_with_handle(handle);
+            }
+            let armed = deadline.map(|deadline| {
+                let delay = deadline.saturating_sub(self.now());
+                let delay_ms = ((delay.as_micros() + 999) / 1000).min(i32::MAX as u128) as i32;
+                let js_func = self.closure.as_js_function();
+                let result =
+                    window.set_timeout_with_callback_and_timeout_and_arguments_0(js_func, delay_ms);
+                let handle =
+                    result.expect("setTimeout should never fail when callback is a function.");
+                (deadline, handle)
+            });
+            self.armed.set(armed);
+        }
+    }
+}
+
+impl Drop for BrowserClockData {
+    fn drop(&mut self) {
+        if let Some((_, handle)) = self.armed.take() {
+            window.clear_timeout_with_handle(handle);
+        }
+    }
+}
+
+impl Clock for BrowserClock {
+    fn now(&self) -> Duration {
+        self.data.now()
+    }
+
+    fn schedule(&self, delay: Duration, callback: Box<dyn FnOnce()>) -> TimerId {
+        let timer = self.data.queue.insert(self.now() + delay, callback);
+        self.data.rearm();
+        timer
+    }
+
+    fn cancel(&self, timer: TimerId) {
+        self.data.queue.remove(timer);
+        self.data.rearm();
+    }
+}
+
+
+
+// ==================
+// === TokioClock ===
+// ==================
+
+/// A clock based on the `tokio` runtime, for use in native applications and tests.
+///
+/// The callbacks are called by a task spawned with [`tokio::task::spawn_local`], so the clock has
+/// to be used within a [`tokio::task::LocalSet`]. A single task is kept running, sleeping until the
+/// earliest deadline. As the clock is based on [`tokio::time::Instant`], it follows the time of a
+/// paused `tokio` runtime.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, CloneRef, Debug)]
+pub struct TokioClock {
+    data: Rc<TokioClockData>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+struct TokioClockData {
+    origin: tokio::time::Instant,
+    queue:  TimerQueue,
+    /// The deadline and the handle of the running task.
+    armed:  RefCell<Option<(Duration, tokio::task::JoinHandle<()>)>>,
+    this:   Weak<TokioClockData>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl TokioClock {
+    /// Constructor. The time of the clock starts at zero.
+    pub fn new() -> Self {
+        let data = Rc::new_cyclic(|this: &Weak<TokioClockData>| {
+            let origin = tokio::time::Instant::now();
+            let this = this.clone();
+            TokioClockData { origin, queue: default(), armed: default(), this }
+        });
+        Self { data }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Default for TokioClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl TokioClockData {
+    fn on_timeout(&self, deadline: Duration) {
+        // The task calling this function is finished, so it does not have to be aborted.
+        self.armed.take();
+        self.queue.fire_due(self.origin.elapsed().max(deadline));
+        self.rearm();
+    }
+
+    /// Start a task sleeping until the earliest deadline, if there is none sleeping until it
+    /// already.
+    fn rearm(&self) {
+        let deadline = self.queue.next_deadline();
+        let mut armed = self.armed.borrow_mut();
+        if deadline != armed.as_ref().map(|(deadline, _)| *deadline) {
+            if let Some((_, task)) = armed.take() {
+                task.abort();
+            }
+            *armed = deadline.map(|deadline| {
+                let wake_time = self.origin + deadline;
+                let data = self.this.clone();
+                let task = tokio::task::spawn_local(async move {
+                    tokio::time::sleep_until(wake_time).await;
+                    if let Some(data) = data.upgrade() {
+                        data.on_timeout(deadline)
+                    }
+                });
+                (deadline, task)
+            });
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for TokioClockData {
+    fn drop(&mut self) {
+        if let Some((_, task)) = self.armed.take() {
+            task.abort();
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Clock for TokioClock {
+    fn now(&self) -> Duration {
+        self.data.origin.elapsed()
+    }
+
+    fn schedule(&self, delay: Duration, callback: Box<dyn FnOnce()>) -> TimerId {
+        let timer = self.data.queue.insert(self.now() + delay, callback);
+        self.data.rearm();
+        timer
+    }
+
+    fn cancel(&self, timer: TimerId) {
+        self.data.queue.remove(timer);
+        self.data.rearm();
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn virtual_clock() {
+        let clock = VirtualClock::new();
+        let calls = Rc::new(RefCell::new(vec![]));
+        let ms = Duration::from_millis;
+        let record = |name: &'static str| {
+            let calls = calls.clone_ref();
+            let clock = clock.clone_ref();
+            Box::new(move || calls.borrow_mut().push((name, clock.now())))
+        };
+        clock.schedule(ms(10), record("a"));
+        let cancelled = clock.schedule(ms(5), record("cancelled"));
+        clock.schedule(ms(5), {
+            let clock = clock.clone_ref();
+            let nested = record("nested");
+            Box::new(move || {
+                clock.schedule(ms(0), nested);
+            })
+        });
+        clock.schedule(ms(10), record("b"));
+        clock.cancel(cancelled);
+        clock.advance(ms(7));
+        assert_eq!(*calls.borrow(), vec![("nested", ms(5))]);
+        assert_eq!(clock.now(), ms(7));
+        clock.advance(ms(3));
+        assert_eq!(*calls.borrow(), vec![("nested", ms(5)), ("a", ms(10)), ("b", ms(10))]);
+    }
+}
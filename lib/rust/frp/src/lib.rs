@@ -167,6 +167,7 @@
 #![recursion_limit = "512"]
 
 pub mod any_data;
+pub mod clock;
 pub mod data;
 pub mod debug;
 pub mod fan;
@@ -177,15 +178,19 @@ pub mod network;
 pub mod node;
 pub mod nodes;
 pub mod stream;
+pub mod testing;
+pub mod transaction;
 
 pub use network::*;
 pub use node::*;
 pub use nodes::*;
 
 pub use any_data::AnyData;
+pub use clock::Clock;
 pub use enso_web as web;
 pub use fan::Fan;
 pub use stream::Stream;
+pub use transaction::Propagation;
 
 /// Set of often used types and functions.
 pub mod prelude {
@@ -199,6 +204,10 @@ pub mod prelude {
 #[cfg(test)]
 mod network_mode_tests {
     use crate as frp;
+    use frp::prelude::*;
+
+    use frp::clock::VirtualClock;
+    use std::time::Duration;
 
     #[test]
     fn lifetime_management() {
@@ -221,6 +230,251 @@ mod network_mode_tests {
         source.emit(());
         assert_eq!(sampler.value(), 2);
     }
+
+    /// Events collected by [`record`].
+    type Events<T> = Rc<RefCell<Vec<T>>>;
+
+    /// Collect the events emitted by the stream.
+    fn record<T: frp::Data>(network: &frp::Network, stream: &frp::Stream<T>) -> Events<T> {
+        let events: Events<T> = default();
+        let stream = stream.clone_ref();
+        frp::extend! { network
+            eval stream ([events](value) events.borrow_mut().push(value.clone()));
+        }
+        events
+    }
+
+    #[test]
+    fn debounce() {
+        let clock = VirtualClock::new();
+        let ms = Duration::from_millis;
+        frp::new_network! { network
+            source    <- source::<usize>();
+            debounced <- source.debounce(&clock, ms(10));
+        }
+        let events = record(&network, &debounced);
+        source.emit(1);
+        clock.advance(ms(5));
+        source.emit(2);
+        clock.advance(ms(9));
+        assert_eq!(*events.borrow(), Vec::<usize>::new());
+        clock.advance(ms(1));
+        assert_eq!(*events.borrow(), vec![2]);
+        source.emit(3);
+        clock.advance(ms(10));
+        assert_eq!(*events.borrow(), vec![2, 3]);
+    }
+
+    #[test]
+    fn throttle() {
+        let clock = VirtualClock::new();
+        let ms = Duration::from_millis;
+        frp::new_network! { network
+            source    <- source::<usize>();
+            throttled <- source.throttle(&clock, ms(10));
+        }
+        let events = record(&network, &throttled);
+        source.emit(1);
+        clock.advance(ms(2));
+        source.emit(2);
+        source.emit(3);
+        assert_eq!(*events.borrow(), vec![1]);
+        clock.advance(ms(8));
+        assert_eq!(*events.borrow(), vec![1, 3]);
+        clock.advance(ms(5));
+        source.emit(4);
+        assert_eq!(*events.borrow(), vec![1, 3]);
+        clock.advance(ms(5));
+        assert_eq!(*events.borrow(), vec![1, 3, 4]);
+        clock.advance(ms(10));
+        source.emit(5);
+        assert_eq!(*events.borrow(), vec![1, 3, 4, 5]);
+    }
+
+    #[test]
+    fn delay() {
+        let clock = VirtualClock::new();
+        let ms = Duration::from_millis;
+        frp::new_network! { network
+            source  <- source::<usize>();
+            delayed <- source.delay(&clock, ms(10));
+        }
+        let events = record(&network, &delayed);
+        source.emit(1);
+        clock.advance(ms(5));
+        source.emit(2);
+        clock.advance(ms(5));
+        assert_eq!(*events.borrow(), vec![1]);
+        source.emit(3);
+        clock.advance(ms(10));
+        assert_eq!(*events.borrow(), vec![1, 2, 3]);
+        source.emit(4);
+        drop(network);
+        clock.advance(ms(10));
+        assert_eq!(*events.borrow(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn buffer_time() {
+        let clock = VirtualClock::new();
+        let ms = Duration::from_millis;
+        frp::new_network! { network
+            source   <- source::<usize>();
+            buffered <- source.buffer_time(&clock, ms(10));
+        }
+        let events = record(&network, &buffered);
+        source.emit(1);
+        clock.advance(ms(4));
+        source.emit(2);
+        clock.advance(ms(6));
+        source.emit(3);
+        clock.advance(ms(20));
+        assert_eq!(*events.borrow(), vec![vec![1, 2], vec![3]]);
+    }
+
+    #[test]
+    fn window_count() {
+        frp::new_network! { network
+            source  <- source::<usize>();
+            windows <- source.window_count(2);
+        }
+        let events = record(&network, &windows);
+        (1..=5).for_each(|value| source.emit(value));
+        assert_eq!(*events.borrow(), vec![vec![1, 2], vec![3, 4]]);
+    }
+
+    /// Network with a diamond-shaped graph: `all` and `map2` over two streams derived from a
+    /// single source. Returns the source and the events of both nodes.
+    fn diamond(
+        network: &frp::Network,
+    ) -> (frp::Source<usize>, Events<(usize, usize)>, Events<usize>) {
+        frp::extend! { network
+            source  <- source::<usize>();
+            doubled <- source.map(|t| t * 2);
+            tripled <- source.map(|t| t * 3);
+            both    <- all(doubled, tripled);
+            sum     <- map2(&doubled, &tripled, |a, b| a + b);
+        }
+        (source, record(network, &both), record(network, &sum))
+    }
+
+    #[test]
+    fn depth_first_diamond() {
+        let network = frp::Network::new("network");
+        let (source, both, sum) = diamond(&network);
+        source.emit(1);
+        assert_eq!(*both.borrow(), vec![(2, 0), (2, 3)]);
+        assert_eq!(*sum.borrow(), vec![2]);
+    }
+
+    #[test]
+    fn transactional_diamond() {
+        let network =
+            frp::Network::new_with_propagation("network", frp::Propagation::Transactional);
+        let (source, both, sum) = diamond(&network);
+        source.emit(1);
+        source.emit(2);
+        assert_eq!(*both.borrow(), vec![(2, 3), (4, 6)]);
+        assert_eq!(*sum.borrow(), vec![5, 10]);
+    }
+
+    #[test]
+    fn transactional_nested_emission() {
+        let network =
+            frp::Network::new_with_propagation("network", frp::Propagation::Transactional);
+        frp::extend! { network
+            source  <- source::<usize>();
+            trigger <- source::<usize>();
+            eval trigger ([source](t) source.emit(t * 10));
+            plus    <- source.map(|t| t + 1);
+            both    <- all(source, plus);
+        }
+        let events = record(&network, &both);
+        trigger.emit(1);
+        source.emit(2);
+        assert_eq!(*events.borrow(), vec![(10, 11), (2, 3)]);
+    }
+
+    /// Transactional network with a diamond-shaped graph over its source. Returns the source and
+    /// the events of `all` and `map2` nodes.
+    fn transactional_diamond_over_source(
+        network: &frp::Network,
+    ) -> (frp::Source<usize>, Events<(usize, usize)>, Events<usize>) {
+        frp::extend! { network
+            source <- source::<usize>();
+            plus   <- source.map(|t| t + 1);
+            both   <- all(source, plus);
+            sum    <- map2(&source, &plus, |a, b| a + b);
+        }
+        (source, record(network, &both), record(network, &sum))
+    }
+
+    #[test]
+    fn transactional_emissions_from_eval() {
+        let network =
+            frp::Network::new_with_propagation("network", frp::Propagation::Transactional);
+        let (source, both, sum) = transactional_diamond_over_source(&network);
+        frp::extend! { network
+            trigger <- source::<()>();
+            eval_ trigger ([source] {
+                source.emit(1);
+                source.emit(2);
+            });
+        }
+        trigger.emit(());
+        assert_eq!(*both.borrow(), vec![(1, 2), (2, 3)]);
+        assert_eq!(*sum.borrow(), vec![3, 5]);
+    }
+
+    #[test]
+    fn transactional_emissions_from_depth_first_network() {
+        let network =
+            frp::Network::new_with_propagation("network", frp::Propagation::Transactional);
+        let (source, both, sum) = transactional_diamond_over_source(&network);
+        frp::new_network! { outer
+            trigger <- source::<()>();
+            eval_ trigger ([source, sum] {
+                source.emit(1);
+                assert_eq!(*sum.borrow(), vec![3]);
+                source.emit(2);
+            });
+        }
+        trigger.emit(());
+        assert_eq!(*both.borrow(), vec![(1, 2), (2, 3)]);
+        assert_eq!(*sum.borrow(), vec![3, 5]);
+    }
+
+    #[test]
+    fn transactional_panic_recovery() {
+        let network =
+            frp::Network::new_with_propagation("network", frp::Propagation::Transactional);
+        frp::extend! { network
+            source <- source::<usize>();
+            _check <- source.map(|t| if *t == 0 { panic!("Expected panic.") });
+            plus   <- source.map(|t| t + 1);
+        }
+        let events = record(&network, &plus);
+        let emit = std::panic::AssertUnwindSafe(|| source.emit(0));
+        assert!(std::panic::catch_unwind(emit).is_err());
+        source.emit(1);
+        assert_eq!(*events.borrow(), vec![2]);
+    }
+
+    #[test]
+    fn transactional_feedback_loop() {
+        let network =
+            frp::Network::new_with_propagation("network", frp::Propagation::Transactional);
+        frp::extend! { network
+            source  <- source::<usize>();
+            counter <- any_mut::<usize>();
+            counter <+ source;
+            next    <- counter.filter(|t| *t > 0).map(|t| t - 1);
+            counter <+ next;
+        }
+        let events = record(&network, &counter.into());
+        source.emit(3);
+        assert_eq!(*events.borrow(), vec![3, 2, 1, 0]);
+    }
 }
 
 #[cfg(test)]
@@ -6,6 +6,7 @@ use crate::prelude::*;
 use crate::debug;
 use crate::stream;
 use crate::stream::Stream;
+use crate::transaction::Propagation;
 
 
 
@@ -48,13 +49,14 @@ impl<T> Item for T where T: HasId + HasLabel + stream::HasOutputTypeLabel {}
 #[derivative(Debug)]
 pub struct NetworkData {
     /// Label of the network.
-    pub label: String,
+    pub label:   String,
+    propagation: Propagation,
     #[derivative(Debug = "ignore")]
-    nodes:     RefCell<Vec<Box<dyn Item>>>,
-    links:     RefCell<HashMap<Id, Link>>,
-    bridges:   RefCell<Vec<BridgeNetwork>>,
+    nodes:       RefCell<Vec<Box<dyn Item>>>,
+    links:       RefCell<HashMap<Id, Link>>,
+    bridges:     RefCell<Vec<BridgeNetwork>>,
     /// Used as a convenient storage of data associated with network, like animation instances.
-    storage:   RefCell<Vec<Box<dyn Any>>>,
+    storage:     RefCell<Vec<Box<dyn Any>>>,
 }
 
 
@@ -62,12 +64,12 @@ pub struct NetworkData {
 
 impl NetworkData {
     /// Constructor.
-    pub fn new(label: String) -> Self {
+    pub fn new(label: String, propagation: Propagation) -> Self {
         let nodes = default();
         let links = default();
         let bridges = default();
         let storage = default();
-        Self { label, nodes, links, bridges, storage }
+        Self { label, propagation, nodes, links, bridges, storage }
     }
 }
 
@@ -80,15 +82,34 @@ impl Drop for NetworkData {
 impl Network {
     /// Constructor.
     pub fn new(label: impl Into<String>) -> Self {
-        Self::new_with_string(label.into())
+        Self::new_with_string(label.into(), default())
+    }
+
+    /// Constructor of a network delivering events to its nodes in the provided way. Use
+    /// [`Propagation::Transactional`] to make every node fire at most once per source emission,
+    /// with consistent inputs. See the docs of the `transaction` module to learn more.
+    ///
+    /// Events emitted into a transactional network from outside of a transaction are fully
+    /// delivered before `emit` returns. However, if a transaction is already running, for example
+    /// when emitting from an `eval` handler of a transactional network, or from a depth-first
+    /// handler called while a transaction is running, the emission is deferred until the running
+    /// transaction finishes. In such a case the handler does not observe the results of its
+    /// emission.
+    pub fn new_with_propagation(label: impl Into<String>, propagation: Propagation) -> Self {
+        Self::new_with_string(label.into(), propagation)
     }
 
     /// Non-generic constructor.
-    fn new_with_string(label: String) -> Self {
-        let data = Rc::new(NetworkData::new(label));
+    fn new_with_string(label: String, propagation: Propagation) -> Self {
+        let data = Rc::new(NetworkData::new(label, propagation));
         Self { data }
     }
 
+    /// The way events are delivered to the nodes of this network.
+    pub fn propagation(&self) -> Propagation {
+        self.data.propagation
+    }
+
     /// Get the weak version.
     pub fn downgrade(&self) -> WeakNetwork {
         WeakNetwork { data: Rc::downgrade(&self.data) }
@@ -117,6 +138,7 @@ impl Network {
 
     /// Register the node and return it's weak reference.
     pub fn register_raw<T: HasOutputStatic>(&self, node: stream::Node<T>) -> stream::WeakNode<T> {
+        node.set_propagation(self.data.propagation);
         let weak = node.downgrade();
         self.register_boxed(Box::new(node));
         weak
@@ -124,6 +146,7 @@ impl Network {
 
     /// Register the node and return a new `Stream` reference.
     pub fn register<Def: HasOutputStatic>(&self, node: stream::Node<Def>) -> Stream<Output<Def>> {
+        node.set_propagation(self.data.propagation);
         let stream = node.clone_ref().into();
         self.register_boxed(Box::new(node));
         stream
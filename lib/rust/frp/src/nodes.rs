@@ -13,6 +13,8 @@ use crate::node::*;
 use crate::prelude::*;
 use enso_generics::traits::*;
 
+use crate::clock::Clock;
+use crate::clock::TimerId;
 use crate::data::watch;
 use crate::stream;
 use crate::stream::CallStack;
@@ -22,6 +24,8 @@ use crate::stream::Stream;
 use crate::stream::ValueProvider;
 
 use enso_generics as generics;
+use std::collections::VecDeque;
+use std::time::Duration;
 
 
 
@@ -1026,6 +1030,99 @@ impl Network {
     where T: EventOutput<Output = usize> {
         self.register(OwnedRepeat::new(label, src))
     }
+
+
+    // === Time ===
+
+    /// Emits the last incoming event once no events have been received for the given time. Useful
+    /// for reacting to a burst of events only after it ends, e.g. to search only after the user
+    /// stops typing.
+    ///
+    /// in (delay 3ms): --a-b------c--d--e------
+    /// out:            -------b------------e---
+    pub fn debounce<T, C>(
+        &self,
+        label: Label,
+        src: &T,
+        clock: &C,
+        delay: Duration,
+    ) -> Stream<Output<T>>
+    where
+        T: EventOutput,
+        C: Clock + CloneRef + 'static,
+    {
+        self.register(OwnedDebounce::new(label, src, clock, delay))
+    }
+
+    /// Passes at most one event per period. An incoming event is passed immediately if no event
+    /// was passed within the last period; otherwise the last such event is passed when the period
+    /// ends, starting the next period.
+    ///
+    /// in (period 4ms): -a-b-------d-e-----
+    /// out:             -a---b-----d---e---
+    pub fn throttle<T, C>(
+        &self,
+        label: Label,
+        src: &T,
+        clock: &C,
+        period: Duration,
+    ) -> Stream<Output<T>>
+    where
+        T: EventOutput,
+        C: Clock + CloneRef + 'static,
+    {
+        self.register(OwnedThrottle::new(label, src, clock, period))
+    }
+
+    /// Passes every incoming event after the given delay.
+    ///
+    /// in (delay 3ms): -a-b------c-----
+    /// out:            ----a-b------c--
+    pub fn delay<T, C>(
+        &self,
+        label: Label,
+        src: &T,
+        clock: &C,
+        delay: Duration,
+    ) -> Stream<Output<T>>
+    where
+        T: EventOutput,
+        C: Clock + CloneRef + 'static,
+    {
+        self.register(OwnedDelay::new(label, src, clock, delay))
+    }
+
+    /// Collects the incoming events for the given period, starting at the first incoming event,
+    /// and emits them as a vector when the period ends. No events are emitted if there are no
+    /// incoming events.
+    ///
+    /// in (period 4ms): -a-b----c--------
+    /// out:             -----x------y----
+    /// where x is [a,b] and y is [c].
+    pub fn buffer_time<T, C>(
+        &self,
+        label: Label,
+        src: &T,
+        clock: &C,
+        period: Duration,
+    ) -> Stream<Vec<Output<T>>>
+    where
+        T: EventOutput,
+        C: Clock + CloneRef + 'static,
+    {
+        self.register(OwnedBufferTime::new(label, src, clock, period))
+    }
+
+    /// Collects the incoming events, emitting them as a vector every `count` events. A `count` of
+    /// zero is treated as one.
+    ///
+    /// in (count 2): -a-b-c---d-e-
+    /// out:          ---x-----y---
+    /// where x is [a,b] and y is [c,d].
+    pub fn window_count<T>(&self, label: Label, src: &T, count: usize) -> Stream<Vec<Output<T>>>
+    where T: EventOutput {
+        self.register(OwnedWindowCount::new(label, src, count))
+    }
 }
 
 
@@ -4388,3 +4485,312 @@ where T: EventOutput<Output = usize>
         }
     }
 }
+
+
+
+// ==================
+// === Scheduling ===
+// ==================
+
+/// Call the function with the node after the delay, unless the node is dropped before that.
+fn schedule_for_node<Def: HasOutputStatic>(
+    node: &stream::Node<Def>,
+    clock: &dyn Clock,
+    delay: Duration,
+    f: impl FnOnce(&stream::Node<Def>) + 'static,
+) -> TimerId {
+    let node = node.downgrade();
+    clock.schedule(
+        delay,
+        Box::new(move || {
+            if let Some(node) = node.upgrade() {
+                f(&node)
+            }
+        }),
+    )
+}
+
+
+
+// ================
+// === Debounce ===
+// ================
+
+#[derive(Debug)]
+pub struct DebounceData<T: EventOutput> {
+    #[allow(dead_code)]
+    /// This is not accessed in this implementation but it needs to be kept so the source struct
+    /// stays alive at least as long as this struct.
+    src:   T,
+    clock: Rc<dyn Clock>,
+    delay: Duration,
+    timer: Cell<Option<TimerId>>,
+}
+pub type OwnedDebounce<T> = stream::Node<DebounceData<T>>;
+pub type Debounce<T> = stream::WeakNode<DebounceData<T>>;
+
+impl<T: EventOutput> HasOutput for DebounceData<T> {
+    type Output = Output<T>;
+}
+
+impl<T: EventOutput> OwnedDebounce<T> {
+    /// Constructor.
+    pub fn new<C>(label: Label, src1: &T, clock: &C, delay: Duration) -> Self
+    where C: Clock + CloneRef + 'static {
+        let src = src1.clone_ref();
+        let clock = Rc::new(clock.clone_ref());
+        let timer = default();
+        let def = DebounceData { src, clock, delay, timer };
+        Self::construct_and_connect(label, src1, def)
+    }
+}
+
+impl<T: EventOutput> stream::EventConsumer<Output<T>> for OwnedDebounce<T> {
+    fn on_event(&self, _: CallStack, event: &Output<T>) {
+        let event = event.clone();
+        let timer = schedule_for_node(self, &*self.clock, self.delay, move |node| {
+            node.timer.set(None);
+            node.emit_event(&default(), &event);
+        });
+        if let Some(timer) = self.timer.replace(Some(timer)) {
+            self.clock.cancel(timer);
+        }
+    }
+}
+
+impl<T: EventOutput> Drop for DebounceData<T> {
+    fn drop(&mut self) {
+        if let Some(timer) = self.timer.take() {
+            self.clock.cancel(timer);
+        }
+    }
+}
+
+
+
+// ================
+// === Throttle ===
+// ================
+
+#[derive(Debug)]
+pub struct ThrottleData<T: EventOutput> {
+    #[allow(dead_code)]
+    /// This is not accessed in this implementation but it needs to be kept so the source struct
+    /// stays alive at least as long as this struct.
+    src:      T,
+    clock:    Rc<dyn Clock>,
+    period:   Duration,
+    timer:    Cell<Option<TimerId>>,
+    trailing: RefCell<Option<Output<T>>>,
+}
+pub type OwnedThrottle<T> = stream::Node<ThrottleData<T>>;
+pub type Throttle<T> = stream::WeakNode<ThrottleData<T>>;
+
+impl<T: EventOutput> HasOutput for ThrottleData<T> {
+    type Output = Output<T>;
+}
+
+impl<T: EventOutput> OwnedThrottle<T> {
+    /// Constructor.
+    pub fn new<C>(label: Label, src1: &T, clock: &C, period: Duration) -> Self
+    where C: Clock + CloneRef + 'static {
+        let src = src1.clone_ref();
+        let clock = Rc::new(clock.clone_ref());
+        let timer = default();
+        let trailing = default();
+        let def = ThrottleData { src, clock, period, timer, trailing };
+        Self::construct_and_connect(label, src1, def)
+    }
+
+    fn start_period(&self) {
+        let timer = schedule_for_node(self, &*self.clock, self.period, |node| {
+            node.timer.set(None);
+            let trailing = node.trailing.take();
+            if let Some(event) = trailing {
+                node.start_period();
+                node.emit_event(&default(), &event);
+            }
+        });
+        self.timer.set(Some(timer));
+    }
+}
+
+impl<T: EventOutput> stream::EventConsumer<Output<T>> for OwnedThrottle<T> {
+    fn on_event(&self, stack: CallStack, event: &Output<T>) {
+        if self.timer.get().is_some() {
+            *self.trailing.borrow_mut() = Some(event.clone());
+        } else {
+            self.start_period();
+            self.emit_event(stack, event);
+        }
+    }
+}
+
+impl<T: EventOutput> Drop for ThrottleData<T> {
+    fn drop(&mut self) {
+        if let Some(timer) = self.timer.take() {
+            self.clock.cancel(timer);
+        }
+    }
+}
+
+
+
+// =============
+// === Delay ===
+// =============
+
+#[derive(Debug)]
+pub struct DelayData<T: EventOutput> {
+    #[allow(dead_code)]
+    /// This is not accessed in this implementation but it needs to be kept so the source struct
+    /// stays alive at least as long as this struct.
+    src:    T,
+    clock:  Rc<dyn Clock>,
+    delay:  Duration,
+    /// The timers of the delayed events. As all events are delayed by the same time, the timers
+    /// expire in order.
+    timers: RefCell<VecDeque<TimerId>>,
+}
+pub type OwnedDelay<T> = stream::Node<DelayData<T>>;
+pub type Delay<T> = stream::WeakNode<DelayData<T>>;
+
+impl<T: EventOutput> HasOutput for DelayData<T> {
+    type Output = Output<T>;
+}
+
+impl<T: EventOutput> OwnedDelay<T> {
+    /// Constructor.
+    pub fn new<C>(label: Label, src1: &T, clock: &C, delay: Duration) -> Self
+    where C: Clock + CloneRef + 'static {
+        let src = src1.clone_ref();
+        let clock = Rc::new(clock.clone_ref());
+        let timers = default();
+        let def = DelayData { src, clock, delay, timers };
+        Self::construct_and_connect(label, src1, def)
+    }
+}
+
+impl<T: EventOutput> stream::EventConsumer<Output<T>> for OwnedDelay<T> {
+    fn on_event(&self, _: CallStack, event: &Output<T>) {
+        let event = event.clone();
+        let timer = schedule_for_node(self, &*self.clock, self.delay, move |node| {
+            node.timers.borrow_mut().pop_front();
+            node.emit_event(&default(), &event);
+        });
+        self.timers.borrow_mut().push_back(timer);
+    }
+}
+
+impl<T: EventOutput> Drop for DelayData<T> {
+    fn drop(&mut self) {
+        for timer in self.timers.take() {
+            self.clock.cancel(timer);
+        }
+    }
+}
+
+
+
+// ==================
+// === BufferTime ===
+// ==================
+
+#[derive(Debug)]
+pub struct BufferTimeData<T: EventOutput> {
+    #[allow(dead_code)]
+    /// This is not accessed in this implementation but it needs to be kept so the source struct
+    /// stays alive at least as long as this struct.
+    src:    T,
+    clock:  Rc<dyn Clock>,
+    period: Duration,
+    timer:  Cell<Option<TimerId>>,
+    buffer: RefCell<Vec<Output<T>>>,
+}
+pub type OwnedBufferTime<T> = stream::Node<BufferTimeData<T>>;
+pub type BufferTime<T> = stream::WeakNode<BufferTimeData<T>>;
+
+impl<T: EventOutput> HasOutput for BufferTimeData<T> {
+    type Output = Vec<Output<T>>;
+}
+
+impl<T: EventOutput> OwnedBufferTime<T> {
+    /// Constructor.
+    pub fn new<C>(label: Label, src1: &T, clock: &C, period: Duration) -> Self
+    where C: Clock + CloneRef + 'static {
+        let src = src1.clone_ref();
+        let clock = Rc::new(clock.clone_ref());
+        let timer = default();
+        let buffer = default();
+        let def = BufferTimeData { src, clock, period, timer, buffer };
+        Self::construct_and_connect(label, src1, def)
+    }
+}
+
+impl<T: EventOutput> stream::EventConsumer<Output<T>> for OwnedBufferTime<T> {
+    fn on_event(&self, _: CallStack, event: &Output<T>) {
+        self.buffer.borrow_mut().push(event.clone());
+        if self.timer.get().is_none() {
+            let timer = schedule_for_node(self, &*self.clock, self.period, |node| {
+                node.timer.set(None);
+                let buffer = node.buffer.take();
+                node.emit_event(&default(), &buffer);
+            });
+            self.timer.set(Some(timer));
+        }
+    }
+}
+
+impl<T: EventOutput> Drop for BufferTimeData<T> {
+    fn drop(&mut self) {
+        if let Some(timer) = self.timer.take() {
+            self.clock.cancel(timer);
+        }
+    }
+}
+
+
+
+// ===================
+// === WindowCount ===
+// ===================
+
+#[derive(Debug)]
+pub struct WindowCountData<T: EventOutput> {
+    #[allow(dead_code)]
+    /// This is not accessed in this implementation but it needs to be kept so the source struct
+    /// stays alive at least as long as this struct.
+    src:    T,
+    count:  usize,
+    window: RefCell<Vec<Output<T>>>,
+}
+pub type OwnedWindowCount<T> = stream::Node<WindowCountData<T>>;
+pub type WindowCount<T> = stream::WeakNode<WindowCountData<T>>;
+
+impl<T: EventOutput> HasOutput for WindowCountData<T> {
+    type Output = Vec<Output<T>>;
+}
+
+impl<T: EventOutput> OwnedWindowCount<T> {
+    /// Constructor.
+    pub fn new(label: Label, src1: &T, count: usize) -> Self {
+        let src = src1.clone_ref();
+        let count = count.max(1);
+        let window = default();
+        let def = WindowCountData { src, count, window };
+        Self::construct_and_connect(label, src1, def)
+    }
+}
+
+impl<T: EventOutput> stream::EventConsumer<Output<T>> for OwnedWindowCount<T> {
+    fn on_event(&self, stack: CallStack, event: &Output<T>) {
+        let full_window = {
+            let mut window = self.window.borrow_mut();
+            window.push(event.clone());
+            (window.len() >= self.count).then(|| mem::take(&mut *window))
+        };
+        if let Some(window) = full_window {
+            self.emit_event(stack, &window);
+        }
+    }
+}
@@ -5,6 +5,8 @@ use crate::node::*;
 use crate::prelude::*;
 
 use crate::data::watch;
+use crate::transaction;
+use crate::transaction::Propagation;
 
 
 
@@ -190,6 +192,7 @@ impl<Def, Input> From<WeakNode<Def>> for EventInput<Input>
 where
     Def: HasOutputStatic,
     Node<Def>: EventConsumer<Input>,
+    Input: Clone + 'static,
 {
     fn from(node: WeakNode<Def>) -> Self {
         Self { data: Rc::new(node) }
@@ -200,6 +203,7 @@ impl<Def, Input> From<&WeakNode<Def>> for EventInput<Input>
 where
     Def: HasOutputStatic,
     Node<Def>: EventConsumer<Input>,
+    Input: Clone + 'static,
 {
     fn from(node: &WeakNode<Def>) -> Self {
         Self { data: Rc::new(node.clone_ref()) }
@@ -229,7 +233,9 @@ const EVALUATIONS_LIMIT: usize = 100;
 /// event targets (the `targets` field), but are watching this node and can ask it for the last
 /// value any time. If the number of such nodes is zero, the value propagated trough this node does
 /// not need to be cached, and it will not be cloned. This minimizes the amount of clones in FRP
-/// networks drastically.
+/// networks drastically. The `transaction` field keeps the rank of the node and the state of its
+/// scheduled deliveries, used if the node belongs to a network in the transactional propagation
+/// mode. See the docs of the `transaction` module to learn more.
 #[derive(Debug)]
 pub struct NodeData<Out = ()> {
     /// Please be very careful when working with this field. When an event is emitted, this field
@@ -241,6 +247,7 @@ pub struct NodeData<Out = ()> {
     value_cache:         RefCell<Out>,
     ongoing_evaluations: Cell<usize>,
     watch_counter:       watch::Counter,
+    transaction:         transaction::NodeState,
     label:               Label,
 }
 
@@ -252,12 +259,14 @@ impl<Out: Default> NodeData<Out> {
         let value_cache = default();
         let evaluations = default();
         let watch_counter = default();
+        let transaction = default();
         Self {
             targets,
             new_targets,
             value_cache,
             ongoing_evaluations: evaluations,
             watch_counter,
+            transaction,
             label,
         }
     }
@@ -274,6 +283,7 @@ impl<Out: Data> HasOutput for NodeData<Out> {
 impl<Out: Data> EventEmitter for NodeData<Out> {
     fn emit_event(&self, stack: CallStack, value: &Out) {
         let new_stack = stack.sub(self.label);
+        let emission = transaction::Emission::begin(&self.transaction);
         if self.ongoing_evaluations.get() > EVALUATIONS_LIMIT {
             let logger: Logger = Logger::new("frp");
             warning!(logger, "The recursive evaluations limit exceeded.", || {
@@ -300,6 +310,7 @@ impl<Out: Data> EventEmitter for NodeData<Out> {
             }
             self.ongoing_evaluations.set(self.ongoing_evaluations.get() - 1);
         }
+        emission.end();
     }
 
     fn register_target(&self, target: EventInput<Out>) {
@@ -448,6 +459,12 @@ impl<Def: HasOutputStatic> Node<Def> {
         this
     }
 
+    /// Set the way events are delivered to this node. See the docs of the `transaction` module to
+    /// learn more.
+    pub fn set_propagation(&self, propagation: Propagation) {
+        self.stream.data.transaction.set_propagation(propagation)
+    }
+
     /// Downgrades to the weak version.
     pub fn downgrade(&self) -> WeakNode<Def> {
         let stream = self.stream.downgrade();
@@ -546,7 +563,18 @@ where Def: HasOutputStatic
 
 impl<Out: Data> EventEmitter for OwnedStream<Out> {
     fn emit_event(&self, stack: CallStack, value: &Self::Output) {
-        self.data.emit_event(stack, value)
+        let transaction = &self.data.transaction;
+        if transaction.defers_emission() {
+            let data = Rc::downgrade(&self.data);
+            let value = value.clone();
+            transaction.defer_emission(move || {
+                if let Some(data) = data.upgrade() {
+                    data.transaction.handle(|| data.emit_event(&default(), &value));
+                }
+            });
+        } else {
+            self.data.emit_event(stack, value)
+        }
     }
 
     fn register_target(&self, target: EventInput<Output<Self>>) {
@@ -603,6 +631,7 @@ impl<Def, T> WeakEventConsumer<T> for WeakNode<Def>
 where
     Def: HasOutputStatic,
     Node<Def>: EventConsumer<T>,
+    T: Clone + 'static,
 {
     fn is_dropped(&self) -> bool {
         self.definition.strong_count() == 0
@@ -611,7 +640,20 @@ where
     fn on_event_if_exists(&self, stack: CallStack, value: &T) -> bool {
         self.upgrade()
             .map(|node| {
-                node.on_event(stack, value);
+                let transaction = &node.stream.data.transaction;
+                if transaction.is_transactional() {
+                    let weak = self.clone_ref();
+                    let value = value.clone();
+                    transaction.schedule(move || {
+                        if let Some(node) = weak.upgrade() {
+                            let transaction = &node.stream.data.transaction;
+                            transaction.on_delivery();
+                            transaction.handle(|| node.on_event(&default(), &value));
+                        }
+                    });
+                } else {
+                    transaction.handle(|| node.on_event(stack, value));
+                }
             })
             .is_some()
     }
@@ -0,0 +1,401 @@
+//! Helpers for testing FRP networks with marble diagrams.
+//!
+//! A marble diagram describes a timeline of events as a string, in which every character is a
+//! frame of time:
+//! - `-` is a frame without events,
+//! - any letter or digit is a frame with a single event, named by the character,
+//! - `(ab)` is a single frame with several events, emitted in order,
+//! - `|` marks the end of the timeline; it is optional, and nothing may follow it.
+//!
+//! Spaces are ignored, so they can be used to align diagrams. The values of the events are given
+//! separately, as a mapping from the names to values.
+//!
+//! The [`Marbles`] harness drives sources created from diagrams with a [`VirtualClock`], which can
+//! also be passed to the time-based nodes of the tested network, and compares the events recorded
+//! on any stream with an expected diagram:
+//!
+//! ```text
+//! let test = Marbles::new();
+//! let input = test.source("-a-b------c--", &[('a', 1), ('b', 2), ('c', 3)]);
+//! frp::new_network! { network
+//!     output <- input.debounce(test.clock(), test.frame() * 3);
+//! }
+//! let output = test.record(&output);
+//! test.expect(&output, "------b------c|", &[('b', 2), ('c', 3)]);
+//! ```
+
+use crate::prelude::*;
+
+use crate as frp;
+use crate::clock::Clock;
+use crate::clock::VirtualClock;
+use crate::node::Data;
+use crate::node::Output;
+use crate::stream::EventOutput;
+
+use std::time::Duration;
+
+
+
+// ================
+// === Timeline ===
+// ================
+
+/// A parsed marble diagram: the names of the events emitted in each frame.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Timeline {
+    /// The event names of every frame of the timeline.
+    pub frames: Vec<Vec<char>>,
+}
+
+impl Timeline {
+    /// Parse a marble diagram.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the diagram is malformed, i.e. it contains unbalanced or nested parentheses, or
+    /// characters after the `|` end marker.
+    pub fn parse(marbles: &str) -> Self {
+        let malformed = |reason: &str| panic!("Malformed marble diagram {marbles:?}: {reason}.");
+        let mut frames = vec![];
+        let mut group: Option<Vec<char>> = None;
+        let mut chars = marbles.chars().filter(|c| !c.is_whitespace());
+        for c in chars.by_ref() {
+            match (c, &mut group) {
+                ('(', None) => group = Some(vec![]),
+                ('(', Some(_)) => malformed("nested parentheses"),
+                (')', None) => malformed("unbalanced parentheses"),
+                (')', Some(_)) => frames.extend(group.take()),
+                ('|', None) => break,
+                ('-', None) => frames.push(vec![]),
+                ('|' | '-', Some(_)) => malformed("a group can only contain events"),
+                (c, None) => frames.push(vec![c]),
+                (c, Some(group)) => group.push(c),
+            }
+        }
+        if group.is_some() {
+            malformed("unbalanced parentheses");
+        }
+        if chars.next().is_some() {
+            malformed("characters after the end marker");
+        }
+        Self { frames }
+    }
+
+    /// The number of frames of the timeline.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Check whether the timeline has no frames.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// The events of the timeline, with the indices of their frames.
+    pub fn events(&self) -> impl Iterator<Item = (usize, char)> + '_ {
+        let frames = self.frames.iter().enumerate();
+        frames.flat_map(|(frame, names)| names.iter().map(move |name| (frame, *name)))
+    }
+}
+
+impl Display for Timeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for names in &self.frames {
+            write!(f, "{}", render_frame(names))?;
+        }
+        write!(f, "|")
+    }
+}
+
+fn render_frame(names: &[char]) -> String {
+    match names {
+        [] => "-".into(),
+        [name] => name.to_string(),
+        names => format!("({})", names.iter().collect::<String>()),
+    }
+}
+
+
+
+// ================
+// === Recorder ===
+// ================
+
+/// The events emitted by a stream, with the times of a clock at which they were emitted. Created
+/// by [`Marbles::record`].
+#[derive(CloneRef, Debug, Derivative)]
+#[derivative(Clone(bound = ""))]
+pub struct Recorder<T> {
+    events: Rc<RefCell<Vec<(Duration, T)>>>,
+}
+
+impl<T: Clone> Recorder<T> {
+    /// The recorded events, with their times.
+    pub fn events(&self) -> Vec<(Duration, T)> {
+        self.events.borrow().clone()
+    }
+
+    /// The values of the recorded events.
+    pub fn values(&self) -> Vec<T> {
+        self.events.borrow().iter().map(|(_, value)| value.clone()).collect()
+    }
+
+    /// Forget the recorded events.
+    pub fn clear(&self) {
+        self.events.borrow_mut().clear()
+    }
+}
+
+
+
+// ===============
+// === Marbles ===
+// ===============
+
+/// A harness driving and checking FRP networks with marble diagrams. See the module docs.
+///
+/// The nodes created by the harness are kept alive as long as the harness is.
+#[derive(Debug)]
+pub struct Marbles {
+    network: frp::Network,
+    clock:   VirtualClock,
+    frame:   Duration,
+}
+
+impl Default for Marbles {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Marbles {
+    /// Constructor. Every frame of the diagrams lasts one millisecond.
+    pub fn new() -> Self {
+        Self::with_frame(Duration::from_millis(1))
+    }
+
+    /// Constructor with the given duration of a frame.
+    pub fn with_frame(frame: Duration) -> Self {
+        let network = frp::Network::new("marbles");
+        let clock = default();
+        Self { network, clock, frame }
+    }
+
+    /// The clock driving the sources, to be used by the time-based nodes of the tested network.
+    pub fn clock(&self) -> &VirtualClock {
+        &self.clock
+    }
+
+    /// The duration of a frame.
+    pub fn frame(&self) -> Duration {
+        self.frame
+    }
+
+    /// The number of the frame the clock is at.
+    pub fn current_frame(&self) -> usize {
+        self.frame_at(self.clock.now())
+    }
+
+    fn frame_at(&self, time: Duration) -> usize {
+        (time.as_nanos() / self.frame.as_nanos().max(1)) as usize
+    }
+
+    /// A stream emitting the events of the diagram, with the given values, at the times of their
+    /// frames, counted from the current time of the clock.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no value is given for an event of the diagram.
+    pub fn source<T: Data>(&self, marbles: &str, values: &[(char, T)]) -> frp::Stream<T> {
+        let timeline = Timeline::parse(marbles);
+        let source = self.network.source::<T>("marbles_source");
+        for (frame, name) in timeline.events() {
+            let value = values.iter().find(|(n, _)| *n == name).map(|(_, value)| value.clone());
+            let value = value.unwrap_or_else(|| panic!("No value given for the event {name:?}."));
+            let source = source.clone_ref();
+            let delay = self.frame * frame as u32;
+            self.clock.schedule(delay, Box::new(move || source.emit(value)));
+        }
+        source.into()
+    }
+
+    /// Record the events emitted by the stream from now on.
+    pub fn record<S: EventOutput>(&self, stream: &S) -> Recorder<Output<S>> {
+        let recorder = Recorder { events: default() };
+        let events = recorder.events.clone_ref();
+        let clock = self.clock.clone_ref();
+        let stream = stream.clone_ref();
+        let network = &self.network;
+        frp::extend! { network
+            eval stream ([events](value) events.borrow_mut().push((clock.now(), value.clone())));
+        }
+        recorder
+    }
+
+    /// Advance the clock to the end of the given frame, calling all the callbacks scheduled
+    /// before the start of the next one.
+    pub fn run_until_frame(&self, frame: usize) {
+        let end = self.frame * (frame + 1) as u32 - Duration::from_nanos(1);
+        self.clock.advance(end.saturating_sub(self.clock.now()));
+    }
+
+    /// Compare the events recorded from the start of the clock with the diagram, after advancing
+    /// the clock to the end of the diagram. Events are compared by the frames they were emitted
+    /// in.
+    pub fn check<T>(
+        &self,
+        recorder: &Recorder<T>,
+        marbles: &str,
+        values: &[(char, T)],
+    ) -> Result<(), Mismatch>
+    where
+        T: Data + PartialEq,
+    {
+        let expected = Timeline::parse(marbles);
+        if !expected.is_empty() {
+            self.run_until_frame(expected.len() - 1);
+        }
+        let mut unknown = vec![];
+        let mut recorded = Timeline::default();
+        for (time, value) in recorder.events() {
+            let name = values.iter().find(|(_, v)| *v == value).map(|(name, _)| *name);
+            let name = name.unwrap_or_else(|| {
+                unknown.push(format!("{value:?}"));
+                '?'
+            });
+            let frame = self.frame_at(time);
+            if recorded.frames.len() <= frame {
+                recorded.frames.resize(frame + 1, vec![]);
+            }
+            recorded.frames[frame].push(name);
+        }
+        let len = recorded.len().max(expected.len());
+        recorded.frames.resize(len, vec![]);
+        let mut padded_expected = expected.clone();
+        padded_expected.frames.resize(len, vec![]);
+        let first_difference = (0..len).find(|&i| recorded.frames[i] != padded_expected.frames[i]);
+        match first_difference {
+            None if unknown.is_empty() => Ok(()),
+            frame => {
+                let frame = frame.unwrap_or_default();
+                let values = values.iter().map(|(name, value)| (*name, format!("{value:?}")));
+                let values = values.collect();
+                Err(Mismatch { expected, recorded, frame, values, unknown })
+            }
+        }
+    }
+
+    /// Like [`Self::check`], but panics with a readable diff if the recorded events differ from
+    /// the diagram.
+    #[track_caller]
+    pub fn expect<T>(&self, recorder: &Recorder<T>, marbles: &str, values: &[(char, T)])
+    where T: Data + PartialEq {
+        if let Err(mismatch) = self.check(recorder, marbles, values) {
+            panic!("{mismatch}");
+        }
+    }
+}
+
+
+
+// ================
+// === Mismatch ===
+// ================
+
+/// A difference between the recorded and the expected events, returned by [`Marbles::check`]. Its
+/// [`Display`] representation shows both timelines, aligned frame by frame with spaces.
+#[derive(Clone, Debug)]
+pub struct Mismatch {
+    /// The expected timeline.
+    pub expected: Timeline,
+    /// The recorded timeline. Events with values not given for the expected diagram are named `?`.
+    pub recorded: Timeline,
+    /// The first frame at which the timelines differ.
+    pub frame:    usize,
+    /// The names of the expected events, with the debug representations of their values.
+    pub values:   Vec<(char, String)>,
+    /// The debug representations of the values of the events named `?`.
+    pub unknown:  Vec<String>,
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let frames = self.expected.len().max(self.recorded.len());
+        let frame_of = |timeline: &Timeline, i: usize| match timeline.frames.get(i) {
+            Some(names) => render_frame(names),
+            None if i == timeline.len() => "|".into(),
+            None => " ".into(),
+        };
+        let mut expected = String::new();
+        let mut recorded = String::new();
+        let mut marker = String::new();
+        for i in 0..=frames {
+            let (e, r) = (frame_of(&self.expected, i), frame_of(&self.recorded, i));
+            let width = e.chars().count().max(r.chars().count());
+            expected.push_str(&format!("{e:width$}"));
+            recorded.push_str(&format!("{r:width$}"));
+            let mark = if i == self.frame { "^" } else { " " };
+            marker.push_str(&format!("{mark:width$}"));
+        }
+        writeln!(f, "The recorded events differ from the expected ones at frame {}.", self.frame)?;
+        writeln!(f, "expected: {}", expected.trim_end())?;
+        writeln!(f, "recorded: {}", recorded.trim_end())?;
+        writeln!(f, "          {}", marker.trim_end())?;
+        for (name, value) in &self.values {
+            writeln!(f, "  {name} = {value}")?;
+        }
+        for value in &self.unknown {
+            writeln!(f, "  ? = {value}")?;
+        }
+        Ok(())
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let timeline = Timeline::parse("-a (bc)- d|");
+        assert_eq!(timeline.frames, vec![vec![], vec!['a'], vec!['b', 'c'], vec![], vec!['d']]);
+        assert_eq!(timeline.to_string(), "-a(bc)-d|");
+    }
+
+    #[test]
+    fn debounce() {
+        let test = Marbles::new();
+        let input = test.source("-a-b------c--", &[('a', 1), ('b', 2), ('c', 3)]);
+        frp::new_network! { network
+            output <- input.debounce(test.clock(), test.frame() * 3);
+        }
+        let output = test.record(&output);
+        test.expect(&output, "------b------c|", &[('b', 2), ('c', 3)]);
+    }
+
+    #[test]
+    fn mismatch() {
+        let test = Marbles::new();
+        let input = test.source("-a-(bc)", &[('a', 1), ('b', 2), ('c', 3)]);
+        let output = test.record(&input);
+        let mismatch = test.check(&output, "-a--b|", &[('a', 1), ('b', 2)]).unwrap_err();
+        assert_eq!(mismatch.frame, 3);
+        let expected = "\
+The recorded events differ from the expected ones at frame 3.
+expected: -a--   b|
+recorded: -a-(b?)-|
+             ^
+  a = 1
+  b = 2
+  ? = 3
+";
+        assert_eq!(mismatch.to_string(), expected);
+    }
+}
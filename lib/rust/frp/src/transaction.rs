@@ -0,0 +1,458 @@
+//! Transactional, rank-ordered event propagation.
+//!
+//! By default, events are delivered depth-first, as soon as they are emitted. In diamond-shaped
+//! graphs, like `all(a,b)` where both `a` and `b` are derived from the same source, this causes
+//! downstream nodes to fire once per path and to observe inputs which were not updated yet. The
+//! [`Propagation::Transactional`] mode solves this issue. Events emitted to transactional nodes
+//! are not delivered immediately. Instead, they are queued and delivered after the emitting node
+//! finishes its emission, in the order of node ranks. As a node rank is always greater than ranks
+//! of its inputs, every node fires at most once per transaction and reads consistent values from
+//! all of its inputs.
+//!
+//! A few details are worth mentioning:
+//! - A transaction is opened only when an event is scheduled for a transactional node. Emissions
+//!   which do not reach any transactional node are not affected by this module.
+//! - Every emission of a transactional node which is not a propagation of the event the node is
+//!   handling, like an emission of a source from an `eval` handler, is delivered in its own
+//!   transaction. If a transaction is running already, the emission is deferred until it finishes,
+//!   so the emitted value is not visible to the handler. The same applies to a node propagating
+//!   more than one event in a single transaction.
+//! - Nodes are ranked in the order of their creation. It is a valid topological order, as nodes can
+//!   be connected only to already existing streams. The exception are nodes which can be attached
+//!   to new inputs after they were created, like `any_mut`. They are still delivered at most once
+//!   per transaction, but their position in the order is not guaranteed to follow their inputs.
+//! - When a node receives several events in a single transaction, only the first one is delivered.
+//!   For example, `any(a,b)` in a diamond graph passes the event of `a` only.
+//! - Events delivered to a node which already fired in the current transaction (for example in
+//!   recursive networks) are delivered in a follow-up transaction. The length of the chains of
+//!   follow-up and deferred transactions is limited, just like the number of recursive evaluations
+//!   in the depth-first mode.
+//! - Delivered events carry an empty call stack, as the emitting node is not on the stack anymore.
+//! - If a delivery panics, all scheduled events are dropped, so the following emissions are not
+//!   affected.
+
+use crate::prelude::*;
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::collections::BinaryHeap;
+
+
+
+// ===================
+// === Propagation ===
+// ===================
+
+/// The way events are delivered to the nodes of a [`crate::Network`]. See the module docs to
+/// learn more.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Propagation {
+    /// Events are delivered depth-first, as soon as they are emitted.
+    #[default]
+    DepthFirst,
+    /// Events are delivered in transactions, in the order of node ranks.
+    Transactional,
+}
+
+
+
+// ============
+// === Rank ===
+// ============
+
+/// Position of a node in the delivery order. See the module docs to learn more.
+pub type Rank = u64;
+
+/// Transaction identifier. Identifiers grow monotonically and the value of `0` is never used.
+type TransactionId = u64;
+
+const TRANSACTION_DEPTH_LIMIT: usize = 100;
+
+thread_local! {
+    static NEXT_RANK: Cell<Rank> = const { Cell::new(0) };
+    static SCHEDULER: Scheduler = default();
+}
+
+
+
+// =================
+// === NodeState ===
+// =================
+
+/// Transaction bookkeeping of a single node.
+#[derive(Debug)]
+pub struct NodeState {
+    rank:       Rank,
+    enabled:    Cell<bool>,
+    handling:   Cell<usize>,
+    emitted_in: Cell<TransactionId>,
+    fired_in:   Cell<TransactionId>,
+    pending:    RefCell<Vec<TransactionId>>,
+}
+
+impl NodeState {
+    /// Constructor. Assigns the next rank to the node.
+    pub fn new() -> Self {
+        let rank = NEXT_RANK.with(|next| next.replace(next.get() + 1));
+        let enabled = default();
+        let handling = default();
+        let emitted_in = default();
+        let fired_in = default();
+        let pending = default();
+        Self { rank, enabled, handling, emitted_in, fired_in, pending }
+    }
+
+    /// Rank of the node.
+    pub fn rank(&self) -> Rank {
+        self.rank
+    }
+
+    /// Set the way events are delivered to the node.
+    pub fn set_propagation(&self, propagation: Propagation) {
+        self.enabled.set(propagation == Propagation::Transactional);
+    }
+
+    /// Check whether events should be scheduled instead of being delivered immediately.
+    pub fn is_transactional(&self) -> bool {
+        self.enabled.get()
+    }
+
+    /// Run `f`, which passes an event to the node or performs its deferred emission. Emissions of
+    /// the node done by `f` propagate the handled event, so they belong to the current transaction.
+    pub fn handle<T>(&self, f: impl FnOnce() -> T) -> T {
+        let _handling = Handling::new(&self.handling);
+        f()
+    }
+
+    /// Check whether the emission of the node should be deferred to a new transaction. See the
+    /// module docs to learn more.
+    pub fn defers_emission(&self) -> bool {
+        self.is_transactional()
+            && SCHEDULER
+                .with(|scheduler| scheduler.running.get() && self.starts_emission(scheduler))
+    }
+
+    /// Defer the emission of the node. The `emit` function will be called in a new transaction,
+    /// within [`Self::handle`].
+    pub fn defer_emission(&self, emit: impl FnOnce() + 'static) {
+        SCHEDULER.with(|scheduler| {
+            let id = scheduler.new_id();
+            scheduler.push(id, self.rank, Box::new(emit));
+        })
+    }
+
+    /// Schedule the delivery of an event, opening a new transaction if none is running. The
+    /// `deliver` function has to call [`Self::on_delivery`] if the node is still alive. If the node
+    /// already has a pending delivery in the transaction, the event is dropped.
+    pub fn schedule(&self, deliver: impl FnOnce() + 'static) {
+        SCHEDULER.with(|scheduler| {
+            if !scheduler.running.get() {
+                scheduler.open();
+            }
+            let mut id = scheduler.scope.get();
+            if self.fired_in.get() == id {
+                id = scheduler.follow_up(id);
+            }
+            let mut pending = self.pending.borrow_mut();
+            if !pending.contains(&id) && scheduler.push(id, self.rank, Box::new(deliver)) {
+                pending.push(id);
+            }
+        })
+    }
+
+    /// Mark the scheduled delivery of the running transaction as done.
+    pub fn on_delivery(&self) {
+        let current = SCHEDULER.with(|scheduler| scheduler.scope.get());
+        self.pending.borrow_mut().retain(|id| *id > current);
+        self.fired_in.set(current);
+    }
+
+    /// Check whether the node emission starts a new emission instead of propagating the event the
+    /// node is handling.
+    fn starts_emission(&self, scheduler: &Scheduler) -> bool {
+        self.handling.get() == 0 || self.emitted_in.get() == scheduler.scope.get()
+    }
+}
+
+impl Default for NodeState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+// === Handling ===
+
+/// Guard of [`NodeState::handle`], restoring the handling counter even if the handler panics.
+#[derive(Debug)]
+struct Handling<'a> {
+    counter: &'a Cell<usize>,
+}
+
+impl<'a> Handling<'a> {
+    fn new(counter: &'a Cell<usize>) -> Self {
+        counter.set(counter.get() + 1);
+        Self { counter }
+    }
+}
+
+impl Drop for Handling<'_> {
+    fn drop(&mut self) {
+        self.counter.set(self.counter.get() - 1);
+    }
+}
+
+
+
+// ================
+// === Emission ===
+// ================
+
+/// Guard of a single node emission. If scheduling an event during the emission opens a new
+/// transaction, [`Self::end`] delivers it. The transaction is closed when the guard is dropped,
+/// dropping all undelivered events if the emission or any of the deliveries panicked.
+#[derive(Debug)]
+pub struct Emission {
+    was_running:    bool,
+    previous_scope: TransactionId,
+}
+
+impl Emission {
+    /// Begin the emission of the node.
+    #[inline]
+    pub fn begin(node: &NodeState) -> Self {
+        SCHEDULER.with(|scheduler| {
+            let was_running = scheduler.running.get();
+            let previous_scope = scheduler.scope.get();
+            if was_running {
+                if node.starts_emission(scheduler) {
+                    scheduler.scope.set(scheduler.new_id());
+                }
+                node.emitted_in.set(scheduler.scope.get());
+            }
+            Self { was_running, previous_scope }
+        })
+    }
+
+    /// Finish the emission, delivering all scheduled events if the emission opened a transaction.
+    #[inline]
+    pub fn end(self) {
+        if !self.was_running {
+            SCHEDULER.with(|scheduler| {
+                if scheduler.running.get() {
+                    scheduler.run();
+                }
+            })
+        }
+    }
+}
+
+impl Drop for Emission {
+    fn drop(&mut self) {
+        SCHEDULER.with(|scheduler| {
+            scheduler.scope.set(self.previous_scope);
+            if !self.was_running && scheduler.running.get() {
+                scheduler.close();
+            }
+        })
+    }
+}
+
+
+
+// ================
+// === Delivery ===
+// ================
+
+/// A scheduled event delivery or a deferred emission.
+#[derive(Derivative)]
+#[derivative(Debug)]
+struct Delivery {
+    rank:    Rank,
+    seq:     u64,
+    #[derivative(Debug = "ignore")]
+    deliver: Box<dyn FnOnce()>,
+}
+
+impl Delivery {
+    fn key(&self) -> (Rank, u64) {
+        (self.rank, self.seq)
+    }
+}
+
+impl PartialEq for Delivery {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Delivery {}
+
+impl PartialOrd for Delivery {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The order is reversed, so the delivery with the lowest rank is on top of the [`BinaryHeap`].
+impl Ord for Delivery {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.key().cmp(&self.key())
+    }
+}
+
+
+
+// ===================
+// === Transaction ===
+// ===================
+
+/// Deliveries scheduled in a single transaction.
+#[derive(Debug)]
+struct Transaction {
+    /// The number of transactions which caused this one, used to break infinite loops.
+    depth:     usize,
+    follow_up: Option<TransactionId>,
+    queue:     BinaryHeap<Delivery>,
+}
+
+impl Transaction {
+    fn new(depth: usize) -> Self {
+        let follow_up = default();
+        let queue = default();
+        Self { depth, follow_up, queue }
+    }
+}
+
+
+
+// =================
+// === Scheduler ===
+// =================
+
+/// Thread-local queue of transactions. Transactions are run in the order of their identifiers, so
+/// emissions are delivered in the order they happened.
+#[derive(Debug, Default)]
+struct Scheduler {
+    running:      Cell<bool>,
+    last_id:      Cell<TransactionId>,
+    /// The transaction new deliveries are scheduled in.
+    scope:        Cell<TransactionId>,
+    /// Depth of the running transaction.
+    depth:        Cell<usize>,
+    next_seq:     Cell<u64>,
+    transactions: RefCell<BTreeMap<TransactionId, Transaction>>,
+}
+
+impl Scheduler {
+    fn new_id(&self) -> TransactionId {
+        let id = self.last_id.get() + 1;
+        self.last_id.set(id);
+        id
+    }
+
+    fn open(&self) {
+        let id = self.new_id();
+        self.running.set(true);
+        self.scope.set(id);
+        self.depth.set(0);
+        self.transactions.borrow_mut().insert(id, Transaction::new(0));
+    }
+
+    fn close(&self) {
+        self.running.set(false);
+        self.transactions.borrow_mut().clear();
+    }
+
+    /// Get the follow-up transaction of the provided one, creating it if needed.
+    fn follow_up(&self, id: TransactionId) -> TransactionId {
+        let depth = self.depth.get();
+        let mut transactions = self.transactions.borrow_mut();
+        let transaction = transactions.entry(id).or_insert_with(|| Transaction::new(depth + 1));
+        *transaction.follow_up.get_or_insert_with(|| self.new_id())
+    }
+
+    /// Push the delivery to the transaction. Returns `false` if the delivery was dropped because
+    /// the transaction depth limit was exceeded.
+    fn push(&self, id: TransactionId, rank: Rank, deliver: Box<dyn FnOnce()>) -> bool {
+        let depth = self.depth.get();
+        let mut transactions = self.transactions.borrow_mut();
+        let transaction = transactions.entry(id).or_insert_with(|| Transaction::new(depth + 1));
+        if transaction.depth > TRANSACTION_DEPTH_LIMIT {
+            warn!("The transaction depth limit exceeded. Dropping the scheduled event.");
+            false
+        } else {
+            let seq = self.next_seq.replace(self.next_seq.get() + 1);
+            transaction.queue.push(Delivery { rank, seq, deliver });
+            true
+        }
+    }
+
+    /// Take the next delivery of the earliest transaction.
+    fn next_delivery(&self) -> Option<(TransactionId, usize, Delivery)> {
+        let mut transactions = self.transactions.borrow_mut();
+        loop {
+            let mut entry = transactions.first_entry()?;
+            match entry.get_mut().queue.pop() {
+                Some(delivery) => return Some((*entry.key(), entry.get().depth, delivery)),
+                None => drop(entry.remove()),
+            }
+        }
+    }
+
+    /// Deliver all scheduled events, including the ones scheduled during the delivery.
+    #[inline(never)]
+    fn run(&self) {
+        while let Some((id, depth, delivery)) = self.next_delivery() {
+            self.scope.set(id);
+            self.depth.set(depth);
+            (delivery.deliver)();
+        }
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delivery_order() {
+        let log: Rc<RefCell<Vec<usize>>> = default();
+        let emitter = NodeState::new();
+        let nodes = (0..3).map(|_| NodeState::new()).collect_vec();
+        nodes.iter().for_each(|node| node.set_propagation(Propagation::Transactional));
+        let emission = Emission::begin(&emitter);
+        for index in [2, 0, 1, 0] {
+            let log = log.clone_ref();
+            nodes[index].schedule(move || log.borrow_mut().push(index));
+        }
+        assert!(log.borrow().is_empty());
+        emission.end();
+        assert_eq!(*log.borrow(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn panic_recovery() {
+        let log: Rc<RefCell<Vec<usize>>> = default();
+        let emitter = NodeState::new();
+        let node = NodeState::new();
+        node.set_propagation(Propagation::Transactional);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let emission = Emission::begin(&emitter);
+            node.schedule(|| panic!("Expected panic."));
+            emission.end();
+        }));
+        assert!(result.is_err());
+        let emission = Emission::begin(&emitter);
+        let log_clone = log.clone_ref();
+        node.schedule(move || log_clone.borrow_mut().push(1));
+        emission.end();
+        assert_eq!(*log.borrow(), vec![1]);
+    }
+}
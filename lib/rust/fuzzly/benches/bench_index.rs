@@ -0,0 +1,149 @@
+//! This file contains benchmarks comparing the fuzzy search using `fuzzly::Index` with scoring
+//! every entry of the corpus.
+
+use criterion::black_box;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::Criterion;
+use fuzzly::find_best_subsequence;
+use fuzzly::matches;
+use fuzzly::metric;
+use fuzzly::Index;
+use std::time::Duration;
+
+
+
+// =================
+// === Utilities ===
+// =================
+
+/// The number of best matches returned by each search.
+const TOP_K: usize = 50;
+
+/// The pattern typed by the user, one character at a time.
+const PATTERN: &str = "vecmap";
+
+/// The base configuration for the benchmarks.
+fn bench_config() -> Criterion {
+    Criterion::default()
+        .measurement_time(Duration::from_secs(20))
+        .warm_up_time(Duration::from_secs(3))
+        .sample_size(25)
+}
+
+/// Create a corpus resembling the labels in the suggestion database with the full standard library
+/// loaded: almost thirty thousand method names qualified with their self type.
+fn gen_corpus() -> Vec<String> {
+    let types = [
+        "Vector", "Text", "Table", "Column", "Date", "Time", "File", "Json", "Map", "Array",
+        "Range", "Integer", "Decimal", "Boolean", "Error", "Http", "Uri", "Duration", "Regex",
+        "Locale",
+    ];
+    let type_suffixes = ["", "_Builder", "_Iterator"];
+    let verbs = [
+        "map", "filter", "fold", "to", "from", "get", "set", "is", "find", "sort", "take", "drop",
+        "join", "split", "parse", "format", "read", "write", "reduce", "zip", "each", "at",
+    ];
+    let nouns = [
+        "text", "vector", "index", "value", "column", "table", "row", "key", "json", "date",
+        "file", "name", "case", "first", "last", "all", "any", "empty", "default", "with",
+    ];
+    let mut corpus = Vec::new();
+    for tp in types {
+        for suffix in type_suffixes {
+            for verb in verbs {
+                for noun in nouns {
+                    corpus.push(format!("{tp}{suffix}.{verb}_{noun}"));
+                }
+                corpus.push(format!("{tp}{suffix}.{verb}"));
+            }
+        }
+    }
+    corpus
+}
+
+/// Find the best matches by checking and scoring every entry in the corpus, as the searcher does
+/// without the index.
+fn linear_top_k(corpus: &[String], pattern: &str) -> Vec<(usize, fuzzly::Subsequence)> {
+    let mut results = corpus
+        .iter()
+        .enumerate()
+        .filter(|(_, text)| matches(text.as_str(), pattern))
+        .filter_map(|(id, text)| {
+            let subsequence = find_best_subsequence(text.as_str(), pattern, metric::default())?;
+            Some((id, subsequence))
+        })
+        .collect::<Vec<_>>();
+    results.sort_by(|(_, lhs), (_, rhs)| rhs.compare_scores(lhs));
+    results.truncate(TOP_K);
+    results
+}
+
+/// All prefixes of [`PATTERN`], in the order they appear when the user types it.
+fn typed_patterns() -> impl Iterator<Item = &'static str> {
+    (1..=PATTERN.len()).map(|len| &PATTERN[..len])
+}
+
+
+
+// ==================
+// === Benchmarks ===
+// ==================
+
+/// Build the index over the whole corpus.
+fn build_index(c: &mut Criterion) {
+    let corpus = gen_corpus();
+    c.bench_function("Build Index", |b| b.iter(|| Index::new(black_box(&corpus))));
+}
+
+/// Score every entry for each keystroke.
+fn linear_typing(c: &mut Criterion) {
+    let corpus = gen_corpus();
+    c.bench_function("Typing, Linear Scan", |b| {
+        b.iter(|| {
+            for pattern in typed_patterns() {
+                black_box(linear_top_k(&corpus, black_box(pattern)));
+            }
+        })
+    });
+}
+
+/// Query the index from scratch for each keystroke.
+fn index_typing(c: &mut Criterion) {
+    let index = Index::new(gen_corpus());
+    c.bench_function("Typing, Index", |b| {
+        b.iter(|| {
+            for pattern in typed_patterns() {
+                black_box(index.top_k(black_box(pattern), TOP_K, metric::default()));
+            }
+        })
+    });
+}
+
+/// Use the incremental search for consecutive keystrokes.
+fn incremental_typing(c: &mut Criterion) {
+    let index = Index::new(gen_corpus());
+    c.bench_function("Typing, Incremental Search", |b| {
+        b.iter(|| {
+            let mut search = index.search();
+            for pattern in typed_patterns() {
+                search.set_pattern(black_box(pattern));
+                black_box(search.top_k(TOP_K, metric::default()));
+            }
+        })
+    });
+}
+
+criterion_group! {
+    name    = index_benchmarks;
+    config  = bench_config();
+    targets = build_index,linear_typing,index_typing,incremental_typing
+}
+
+
+
+// ==============
+// === Runner ===
+// ==============
+
+criterion_main!(index_benchmarks);
@@ -0,0 +1,321 @@
+//! A precomputed index over a corpus of strings, allowing fast fuzzy search in large sets.
+
+use crate::prelude::*;
+
+use crate::metric::Metric;
+use crate::score::find_best_subsequence;
+use crate::score::matches;
+use crate::score::Subsequence;
+
+
+
+// ================
+// === CharMask ===
+// ================
+
+/// A set of characters present in some text, stored as a bitset.
+///
+/// The characters are compared case-insensitively, the same way as in [`matches`]. ASCII
+/// alphanumeric characters and underscore have dedicated bits, all other characters share the
+/// remaining bits by their code point. Therefore the mask may only be used as a necessary
+/// condition: if the pattern's mask is not a subset of the text's mask, the text cannot match.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+struct CharMask(u64);
+
+impl CharMask {
+    const DEDICATED_BITS: u32 = 37;
+    const SHARED_BITS: u32 = u64::BITS - Self::DEDICATED_BITS;
+
+    fn new(text: &str) -> Self {
+        text.chars().fold(default(), |mask: Self, ch| Self(mask.0 | Self::bit(ch)))
+    }
+
+    fn bit(ch: char) -> u64 {
+        let index = match ch.to_ascii_lowercase() {
+            ch @ 'a'..='z' => ch as u32 - 'a' as u32,
+            ch @ '0'..='9' => ch as u32 - '0' as u32 + 26,
+            '_' => 36,
+            ch => Self::DEDICATED_BITS + ch as u32 % Self::SHARED_BITS,
+        };
+        1 << index
+    }
+
+    fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+
+
+// =============
+// === Match ===
+// =============
+
+/// A single result of the search in [`Index`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Match {
+    /// The id of matched text, being its position in the corpus the index was built from.
+    pub id:          usize,
+    /// The best subsequence of the text matching the pattern.
+    pub subsequence: Subsequence,
+}
+
+
+
+// =============
+// === Index ===
+// =============
+
+/// A precomputed index over a corpus of strings.
+///
+/// Scoring each text with [`find_best_subsequence`] is relatively costly, so when searching in
+/// large corpora (like the whole suggestion database) we want to score only the texts which
+/// actually match the pattern. The index keeps, for each text, the [`CharMask`] of its characters
+/// and, for each character, the sorted list of texts containing it (the "postings"). The candidates
+/// for the pattern are taken from the postings of its rarest character, filtered by the mask and
+/// finally checked with [`matches`].
+///
+/// The texts are identified by their position in the corpus passed to [`Index::new`].
+#[derive(Clone, Debug, Default)]
+pub struct Index {
+    texts:    Vec<String>,
+    masks:    Vec<CharMask>,
+    postings: HashMap<char, Vec<usize>>,
+}
+
+impl Index {
+    /// Build the index over given corpus.
+    pub fn new<S: AsRef<str>>(corpus: impl IntoIterator<Item = S>) -> Self {
+        let texts = corpus.into_iter().map(|text| text.as_ref().to_owned()).collect_vec();
+        let masks = texts.iter().map(|text| CharMask::new(text)).collect();
+        let mut postings: HashMap<char, Vec<usize>> = default();
+        for (id, text) in texts.iter().enumerate() {
+            for ch in text.chars().map(|ch| ch.to_ascii_lowercase()).unique() {
+                postings.entry(ch).or_default().push(id);
+            }
+        }
+        Self { texts, masks, postings }
+    }
+
+    /// The number of indexed texts.
+    pub fn len(&self) -> usize {
+        self.texts.len()
+    }
+
+    /// Check if there are no indexed texts.
+    pub fn is_empty(&self) -> bool {
+        self.texts.is_empty()
+    }
+
+    /// Get the text with given id.
+    pub fn text(&self, id: usize) -> Option<&str> {
+        self.texts.get(id).map(|text| text.as_str())
+    }
+
+    /// Return ids of all texts matching the `pattern` (in the sense of [`matches`]), in
+    /// ascending order.
+    pub fn candidates(&self, pattern: impl Str) -> Vec<usize> {
+        let pattern = pattern.as_ref();
+        match self.rarest_char_postings(pattern) {
+            Some(postings) => self.filter_candidates(postings.iter().copied(), pattern),
+            None => (0..self.len()).collect(),
+        }
+    }
+
+    /// Return the `k` best matches of `pattern` in terms of given `metric`, best first.
+    pub fn top_k(&self, pattern: impl Str, k: usize, metric: impl Metric) -> Vec<Match> {
+        let pattern = pattern.as_ref();
+        let candidates = self.candidates(pattern);
+        self.top_k_of(&candidates, pattern, k, metric, |_| 0.0)
+    }
+
+    /// Return the `k` best matches of `pattern` in terms of given `metric`, best first. The score
+    /// of each match is increased by the `prior` of its text id, e.g. one computed by
+    /// [`UsagePrior`](crate::usage::UsagePrior).
+    pub fn top_k_with_prior(
+        &self,
+        pattern: impl Str,
+        k: usize,
+        metric: impl Metric,
+        prior: impl Fn(usize) -> f32,
+    ) -> Vec<Match> {
+        let pattern = pattern.as_ref();
+        let candidates = self.candidates(pattern);
+        self.top_k_of(&candidates, pattern, k, metric, prior)
+    }
+
+    /// Start an incremental search. See [`Search`] docs.
+    pub fn search(&self) -> Search<'_> {
+        Search::new(self)
+    }
+
+    /// The postings of the `pattern`'s character occurring in the fewest texts. Returns `None` for
+    /// an empty pattern.
+    fn rarest_char_postings(&self, pattern: &str) -> Option<&[usize]> {
+        let postings_of = |ch: char| self.postings.get(&ch.to_ascii_lowercase());
+        let postings = pattern.chars().map(|ch| postings_of(ch).map_or(&[][..], |p| p.as_slice()));
+        postings.min_by_key(|postings| postings.len())
+    }
+
+    fn filter_candidates(&self, ids: impl Iterator<Item = usize>, pattern: &str) -> Vec<usize> {
+        let pattern_mask = CharMask::new(pattern);
+        let has_all_chars = |id: &usize| self.masks[*id].contains(pattern_mask);
+        ids.filter(has_all_chars).filter(|id| matches(&self.texts[*id], pattern)).collect()
+    }
+
+    fn top_k_of(
+        &self,
+        candidates: &[usize],
+        pattern: &str,
+        k: usize,
+        metric: impl Metric,
+        prior: impl Fn(usize) -> f32,
+    ) -> Vec<Match> {
+        let mut best: Vec<Match> = Vec::with_capacity(k + 1);
+        if k == 0 {
+            return best;
+        }
+        for &id in candidates {
+            if let Some(mut subsequence) = find_best_subsequence(&self.texts[id], pattern, &metric)
+            {
+                subsequence.score += prior(id);
+                let is_better = |m: &Match| m.subsequence.compare_scores(&subsequence).is_lt();
+                let position = best.iter().position(is_better).unwrap_or(best.len());
+                if position < k {
+                    best.insert(position, Match { id, subsequence });
+                    best.truncate(k);
+                }
+            }
+        }
+        best
+    }
+}
+
+
+
+// ==============
+// === Search ===
+// ==============
+
+/// An incremental search in [`Index`].
+///
+/// The search remembers the candidates for the last pattern. When the pattern is extended (e.g.
+/// the user typed another character), every text matching the new pattern must also match the
+/// previous one, so only the previous candidates are checked, unless the postings of the rarest
+/// pattern's character are even shorter.
+#[derive(Clone, Debug)]
+pub struct Search<'a> {
+    index:      &'a Index,
+    pattern:    String,
+    candidates: Vec<usize>,
+}
+
+impl<'a> Search<'a> {
+    fn new(index: &'a Index) -> Self {
+        let candidates = (0..index.len()).collect();
+        Self { index, pattern: default(), candidates }
+    }
+
+    /// The current pattern.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Ids of all texts matching the current pattern, in ascending order.
+    pub fn candidates(&self) -> &[usize] {
+        &self.candidates
+    }
+
+    /// Change the pattern, reusing the previous results if the new pattern extends the old one.
+    pub fn set_pattern(&mut self, pattern: impl Str) {
+        let pattern = pattern.as_ref();
+        if pattern != self.pattern {
+            let rarest_char_postings = self.index.rarest_char_postings(pattern);
+            let is_extension = pattern.starts_with(&self.pattern);
+            self.candidates = match rarest_char_postings {
+                Some(postings) if is_extension && postings.len() < self.candidates.len() =>
+                    self.index.filter_candidates(postings.iter().copied(), pattern),
+                _ if is_extension =>
+                    self.index.filter_candidates(self.candidates.iter().copied(), pattern),
+                _ => self.index.candidates(pattern),
+            };
+            self.pattern = pattern.to_owned();
+        }
+    }
+
+    /// Return the `k` best matches of the current pattern in terms of given `metric`, best first.
+    pub fn top_k(&self, k: usize, metric: impl Metric) -> Vec<Match> {
+        self.index.top_k_of(&self.candidates, &self.pattern, k, metric, |_| 0.0)
+    }
+
+    /// Return the `k` best matches of the current pattern in terms of given `metric`, with the
+    /// `prior` of each text id added to its score. See [`Index::top_k_with_prior`].
+    pub fn top_k_with_prior(
+        &self,
+        k: usize,
+        metric: impl Metric,
+        prior: impl Fn(usize) -> f32,
+    ) -> Vec<Match> {
+        self.index.top_k_of(&self.candidates, &self.pattern, k, metric, prior)
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::metric;
+
+    const CORPUS: &[&str] = &["map", "filter", "fold", "flat_map", "Maybe", "to_text", "Ümlaut"];
+
+    fn linear_candidates(pattern: &str) -> Vec<usize> {
+        CORPUS.iter().positions(|text| matches(*text, pattern)).collect()
+    }
+
+    #[test]
+    fn candidates_are_the_same_as_in_linear_scan() {
+        let index = Index::new(CORPUS);
+        let patterns = ["", "m", "MA", "map", "fl", "ft", "xyz", "ü", "Üm", "_", "t_t"];
+        for pattern in patterns {
+            assert_eq!(index.candidates(pattern), linear_candidates(pattern), "{pattern:?}");
+        }
+    }
+
+    #[test]
+    fn incremental_search() {
+        let index = Index::new(CORPUS);
+        let mut search = index.search();
+        assert_eq!(search.candidates(), &[0, 1, 2, 3, 4, 5, 6]);
+        for pattern in ["f", "fl", "fla", "fl", "m", "ma", "map", "mp", ""] {
+            search.set_pattern(pattern);
+            assert_eq!(search.pattern(), pattern);
+            assert_eq!(search.candidates(), linear_candidates(pattern), "{pattern:?}");
+        }
+    }
+
+    #[test]
+    fn top_k_matches() {
+        let index = Index::new(CORPUS);
+        let score = |text: &str| find_best_subsequence(text, "ma", metric::default()).unwrap();
+        let expected = vec![Match { id: 0, subsequence: score("map") }, Match {
+            id:          4,
+            subsequence: score("Maybe"),
+        }];
+        assert_eq!(index.top_k("ma", 2, metric::default()), expected);
+        let all = index.top_k("ma", 10, metric::default());
+        assert_eq!(all.iter().map(|m| m.id).sorted().collect_vec(), vec![0, 3, 4, 6]);
+        assert!(index.top_k("ma", 0, metric::default()).is_empty());
+        let mut search = index.search();
+        search.set_pattern("ma");
+        assert_eq!(search.top_k(2, metric::default()), expected);
+        let prefer_flat_map = |id| if id == 3 { 10.0 } else { 0.0 };
+        let boosted = search.top_k_with_prior(1, metric::default(), prefer_flat_map);
+        assert_eq!(boosted.iter().map(|m| m.id).collect_vec(), vec![3]);
+    }
+}
@@ -7,6 +7,10 @@
 //!
 //! The metrics used for scoring may be adjusted by implementing `Metric` trait, or by customizing
 //! parameters of metrics defined in `metric` module.
+//!
+//! When searching in a large, rarely changing set of names, build the `Index` once and use it to
+//! prune the candidates before scoring. The `usage` module allows ranking the items the user
+//! chooses often or recently higher than the others.
 
 // === Features ===
 #![feature(option_result_contains)]
@@ -29,11 +33,14 @@
 // === Export ===
 // ==============
 
+pub mod index;
 pub mod metric;
 pub mod score;
 pub mod subsequence_graph;
+pub mod usage;
 
 pub use enso_prelude as prelude;
+pub use index::Index;
 pub use metric::Metric;
 pub use score::find_best_subsequence;
 pub use score::matches;
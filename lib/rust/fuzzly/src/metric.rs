@@ -29,6 +29,16 @@ pub trait Metric {
     }
 }
 
+impl<M: Metric> Metric for &M {
+    fn measure_vertex(&self, vertex: subsequence_graph::Vertex, text: &str, pattern: &str) -> f32 {
+        (*self).measure_vertex(vertex, text, pattern)
+    }
+
+    fn measure_edge(&self, edge: subsequence_graph::Edge, text: &str, pattern: &str) -> f32 {
+        (*self).measure_edge(edge, text, pattern)
+    }
+}
+
 
 
 // ==========================
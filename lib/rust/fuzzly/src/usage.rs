@@ -0,0 +1,242 @@
+//! Usage-based priors for ranking the matches.
+//!
+//! The [`Metric`](crate::Metric) sees only the text and the pattern, so all items with similar
+//! names get similar scores. This module keeps the history of the user's choices and turns it into
+//! a prior added to the [`Subsequence`] score: items which are chosen often, were chosen recently
+//! or were marked as favourites are listed first.
+
+use crate::prelude::*;
+
+use crate::score::Subsequence;
+
+use serde::Deserialize;
+use serde::Serialize;
+use std::time::Duration;
+
+
+
+// =================
+// === ItemUsage ===
+// =================
+
+/// The usage statistics of a single item.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ItemUsage {
+    /// How many times the item was chosen.
+    pub count:     usize,
+    /// When the item was chosen for the last time, as a duration since the UNIX epoch.
+    pub last_used: Option<Duration>,
+    /// Whether the user marked the item as favourite.
+    pub favourite: bool,
+}
+
+
+
+// ====================
+// === UsageHistory ===
+// ====================
+
+/// The usage statistics of all items, identified by string keys (e.g. qualified names).
+///
+/// The history may be persisted as JSON between sessions, see [`UsageHistory::to_json`] and
+/// [`UsageHistory::from_json`].
+///
+/// The time is always passed explicitly, because this crate is also used in WASM, where the
+/// system clock is not available.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct UsageHistory {
+    items: HashMap<String, ItemUsage>,
+}
+
+impl UsageHistory {
+    /// Create an empty history.
+    pub fn new() -> Self {
+        default()
+    }
+
+    /// Restore the history from its JSON representation.
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// Serialize the history to JSON.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    /// Get the usage statistics of the item. Never used items have default statistics.
+    pub fn get(&self, key: &str) -> ItemUsage {
+        self.items.get(key).copied().unwrap_or_default()
+    }
+
+    /// Record that the user has chosen the item at the given time.
+    pub fn record_use(&mut self, key: impl Str, now: Duration) {
+        let usage = self.items.entry(key.into()).or_default();
+        usage.count += 1;
+        usage.last_used = Some(usage.last_used.map_or(now, |last_used| last_used.max(now)));
+    }
+
+    /// Mark or unmark the item as favourite.
+    pub fn set_favourite(&mut self, key: impl Str, favourite: bool) {
+        self.items.entry(key.into()).or_default().favourite = favourite;
+    }
+
+    /// Remove all statistics of the item.
+    pub fn forget(&mut self, key: &str) {
+        self.items.remove(key);
+    }
+}
+
+
+
+// ====================
+// === UsageWeights ===
+// ====================
+
+/// The parameters of [`UsagePrior`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UsageWeights {
+    /// The weight of the logarithm of the usage count.
+    pub count_weight:      f32,
+    /// The bonus for the item used just now. It decays exponentially with time.
+    pub recency_weight:    f32,
+    /// After this time since the last use, the recency bonus is halved.
+    pub recency_half_life: Duration,
+    /// The bonus for the favourite items.
+    pub favourite_bonus:   f32,
+}
+
+impl Default for UsageWeights {
+    fn default() -> Self {
+        const WEEK: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+        UsageWeights {
+            count_weight:      0.1,
+            recency_weight:    0.5,
+            recency_half_life: WEEK,
+            favourite_bonus:   1.0,
+        }
+    }
+}
+
+
+
+// ==================
+// === UsagePrior ===
+// ==================
+
+/// The prior computed from the [`UsageHistory`] at some specific moment.
+///
+/// The prior of an item is a sum of three components: the logarithm of the usage count, the
+/// recency bonus halved every [`UsageWeights::recency_half_life`], and the favourite bonus, each
+/// multiplied by its weight. Never used items have the prior equal to 0.0.
+#[derive(Clone, Copy, Debug)]
+pub struct UsagePrior<'a> {
+    history: &'a UsageHistory,
+    weights: UsageWeights,
+    now:     Duration,
+}
+
+impl<'a> UsagePrior<'a> {
+    /// Constructor with default weights. The `now` is a duration since the UNIX epoch.
+    pub fn new(history: &'a UsageHistory, now: Duration) -> Self {
+        Self { history, weights: default(), now }
+    }
+
+    /// Replace the weights.
+    pub fn with_weights(self, weights: UsageWeights) -> Self {
+        Self { weights, ..self }
+    }
+
+    /// The prior of the item.
+    pub fn prior(&self, key: &str) -> f32 {
+        let usage = self.history.get(key);
+        let count_bonus = self.weights.count_weight * (usage.count as f32).ln_1p();
+        let recency_bonus = usage.last_used.map_or(0.0, |last_used| {
+            let age = self.now.saturating_sub(last_used);
+            let half_lives = age.as_secs_f32() / self.weights.recency_half_life.as_secs_f32();
+            self.weights.recency_weight * 0.5_f32.powf(half_lives)
+        });
+        let favourite_bonus = if usage.favourite { self.weights.favourite_bonus } else { 0.0 };
+        count_bonus + recency_bonus + favourite_bonus
+    }
+
+    /// Add the prior of the item to the subsequence score.
+    pub fn boost(&self, key: &str, subsequence: Subsequence) -> Subsequence {
+        let score = subsequence.score + self.prior(key);
+        Subsequence { score, ..subsequence }
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    #[test]
+    fn recording_usage() {
+        let mut history = UsageHistory::new();
+        assert_eq!(history.get("map"), default());
+        history.record_use("map", DAY * 2);
+        history.record_use("map", DAY);
+        history.set_favourite("filter", true);
+        let expected = ItemUsage { count: 2, last_used: Some(DAY * 2), favourite: false };
+        assert_eq!(history.get("map"), expected);
+        let expected = ItemUsage { count: 0, last_used: None, favourite: true };
+        assert_eq!(history.get("filter"), expected);
+        history.forget("map");
+        assert_eq!(history.get("map"), default());
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let mut history = UsageHistory::new();
+        history.record_use("Standard.Base.Data.Vector.Vector.map", DAY);
+        history.set_favourite("Standard.Base.Data.Vector.Vector.filter", true);
+        let json = history.to_json().unwrap();
+        assert_eq!(UsageHistory::from_json(&json).unwrap(), history);
+        let partial = r#"{"sort": {"count": 3}}"#;
+        let restored = UsageHistory::from_json(partial).unwrap();
+        assert_eq!(restored.get("sort"), ItemUsage { count: 3, ..default() });
+    }
+
+    #[test]
+    fn prior_values() {
+        let weights = UsageWeights {
+            count_weight:      1.0,
+            recency_weight:    2.0,
+            recency_half_life: DAY,
+            favourite_bonus:   4.0,
+        };
+        let mut history = UsageHistory::new();
+        history.record_use("used", DAY);
+        history.set_favourite("favourite", true);
+        let prior = |now| UsagePrior::new(&history, now).with_weights(weights);
+        assert_eq!(prior(DAY).prior("unknown"), 0.0);
+        assert_eq!(prior(DAY).prior("favourite"), 4.0);
+        assert_approx_eq!(prior(DAY).prior("used"), 2.0_f32.ln() + 2.0);
+        assert_approx_eq!(prior(DAY * 3).prior("used"), 2.0_f32.ln() + 0.5);
+    }
+
+    #[test]
+    fn boosting_changes_order() {
+        let metric = crate::metric::default;
+        let map = crate::find_best_subsequence("map", "ma", metric()).unwrap();
+        let max = crate::find_best_subsequence("max", "ma", metric()).unwrap();
+        assert!(map.compare_scores(&max).is_eq());
+        let mut history = UsageHistory::new();
+        history.record_use("max", DAY);
+        let prior = UsagePrior::new(&history, DAY * 2);
+        let map = prior.boost("map", map);
+        let max = prior.boost("max", max);
+        assert!(max.compare_scores(&map).is_gt());
+    }
+}
@@ -0,0 +1,71 @@
+//! Procedural macros of the `json-rpc` crate. Should not be used directly, but through the
+//! re-exports in `json-rpc`, as the generated code relies on its items.
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+// === Non-Standard Linter Configuration ===
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+
+
+
+mod rpc_api;
+
+
+
+// =================
+// === Interface ===
+// =================
+
+/// Generates the client of a JSON-RPC API from a trait declaring its remote methods. Each method
+/// should take `&self` and be annotated with the `rpc` attribute giving the remote method name,
+/// e.g.:
+/// ```text
+/// #[json_rpc::rpc_api(title = "Calculator")]
+/// /// An interface of the calculator service.
+/// pub trait API {
+///     /// Raise the number to the given power.
+///     #[rpc(name = "math/pow")]
+///     fn pow(&self, base: &f64, exponent: Option<u32>) -> f64;
+///
+///     /// Inform the service that the client is alive. Not replied to.
+///     #[rpc(name = "session/ping", notification)]
+///     fn ping(&self, client_id: Uuid);
+/// }
+/// ```
+///
+/// The parameters can be passed by reference (`&T`) or by value (`T`). The optional parameters
+/// (`Option<T>`, `Option<&T>` or `&Option<T>`) are skipped in the request when `None`. The
+/// parameters are serialized by name, in camel case.
+///
+/// The methods of the generated `API` trait return a `Future` yielding `json_rpc::api::Result`.
+/// The macro generates the following items, which expect the `Notification` and `Event` types to
+/// be defined in the same module:
+/// - The `API` trait itself, with an additional `events` method.
+/// - The `Client` implementing `API`, which makes the remote calls using `json_rpc::Handler`. The
+///   methods marked as `notification` are sent as notifications and their `Future` is ready once
+///   the message is sent.
+/// - The `MockClient` implementing `API`, which calls the handlers of the expected calls set up in
+///   its `ExpectedCalls`, see also the `expect_call!` macro. The handlers get the owned parameters
+///   by reference and the optional parameters as `&Option<T>` (`Option<&T>` parameters are cloned).
+/// - The `open_rpc_document` function, returning the [OpenRPC](https://spec.open-rpc.org) document
+///   describing the methods, titled by the `title` argument (the trait name by default).
+///
+/// The generated code refers to the `serde`, `serde_json` and `futures` crates by their absolute
+/// paths (`::serde`, `::serde_json` and `::futures`), so they must be dependencies of the crate
+/// using the macro. The parameter and result types must implement `serde::Serialize`,
+/// `serde::Deserialize` and `schemars::JsonSchema` (available as `json_rpc::schemars`).
+#[proc_macro_attribute]
+pub fn rpc_api(
+    args: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    rpc_api::rpc_api(args, item)
+}
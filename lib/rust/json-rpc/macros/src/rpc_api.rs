@@ -0,0 +1,754 @@
+//! Implementation of the `#[rpc_api]` attribute, generating the client of a JSON-RPC API from a
+//! trait declaring its remote methods.
+
+use inflector::cases::camelcase::to_camel_case;
+use inflector::cases::pascalcase::to_pascal_case;
+use proc_macro2::Span;
+use proc_macro2::TokenStream;
+use quote::format_ident;
+use quote::quote;
+use serde_json::json;
+use syn::spanned::Spanned;
+use syn::visit_mut::VisitMut;
+use syn::Error;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The lifetime of the references passed to the generated methods.
+const LIFETIME: &str = "'a";
+
+/// The version of the OpenRPC specification followed by the generated documents.
+const OPEN_RPC_VERSION: &str = "1.2.6";
+
+/// The name of the attribute describing a remote method.
+const METHOD_ATTRIBUTE: &str = "rpc";
+
+
+
+// ===============
+// === Helpers ===
+// ===============
+
+/// The lines of the doc comments among `attrs`.
+fn doc_lines(attrs: &[syn::Attribute]) -> Vec<String> {
+    let docs = attrs.iter().filter(|attr| attr.path.is_ident("doc"));
+    docs.filter_map(|attr| match attr.parse_meta() {
+        Ok(syn::Meta::NameValue(syn::MetaNameValue { lit: syn::Lit::Str(text), .. })) =>
+            Some(text.value().trim().to_owned()),
+        _ => None,
+    })
+    .collect()
+}
+
+/// Join the doc comment lines into paragraphs separated by empty lines.
+fn description(lines: &[String]) -> String {
+    let paragraphs = lines.split(|line| line.is_empty()).filter(|lines| !lines.is_empty());
+    paragraphs.map(|lines| lines.join(" ")).collect::<Vec<_>>().join("\n\n")
+}
+
+/// The first sentence of the description.
+fn summary(description: &str) -> String {
+    let paragraph = description.split("\n\n").next().unwrap_or_default();
+    match paragraph.find(". ") {
+        Some(end) => paragraph[..=end].to_owned(),
+        None => paragraph.to_owned(),
+    }
+}
+
+/// If `ty` is `Option<T>`, returns `T`.
+fn option_argument(ty: &syn::Type) -> Option<&syn::Type> {
+    let segment = match ty {
+        syn::Type::Path(path) if path.qself.is_none() => path.path.segments.last()?,
+        _ => return None,
+    };
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) if segment.ident == "Option" =>
+            match args.args.iter().collect::<Vec<_>>().as_slice() {
+                [syn::GenericArgument::Type(argument)] => Some(argument),
+                _ => None,
+            },
+        _ => None,
+    }
+}
+
+/// Sets the `LIFETIME` to all the references without an explicit lifetime.
+struct AddLifetime;
+
+impl VisitMut for AddLifetime {
+    fn visit_type_reference_mut(&mut self, reference: &mut syn::TypeReference) {
+        if reference.lifetime.is_none() {
+            reference.lifetime = Some(syn::Lifetime::new(LIFETIME, Span::call_site()));
+        }
+        syn::visit_mut::visit_type_reference_mut(self, reference);
+    }
+}
+
+fn with_lifetime(ty: &syn::Type) -> syn::Type {
+    let mut ty = ty.clone();
+    AddLifetime.visit_type_mut(&mut ty);
+    ty
+}
+
+
+
+// =============
+// === Param ===
+// =============
+
+/// The way a parameter is passed to the remote method.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ParamKind {
+    /// `T`
+    Owned,
+    /// `&T`
+    Borrowed,
+    /// `Option<T>`
+    OptionalOwned,
+    /// `Option<&T>`
+    OptionalBorrowed,
+    /// `&Option<T>`
+    OptionalRef,
+}
+
+/// A parameter of the remote method.
+#[derive(Debug)]
+struct Param {
+    ident:    syn::Ident,
+    /// The type as declared in the trait.
+    ty:       syn::Type,
+    kind:     ParamKind,
+    /// The type of the parameter value, without the references and `Option`.
+    value_ty: syn::Type,
+}
+
+impl Param {
+    fn parse(arg: &syn::FnArg) -> syn::Result<Self> {
+        let typed = match arg {
+            syn::FnArg::Typed(typed) => typed,
+            syn::FnArg::Receiver(receiver) =>
+                return Err(Error::new(receiver.span(), "Unexpected receiver.")),
+        };
+        let ident = match &*typed.pat {
+            syn::Pat::Ident(pat) => pat.ident.clone(),
+            pat => return Err(Error::new(pat.span(), "Expected a parameter name.")),
+        };
+        let ty = (*typed.ty).clone();
+        let (kind, value_ty) = match &ty {
+            syn::Type::Reference(reference) => match option_argument(&reference.elem) {
+                Some(argument) => (ParamKind::OptionalRef, argument.clone()),
+                None => (ParamKind::Borrowed, (*reference.elem).clone()),
+            },
+            _ => match option_argument(&ty) {
+                Some(syn::Type::Reference(argument)) =>
+                    (ParamKind::OptionalBorrowed, (*argument.elem).clone()),
+                Some(argument) => (ParamKind::OptionalOwned, argument.clone()),
+                None => (ParamKind::Owned, ty.clone()),
+            },
+        };
+        Ok(Self { ident, ty, kind, value_ty })
+    }
+
+    fn is_optional(&self) -> bool {
+        use ParamKind::*;
+        matches!(self.kind, OptionalOwned | OptionalBorrowed | OptionalRef)
+    }
+
+    /// The parameter in the signature of the generated methods.
+    fn signature(&self) -> TokenStream {
+        let ident = &self.ident;
+        let ty = with_lifetime(&self.ty);
+        quote!(#ident: #ty)
+    }
+
+    /// The field of the structure with the method's input. The optional parameters are skipped
+    /// when `None`.
+    fn field(&self) -> TokenStream {
+        let ident = &self.ident;
+        let ty = match self.kind {
+            ParamKind::OptionalRef => {
+                let value_ty = with_lifetime(&self.value_ty);
+                let lifetime = syn::Lifetime::new(LIFETIME, Span::call_site());
+                quote!(Option<&#lifetime #value_ty>)
+            }
+            _ => {
+                let ty = with_lifetime(&self.ty);
+                quote!(#ty)
+            }
+        };
+        let skip =
+            self.is_optional().then(|| quote!(#[serde(skip_serializing_if = "Option::is_none")]));
+        quote!(#skip #ident: #ty)
+    }
+
+    /// The initializer of the input structure's field.
+    fn field_value(&self) -> TokenStream {
+        let ident = &self.ident;
+        match self.kind {
+            ParamKind::OptionalRef => quote!(#ident: #ident.as_ref()),
+            _ => quote!(#ident),
+        }
+    }
+
+    /// The type of the parameter given to the handlers of the mock client. The owned values are
+    /// borrowed, and the optional ones are given as `&Option<T>`.
+    fn handler_type(&self) -> TokenStream {
+        let ty = &self.ty;
+        let value_ty = &self.value_ty;
+        match self.kind {
+            ParamKind::Owned | ParamKind::OptionalOwned => quote!(&#ty),
+            ParamKind::Borrowed | ParamKind::OptionalRef => quote!(#ty),
+            ParamKind::OptionalBorrowed => quote!(&Option<#value_ty>),
+        }
+    }
+
+    /// The argument passed to the handlers of the mock client, see `handler_type`.
+    fn handler_argument(&self) -> TokenStream {
+        let ident = &self.ident;
+        match self.kind {
+            ParamKind::Owned | ParamKind::OptionalOwned => quote!(&#ident),
+            ParamKind::Borrowed | ParamKind::OptionalRef => quote!(#ident),
+            ParamKind::OptionalBorrowed => quote!(&#ident.cloned()),
+        }
+    }
+
+    fn open_rpc(&self) -> serde_json::Value {
+        json!({
+            "name": to_camel_case(&self.ident.to_string()),
+            "required": !self.is_optional(),
+        })
+    }
+
+    /// The statement filling the schema of the parameter in the `document` generated by
+    /// `open_rpc_document`, where `path` indexes the parameter.
+    fn open_rpc_schema(&self, path: TokenStream) -> TokenStream {
+        let value_ty = &self.value_ty;
+        quote!(document #path ["schema"] = schema::<#value_ty>(&mut generator);)
+    }
+}
+
+
+
+// ==============
+// === Method ===
+// ==============
+
+/// A remote method, declared in the trait as:
+/// ```text
+/// /// Documentation.
+/// #[rpc(name = "remote/method")]
+/// fn method(&self, param: &Param) -> Result;
+/// ```
+#[derive(Debug)]
+struct Method {
+    ident:        syn::Ident,
+    /// The attributes of the declaration, except the `METHOD_ATTRIBUTE`.
+    attrs:        Vec<syn::Attribute>,
+    rpc_name:     String,
+    /// If set, the method is a notification: it is not replied to by the peer.
+    notification: bool,
+    params:       Vec<Param>,
+    result:       syn::Type,
+    /// The name of the structure with the method's input.
+    input:        syn::Ident,
+}
+
+impl Method {
+    fn parse(item: &syn::TraitItem) -> syn::Result<Self> {
+        let method = match item {
+            syn::TraitItem::Method(method) => method,
+            item => return Err(Error::new(item.span(), "Only methods are supported.")),
+        };
+        let signature = &method.sig;
+        let ident = signature.ident.clone();
+        let (rpc_name, notification) = Self::parse_attribute(method)?;
+        let attrs = method.attrs.iter().filter(|attr| !attr.path.is_ident(METHOD_ATTRIBUTE));
+        let attrs = attrs.cloned().collect();
+        if !signature.generics.params.is_empty() {
+            let error = "Remote methods cannot be generic.";
+            return Err(Error::new(signature.generics.span(), error));
+        }
+        let mut inputs = signature.inputs.iter();
+        match inputs.next() {
+            Some(syn::FnArg::Receiver(receiver))
+                if receiver.reference.is_some() && receiver.mutability.is_none() => {}
+            _ => return Err(Error::new(signature.span(), "Expected a `&self` receiver.")),
+        }
+        let params = inputs.map(Param::parse).collect::<syn::Result<_>>()?;
+        let result = match &signature.output {
+            syn::ReturnType::Default => syn::parse_quote!(()),
+            syn::ReturnType::Type(_, ty) => (**ty).clone(),
+        };
+        let is_unit = matches!(&result, syn::Type::Tuple(tuple) if tuple.elems.is_empty());
+        if notification && !is_unit {
+            return Err(Error::new(result.span(), "Notifications cannot return a value."));
+        }
+        let input = format_ident!("{}Input", to_pascal_case(&ident.to_string()));
+        Ok(Self { ident, attrs, rpc_name, notification, params, result, input })
+    }
+
+    /// Parse the `#[rpc(name = "remote/method", notification)]` attribute.
+    fn parse_attribute(method: &syn::TraitItemMethod) -> syn::Result<(String, bool)> {
+        let attr = method.attrs.iter().find(|attr| attr.path.is_ident(METHOD_ATTRIBUTE));
+        let error = "Expected a `#[rpc(name = \"...\")]` attribute.";
+        let attr = attr.ok_or_else(|| Error::new(method.sig.ident.span(), error))?;
+        let list = match attr.parse_meta()? {
+            syn::Meta::List(list) => list,
+            meta => return Err(Error::new(meta.span(), error)),
+        };
+        let mut name = None;
+        let mut notification = false;
+        for argument in list.nested {
+            use syn::Meta::NameValue;
+            use syn::Meta::Path;
+            use syn::NestedMeta::Meta;
+            match argument {
+                Meta(NameValue(value)) if value.path.is_ident("name") => match value.lit {
+                    syn::Lit::Str(text) => name = Some(text.value()),
+                    lit => return Err(Error::new(lit.span(), "Expected a string.")),
+                },
+                Meta(Path(path)) if path.is_ident("notification") => notification = true,
+                argument => return Err(Error::new(argument.span(), "Unknown argument.")),
+            }
+        }
+        let name = name.ok_or_else(|| Error::new(attr.span(), error))?;
+        Ok((name, notification))
+    }
+
+    fn result_type(&self) -> TokenStream {
+        let result = &self.result;
+        quote! {
+            ::std::pin::Pin<Box<dyn ::std::future::Future<Output = ::json_rpc::api::Result<#result>>>>
+        }
+    }
+
+    /// The signature of the method in the trait and its implementations.
+    fn signature(&self) -> TokenStream {
+        let ident = &self.ident;
+        let lifetime = syn::Lifetime::new(LIFETIME, Span::call_site());
+        let params = self.params.iter().map(Param::signature);
+        let result = self.result_type();
+        quote!(fn #ident<#lifetime>(&#lifetime self #(, #params)*) -> #result)
+    }
+
+    /// The declaration of the method in the trait.
+    fn declaration(&self) -> TokenStream {
+        let attrs = &self.attrs;
+        let signature = self.signature();
+        quote!(#(#attrs)* #signature;)
+    }
+
+    /// The implementation of the method in the `Client`.
+    fn client_implementation(&self) -> TokenStream {
+        let ident = &self.ident;
+        let signature = self.signature();
+        let input = &self.input;
+        let rpc_name = &self.rpc_name;
+        let field_values = self.params.iter().map(Param::field_value);
+        let prepare_input = quote! {
+            ::json_rpc::log::rpc_request(#rpc_name);
+            let phantom = ::std::marker::PhantomData;
+            let input = #input { phantom #(, #field_values)* };
+            let input_json = ::serde_json::to_value(input).unwrap();
+        };
+        if self.notification {
+            quote! {
+                #signature {
+                    #prepare_input
+                    let result = self.handler.borrow().send_notification(#rpc_name, &input_json);
+                    Box::pin(::futures::future::ready(result))
+                }
+            }
+        } else {
+            quote! {
+                #signature {
+                    use ::futures::FutureExt;
+                    use ::json_rpc::enso_profiler as profiler;
+                    use ::json_rpc::enso_profiler::internal::Profiler;
+                    use ::json_rpc::enso_profiler::internal::StartState;
+
+                    let label = profiler::internal::Label(stringify!(#ident));
+                    let parent = profiler::internal::EventId::implicit();
+                    let now = Some(profiler::internal::Timestamp::now());
+                    let profiler = profiler::Task::start(parent, label, now, StartState::Active);
+
+                    #prepare_input
+                    let name = <#input as ::json_rpc::RemoteMethodCall>::NAME;
+                    let options = self.options.clone();
+                    let handler = self.handler.borrow();
+                    let result_fut = handler.open_request_with_options(name, &input_json, options);
+
+                    profiler.pause();
+
+                    let result_fut = result_fut.map(move |value| {
+                        profiler.resume();
+                        profiler.finish();
+                        value
+                    });
+                    Box::pin(result_fut)
+                }
+            }
+        }
+    }
+
+    /// The structure with the method's input, serialized as the request's params.
+    fn input_structure(&self) -> TokenStream {
+        let input = &self.input;
+        let rpc_name = &self.rpc_name;
+        let result = &self.result;
+        let lifetime = syn::Lifetime::new(LIFETIME, Span::call_site());
+        let fields = self.params.iter().map(Param::field);
+        quote! {
+            /// Structure transporting method arguments.
+            #[derive(Debug, ::serde::Serialize)]
+            #[serde(rename_all = "camelCase")]
+            struct #input<#lifetime> {
+                #[serde(skip)]
+                phantom: ::std::marker::PhantomData<&#lifetime ()>,
+                #(#fields),*
+            }
+
+            impl ::json_rpc::RemoteMethodCall for #input<'_> {
+                const NAME: &'static str = #rpc_name;
+                type Returned = #result;
+            }
+        }
+    }
+
+    /// The implementation of the method in the `MockClient`.
+    fn mock_implementation(&self) -> TokenStream {
+        let ident = &self.ident;
+        let signature = self.signature();
+        let rpc_name = &self.rpc_name;
+        let arguments = self.params.iter().map(Param::handler_argument);
+        quote! {
+            #signature {
+                let mut handlers = self.expect.#ident.borrow_mut();
+                assert!(!handlers.is_empty(), "Unexpected call {}", #rpc_name);
+                let handler = handlers.remove(0);
+                let result = handler(#(#arguments),*);
+                Box::pin(::futures::future::ready(result))
+            }
+        }
+    }
+
+    /// The signature of the handlers of the expected calls in the `MockClient`.
+    fn handler_signature(&self) -> TokenStream {
+        let result = &self.result;
+        let params = self.params.iter().map(Param::handler_type);
+        quote!(FnOnce(#(#params),*) -> ::json_rpc::api::Result<#result>)
+    }
+
+    fn open_rpc(&self) -> serde_json::Value {
+        let description = description(&doc_lines(&self.attrs));
+        let params = self.params.iter().map(Param::open_rpc).collect::<Vec<_>>();
+        let mut method = json!({
+            "name": self.rpc_name,
+            "summary": summary(&description),
+            "description": description,
+            "paramStructure": "by-name",
+            "params": params,
+        });
+        if self.notification {
+            method["x-notification"] = true.into();
+        } else {
+            method["result"] = json!({ "name": "result" });
+        }
+        method
+    }
+
+    /// The statements filling the schemas of the parameters and of the result in the `document`
+    /// generated by `open_rpc_document`, where the method is at the given `index`.
+    fn open_rpc_schemas(&self, index: usize) -> TokenStream {
+        let params = self.params.iter().enumerate();
+        let params = params
+            .map(|(i, param)| param.open_rpc_schema(quote!(["methods"][#index]["params"][#i])));
+        let result = &self.result;
+        let result = (!self.notification).then(|| {
+            quote!(document["methods"][#index]["result"]["schema"] = schema::<#result>(&mut generator);)
+        });
+        quote!(#(#params)* #result)
+    }
+}
+
+
+
+// ===========
+// === Api ===
+// ===========
+
+/// The API declared by the trait.
+#[derive(Debug)]
+struct Api {
+    item:    syn::ItemTrait,
+    /// The title of the OpenRPC document.
+    title:   String,
+    methods: Vec<Method>,
+}
+
+impl Api {
+    fn parse(args: syn::AttributeArgs, item: syn::ItemTrait) -> syn::Result<Self> {
+        let mut title = item.ident.to_string();
+        for argument in args {
+            use syn::Meta::NameValue;
+            use syn::NestedMeta::Meta;
+            match argument {
+                Meta(NameValue(syn::MetaNameValue { path, lit: syn::Lit::Str(text), .. }))
+                    if path.is_ident("title") =>
+                    title = text.value(),
+                argument => return Err(Error::new(argument.span(), "Unknown argument.")),
+            }
+        }
+        let methods = item.items.iter().map(Method::parse).collect::<syn::Result<_>>()?;
+        Ok(Self { item, title, methods })
+    }
+
+    fn generate(&self) -> TokenStream {
+        let api = self.api_trait();
+        let client = self.client();
+        let inputs = self.methods.iter().map(Method::input_structure);
+        let mock = self.mock_client();
+        let document = self.open_rpc_document();
+        quote! {
+            #api
+            #client
+            #(#inputs)*
+            #mock
+            #document
+        }
+    }
+
+    fn api_trait(&self) -> TokenStream {
+        let syn::ItemTrait { attrs, vis, ident, .. } = &self.item;
+        let declarations = self.methods.iter().map(Method::declaration);
+        quote! {
+            #(#attrs)*
+            #[allow(clippy::ptr_arg)]
+            #vis trait #ident {
+                #(#declarations)*
+
+                /// Asynchronous event stream with notification and errors.
+                ///
+                /// On a repeated call, previous stream is closed.
+                fn events(&self) -> ::futures::stream::LocalBoxStream<'static, Event>;
+            }
+        }
+    }
+
+    fn client(&self) -> TokenStream {
+        let syn::ItemTrait { attrs, ident, .. } = &self.item;
+        let docs = attrs.iter().filter(|attr| attr.path.is_ident("doc"));
+        let implementations = self.methods.iter().map(Method::client_implementation);
+        quote! {
+            #(#docs)*
+            #[derive(Debug)]
+            pub struct Client {
+                /// JSON-RPC protocol handler.
+                handler: ::std::cell::RefCell<::json_rpc::Handler<Notification>>,
+                /// Options of the calls made by this client.
+                options: ::json_rpc::CallOptions,
+            }
+
+            impl Client {
+                /// Create a new client that will use given transport.
+                pub fn new(transport: impl ::json_rpc::Transport + 'static) -> Self {
+                    let handler = ::std::cell::RefCell::new(::json_rpc::Handler::new(transport));
+                    let options = ::json_rpc::CallOptions::default();
+                    Self { handler, options }
+                }
+
+                /// Returns a client sharing the connection with this one, which makes the calls
+                /// with the given options.
+                ///
+                /// The returned client should be used for making calls only: the `runner` and
+                /// `events` should be obtained from the original one.
+                pub fn with_options(&self, options: ::json_rpc::CallOptions) -> Self {
+                    let handler = ::std::cell::RefCell::new(self.handler.borrow().clone());
+                    Self { handler, options }
+                }
+
+                /// Returns a future that performs any background, asynchronous work needed
+                /// for this Client to correctly work. Should be continually run while the
+                /// `Client` is used. Will end once `Client` is dropped.
+                pub fn runner(&self) -> impl ::std::future::Future<Output = ()> {
+                    self.handler.borrow_mut().runner()
+                }
+
+                /// Set new timeout for future requests. Pending requests are not affected.
+                pub fn set_timeout(&mut self, timeout: ::std::time::Duration) {
+                    self.handler.borrow().set_timeout(timeout);
+                }
+            }
+
+            #[allow(clippy::ptr_arg)]
+            impl #ident for Client {
+                #(#implementations)*
+
+                fn events(&self) -> ::futures::stream::LocalBoxStream<'static, Event> {
+                    use ::futures::StreamExt;
+                    self.handler.borrow_mut().handler_event_stream().boxed_local()
+                }
+            }
+        }
+    }
+
+    fn mock_client(&self) -> TokenStream {
+        let api = &self.item.ident;
+        let implementations = self.methods.iter().map(Method::mock_implementation);
+        let methods = self.methods.iter().map(|method| &method.ident).collect::<Vec<_>>();
+        let handlers = self.methods.iter().map(Method::handler_signature).collect::<Vec<_>>();
+        quote! {
+            /// Utilities for mocking client.
+            pub mod mock {
+                use super::*;
+
+                /// Mock used for tests.
+                ///
+                /// You may specify expected calls and their return values by setting appropriate
+                /// call handler as in the following example:
+                /// ```text
+                ///     let mock = MockClient::default();
+                ///     mock.expect.some_method(|param1, param2| result);
+                /// ```
+                #[derive(Debug, Default)]
+                pub struct Client {
+                    require_all_calls: ::std::cell::Cell<bool>,
+                    /// Expected calls handlers.
+                    pub expect:        ExpectedCalls,
+                    events: ::std::cell::RefCell<
+                        Option<::futures::channel::mpsc::UnboundedReceiver<Event>>,
+                    >,
+                }
+
+                #[allow(clippy::ptr_arg)]
+                impl #api for Client {
+                    #(#implementations)*
+
+                    fn events(&self) -> ::futures::stream::LocalBoxStream<'static, Event> {
+                        use ::futures::StreamExt;
+                        if let Some(receiver) = self.events.borrow_mut().take() {
+                            receiver.boxed_local()
+                        } else {
+                            ::futures::stream::empty().boxed_local()
+                        }
+                    }
+                }
+
+                impl Client {
+                    /// Mark all calls defined by `expect` as required. If client will be dropped
+                    /// without calling the test will fail.
+                    pub fn require_all_calls(&self) {
+                        self.require_all_calls.set(true);
+                    }
+
+                    /// Set up a channel that will feed `events` stream with events.
+                    pub fn setup_events(&self) -> ::futures::channel::mpsc::UnboundedSender<Event> {
+                        let (sender, receiver) = ::futures::channel::mpsc::unbounded();
+                        *self.events.borrow_mut() = Some(receiver);
+                        sender
+                    }
+                }
+
+                impl Drop for Client {
+                    fn drop(&mut self) {
+                        if self.require_all_calls.get() && !std::thread::panicking() {
+                            #(
+                                let method = stringify!(#methods);
+                                let message = format!("An expected call to {} was not made.", method);
+                                assert!(self.expect.#methods.borrow().is_empty(), "{}", message);
+                            )*
+                        }
+                    }
+                }
+
+                /// A set of handlers of expected Mock Client calls. Handlers get call's parameters
+                /// and returns the value to be returned.
+                #[derive(Default)]
+                pub struct ExpectedCalls {
+                    #(#methods: ::std::cell::RefCell<Vec<Box<dyn #handlers>>>,)*
+                }
+
+                impl ExpectedCalls {
+                    #(
+                        /// Adds handler to the next expected call of the method. Each handler will
+                        /// be called once and removed. The handlers will be called in the same
+                        /// order as set by this function.
+                        pub fn #methods(&self, handler: impl #handlers + 'static) {
+                            self.#methods.borrow_mut().push(Box::new(handler));
+                        }
+                    )*
+                }
+
+                impl ::std::fmt::Debug for ExpectedCalls {
+                    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                        write!(f, "Expected calls for Mock Client")
+                    }
+                }
+            }
+            pub use mock::Client as MockClient;
+        }
+    }
+
+    fn open_rpc_document(&self) -> TokenStream {
+        let api = self.item.ident.to_string();
+        let methods = self.methods.iter().map(Method::open_rpc).collect::<Vec<_>>();
+        let document = json!({
+            "openrpc": OPEN_RPC_VERSION,
+            "info": { "title": self.title, "version": "" },
+            "methods": methods,
+        });
+        let document = document.to_string();
+        let schemas =
+            self.methods.iter().enumerate().map(|(index, method)| method.open_rpc_schemas(index));
+        let doc = format!(
+            " The [OpenRPC](https://spec.open-rpc.org) document describing the methods of `{api}`."
+        );
+        quote! {
+            #[doc = #doc]
+            ///
+            /// The schemas of the parameters and results are generated from their `JsonSchema`
+            /// implementations, with the shared definitions placed in `components.schemas`. The
+            /// notifications, which are not replied to, are marked with the `x-notification` field.
+            pub fn open_rpc_document() -> ::serde_json::Value {
+                use ::json_rpc::schemars;
+                fn schema<T: schemars::JsonSchema + ?Sized>(
+                    generator: &mut schemars::gen::SchemaGenerator,
+                ) -> ::serde_json::Value {
+                    ::serde_json::to_value(generator.subschema_for::<T>()).unwrap()
+                }
+                let settings = schemars::gen::SchemaSettings::draft07().with(|settings| {
+                    settings.definitions_path = "#/components/schemas/".into();
+                });
+                let mut generator = settings.into_generator();
+                let mut document: ::serde_json::Value = ::serde_json::from_str(#document).unwrap();
+                document["info"]["version"] = env!("CARGO_PKG_VERSION").into();
+                #(#schemas)*
+                let definitions = generator.take_definitions();
+                document["components"]["schemas"] = ::serde_json::to_value(definitions).unwrap();
+                document
+            }
+        }
+    }
+}
+
+
+
+// =================
+// === Interface ===
+// =================
+
+/// Generate the API trait, `Client`, `MockClient` and OpenRPC document of the annotated trait.
+pub fn rpc_api(
+    args: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let args = syn::parse_macro_input!(args as syn::AttributeArgs);
+    let item = syn::parse_macro_input!(item as syn::ItemTrait);
+    match Api::parse(args, item) {
+        Ok(api) => api.generate().into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
@@ -0,0 +1,184 @@
+//! Per-call options of the remote method calls: deadlines, cancellation and retry policies.
+
+use crate::prelude::*;
+
+use crate::api::Result;
+use crate::ensogl::Duration;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The default delay before the first retry.
+pub const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+
+/// The default factor by which the delay grows with each subsequent retry.
+pub const DEFAULT_BACKOFF_MULTIPLIER: f64 = 2.0;
+
+/// The default upper bound of the delay between retries.
+pub const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+
+
+// ===================
+// === RetryPolicy ===
+// ===================
+
+/// Describes how a failed call should be retried. Only the calls that failed due to a transient
+/// error (see `crate::error::RpcError::is_transient`) are retried, so the policy should be used for
+/// idempotent methods only.
+///
+/// The delay between the subsequent attempts grows exponentially:
+/// `initial_backoff * backoff_multiplier ^ (retry - 1)`, up to `max_backoff`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts, including the first one.
+    pub max_attempts:       usize,
+    /// The delay before the first retry.
+    pub initial_backoff:    Duration,
+    /// The factor by which the delay grows with each subsequent retry.
+    pub backoff_multiplier: f64,
+    /// The upper bound of the delay between retries.
+    pub max_backoff:        Duration,
+}
+
+impl RetryPolicy {
+    /// Create a policy making at most `max_attempts` attempts, with the default backoff.
+    pub fn new(max_attempts: usize) -> Self {
+        let initial_backoff = DEFAULT_INITIAL_BACKOFF;
+        let backoff_multiplier = DEFAULT_BACKOFF_MULTIPLIER;
+        let max_backoff = DEFAULT_MAX_BACKOFF;
+        Self { max_attempts, initial_backoff, backoff_multiplier, max_backoff }
+    }
+
+    /// Set the delay before the first retry.
+    pub fn initial_backoff(mut self, delay: Duration) -> Self {
+        self.initial_backoff = delay;
+        self
+    }
+
+    /// Set the factor by which the delay grows with each subsequent retry.
+    pub fn backoff_multiplier(mut self, multiplier: f64) -> Self {
+        self.backoff_multiplier = multiplier;
+        self
+    }
+
+    /// Set the upper bound of the delay between retries.
+    pub fn max_backoff(mut self, delay: Duration) -> Self {
+        self.max_backoff = delay;
+        self
+    }
+
+    /// The delay before the given retry. Retries are numbered from 1.
+    pub fn backoff(&self, retry: usize) -> Duration {
+        let exponent = retry.saturating_sub(1).min(i32::MAX as usize) as i32;
+        let delay = self.initial_backoff.as_secs_f64() * self.backoff_multiplier.powi(exponent);
+        Duration::from_secs_f64(delay.min(self.max_backoff.as_secs_f64()))
+    }
+
+    /// Check if the call should be retried after the given number of attempts yielded the
+    /// `result`.
+    pub fn should_retry<T>(&self, attempts: usize, result: &Result<T>) -> bool {
+        let transient = matches!(result, Err(error) if error.is_transient());
+        transient && attempts < self.max_attempts
+    }
+}
+
+
+
+// ===================
+// === CallOptions ===
+// ===================
+
+/// Options of a single remote call, built with a builder-style API, e.g.:
+/// ```text
+/// let options = CallOptions::new().deadline(timeout).retry(RetryPolicy::new(3));
+/// ```
+///
+/// The default options make a single attempt, bounded by the `Handler`'s timeout, and keep the
+/// request registered when its future is dropped.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CallOptions {
+    /// Time after which the call fails with `RpcError::TimeoutError`, including all the retries.
+    /// If not set, each attempt is bounded by the `Handler`'s timeout instead.
+    pub deadline:            Option<Duration>,
+    /// If set, dropping the call's future (also when the deadline is reached) before receiving
+    /// the reply removes the ongoing request, so a late reply is reported as
+    /// `HandlingError::UnexpectedResponse`.
+    pub cancel_on_drop:      bool,
+    /// The method of a notification sent to the peer when the request is cancelled on drop. The
+    /// notification params are `{"id": <cancelled request id>}`, like LSP's `$/cancelRequest`.
+    pub cancel_notification: Option<String>,
+    /// The policy of retrying the failed call. If not set, the call is not retried.
+    pub retry:               Option<RetryPolicy>,
+}
+
+impl CallOptions {
+    /// Create the default options.
+    pub fn new() -> Self {
+        default()
+    }
+
+    /// Set the deadline of the call.
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Cancel the request when its future is dropped before receiving the reply.
+    pub fn cancel_on_drop(mut self) -> Self {
+        self.cancel_on_drop = true;
+        self
+    }
+
+    /// Cancel the request when its future is dropped before receiving the reply, and notify the
+    /// peer about it with the given notification method.
+    pub fn cancel_notification(mut self, method: impl Str) -> Self {
+        self.cancel_on_drop = true;
+        self.cancel_notification = Some(method.into());
+        self
+    }
+
+    /// Set the retry policy of the call.
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::error::RpcError;
+
+    #[test]
+    fn backoff() {
+        let policy = RetryPolicy::new(5)
+            .initial_backoff(Duration::from_millis(10))
+            .backoff_multiplier(3.0)
+            .max_backoff(Duration::from_millis(200));
+        let delays = (1..=5).map(|retry| policy.backoff(retry).as_millis()).collect_vec();
+        assert_eq!(delays, vec![10, 30, 90, 200, 200]);
+    }
+
+    #[test]
+    fn should_retry() {
+        let policy = RetryPolicy::new(2);
+        let timeout: Result<()> = Err(RpcError::TimeoutError { millis: 1 });
+        let remote: Result<()> = Err(RpcError::new_remote_error(1, "error"));
+        assert!(policy.should_retry(1, &timeout));
+        assert!(policy.should_retry(1, &Err::<(), _>(RpcError::LostConnection)));
+        assert!(!policy.should_retry(2, &timeout));
+        assert!(!policy.should_retry(1, &remote));
+        assert!(!policy.should_retry(1, &Ok(())));
+    }
+}
@@ -0,0 +1,548 @@
+//! Module providing `Dispatcher`, the server-side counterpart of the `Handler`.
+
+use crate::prelude::*;
+
+use crate::api::RemoteMethodCall;
+use crate::messages;
+use crate::messages::Id;
+use crate::messages::IncomingCall;
+use crate::messages::Message;
+use crate::messages::MethodCall;
+use crate::transport::Transport;
+use crate::transport::TransportEvent;
+
+use futures::future::LocalBoxFuture;
+use futures::FutureExt;
+use futures::Stream;
+use futures::StreamExt;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::future::Future;
+
+
+
+// ====================
+// === MethodResult ===
+// ====================
+
+/// A result of a method call handled on the local side. The error is sent to the peer as-is.
+pub type MethodResult<T> = Result<T, messages::Error>;
+
+/// A type-erased method handler. Takes JSON-serialized params and yields a JSON-serialized result.
+pub type MethodHandler = Rc<dyn Fn(Value) -> LocalBoxFuture<'static, MethodResult<Value>>>;
+
+
+
+// ===============
+// === Methods ===
+// ===============
+
+/// A registry of method handlers, indexed by method names.
+#[derive(Clone, Default)]
+pub struct Methods {
+    handlers: HashMap<String, MethodHandler>,
+}
+
+impl Methods {
+    /// Register a handler of the `M` method. The previously registered handler of the same method,
+    /// if any, is replaced.
+    pub fn insert<M, F, Fut>(&mut self, handler: F)
+    where
+        M: RemoteMethodCall + DeserializeOwned,
+        M::Returned: Serialize,
+        F: Fn(M) -> Fut + 'static,
+        Fut: Future<Output = MethodResult<M::Returned>> + 'static, {
+        let handler: MethodHandler = Rc::new(move |params| match serde_json::from_value(params) {
+            Ok(input) => handler(input).map(|result| result.and_then(encode_result)).boxed_local(),
+            Err(error) =>
+                futures::future::ready(Err(messages::Error::invalid_params(error))).boxed_local(),
+        });
+        self.handlers.insert(M::NAME.into(), handler);
+    }
+
+    /// Get the handler of the given method.
+    pub fn get(&self, method: &str) -> Option<MethodHandler> {
+        self.handlers.get(method).cloned()
+    }
+
+    /// Check if there is a handler registered for the given method.
+    pub fn contains(&self, method: &str) -> bool {
+        self.handlers.contains_key(method)
+    }
+}
+
+impl Debug for Methods {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.handlers.keys()).finish()
+    }
+}
+
+/// Serialize the value returned by a method handler.
+fn encode_result<T: Serialize>(result: T) -> MethodResult<Value> {
+    serde_json::to_value(result).map_err(messages::Error::internal_error)
+}
+
+/// Call the method using the given handler (if any). The returned future does not borrow the
+/// handlers registry, so the handler is free to modify it.
+pub fn call_method(
+    handler: Option<MethodHandler>,
+    call: MethodCall<Value>,
+) -> LocalBoxFuture<'static, MethodResult<Value>> {
+    match handler {
+        Some(handler) => handler(call.params),
+        None => futures::future::ready(Err(messages::Error::method_not_found(&call.method)))
+            .boxed_local(),
+    }
+}
+
+
+
+// ===================
+// === Reply Codec ===
+// ===================
+
+/// Build a JSON-serialized response message to the call with the given id.
+///
+/// If the id is not known (e.g. because the request could not be parsed), the response bears a
+/// `null` id, as required by the JSON-RPC 2.0 specification.
+pub fn encode_reply(id: Option<Id>, result: MethodResult<Value>) -> String {
+    let result = match result {
+        Ok(result) => messages::Result::new_success(result),
+        Err(error) => messages::Result::Error { error },
+    };
+    let message = match id {
+        Some(id) => serde_json::to_value(Message::new(messages::Response { id, result })),
+        None => serde_json::to_value(Message::new(result)).map(|mut message| {
+            message["id"] = Value::Null;
+            message
+        }),
+    };
+    // Serialization of JSON values and ids cannot fail.
+    message.expect("Failed to serialize a JSON-RPC response.").to_string()
+}
+
+/// Process a message that may be a batch, i.e. a non-empty JSON array of messages.
+///
+/// Each message of the batch is processed with `process_one`, and the replies are joined into a
+/// single JSON array. If none of the messages was replied to, the batch is not replied to either.
+/// A message that is not a batch (including the empty array) is processed with `process_one`
+/// directly.
+pub fn process_batch(
+    message: Value,
+    mut process_one: impl FnMut(Value) -> LocalBoxFuture<'static, Option<String>>,
+) -> LocalBoxFuture<'static, Option<String>> {
+    match message {
+        Value::Array(messages) if !messages.is_empty() => {
+            let replies = messages.into_iter().map(process_one);
+            futures::future::join_all(replies)
+                .map(|replies| {
+                    let replies = replies.into_iter().flatten().collect_vec();
+                    (!replies.is_empty()).then(|| format!("[{}]", replies.join(",")))
+                })
+                .boxed_local()
+        }
+        message => process_one(message),
+    }
+}
+
+
+
+// ==================
+// === Dispatcher ===
+// ==================
+
+/// Dispatcher is a server-side provider of RPC protocol. Given with a transport capable of
+/// transporting text messages, it answers the peer's requests with registered method handlers.
+///
+/// Methods are described by values implementing both `RemoteMethodCall` and
+/// `DeserializeOwned`. The handler's error is replied to the peer as-is, while unknown
+/// methods, malformed params and malformed messages are replied to with the errors defined in
+/// `messages::error_code`.
+///
+/// Notifications from the peer are passed to the registered handlers as well, but their
+/// results are discarded. Notifications to the peer are sent with `send_notification`.
+pub use enso_shapely::shared;
+
+shared! { Dispatcher
+
+/// Mutable state of the `Dispatcher`.
+#[derive(Debug)]
+pub struct DispatcherData {
+    /// Registered method handlers.
+    methods   : Methods,
+    /// Transports text messages between this dispatcher and the peer.
+    transport : Box<dyn Transport>,
+}
+
+impl {
+    /// Get the handler of the given method.
+    pub fn method_handler(&self, method:&str) -> Option<MethodHandler> {
+        self.methods.get(method)
+    }
+
+    /// Sends a text message to the peer.
+    pub fn send_text_message(&mut self, text:&str) -> FallibleResult {
+        self.transport.send_text(text)
+    }
+}
+} // shared!
+
+
+// === Dispatcher methods ===
+
+impl Dispatcher {
+    /// Creates a new dispatcher working on a given `Transport`.
+    pub fn new(transport: impl Transport + 'static) -> Dispatcher {
+        let data = DispatcherData { methods: default(), transport: Box::new(transport) };
+        Dispatcher { rc: Rc::new(RefCell::new(data)) }
+    }
+
+    /// Register a handler of the `M` method. The previously registered handler of the same method,
+    /// if any, is replaced.
+    pub fn register_method<M, F>(&self, handler: F)
+    where
+        M: RemoteMethodCall + DeserializeOwned,
+        M::Returned: Serialize + 'static,
+        F: Fn(M) -> MethodResult<M::Returned> + 'static, {
+        self.register_async_method(move |input| futures::future::ready(handler(input)))
+    }
+
+    /// Register an asynchronous handler of the `M` method. The previously registered handler of
+    /// the same method, if any, is replaced.
+    ///
+    /// The calls are handled concurrently, so the reply is sent as soon as the handler's future
+    /// completes, regardless of other pending calls.
+    pub fn register_async_method<M, F, Fut>(&self, handler: F)
+    where
+        M: RemoteMethodCall + DeserializeOwned,
+        M::Returned: Serialize,
+        F: Fn(M) -> Fut + 'static,
+        Fut: Future<Output = MethodResult<M::Returned>> + 'static, {
+        self.rc.borrow_mut().methods.insert(handler)
+    }
+
+    /// Sends a notification to the peer.
+    ///
+    /// `notification` should serialize to a JSON map with `method` and `params` fields, like
+    /// `MethodCall` or an enum tagged with `#[serde(tag = "method", content = "params")]`.
+    pub fn send_notification<N: Serialize>(&self, notification: N) -> FallibleResult {
+        let message = serde_json::to_string(&Message::new(notification))?;
+        self.send_text_message(&message)
+    }
+
+    /// Deal with incoming text message from the peer. The message may be a single call or a batch
+    /// of calls.
+    ///
+    /// Returns a `Future` yielding the JSON-serialized reply, or `None` if the message consists of
+    /// notifications only, that shall not be replied to.
+    pub fn process_incoming_message(
+        &self,
+        message: &str,
+    ) -> LocalBoxFuture<'static, Option<String>> {
+        match serde_json::from_str::<Value>(message) {
+            Ok(message) => process_batch(message, |call| self.process_call(call)),
+            Err(err) => {
+                let reply = encode_reply(None, Err(messages::Error::parse_error(err)));
+                futures::future::ready(Some(reply)).boxed_local()
+            }
+        }
+    }
+
+    /// Deal with a single call from the peer, already parsed as JSON.
+    ///
+    /// See `process_incoming_message`.
+    pub fn process_call(&self, message: Value) -> LocalBoxFuture<'static, Option<String>> {
+        let id = message.get("id").and_then(|id| serde_json::from_value::<Id>(id.clone()).ok());
+        match messages::decode_incoming_call(message) {
+            Ok(IncomingCall::Request(request)) => {
+                let handler = self.method_handler(&request.call.method);
+                let id = request.id;
+                call_method(handler, request.call)
+                    .map(move |result| Some(encode_reply(Some(id), result)))
+                    .boxed_local()
+            }
+            Ok(IncomingCall::Notification(messages::Notification(call))) => {
+                let handler = self.method_handler(&call.method);
+                let method = call.method.clone();
+                call_method(handler, call)
+                    .map(move |result| {
+                        if let Err(error) = result {
+                            warn!("Failed to handle notification {method}: {error:?}.");
+                        }
+                        None
+                    })
+                    .boxed_local()
+            }
+            Err(err) => {
+                let reply = encode_reply(id, Err(messages::Error::invalid_request(err)));
+                futures::future::ready(Some(reply)).boxed_local()
+            }
+        }
+    }
+
+    /// Obtains stream of events from our transport layer.
+    ///
+    /// Calling this function invalidates (closes) any previous stream obtained from this function.
+    fn transport_event_stream(&self) -> impl Stream<Item = TransportEvent> {
+        self.rc.borrow_mut().transport.establish_event_stream()
+    }
+
+    /// Returns a `Future` that processes transport events incoming to this `Dispatcher` and sends
+    /// the replies. Subsequent call will invalidate a previous one (though old future should
+    /// gracefully finish).
+    ///
+    /// A returned `Future` shall hold a weak handle to the data. Future will finish, when the
+    /// `Transport`'s event stream finishes, e.g. due to dropping the `Transport` itself.
+    ///
+    /// It is expected that upon setting up the `Dispatcher`, this future shall be passed to the
+    /// main executor.
+    pub fn runner(&self) -> impl Future<Output = ()> {
+        let event_receiver = self.transport_event_stream();
+        let weak = self.downgrade();
+        event_receiver.for_each_concurrent(None, move |event| {
+            let weak = weak.clone_ref();
+            let dispatcher = weak.upgrade();
+            match (dispatcher, event) {
+                (Some(dispatcher), TransportEvent::TextMessage(message)) => {
+                    let reply = dispatcher.process_incoming_message(&message);
+                    reply
+                        .map(move |reply| {
+                            let dispatcher = weak.upgrade();
+                            if let (Some(dispatcher), Some(reply)) = (dispatcher, reply) {
+                                if let Err(err) = dispatcher.send_text_message(&reply) {
+                                    warn!("Failed to send a reply: {err}.");
+                                }
+                            }
+                        })
+                        .boxed_local()
+                }
+                (Some(_), TransportEvent::BinaryMessage(_)) => {
+                    warn!("Dispatcher received an unexpected binary message.");
+                    futures::future::ready(()).boxed_local()
+                }
+                // If the data is inaccessible, it is ok to just drop the event here.
+                _ => futures::future::ready(()).boxed_local(),
+            }
+        })
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::handler::Handler;
+    use crate::messages::error_code;
+    use crate::test_util::transport::mock::MockTransport;
+
+    use futures::channel::oneshot;
+    use futures::executor::LocalPool;
+    use futures::task::LocalSpawnExt;
+    use serde::Deserialize;
+
+
+    // === Mock Protocol ===
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+    struct Pow {
+        i: i64,
+    }
+
+    impl RemoteMethodCall for Pow {
+        const NAME: &'static str = "pow";
+        type Returned = i64;
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+    struct Log {
+        text: String,
+    }
+
+    impl RemoteMethodCall for Log {
+        const NAME: &'static str = "log";
+        type Returned = ();
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+    #[serde(tag = "method", content = "params")]
+    enum MockNotification {
+        Meow { text: String },
+    }
+
+    type ErrorMessage = messages::ResponseMessage<Value>;
+
+
+    // === Fixture ===
+
+    struct Fixture {
+        transport:  MockTransport,
+        dispatcher: Dispatcher,
+        pool:       LocalPool,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let transport = MockTransport::new();
+            let dispatcher = Dispatcher::new(transport.clone_ref());
+            dispatcher.register_method(|Pow { i }| Ok(i * i));
+            let pool = LocalPool::new();
+            pool.spawner().spawn_local(dispatcher.runner()).unwrap();
+            Self { transport, dispatcher, pool }
+        }
+
+        fn call(&mut self, request: &str) -> Option<Value> {
+            self.transport.mock_peer_text_message(request);
+            self.pool.run_until_stalled();
+            let reply = self.transport.with_mut_data(|data| data.sent_text_msgs.pop_front());
+            reply.map(|reply| serde_json::from_str(&reply).unwrap())
+        }
+
+        fn expect_error(&mut self, request: &str) -> (Value, i64) {
+            let reply = self.call(request).expect("Expected a reply.");
+            let code = reply["error"]["code"].as_i64().expect("Expected an error reply.");
+            (reply["id"].clone(), code)
+        }
+    }
+
+
+    // === Tests ===
+
+    #[test]
+    fn success_call() {
+        let mut fixture = Fixture::new();
+        let request = Message::new_request(Id(7), Pow::NAME, Pow { i: 8 });
+        fixture.transport.mock_peer_json_message(request);
+        fixture.pool.run_until_stalled();
+        let reply = fixture.transport.expect_json_message::<messages::ResponseMessage<i64>>();
+        assert_eq!(reply, Message::new_success(Id(7), 64));
+    }
+
+    #[test]
+    fn async_call() {
+        let mut fixture = Fixture::new();
+        let (sender, receiver) = oneshot::channel::<()>();
+        let receiver = RefCell::new(Some(receiver));
+        fixture.dispatcher.register_async_method(move |_: Log| {
+            let receiver = receiver.borrow_mut().take().unwrap();
+            receiver.map(|_| Ok(()))
+        });
+        let request = Message::new_request(Id(1), Log::NAME, Log { text: "hello".into() });
+        fixture.transport.mock_peer_json_message(request);
+        // A pending call must not block other ones.
+        assert_eq!(
+            fixture.call(r#"{"jsonrpc":"2.0","id":2,"method":"pow","params":{"i":3}}"#),
+            Some(serde_json::json!({"jsonrpc":"2.0","id":2,"result":9}))
+        );
+        sender.send(()).unwrap();
+        fixture.pool.run_until_stalled();
+        let reply = fixture.transport.expect_json_message::<messages::ResponseMessage<()>>();
+        assert_eq!(reply, Message::new_success(Id(1), ()));
+    }
+
+    #[test]
+    fn error_replies() {
+        let mut fixture = Fixture::new();
+        fixture.dispatcher.register_method(|Log { text }| Err(messages::Error::new(-1, text)));
+        let (id, code) =
+            fixture.expect_error(r#"{"jsonrpc":"2.0","id":1,"method":"cat","params":{}}"#);
+        assert_eq!((id, code), (Value::from(1), error_code::METHOD_NOT_FOUND));
+        let (id, code) =
+            fixture.expect_error(r#"{"jsonrpc":"2.0","id":2,"method":"pow","params":{"i":"8"}}"#);
+        assert_eq!((id, code), (Value::from(2), error_code::INVALID_PARAMS));
+        let (id, code) = fixture.expect_error(r#"{"jsonrpc":"2.0","id":3,"method":"pow"}"#);
+        assert_eq!((id, code), (Value::from(3), error_code::INVALID_PARAMS));
+        let (id, code) = fixture.expect_error(r#"{"jsonrpc":"2.0","method":1,"params":{}"#);
+        assert_eq!((id, code), (Value::Null, error_code::PARSE_ERROR));
+        let (id, code) =
+            fixture.expect_error(r#"{"jsonrpc":"1.0","id":4,"method":"pow","params":{}}"#);
+        assert_eq!((id, code), (Value::from(4), error_code::INVALID_REQUEST));
+        let (id, code) =
+            fixture.expect_error(r#"{"jsonrpc":"2.0","id":"x","method":"pow","params":{}}"#);
+        assert_eq!((id, code), (Value::Null, error_code::INVALID_REQUEST));
+
+        let request = Message::new_request(Id(5), Log::NAME, Log { text: "oops".into() });
+        fixture.transport.mock_peer_json_message(request);
+        fixture.pool.run_until_stalled();
+        let reply = fixture.transport.expect_json_message::<ErrorMessage>();
+        assert_eq!(reply, Message::new_error(Id(5), -1, "oops".into(), None));
+    }
+
+    #[test]
+    fn notifications() {
+        let mut fixture = Fixture::new();
+        let logged = Rc::new(RefCell::new(Vec::new()));
+        let logged_in_handler = logged.clone_ref();
+        fixture.dispatcher.register_method(move |Log { text }| {
+            logged_in_handler.borrow_mut().push(text);
+            Ok(())
+        });
+        assert_eq!(fixture.call(r#"{"jsonrpc":"2.0","method":"log","params":{"text":"a"}}"#), None);
+        assert_eq!(fixture.call(r#"{"jsonrpc":"2.0","method":"cat","params":{}}"#), None);
+        assert_eq!(*logged.borrow(), vec!["a".to_string()]);
+
+        let notification = MockNotification::Meow { text: "meow!".into() };
+        fixture.dispatcher.send_notification(&notification).unwrap();
+        let sent = fixture.transport.expect_json_message::<Message<MockNotification>>();
+        assert_eq!(sent.payload, notification);
+    }
+
+    #[test]
+    fn batches() {
+        let mut fixture = Fixture::new();
+        let batch = r#"[
+            {"jsonrpc":"2.0","id":1,"method":"pow","params":{"i":2}},
+            {"jsonrpc":"2.0","method":"pow","params":{"i":3}},
+            {"jsonrpc":"2.0","id":2,"method":"cat","params":{}},
+            {"jsonrpc":"2.0","id":3,"method":"pow","params":{"i":4}}
+        ]"#;
+        let reply = fixture.call(batch).expect("Expected a reply.");
+        let replies = reply.as_array().expect("Expected a batch reply.");
+        assert_eq!(replies.len(), 3);
+        assert_eq!(replies[0], serde_json::json!({"jsonrpc":"2.0","id":1,"result":4}));
+        assert_eq!(replies[1]["error"]["code"], error_code::METHOD_NOT_FOUND);
+        assert_eq!(replies[2], serde_json::json!({"jsonrpc":"2.0","id":3,"result":16}));
+
+        let notifications = r#"[{"jsonrpc":"2.0","method":"pow","params":{"i":3}}]"#;
+        assert_eq!(fixture.call(notifications), None);
+        let (id, code) = fixture.expect_error("[]");
+        assert_eq!((id, code), (Value::Null, error_code::INVALID_REQUEST));
+        let reply = fixture.call("[1]").expect("Expected a reply.");
+        assert_eq!(reply[0]["error"]["code"], error_code::INVALID_REQUEST);
+    }
+
+    #[test]
+    fn round_trip_with_handler() {
+        let mut client_transport = MockTransport::new();
+        let mut server_transport = MockTransport::new();
+        let mut client = Handler::<MockNotification>::new(client_transport.clone_ref());
+        let dispatcher = Dispatcher::new(server_transport.clone_ref());
+        dispatcher.register_method(|Pow { i }| Ok(i * i));
+        let mut pool = LocalPool::new();
+        pool.spawner().spawn_local(client.runner()).unwrap();
+        pool.spawner().spawn_local(dispatcher.runner()).unwrap();
+        let mut events = Box::pin(client.handler_event_stream());
+
+        let mut result = Box::pin(client.open_request(Pow { i: 5 }));
+        server_transport.mock_peer_text_message(client_transport.expect_text_message());
+        pool.run_until_stalled();
+        client_transport.mock_peer_text_message(server_transport.expect_text_message());
+        pool.run_until_stalled();
+        assert_eq!(result.expect_ok(), 25);
+
+        dispatcher.send_notification(MockNotification::Meow { text: "meow!".into() }).unwrap();
+        client_transport.mock_peer_text_message(server_transport.expect_text_message());
+        pool.run_until_stalled();
+        match events.expect_next() {
+            crate::handler::Event::Notification(MockNotification::Meow { text }) =>
+                assert_eq!(text, "meow!"),
+            event => panic!("Expected a notification, got {event:?}."),
+        }
+    }
+}
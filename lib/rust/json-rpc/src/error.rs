@@ -25,6 +25,12 @@ pub enum RpcError<Payload: Debug + Send + Sync + 'static = serde_json::Value> {
     #[fail(display = "Lost connection before receiving a reply.")]
     LostConnection,
 
+    /// The connection was interrupted while waiting for response. Unlike `LostConnection`, the
+    /// transport is re-establishing the connection, so the call may be repeated once it is
+    /// restored.
+    #[fail(display = "Connection was interrupted before receiving a reply.")]
+    ConnectionInterrupted,
+
     /// Failed to deserialize message from server.
     #[fail(display = "Failed to deserialize a message: {}.", _0)]
     DeserializationFailed(serde_json::Error),
@@ -47,6 +53,13 @@ impl RpcError {
     pub fn new_remote_error(code: i64, message: impl Str) -> RpcError {
         RpcError::RemoteError(Error { code, message: message.into(), data: None })
     }
+
+    /// Check if the error may not occur when the call is repeated, i.e. it is caused by the
+    /// connection rather than the call itself.
+    pub fn is_transient(&self) -> bool {
+        use RpcError::*;
+        matches!(self, LostConnection | ConnectionInterrupted | TimeoutError { .. })
+    }
 }
 
 impl From<Canceled> for RpcError {
@@ -4,12 +4,18 @@ use crate::prelude::*;
 
 use crate::api;
 use crate::api::Result;
+use crate::call_options::CallOptions;
+use crate::dispatcher;
+use crate::dispatcher::MethodHandler;
+use crate::dispatcher::MethodResult;
 use crate::ensogl::sleep;
 use crate::ensogl::Duration;
 use crate::error::HandlingError;
 use crate::error::RpcError;
 use crate::messages;
 use crate::messages::Id;
+use crate::messages::MethodCall;
+use crate::reconnecting::NotConnected;
 use crate::transport::Transport;
 use crate::transport::TransportEvent;
 
@@ -17,10 +23,13 @@ use futures::channel::mpsc::unbounded;
 use futures::channel::mpsc::UnboundedSender;
 use futures::channel::oneshot;
 use futures::future;
+use futures::future::LocalBoxFuture;
 use futures::FutureExt;
 use futures::Stream;
 use futures::StreamExt;
 use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
 use std::future::Future;
 
 
@@ -32,12 +41,10 @@ use std::future::Future;
 /// Partially decoded reply message.
 ///
 /// Known if `Error` or `Success` but returned value remains in JSON form.
-pub type ReplyMessage = messages::Result<serde_json::Value>;
+pub type ReplyMessage = messages::Result<Value>;
 
 /// Converts remote message with JSON-serialized result into `Result<Ret>`.
-pub fn decode_result<Ret: DeserializeOwned>(
-    result: messages::Result<serde_json::Value>,
-) -> Result<Ret> {
+pub fn decode_result<Ret: DeserializeOwned>(result: messages::Result<Value>) -> Result<Ret> {
     match result {
         messages::Result::Success(ret) => Ok(serde_json::from_value::<Ret>(ret.result)?),
         messages::Result::Error { error } => Err(RpcError::RemoteError(error)),
@@ -94,6 +101,11 @@ impl Default for IdGenerator {
 pub enum Event<N> {
     /// Transport has been closed.
     Closed,
+    /// Transport has lost the connection and is trying to re-establish it.
+    Reconnecting,
+    /// Transport has re-established the connection. The peer does not remember the previous
+    /// session, so it should be restored by the owner.
+    Reconnected,
     /// Error occurred.
     Error(HandlingError),
     /// Notification received.
@@ -109,7 +121,11 @@ pub enum Event<N> {
 /// Container that stores Sender's for ongoing calls. Each call identified by
 /// id has its own sender. After reply is received, the call is removed
 /// from this container.
-pub type OngoingCalls = HashMap<Id, oneshot::Sender<ReplyMessage>>;
+pub type OngoingCalls = HashMap<Id, ReplySender>;
+
+/// Completes an ongoing call with the peer's reply, or fails it with an error that is not a reply
+/// from the peer.
+pub type ReplySender = oneshot::Sender<Result<ReplyMessage>>;
 
 
 
@@ -125,6 +141,9 @@ pub type OngoingCalls = HashMap<Id, oneshot::Sender<ReplyMessage>>;
 ///
 /// Notifications and internal messages are emitted using the `events` stream.
 ///
+/// Requests initiated by the peer are answered by the method handlers registered with
+/// `register_method`, the same way as the `Dispatcher` does.
+///
 /// `Notification` is a type for notifications. It should implement
 /// `DeserializeOwned` and deserialize from JSON maps with `method` and `params`
 /// fields.
@@ -145,12 +164,14 @@ pub struct HandlerData<Notification> {
     id_generator    : IdGenerator,
     /// Transports text messages between this handler and the peer.
     transport       : Box<dyn Transport>,
+    /// Handlers of the requests initiated by the peer.
+    methods         : dispatcher::Methods,
 }
 
 
 impl<Notification> {
     /// Inserts a new entry for an ongoing request awaiting reply.
-    pub fn insert_ongoing_request(&mut self, id:Id, sender:oneshot::Sender<ReplyMessage>) {
+    pub fn insert_ongoing_request(&mut self, id:Id, sender:ReplySender) {
         self.ongoing_calls.insert(id,sender);
     }
 
@@ -160,7 +181,7 @@ impl<Notification> {
     ///
     /// Returns the channel handle for the request, it should be immediately
     /// after notified or dropped.
-    pub fn remove_ongoing_request(&mut self, id:Id) -> Option<oneshot::Sender<ReplyMessage>> {
+    pub fn remove_ongoing_request(&mut self, id:Id) -> Option<ReplySender> {
         self.ongoing_calls.remove(&id)
     }
 
@@ -175,6 +196,11 @@ impl<Notification> {
         self.id_generator.generate()
     }
 
+    /// Get the handler of the given method.
+    pub fn method_handler(&self, method:&str) -> Option<MethodHandler> {
+        self.methods.get(method)
+    }
+
     /// Sends a text message to the peer.
     pub fn send_text_message(&mut self, text:&str) -> std::result::Result<(), failure::Error> {
         self.transport.send_text(text)
@@ -228,6 +254,16 @@ impl<Notification> Handler<Notification> {
         event_receiver
     }
 
+    /// Removes all the ongoing requests, failing their `Future`s with
+    /// `RpcError::ConnectionInterrupted`.
+    pub fn interrupt_ongoing_requests(&self) {
+        let ongoing_calls = mem::take(&mut self.rc.borrow_mut().ongoing_calls);
+        for (_, sender) in ongoing_calls {
+            // The caller might have already dropped the future, which is fine.
+            sender.send(Err(RpcError::ConnectionInterrupted)).ok();
+        }
+    }
+
     /// Creates a new handler working on a given `Transport`.
     ///
     /// `Transport` must be functional (e.g. not in the process of opening).
@@ -238,6 +274,7 @@ impl<Notification> Handler<Notification> {
             id_generator:    IdGenerator::new(),
             transport:       Box::new(transport),
             outgoing_events: None,
+            methods:         default(),
         };
         Handler { rc: Rc::new(RefCell::new(data)) }
     }
@@ -268,7 +305,7 @@ impl<Notification> Handler<Notification> {
     pub fn open_request_with_json<Returned: DeserializeOwned>(
         &self,
         method_name: &str,
-        input: &serde_json::Value,
+        input: &Value,
     ) -> impl Future<Output = Result<Returned>> {
         let id = self.generate_new_id();
         let message = crate::messages::Message::new_request(id, method_name, input);
@@ -285,33 +322,168 @@ impl<Notification> Handler<Notification> {
         id: Id,
         message_json: &str,
     ) -> impl Future<Output = Result<Returned>> {
-        let (sender, receiver) = oneshot::channel::<ReplyMessage>();
+        let ret = self.expect_reply(id, self.timeout());
+        if let Err(error) = self.send_text_message(message_json) {
+            // If message cannot be send, future ret must be cancelled.
+            self.abort_request(id, &error);
+        }
+        ret
+    }
+
+    /// Sends a request to the peer and returns a `Future` that shall yield a reply message,
+    /// according to the given per-call options. See `CallOptions` for details.
+    ///
+    /// The first attempt is sent immediately, the retries (if any) are sent by the returned
+    /// `Future`.
+    pub fn open_request_with_options<Returned: DeserializeOwned + 'static>(
+        &self,
+        method_name: &str,
+        input: &Value,
+        options: CallOptions,
+    ) -> LocalBoxFuture<'static, Result<Returned>>
+    where
+        Notification: 'static,
+    {
+        let first_attempt = self.open_request_attempt(method_name, input, &options);
+        let retried_call = options.retry.map(|_| (method_name.to_owned(), input.clone()));
+        let weak = self.downgrade();
+        let deadline = options.deadline;
+        let call = async move {
+            let mut result = first_attempt.await;
+            let mut attempts = 1;
+            while let Some(retry) = options.retry.filter(|p| p.should_retry(attempts, &result)) {
+                sleep(retry.backoff(attempts)).await;
+                let handler = weak.upgrade();
+                let (handler, (method_name, input)) = match (handler, &retried_call) {
+                    (Some(handler), Some(call)) => (handler, call),
+                    _ => break,
+                };
+                let attempt = handler.open_request_attempt(method_name, input, &options);
+                drop(handler);
+                result = attempt.await;
+                attempts += 1;
+            }
+            result
+        };
+        match deadline {
+            Some(deadline) => with_timeout(call.boxed_local(), deadline).boxed_local(),
+            None => call.boxed_local(),
+        }
+    }
+
+    /// Sends a single attempt of the request made by `open_request_with_options`.
+    fn open_request_attempt<Returned: DeserializeOwned + 'static>(
+        &self,
+        method_name: &str,
+        input: &Value,
+        options: &CallOptions,
+    ) -> impl Future<Output = Result<Returned>> + 'static
+    where
+        Notification: 'static,
+    {
+        let id = self.generate_new_id();
+        let message = crate::messages::Message::new_request(id, method_name, input);
+        let serialized_message = serde_json::to_string(&message).unwrap();
+        let timeout = options.deadline.unwrap_or_else(|| self.timeout());
+        let ret = self.expect_reply(id, timeout);
+        let cancel_guard = options.cancel_on_drop.then(|| CancelGuard {
+            handler: self.downgrade(),
+            id,
+            notification: options.cancel_notification.clone(),
+        });
+        if let Err(error) = self.send_text_message(&serialized_message) {
+            // If message cannot be send, future ret must be cancelled.
+            self.abort_request(id, &error);
+        }
+        async move {
+            let _cancel_guard = cancel_guard;
+            ret.await
+        }
+    }
+
+    /// Registers an ongoing request with the given id and returns a `Future` that shall yield a
+    /// reply message, or fail after `timeout`. The request message itself should be sent by the
+    /// caller.
+    fn expect_reply<Returned: DeserializeOwned>(
+        &self,
+        id: Id,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<Returned>> {
+        let (sender, receiver) = oneshot::channel::<Result<ReplyMessage>>();
         let ret = receiver.map(|result_or_cancel| {
-            let result = result_or_cancel?;
+            let result = result_or_cancel??;
             decode_result(result)
         });
-
         self.insert_ongoing_request(id, sender);
-        if self.send_text_message(message_json).is_err() {
-            // If message cannot be send, future ret must be cancelled.
-            self.remove_ongoing_request(id);
+        with_timeout(ret, timeout)
+    }
+
+    /// Removes the request that could not be sent to the peer due to the `error`. Its `Future`
+    /// fails with `RpcError::ConnectionInterrupted` if the transport is re-establishing the
+    /// connection, or with `RpcError::LostConnection` otherwise.
+    fn abort_request(&self, id: Id, error: &failure::Error) {
+        if let Some(sender) = self.remove_ongoing_request(id) {
+            if error.downcast_ref::<NotConnected>().is_some() {
+                sender.send(Err(RpcError::ConnectionInterrupted)).ok();
+            }
         }
+    }
 
-        let millis = self.timeout().as_millis();
-        future::select(ret, sleep(self.timeout()).boxed_local()).map(move |either| match either {
-            future::Either::Left((x, _)) => x,
-            future::Either::Right((_, _)) => Err(RpcError::TimeoutError { millis }),
+    /// Sends a notification to the peer. Unlike the requests, the notifications are not replied
+    /// to. Fails with `RpcError::ConnectionInterrupted` if the transport is re-establishing the
+    /// connection, or with `RpcError::LostConnection` if the message could not be sent otherwise.
+    pub fn send_notification(&self, method: &str, params: &Value) -> Result<()> {
+        let call = MethodCall { method: method.to_owned(), params };
+        let message = messages::Message::new(messages::Notification(call));
+        let serialized_message = serde_json::to_string(&message).unwrap();
+        self.send_text_message(&serialized_message).map_err(|error| {
+            if error.downcast_ref::<NotConnected>().is_some() {
+                RpcError::ConnectionInterrupted
+            } else {
+                RpcError::LostConnection
+            }
         })
     }
 
+    /// Creates a new batch of requests, that will be sent to the peer in a single message.
+    pub fn batch(&self) -> Batch<Notification> {
+        Batch { handler: self.clone(), ids: default(), messages: default() }
+    }
+
+    /// Register a handler of the `M` method, called when the peer makes a request. The previously
+    /// registered handler of the same method, if any, is replaced.
+    ///
+    /// See `Dispatcher::register_method`.
+    pub fn register_method<M, F>(&self, handler: F)
+    where
+        M: api::RemoteMethodCall + DeserializeOwned,
+        M::Returned: Serialize + 'static,
+        F: Fn(M) -> MethodResult<M::Returned> + 'static, {
+        self.register_async_method(move |input| future::ready(handler(input)))
+    }
+
+    /// Register an asynchronous handler of the `M` method, called when the peer makes a request.
+    /// The previously registered handler of the same method, if any, is replaced.
+    ///
+    /// See `Dispatcher::register_async_method`.
+    pub fn register_async_method<M, F, Fut>(&self, handler: F)
+    where
+        M: api::RemoteMethodCall + DeserializeOwned,
+        M::Returned: Serialize,
+        F: Fn(M) -> Fut + 'static,
+        Fut: Future<Output = MethodResult<M::Returned>> + 'static, {
+        self.rc.borrow_mut().methods.insert(handler)
+    }
+
     /// Deal with `Response` message from the peer.
     ///
     /// It shall be either matched with an open request or yield an error.
-    pub fn process_response(&self, message: messages::Response<serde_json::Value>) {
+    pub fn process_response(&self, message: messages::Response<Value>) {
+        crate::log::rpc_response(message.id);
         if let Some(sender) = self.remove_ongoing_request(message.id) {
             // Disregard any error. We do not care if RPC caller already
             // dropped the future.
-            sender.send(message.result).ok();
+            sender.send(Ok(message.result)).ok();
         } else {
             self.error_occurred(HandlingError::UnexpectedResponse(message));
         }
@@ -321,7 +493,7 @@ impl<Notification> Handler<Notification> {
     ///
     /// If possible, emits a message with notification. In case of failure,
     /// emits relevant error.
-    pub fn process_notification(&self, message: messages::Notification<serde_json::Value>)
+    pub fn process_notification(&self, message: messages::Notification<Value>)
     where Notification: DeserializeOwned {
         match serde_json::from_value(message.0) {
             Ok(notification) => {
@@ -335,18 +507,66 @@ impl<Notification> Handler<Notification> {
         }
     }
 
-    /// Deal with incoming text message from the peer.
+    /// Deal with `Request` message from the peer.
     ///
-    /// The message must conform either to the `Response` or to the
+    /// Returns a `Future` yielding the JSON-serialized response, which should be sent back to the
+    /// peer.
+    pub fn process_request(
+        &self,
+        request: messages::Request<MethodCall<Value>>,
+    ) -> LocalBoxFuture<'static, Option<String>> {
+        let handler = self.method_handler(&request.call.method);
+        let id = request.id;
+        let result = dispatcher::call_method(handler, request.call);
+        result.map(move |result| Some(dispatcher::encode_reply(Some(id), result))).boxed_local()
+    }
+
+    /// Deal with a single incoming message from the peer, already parsed as JSON.
+    ///
+    /// The message must conform either to the `Response`, `Request` or to the
     /// `Notification` JSON-serialized format. Otherwise, an error is raised.
-    pub fn process_incoming_message(&self, message: String)
-    where Notification: DeserializeOwned {
-        match messages::decode_incoming_message(&message) {
+    ///
+    /// Returns a `Future` yielding the JSON-serialized response if the message is a request.
+    pub fn process_incoming_value(
+        &self,
+        message: Value,
+    ) -> LocalBoxFuture<'static, Option<String>>
+    where
+        Notification: DeserializeOwned,
+    {
+        match messages::decode_incoming_value(message) {
             Ok(messages::IncomingMessage::Response(response)) => self.process_response(response),
+            Ok(messages::IncomingMessage::Request(request)) =>
+                return self.process_request(request),
             Ok(messages::IncomingMessage::Notification(notification)) =>
                 self.process_notification(notification),
             Err(err) => self.error_occurred(HandlingError::InvalidMessage(err)),
         }
+        future::ready(None).boxed_local()
+    }
+
+    /// Deal with incoming text message from the peer.
+    ///
+    /// The message must be either a single message accepted by `process_incoming_value` or a batch
+    /// (JSON array) of such. Otherwise, an error is raised.
+    ///
+    /// Returns a `Future` yielding the JSON-serialized response to the requests from the message.
+    /// Responses to the requests from a batch are batched as well.
+    pub fn process_incoming_message(
+        &self,
+        message: String,
+    ) -> LocalBoxFuture<'static, Option<String>>
+    where
+        Notification: DeserializeOwned,
+    {
+        match serde_json::from_str(&message) {
+            Ok(message) =>
+                dispatcher::process_batch(message, |message| self.process_incoming_value(message)),
+            Err(err) => {
+                self.error_occurred(HandlingError::InvalidMessage(err));
+                future::ready(None).boxed_local()
+            }
+        }
     }
 
     /// With with a handling error. Uses `on_error` callback to notify the
@@ -357,11 +577,24 @@ impl<Notification> Handler<Notification> {
 
     /// Processes a single transport event.
     ///
-    /// Each event either completes a requests or is translated into `Event`.
-    pub fn process_event(&self, event: TransportEvent)
-    where Notification: DeserializeOwned {
+    /// Each event either completes a requests or is translated into `Event`. Requests from the peer
+    /// are handled by the returned `Future`, which sends the responses once it completes.
+    pub fn process_event(&self, event: TransportEvent) -> LocalBoxFuture<'static, ()>
+    where Notification: DeserializeOwned + 'static {
         match event {
-            TransportEvent::TextMessage(msg) => self.process_incoming_message(msg),
+            TransportEvent::TextMessage(msg) => {
+                let weak = self.downgrade();
+                let reply = self.process_incoming_message(msg);
+                return reply
+                    .map(move |reply| {
+                        if let (Some(handler), Some(reply)) = (weak.upgrade(), reply) {
+                            if let Err(err) = handler.send_text_message(&reply) {
+                                warn!("Failed to send a reply: {err}.");
+                            }
+                        }
+                    })
+                    .boxed_local();
+            }
             TransportEvent::BinaryMessage(data) =>
                 self.error_occurred(HandlingError::UnexpectedBinaryMessage(data)),
             TransportEvent::Opened => {}
@@ -370,7 +603,14 @@ impl<Notification> Handler<Notification> {
                 self.clear_ongoing_requests();
                 self.emit_event(Event::Closed);
             }
+            TransportEvent::Reconnecting => {
+                // The replies will never come, as the peer will not remember the requests.
+                self.interrupt_ongoing_requests();
+                self.emit_event(Event::Reconnecting);
+            }
+            TransportEvent::Reconnected => self.emit_event(Event::Reconnected),
         }
+        future::ready(()).boxed_local()
     }
 
     /// Returns a `Future` that processes transport events incoming to this `Handler`.
@@ -387,15 +627,135 @@ impl<Notification> Handler<Notification> {
     where Notification: DeserializeOwned + 'static {
         let event_receiver = self.transport_event_stream();
         let weak_data = Rc::downgrade(&self.rc);
-        event_receiver.for_each(move |event: TransportEvent| {
+        event_receiver.for_each_concurrent(None, move |event: TransportEvent| {
             let data_opt = weak_data.clone().upgrade();
             let handler_opt = data_opt.map(|rc| Handler { rc });
             if let Some(handler) = handler_opt {
                 handler.process_event(event)
             } else {
                 // If the data is inaccessible, it is ok to just drop the event here.
+                futures::future::ready(()).boxed_local()
             }
-            futures::future::ready(())
         })
     }
 }
+
+
+
+/// Make the `Future` fail with `RpcError::TimeoutError` if it does not complete in given time.
+fn with_timeout<T>(
+    future: impl Future<Output = Result<T>> + Unpin,
+    timeout: Duration,
+) -> impl Future<Output = Result<T>> {
+    let millis = timeout.as_millis();
+    future::select(future, sleep(timeout).boxed_local()).map(move |either| match either {
+        future::Either::Left((x, _)) => x,
+        future::Either::Right((_, _)) => Err(RpcError::TimeoutError { millis }),
+    })
+}
+
+
+
+// ===================
+// === CancelGuard ===
+// ===================
+
+/// Cancels the ongoing request when dropped before the reply was received.
+#[derive(Debug)]
+struct CancelGuard<Notification> {
+    handler:      WeakHandler<Notification>,
+    id:           Id,
+    /// The method of a notification informing the peer about the cancellation.
+    notification: Option<String>,
+}
+
+impl<Notification> Drop for CancelGuard<Notification> {
+    fn drop(&mut self) {
+        if let Some(handler) = self.handler.upgrade() {
+            let was_ongoing = handler.remove_ongoing_request(self.id).is_some();
+            if let (true, Some(method)) = (was_ongoing, self.notification.take()) {
+                let params = serde_json::json!({ "id": self.id });
+                // The peer will ignore the reply anyway, so failing to send is not a problem.
+                handler.send_notification(&method, &params).ok();
+            }
+        }
+    }
+}
+
+
+
+// =============
+// === Batch ===
+// =============
+
+/// A batch of requests and notifications, sent to the peer in a single message by `send`. The
+/// peer shall reply to all the requests with a single message, resolving the futures at once.
+///
+/// Requests of a batch that is dropped without being sent fail with `RpcError::LostConnection`.
+#[derive(Debug)]
+pub struct Batch<Notification> {
+    handler:  Handler<Notification>,
+    ids:      Vec<Id>,
+    messages: Vec<Value>,
+}
+
+impl<Notification> Batch<Notification> {
+    /// Adds a request to the batch and returns a `Future` that shall yield a reply message. It is
+    /// automatically decoded into the expected type.
+    pub fn open_request<In: api::RemoteMethodCall>(
+        &mut self,
+        input: In,
+    ) -> impl Future<Output = Result<In::Returned>> {
+        let id = self.handler.generate_new_id();
+        let message = api::into_request_message(input, id);
+        self.messages.push(serde_json::to_value(message).unwrap());
+        self.ids.push(id);
+        self.handler.expect_reply(id, self.handler.timeout())
+    }
+
+    /// Adds a notification to the batch.
+    ///
+    /// `notification` should serialize to a JSON map with `method` and `params` fields, like
+    /// `MethodCall` or an enum tagged with `#[serde(tag = "method", content = "params")]`.
+    pub fn add_notification<N: Serialize>(&mut self, notification: N) -> serde_json::Result<()> {
+        let message = serde_json::to_value(messages::Message::new(notification))?;
+        self.messages.push(message);
+        Ok(())
+    }
+
+    /// Number of the requests and notifications in the batch.
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Checks if the batch has no requests nor notifications.
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// Sends the batch to the peer. An empty batch is not sent at all, as the JSON-RPC
+    /// specification forbids it.
+    pub fn send(mut self) -> FallibleResult {
+        let ids = mem::take(&mut self.ids);
+        let messages = mem::take(&mut self.messages);
+        if messages.is_empty() {
+            return Ok(());
+        }
+        let result = self.handler.send_text_message(&Value::Array(messages).to_string());
+        if let Err(error) = &result {
+            // If message cannot be send, futures of all requests must be cancelled.
+            for id in ids {
+                self.handler.abort_request(id, error);
+            }
+        }
+        result
+    }
+}
+
+impl<Notification> Drop for Batch<Notification> {
+    fn drop(&mut self) {
+        for id in mem::take(&mut self.ids) {
+            self.handler.remove_ongoing_request(id);
+        }
+    }
+}
@@ -1,5 +1,6 @@
 //! This is a library aimed to facilitate implementing JSON-RPC protocol
-//! clients. The main type is `Handler` that a client should build upon.
+//! clients and servers. The main type is `Handler` that a client should build
+//! upon, while servers should build upon `Dispatcher`.
 
 // === Features ===
 #![feature(trait_alias)]
@@ -23,16 +24,23 @@
 // ==============
 
 pub mod api;
+pub mod call_options;
+pub mod dispatcher;
 pub mod error;
 pub mod handler;
 pub mod log;
 pub mod macros;
 pub mod messages;
+pub mod reconnecting;
+pub mod recording;
 pub mod test_util;
 pub mod transport;
 
 pub use api::RemoteMethodCall;
 pub use api::Result;
+pub use call_options::CallOptions;
+pub use call_options::RetryPolicy;
+pub use dispatcher::Dispatcher;
 pub use enso_prelude as prelude;
 pub use enso_profiler;
 pub use enso_profiler_data;
@@ -40,6 +48,10 @@ pub use enso_web as ensogl;
 pub use error::RpcError;
 pub use handler::Event;
 pub use handler::Handler;
+pub use json_rpc_macros::rpc_api;
+pub use reconnecting::ReconnectingTransport;
+pub use recording::RecordingTransport;
+pub use schemars;
 pub use transport::Transport;
 pub use transport::TransportEvent;
 
@@ -1,13 +1,14 @@
-//! Interface for logging RPC Requests as [`enso_profiler`] metadata, and interpreting the resultant
-//! log events.
+//! Interface for logging RPC Requests and Responses as [`enso_profiler`] metadata, and interpreting
+//! the resultant log events.
 
 
 
-// ===========================
-// === Rpc Request Logging ===
-// ===========================
+// ===================
+// === Rpc Logging ===
+// ===================
 
 enso_profiler::metadata_logger!("RpcRequest", rpc_request(&'static str));
+enso_profiler::metadata_logger!("RpcResponse", rpc_response(crate::messages::Id));
 
 
 
@@ -15,7 +16,7 @@ enso_profiler::metadata_logger!("RpcRequest", rpc_request(&'static str));
 // === RpcRequest ===
 // ==================
 
-/// Message sent from the IDE to the Language Server.
+/// Message sent from the IDE to the Language Server, identified by its JSON-RPC method name.
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
 pub struct RpcRequest(std::borrow::Cow<'static, str>);
 
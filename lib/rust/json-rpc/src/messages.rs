@@ -193,13 +193,58 @@ pub struct Error<Payload = serde_json::Value> {
     pub data:    Option<Payload>,
 }
 
-/// A message that can come from Server to Client — either a response or
-/// notification.
+impl Error {
+    /// Construct an error that bears no optional data.
+    pub fn new(code: i64, message: impl Str) -> Error {
+        Error { code, message: message.into(), data: None }
+    }
+
+    /// Invalid JSON was received by the server.
+    pub fn parse_error(cause: impl Display) -> Error {
+        Self::new(error_code::PARSE_ERROR, format!("Parse error: {cause}."))
+    }
+
+    /// The JSON sent is not a valid request object.
+    pub fn invalid_request(cause: impl Display) -> Error {
+        Self::new(error_code::INVALID_REQUEST, format!("Invalid request: {cause}."))
+    }
+
+    /// The method does not exist or is not available.
+    pub fn method_not_found(method: &str) -> Error {
+        Self::new(error_code::METHOD_NOT_FOUND, format!("Method not found: {method}."))
+    }
+
+    /// Invalid method parameters.
+    pub fn invalid_params(cause: impl Display) -> Error {
+        Self::new(error_code::INVALID_PARAMS, format!("Invalid params: {cause}."))
+    }
+
+    /// Internal JSON-RPC error.
+    pub fn internal_error(cause: impl Display) -> Error {
+        Self::new(error_code::INTERNAL_ERROR, format!("Internal error: {cause}."))
+    }
+}
+
+/// Error codes predefined by the JSON-RPC 2.0 specification. Codes from -32000 to -32099 are
+/// reserved for implementation-defined server errors.
+#[allow(missing_docs)]
+pub mod error_code {
+    pub const PARSE_ERROR: i64 = -32700;
+    pub const INVALID_REQUEST: i64 = -32600;
+    pub const METHOD_NOT_FOUND: i64 = -32601;
+    pub const INVALID_PARAMS: i64 = -32602;
+    pub const INTERNAL_ERROR: i64 = -32603;
+}
+
+/// A message that can come from Server to Client — either a response,
+/// request or notification.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
 #[serde(untagged)]
 pub enum IncomingMessage {
     /// A response to a call made by client.
     Response(Response<serde_json::Value>),
+    /// A call initiated by the server, expecting a response.
+    Request(Request<MethodCall<serde_json::Value>>),
     /// A notification call (initiated by the server).
     Notification(Notification<serde_json::Value>),
 }
@@ -207,13 +252,55 @@ pub enum IncomingMessage {
 /// Partially decodes incoming message.
 ///
 /// This checks if has `jsonrpc` version string, and whether it is a
-/// response or a notification.
+/// response, request or a notification.
 pub fn decode_incoming_message(message: &str) -> serde_json::Result<IncomingMessage> {
-    use serde_json::from_str;
+    decode_incoming_value(serde_json::from_str(message)?)
+}
+
+/// Partially decodes incoming message, already parsed as JSON.
+///
+/// See `decode_incoming_message`.
+pub fn decode_incoming_value(message: serde_json::Value) -> serde_json::Result<IncomingMessage> {
+    use serde_json::from_value;
+    use serde_json::Value;
+    let mut payload = from_value::<Message<Value>>(message)?.payload;
+    if payload.get("id").is_some() && payload.get("method").is_some() {
+        default_params(&mut payload);
+    }
+    from_value::<IncomingMessage>(payload)
+}
+
+/// Fill the omitted `params` field of a call with `null`, as the specification allows omitting it.
+fn default_params(call: &mut serde_json::Value) {
+    if let Some(fields) = call.as_object_mut() {
+        fields.entry("params").or_insert(serde_json::Value::Null);
+    }
+}
+
+/// A message that can come from Client to Server — either a request or notification.
+#[derive(Serialize, Debug, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum IncomingCall {
+    /// A call expecting a response.
+    Request(Request<MethodCall<serde_json::Value>>),
+    /// A call that shall not be replied to.
+    Notification(Notification<MethodCall<serde_json::Value>>),
+}
+
+/// Partially decodes incoming call.
+///
+/// This checks if has `jsonrpc` version string, and whether it is a request (bears an `id`) or a
+/// notification. Omitted `params` are decoded as `null`.
+pub fn decode_incoming_call(message: serde_json::Value) -> serde_json::Result<IncomingCall> {
     use serde_json::from_value;
     use serde_json::Value;
-    let message = from_str::<Message<Value>>(message)?;
-    from_value::<IncomingMessage>(message.payload)
+    let mut payload = from_value::<Message<Value>>(message)?.payload;
+    default_params(&mut payload);
+    if payload.get("id").is_some() {
+        from_value(payload).map(IncomingCall::Request)
+    } else {
+        from_value(payload).map(IncomingCall::Notification)
+    }
 }
 
 /// Message from server to client.
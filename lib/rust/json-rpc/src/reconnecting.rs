@@ -0,0 +1,323 @@
+//! A `Transport` wrapper that re-establishes the connection whenever it is lost.
+
+use crate::prelude::*;
+
+use crate::call_options::RetryPolicy;
+use crate::ensogl::sleep;
+use crate::transport::Transport;
+use crate::transport::TransportEvent;
+
+use failure::Error;
+use futures::channel::mpsc::unbounded;
+use futures::channel::mpsc::UnboundedSender;
+use futures::future::LocalBoxFuture;
+use futures::StreamExt;
+use std::future::Future;
+
+
+
+// ==============
+// === Errors ===
+// ==============
+
+/// Error returned when trying to send a message while the connection is being re-established.
+#[derive(Clone, Copy, Debug, Fail)]
+#[fail(display = "Cannot send a message while the connection is being re-established.")]
+pub struct NotConnected;
+
+
+
+// =================
+// === Connector ===
+// =================
+
+/// A function establishing a new connection. The yielded transport must be functional (e.g. not
+/// in the process of opening).
+pub type Connector = Box<dyn Fn() -> LocalBoxFuture<'static, FallibleResult<Box<dyn Transport>>>>;
+
+
+
+// =============
+// === Model ===
+// =============
+
+struct Model {
+    /// Establishes the new connections.
+    connector:       Connector,
+    /// Describes the delays between the subsequent reconnecting attempts and their limit.
+    policy:          RetryPolicy,
+    /// The current connection. `None` while reconnecting.
+    transport:       Option<Box<dyn Transport>>,
+    /// Passes the events of the subsequent connections to the `runner`.
+    inner_events:    Option<UnboundedSender<TransportEvent>>,
+    /// Passes the events to the owner of this transport.
+    outgoing_events: Option<UnboundedSender<TransportEvent>>,
+}
+
+impl Model {
+    fn set_transport(&mut self, mut transport: Box<dyn Transport>) {
+        if let Some(transmitter) = self.inner_events.as_ref() {
+            transport.set_event_transmitter(transmitter.clone());
+        }
+        self.transport = Some(transport);
+    }
+
+    fn emit_event(&self, event: TransportEvent) {
+        if let Some(transmitter) = self.outgoing_events.as_ref() {
+            channel::emit(transmitter, event);
+        }
+    }
+}
+
+impl Debug for Model {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Model")
+            .field("policy", &self.policy)
+            .field("transport", &self.transport)
+            .finish()
+    }
+}
+
+
+
+// =============================
+// === ReconnectingTransport ===
+// =============================
+
+/// A `Transport` wrapper that re-establishes the connection with exponential backoff whenever it
+/// is lost.
+///
+/// When the connection is lost, `TransportEvent::Reconnecting` is emitted and the messages cannot
+/// be sent until `TransportEvent::Reconnected` is emitted. If all the attempts allowed by the
+/// `RetryPolicy` fail, the transport gives up and emits `TransportEvent::Closed`. Use
+/// `RetryPolicy::new(usize::MAX)` to never give up.
+///
+/// The connections are established and their events processed by the `runner` `Future`, which
+/// should be passed to the executor.
+#[derive(Clone, CloneRef, Debug)]
+pub struct ReconnectingTransport {
+    model: Rc<RefCell<Model>>,
+}
+
+impl ReconnectingTransport {
+    /// Wrap an already established connection. The `connector` will be used to establish the new
+    /// connections once it is lost.
+    pub fn new<F>(transport: impl Transport + 'static, connector: F, policy: RetryPolicy) -> Self
+    where F: Fn() -> LocalBoxFuture<'static, FallibleResult<Box<dyn Transport>>> + 'static {
+        let model = Model {
+            connector: Box::new(connector),
+            policy,
+            transport: Some(Box::new(transport)),
+            inner_events: None,
+            outgoing_events: None,
+        };
+        Self { model: Rc::new(RefCell::new(model)) }
+    }
+
+    /// Check if the connection is currently established.
+    pub fn is_connected(&self) -> bool {
+        self.model.borrow().transport.is_some()
+    }
+
+    /// Returns a `Future` that processes the events of the wrapped connections and re-establishes
+    /// them. Subsequent call will invalidate a previous one.
+    ///
+    /// The `Future` holds a weak handle to the transport and finishes when the transport is
+    /// dropped or gives up reconnecting.
+    pub fn runner(&self) -> impl Future<Output = ()> {
+        let (transmitter, mut receiver) = unbounded();
+        with(self.model.borrow_mut(), |mut model| {
+            if let Some(transport) = model.transport.as_mut() {
+                transport.set_event_transmitter(transmitter.clone());
+            }
+            model.inner_events = Some(transmitter);
+        });
+        let weak = Rc::downgrade(&self.model);
+        async move {
+            while let Some(event) = receiver.next().await {
+                let model = match weak.upgrade() {
+                    Some(model) => model,
+                    None => break,
+                };
+                if let TransportEvent::Closed = event {
+                    model.borrow_mut().transport = None;
+                    model.borrow().emit_event(TransportEvent::Reconnecting);
+                    drop(model);
+                    let reconnected = Self::reconnect(&weak).await;
+                    let event = if reconnected {
+                        TransportEvent::Reconnected
+                    } else {
+                        TransportEvent::Closed
+                    };
+                    if let Some(model) = weak.upgrade() {
+                        model.borrow().emit_event(event);
+                    }
+                    if !reconnected {
+                        break;
+                    }
+                } else {
+                    model.borrow().emit_event(event);
+                }
+            }
+        }
+    }
+
+    /// Try establishing a new connection until it succeeds or the `RetryPolicy` gives up. Returns
+    /// `true` if the connection has been established.
+    async fn reconnect(model: &Weak<RefCell<Model>>) -> bool {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let policy = match model.upgrade() {
+                Some(model) => model.borrow().policy,
+                None => return false,
+            };
+            if attempt > policy.max_attempts {
+                warn!("Giving up reconnecting after {} attempts.", policy.max_attempts);
+                return false;
+            }
+            sleep(policy.backoff(attempt)).await;
+            let connecting = match model.upgrade() {
+                Some(model) => (model.borrow().connector)(),
+                None => return false,
+            };
+            match connecting.await {
+                Ok(transport) =>
+                    if let Some(model) = model.upgrade() {
+                        model.borrow_mut().set_transport(transport);
+                        return true;
+                    } else {
+                        return false;
+                    },
+                Err(error) => warn!("Reconnecting attempt {attempt} failed: {error}"),
+            }
+        }
+    }
+
+    fn with_transport<R>(
+        &mut self,
+        f: impl FnOnce(&mut dyn Transport) -> Result<R, Error>,
+    ) -> Result<R, Error> {
+        match self.model.borrow_mut().transport.as_mut() {
+            Some(transport) => f(transport.as_mut()),
+            None => Err(NotConnected.into()),
+        }
+    }
+}
+
+impl Transport for ReconnectingTransport {
+    fn send_text(&mut self, message: &str) -> Result<(), Error> {
+        self.with_transport(|transport| transport.send_text(message))
+    }
+
+    fn send_binary(&mut self, message: &[u8]) -> Result<(), Error> {
+        self.with_transport(|transport| transport.send_binary(message))
+    }
+
+    fn set_event_transmitter(&mut self, transmitter: UnboundedSender<TransportEvent>) {
+        self.model.borrow_mut().outgoing_events = Some(transmitter);
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::ensogl::Duration;
+    use crate::test_util::transport::mock::MockTransport;
+
+    use futures::channel::mpsc::UnboundedReceiver;
+    use futures::executor::LocalPool;
+    use futures::task::LocalSpawnExt;
+    use futures::FutureExt;
+
+    struct Fixture {
+        /// The connections made by the connector, except the first one.
+        connections: Rc<RefCell<Vec<MockTransport>>>,
+        /// Number of the next connecting attempts that shall fail.
+        failing:     Rc<Cell<usize>>,
+        first:       MockTransport,
+        transport:   ReconnectingTransport,
+        events:      UnboundedReceiver<TransportEvent>,
+        pool:        LocalPool,
+    }
+
+    impl Fixture {
+        fn new(policy: RetryPolicy) -> Self {
+            let connections = Rc::new(RefCell::new(Vec::<MockTransport>::new()));
+            let failing = Rc::new(Cell::new(0));
+            let connector = {
+                let connections = connections.clone_ref();
+                let failing = failing.clone_ref();
+                move || {
+                    let result: FallibleResult<Box<dyn Transport>> = if failing.get() > 0 {
+                        failing.set(failing.get() - 1);
+                        Err(NotConnected.into())
+                    } else {
+                        let transport = MockTransport::new();
+                        connections.borrow_mut().push(transport.clone_ref());
+                        Ok(Box::new(transport))
+                    };
+                    futures::future::ready(result).boxed_local()
+                }
+            };
+            let first = MockTransport::new();
+            let mut transport = ReconnectingTransport::new(first.clone_ref(), connector, policy);
+            let events = transport.establish_event_stream();
+            let pool = LocalPool::new();
+            pool.spawner().spawn_local(transport.runner()).unwrap();
+            Self { connections, failing, first, transport, events, pool }
+        }
+
+        fn next_event(&mut self) -> Option<TransportEvent> {
+            self.pool.run_until(self.events.next())
+        }
+    }
+
+    fn policy(max_attempts: usize) -> RetryPolicy {
+        RetryPolicy::new(max_attempts).initial_backoff(Duration::from_millis(1))
+    }
+
+    #[test]
+    fn reconnecting() {
+        let mut fixture = Fixture::new(policy(5));
+        fixture.first.mock_peer_text_message("first");
+        assert!(
+            matches!(fixture.next_event(), Some(TransportEvent::TextMessage(t)) if t == "first")
+        );
+
+        fixture.failing.set(2);
+        fixture.first.mock_connection_closed();
+        assert!(matches!(fixture.next_event(), Some(TransportEvent::Reconnecting)));
+        assert!(!fixture.transport.is_connected());
+        assert!(fixture.transport.send_text("lost").is_err());
+        assert!(matches!(fixture.next_event(), Some(TransportEvent::Reconnected)));
+        assert!(fixture.transport.is_connected());
+        assert_eq!(fixture.failing.get(), 0);
+
+        let mut second = fixture.connections.borrow()[0].clone_ref();
+        fixture.transport.send_text("sent").unwrap();
+        assert_eq!(second.expect_text_message(), "sent");
+        second.mock_peer_text_message("second");
+        assert!(
+            matches!(fixture.next_event(), Some(TransportEvent::TextMessage(t)) if t == "second")
+        );
+    }
+
+    #[test]
+    fn giving_up() {
+        let mut fixture = Fixture::new(policy(2));
+        fixture.failing.set(2);
+        fixture.first.mock_connection_closed();
+        assert!(matches!(fixture.next_event(), Some(TransportEvent::Reconnecting)));
+        assert!(matches!(fixture.next_event(), Some(TransportEvent::Closed)));
+        assert!(fixture.connections.borrow().is_empty());
+        assert!(!fixture.transport.is_connected());
+    }
+}
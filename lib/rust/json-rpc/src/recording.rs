@@ -0,0 +1,278 @@
+//! A `Transport` decorator recording all the exchanged frames, and the format of the recordings.
+//!
+//! A recording is a JSON-lines file, each line being a serialized `Frame`, e.g.:
+//! ```text
+//! {"time":12.0,"direction":"sent","type":"text","data":"{\"jsonrpc\":\"2.0\",\"id\":0,...}"}
+//! {"time":15.0,"direction":"received","type":"binary","data":[12,0,0,0,...]}
+//! ```
+//! The recorded sessions can be served back by
+//! `crate::test_util::transport::replay::ReplayTransport`.
+
+use crate::prelude::*;
+
+use crate::ensogl::time_from_start;
+use crate::transport::Transport;
+use crate::transport::TransportEvent;
+
+use failure::Error;
+use futures::channel::mpsc::unbounded;
+use futures::channel::mpsc::UnboundedSender;
+use futures::StreamExt;
+use serde::Deserialize;
+use serde::Serialize;
+use std::fs::File;
+use std::future::Future;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::path::Path;
+
+
+
+// =============
+// === Frame ===
+// =============
+
+/// The direction in which a frame was transferred.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Direction {
+    /// Sent by the owner of the transport to the peer.
+    Sent,
+    /// Received from the peer.
+    Received,
+}
+
+/// The contents of a frame.
+#[allow(missing_docs)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "camelCase")]
+pub enum Payload {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+impl Payload {
+    /// Get the payload of the event carrying a message.
+    pub fn from_event(event: &TransportEvent) -> Option<Self> {
+        match event {
+            TransportEvent::TextMessage(text) => Some(Payload::Text(text.clone())),
+            TransportEvent::BinaryMessage(data) => Some(Payload::Binary(data.clone())),
+            _ => None,
+        }
+    }
+
+    /// Convert the payload to the event of receiving it from the peer.
+    pub fn into_event(self) -> TransportEvent {
+        match self {
+            Payload::Text(text) => TransportEvent::TextMessage(text),
+            Payload::Binary(data) => TransportEvent::BinaryMessage(data),
+        }
+    }
+}
+
+/// A single recorded frame.
+#[allow(missing_docs)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Frame {
+    /// Milliseconds elapsed since the recording started.
+    pub time:      f64,
+    pub direction: Direction,
+    #[serde(flatten)]
+    pub payload:   Payload,
+}
+
+/// Read the frames of a recording. Empty lines are skipped.
+pub fn read_frames(reader: impl BufRead) -> FallibleResult<Vec<Frame>> {
+    let mut frames = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            frames.push(serde_json::from_str(&line)?);
+        }
+    }
+    Ok(frames)
+}
+
+/// Read the frames of a recording from the file.
+pub fn read_frames_from_file(path: impl AsRef<Path>) -> FallibleResult<Vec<Frame>> {
+    read_frames(BufReader::new(File::open(path)?))
+}
+
+
+
+// =============
+// === Model ===
+// =============
+
+struct Model {
+    /// The output of the recording.
+    sink:            Box<dyn Write>,
+    /// The time at which the recording started, as given by `time_from_start`.
+    start:           f64,
+    /// Passes the events to the owner of the transport.
+    outgoing_events: Option<UnboundedSender<TransportEvent>>,
+}
+
+impl Model {
+    fn record(&mut self, direction: Direction, payload: Payload) {
+        let time = time_from_start() - self.start;
+        let frame = Frame { time, direction, payload };
+        let written = serde_json::to_string(&frame).map_err(Error::from).and_then(|mut line| {
+            line.push('\n');
+            Ok(self.sink.write_all(line.as_bytes())?)
+        });
+        if let Err(error) = written {
+            warn!("Failed to record a {direction:?} frame: {error}");
+        }
+    }
+}
+
+impl Debug for Model {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Model").field("start", &self.start).finish()
+    }
+}
+
+
+
+// ==========================
+// === RecordingTransport ===
+// ==========================
+
+/// A `Transport` decorator writing every sent and received text or binary frame to the given sink,
+/// in the format described in the module docs. Failing to write a frame is logged and does not
+/// affect the communication.
+///
+/// The received frames are recorded by the `runner` `Future`, which should be passed to the
+/// executor.
+#[derive(Debug)]
+pub struct RecordingTransport<T> {
+    transport: T,
+    model:     Rc<RefCell<Model>>,
+}
+
+impl<T: Transport> RecordingTransport<T> {
+    /// Record the frames transferred by `transport` to `sink`.
+    pub fn new(transport: T, sink: impl Write + 'static) -> Self {
+        let sink = Box::new(sink);
+        let start = time_from_start();
+        let model = Model { sink, start, outgoing_events: None };
+        Self { transport, model: Rc::new(RefCell::new(model)) }
+    }
+
+    /// Record the frames transferred by `transport` to a newly created file.
+    pub fn new_to_file(transport: T, path: impl AsRef<Path>) -> FallibleResult<Self> {
+        Ok(Self::new(transport, File::create(path)?))
+    }
+
+    /// Returns a `Future` that records the events received by the wrapped transport and passes
+    /// them further. Subsequent call will invalidate a previous one.
+    ///
+    /// The `Future` holds a weak handle to the recorder and finishes when the recording
+    /// transport or the wrapped transport's event transmitter is dropped.
+    pub fn runner(&mut self) -> impl Future<Output = ()> {
+        let (transmitter, mut receiver) = unbounded();
+        self.transport.set_event_transmitter(transmitter);
+        let weak = Rc::downgrade(&self.model);
+        async move {
+            while let Some(event) = receiver.next().await {
+                let model = match weak.upgrade() {
+                    Some(model) => model,
+                    None => break,
+                };
+                let mut model = model.borrow_mut();
+                if let Some(payload) = Payload::from_event(&event) {
+                    model.record(Direction::Received, payload);
+                }
+                if let Some(transmitter) = model.outgoing_events.as_ref() {
+                    channel::emit(transmitter, event);
+                }
+            }
+        }
+    }
+}
+
+impl<T: Transport> Transport for RecordingTransport<T> {
+    fn send_text(&mut self, message: &str) -> Result<(), Error> {
+        self.transport.send_text(message)?;
+        self.model.borrow_mut().record(Direction::Sent, Payload::Text(message.into()));
+        Ok(())
+    }
+
+    fn send_binary(&mut self, message: &[u8]) -> Result<(), Error> {
+        self.transport.send_binary(message)?;
+        self.model.borrow_mut().record(Direction::Sent, Payload::Binary(message.into()));
+        Ok(())
+    }
+
+    fn set_event_transmitter(&mut self, transmitter: UnboundedSender<TransportEvent>) {
+        self.model.borrow_mut().outgoing_events = Some(transmitter);
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util::transport::mock::MockTransport;
+
+    use futures::executor::LocalPool;
+    use futures::task::LocalSpawnExt;
+
+    /// A sink that can be inspected while being written to.
+    #[derive(Clone, Debug, Default)]
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn recording_frames() {
+        let buffer = SharedBuffer::default();
+        let mut mock = MockTransport::new();
+        let mut transport = RecordingTransport::new(mock.clone_ref(), buffer.clone());
+        let mut events = transport.establish_event_stream();
+        let mut pool = LocalPool::new();
+        pool.spawner().spawn_local(transport.runner()).unwrap();
+
+        transport.send_text("request").unwrap();
+        mock.mock_peer_binary_message(&[1, 2, 3]);
+        mock.mock_connection_closed();
+        let event = pool.run_until(events.next());
+        assert!(matches!(event, Some(TransportEvent::BinaryMessage(data)) if data == [1, 2, 3]));
+        let event = pool.run_until(events.next());
+        assert!(matches!(event, Some(TransportEvent::Closed)));
+        assert!(transport.send_binary(&[4]).is_err());
+
+        let frames = read_frames(buffer.0.borrow().as_slice()).unwrap();
+        let frames = frames.into_iter().map(|frame| (frame.direction, frame.payload)).collect_vec();
+        let expected = vec![
+            (Direction::Sent, Payload::Text("request".into())),
+            (Direction::Received, Payload::Binary(vec![1, 2, 3])),
+        ];
+        assert_eq!(frames, expected);
+    }
+
+    #[test]
+    fn frame_format() {
+        let line = r#"{"time":1.5,"direction":"received","type":"text","data":"{}"}"#;
+        let frame: Frame = serde_json::from_str(line).unwrap();
+        let payload = Payload::Text("{}".into());
+        assert_eq!(frame, Frame { time: 1.5, direction: Direction::Received, payload });
+        assert_eq!(serde_json::to_string(&frame).unwrap(), line);
+    }
+}
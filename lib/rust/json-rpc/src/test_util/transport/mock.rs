@@ -138,6 +138,27 @@ impl MockTransport {
         })
     }
 
+    /// Mocks event generated by a reconnecting transport when the connection is lost and is
+    /// being re-established.
+    pub fn mock_connection_interrupted(&mut self) {
+        self.with_mut_data(|data| {
+            if let Some(ref mut transmitter) = data.event_transmitter {
+                data.is_closed = true;
+                channel::emit(transmitter, TransportEvent::Reconnecting);
+            }
+        })
+    }
+
+    /// Mocks event generated by a reconnecting transport when the connection is re-established.
+    pub fn mock_connection_restored(&mut self) {
+        self.with_mut_data(|data| {
+            if let Some(ref mut transmitter) = data.event_transmitter {
+                data.is_closed = false;
+                channel::emit(transmitter, TransportEvent::Reconnected);
+            }
+        })
+    }
+
     /// Takes the text message sent by the client and returns its contents.
     ///
     /// If the client has not sent any text messages, panics.
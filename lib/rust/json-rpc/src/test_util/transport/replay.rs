@@ -0,0 +1,366 @@
+//! Module provides a `ReplayTransport` that serves back a session recorded by
+//! `crate::recording::RecordingTransport`.
+//!
+//! It is meant to be used in tests.
+
+use crate::prelude::*;
+
+use crate::recording::read_frames;
+use crate::recording::read_frames_from_file;
+use crate::recording::Direction;
+use crate::recording::Frame;
+use crate::recording::Payload;
+use crate::transport::Transport;
+use crate::transport::TransportEvent;
+
+use failure::Error;
+use futures::channel::mpsc::UnboundedSender;
+use serde_json::Value;
+use std::path::Path;
+
+
+
+// ==============
+// === Errors ===
+// ==============
+
+/// Error returned when the sent message does not match any of the recorded ones.
+#[derive(Clone, Debug, Fail)]
+#[fail(display = "The sent message does not match any recorded one: {:?}.", _0)]
+pub struct UnexpectedMessage(pub Payload);
+
+
+
+// ===============
+// === Helpers ===
+// ===============
+
+/// The part of a JSON-RPC message compared when matching the sent messages with the recorded ones.
+/// The ids of the requests are skipped, as they are specific to the session. So are the parameters
+/// listed in `ignored`.
+fn matching_key(message: &Value, ignored: &[IgnoredParam]) -> Value {
+    match message {
+        Value::Array(messages) =>
+            Value::Array(messages.iter().map(|message| matching_key(message, ignored)).collect()),
+        Value::Object(fields) if fields.contains_key("method") => {
+            let mut fields = fields.clone();
+            fields.remove("id");
+            let method = fields.get("method").and_then(Value::as_str).map(ToOwned::to_owned);
+            if let Some(params) = fields.get_mut("params") {
+                let ignored = ignored.iter().filter(|param| Some(&param.method) == method.as_ref());
+                for param in ignored {
+                    if let Some(value) = params.pointer_mut(&param.pointer) {
+                        *value = Value::Null;
+                    }
+                }
+            }
+            Value::Object(fields)
+        }
+        other => other.clone(),
+    }
+}
+
+/// The ids of the requests in the JSON-RPC message (or batch), in order.
+fn request_ids(message: &Value) -> Vec<&Value> {
+    match message {
+        Value::Array(messages) => messages.iter().flat_map(request_ids).collect(),
+        Value::Object(fields) if fields.contains_key("method") =>
+            fields.get("id").into_iter().collect(),
+        _ => default(),
+    }
+}
+
+/// Replace the ids of the responses in the JSON-RPC message (or batch) using the `ids` map, keyed
+/// by the serialized recorded ids. Returns `true` if any id has been replaced.
+fn rewrite_response_ids(message: &mut Value, ids: &HashMap<String, Value>) -> bool {
+    match message {
+        Value::Array(messages) => messages
+            .iter_mut()
+            .fold(false, |rewritten, message| rewrite_response_ids(message, ids) || rewritten),
+        Value::Object(fields) if !fields.contains_key("method") => {
+            let id = fields.get_mut("id");
+            let live_id = id.as_ref().and_then(|id| ids.get(&id.to_string()));
+            match (id, live_id) {
+                (Some(id), Some(live_id)) => {
+                    *id = live_id.clone();
+                    true
+                }
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+
+
+// ====================
+// === IgnoredParam ===
+// ====================
+
+/// A parameter of the requests which is skipped when matching the sent messages with the recorded
+/// ones.
+#[derive(Clone, Debug)]
+pub struct IgnoredParam {
+    /// The name of the method.
+    pub method:  String,
+    /// The JSON pointer to the parameter, relative to the `params` object.
+    pub pointer: String,
+}
+
+
+
+// ==================
+// === ReplayData ===
+// ==================
+
+/// The state of the replayed session.
+#[derive(Debug, Default)]
+pub struct ReplayData {
+    /// The recorded frames.
+    pub frames:            Vec<Frame>,
+    /// For each frame, whether it has already been matched (if sent) or emitted (if received).
+    pub done:              Vec<bool>,
+    /// Maps the request ids used in the recording to the ones used in the replayed session. The
+    /// keys are the serialized recorded ids.
+    pub ids:               HashMap<String, Value>,
+    /// The parameters skipped when matching the sent messages.
+    pub ignored_params:    Vec<IgnoredParam>,
+    /// Events sink.
+    pub event_transmitter: Option<UnboundedSender<TransportEvent>>,
+}
+
+impl ReplayData {
+    /// Find the first recorded sent frame matching the `payload` and mark it as done.
+    ///
+    /// The text frames are compared as JSON-RPC messages, skipping the request ids, or as plain
+    /// text if they are not valid JSON. The binary frames are matched in the recorded order, as
+    /// their contents usually contain the session-specific message ids.
+    fn match_sent(&mut self, payload: &Payload) -> Option<usize> {
+        let sent_json = match payload {
+            Payload::Text(text) => serde_json::from_str::<Value>(text).ok(),
+            Payload::Binary(_) => None,
+        };
+        let ignored = &self.ignored_params;
+        let key = sent_json.as_ref().map(|sent| matching_key(sent, ignored));
+        let index = self.frames.iter().zip(&self.done).position(|(frame, done)| {
+            let candidate = !done && frame.direction == Direction::Sent;
+            candidate
+                && match (&frame.payload, payload) {
+                    (Payload::Text(recorded), Payload::Text(sent)) => match &key {
+                        Some(key) => serde_json::from_str::<Value>(recorded)
+                            .map_or(false, |recorded| &matching_key(&recorded, ignored) == key),
+                        None => recorded == sent,
+                    },
+                    (Payload::Binary(_), Payload::Binary(_)) => true,
+                    _ => false,
+                }
+        })?;
+        self.done[index] = true;
+        if let (Payload::Text(recorded), Some(sent)) = (&self.frames[index].payload, &sent_json) {
+            if let Ok(recorded) = serde_json::from_str::<Value>(recorded) {
+                for (recorded_id, live_id) in
+                    request_ids(&recorded).into_iter().zip(request_ids(sent))
+                {
+                    self.ids.insert(recorded_id.to_string(), live_id.clone());
+                }
+            }
+        }
+        Some(index)
+    }
+
+    /// Emit all the received frames which are not preceded by a not yet matched sent frame.
+    fn emit_ready_frames(&mut self) {
+        let transmitter = match self.event_transmitter.as_ref() {
+            Some(transmitter) => transmitter,
+            None => return,
+        };
+        for (frame, done) in self.frames.iter().zip(self.done.iter_mut()) {
+            if *done {
+                continue;
+            }
+            if frame.direction == Direction::Sent {
+                break;
+            }
+            *done = true;
+            let mut payload = frame.payload.clone();
+            if let Payload::Text(text) = &mut payload {
+                if let Ok(mut message) = serde_json::from_str::<Value>(text) {
+                    if rewrite_response_ids(&mut message, &self.ids) {
+                        *text = message.to_string();
+                    }
+                }
+            }
+            channel::emit(transmitter, payload.into_event());
+        }
+    }
+}
+
+
+
+// =======================
+// === ReplayTransport ===
+// =======================
+
+/// A `Transport` serving back a recorded session.
+///
+/// The recorded received frames are emitted in the recorded order, each as soon as all the sent
+/// frames preceding it in the recording have been sent. The timing of the recording is ignored,
+/// so the replay is deterministic. The ids of the requests are rewritten, so the responses match
+/// the requests of the replayed session. Sending a message not matching any recorded one fails
+/// with `UnexpectedMessage`.
+#[derive(Clone, CloneRef, Debug, Default)]
+pub struct ReplayTransport(Rc<RefCell<ReplayData>>);
+
+impl ReplayTransport {
+    /// Create a transport replaying the given frames.
+    pub fn new(frames: Vec<Frame>) -> Self {
+        let done = vec![false; frames.len()];
+        let data = ReplayData { frames, done, ..default() };
+        Self(Rc::new(RefCell::new(data)))
+    }
+
+    /// Create a transport replaying the recording in the JSON-lines format.
+    pub fn from_recording(recording: &str) -> FallibleResult<Self> {
+        Ok(Self::new(read_frames(recording.as_bytes())?))
+    }
+
+    /// Create a transport replaying the recording stored in the file.
+    pub fn from_file(path: impl AsRef<Path>) -> FallibleResult<Self> {
+        Ok(Self::new(read_frames_from_file(path)?))
+    }
+
+    /// Skip the parameter of the `method` requests when matching the sent messages with the
+    /// recorded ones. The `pointer` is a JSON pointer relative to the `params` object, e.g.
+    /// `/edit/newVersion`.
+    ///
+    /// This is meant for the parameters which are not deterministic, like randomly generated ids.
+    pub fn ignore_param(&self, method: impl Into<String>, pointer: impl Into<String>) {
+        let param = IgnoredParam { method: method.into(), pointer: pointer.into() };
+        self.0.borrow_mut().ignored_params.push(param);
+    }
+
+    /// The recorded frames which have not been matched or emitted yet.
+    pub fn pending_frames(&self) -> Vec<Frame> {
+        let data = self.0.borrow();
+        let frames = data.frames.iter().zip(&data.done);
+        frames.filter(|(_, done)| !**done).map(|(frame, _)| frame.clone()).collect()
+    }
+
+    /// Check if the whole recorded session has been replayed.
+    pub fn is_finished(&self) -> bool {
+        self.0.borrow().done.iter().all(|done| *done)
+    }
+
+    fn send(&mut self, payload: Payload) -> Result<(), Error> {
+        let mut data = self.0.borrow_mut();
+        match data.match_sent(&payload) {
+            Some(_) => {
+                data.emit_ready_frames();
+                Ok(())
+            }
+            None => Err(UnexpectedMessage(payload).into()),
+        }
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn send_text(&mut self, message: &str) -> Result<(), Error> {
+        self.send(Payload::Text(message.into()))
+    }
+
+    fn send_binary(&mut self, message: &[u8]) -> Result<(), Error> {
+        self.send(Payload::Binary(message.into()))
+    }
+
+    fn set_event_transmitter(&mut self, transmitter: UnboundedSender<TransportEvent>) {
+        let mut data = self.0.borrow_mut();
+        data.event_transmitter = Some(transmitter);
+        data.emit_ready_frames();
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::channel::mpsc::UnboundedReceiver;
+
+    const RECORDING: &str = r#"
+{"time":0.0,"direction":"received","type":"text","data":"{\"jsonrpc\":\"2.0\",\"method\":\"hello\",\"params\":{}}"}
+{"time":1.0,"direction":"sent","type":"text","data":"{\"jsonrpc\":\"2.0\",\"id\":0,\"method\":\"pow\",\"params\":{\"i\":2}}"}
+{"time":2.0,"direction":"sent","type":"text","data":"{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"pow\",\"params\":{\"i\":3}}"}
+{"time":3.0,"direction":"received","type":"text","data":"{\"jsonrpc\":\"2.0\",\"id\":1,\"result\":{\"result\":9}}"}
+{"time":4.0,"direction":"received","type":"text","data":"{\"jsonrpc\":\"2.0\",\"id\":0,\"result\":{\"result\":4}}"}
+{"time":5.0,"direction":"sent","type":"binary","data":[1,2]}
+{"time":6.0,"direction":"received","type":"binary","data":[3]}
+"#;
+
+    fn next_text(events: &mut UnboundedReceiver<TransportEvent>) -> Value {
+        match events.try_next() {
+            Ok(Some(TransportEvent::TextMessage(text))) => serde_json::from_str(&text).unwrap(),
+            other => panic!("Expected a text message, got {other:?}"),
+        }
+    }
+
+    fn request(id: i64, i: i64) -> String {
+        let message = serde_json::json!({"jsonrpc":"2.0","id":id,"method":"pow","params":{"i":i}});
+        message.to_string()
+    }
+
+    #[test]
+    fn replaying_session() {
+        let mut transport = ReplayTransport::from_recording(RECORDING).unwrap();
+        let mut events = transport.establish_event_stream();
+        assert_eq!(next_text(&mut events)["method"], "hello");
+        assert!(events.try_next().is_err());
+
+        transport.send_text(&request(10, 3)).unwrap();
+        assert!(events.try_next().is_err());
+        transport.send_text(&request(11, 2)).unwrap();
+        let first_reply = next_text(&mut events);
+        assert_eq!(first_reply["id"], 10);
+        assert_eq!(first_reply["result"]["result"], 9);
+        let second_reply = next_text(&mut events);
+        assert_eq!(second_reply["id"], 11);
+        assert_eq!(second_reply["result"]["result"], 4);
+        assert!(!transport.is_finished());
+
+        transport.send_binary(&[7, 7]).unwrap();
+        let binary = events.try_next();
+        assert!(matches!(binary, Ok(Some(TransportEvent::BinaryMessage(data))) if data == [3]));
+        assert!(transport.is_finished());
+        assert!(transport.pending_frames().is_empty());
+    }
+
+    #[test]
+    fn unexpected_message() {
+        let mut transport = ReplayTransport::from_recording(RECORDING).unwrap();
+        let _events = transport.establish_event_stream();
+        let error = transport.send_text(&request(0, 5)).unwrap_err();
+        assert!(error.downcast_ref::<UnexpectedMessage>().is_some());
+        assert!(transport.send_text("garbage").is_err());
+        assert_eq!(transport.pending_frames().len(), 6);
+    }
+
+    #[test]
+    fn ignoring_params() {
+        let mut transport = ReplayTransport::from_recording(RECORDING).unwrap();
+        let mut events = transport.establish_event_stream();
+        assert_eq!(next_text(&mut events)["method"], "hello");
+        assert!(transport.send_text(&request(0, 5)).is_err());
+
+        transport.ignore_param("pow", "/i");
+        transport.send_text(&request(0, 5)).unwrap();
+        transport.send_text(&request(1, 6)).unwrap();
+        assert_eq!(next_text(&mut events)["id"], 1);
+        assert_eq!(next_text(&mut events)["id"], 0);
+        assert!(transport.send_text(&request(2, 5)).is_err());
+    }
+}
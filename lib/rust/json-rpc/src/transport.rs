@@ -46,4 +46,10 @@ pub enum TransportEvent {
     /// A socket has been closed by the peer.
     /// This event may be also emitted when reconnecting has failed.
     Closed,
+    /// The connection has been lost and the transport is trying to re-establish it. The requests
+    /// awaiting replies will not receive them.
+    Reconnecting,
+    /// The connection has been re-established. The peer does not remember any state of the
+    /// previous connection, so the session should be restored.
+    Reconnected,
 }
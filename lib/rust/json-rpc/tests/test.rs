@@ -11,12 +11,16 @@ use futures::FutureExt;
 use futures::Stream;
 use json_rpc::api::RemoteMethodCall;
 use json_rpc::api::Result;
+use json_rpc::call_options::CallOptions;
+use json_rpc::call_options::RetryPolicy;
+use json_rpc::dispatcher::MethodResult;
 use json_rpc::error::HandlingError;
 use json_rpc::error::RpcError;
 use json_rpc::messages::Id;
 use json_rpc::messages::Message;
 use json_rpc::messages::Version;
 use json_rpc::test_util::transport::mock::MockTransport;
+use json_rpc::test_util::transport::replay::ReplayTransport;
 use serde::Deserialize;
 use serde::Serialize;
 use std::future::Future;
@@ -96,6 +100,25 @@ impl Client {
         self.handler.open_request(input).map(|result| result.map(|r| r.result))
     }
 
+    pub fn pow_with_options(
+        &mut self,
+        i: i64,
+        options: CallOptions,
+    ) -> impl Future<Output = Result<i64>> {
+        let input = serde_json::to_value(MockRequest { i }).unwrap();
+        let result = self.handler.open_request_with_options(MockRequest::NAME, &input, options);
+        result.map(|result| result.map(|r: MockResponse| r.result))
+    }
+
+    pub fn pow_in_batch(
+        &mut self,
+        batch: &mut json_rpc::handler::Batch<MockNotification>,
+        i: i64,
+    ) -> impl Future<Output = Result<i64>> {
+        let input = MockRequest { i };
+        batch.open_request(input).map(|result| result.map(|r| r.result))
+    }
+
     pub fn events_processor(&mut self) -> impl Future<Output = ()> {
         self.handler.runner()
     }
@@ -254,6 +277,26 @@ fn test_disconnect_error() {
     }
 }
 
+#[test]
+fn test_interrupted_call() {
+    let mut fixture = Fixture::new();
+    let mut fut = Box::pin(fixture.client.pow(8));
+    fixture.transport.expect_json_message::<MockRequestMessage>();
+    fixture.transport.mock_connection_interrupted();
+    fixture.pool.run_until_stalled();
+    assert!(matches!(fut.expect_err(), RpcError::ConnectionInterrupted));
+    assert!(matches!(fixture.client.events_stream.expect_next(), MockEvent::Reconnecting));
+
+    fixture.transport.mock_connection_restored();
+    fixture.pool.run_until_stalled();
+    assert!(matches!(fixture.client.events_stream.expect_next(), MockEvent::Reconnected));
+    let mut fut = Box::pin(fixture.client.pow(8));
+    let request = fixture.transport.expect_json_message::<MockRequestMessage>();
+    fixture.transport.mock_peer_json_message(pow_impl(request));
+    fixture.pool.run_until_stalled();
+    assert_eq!(fut.expect_ok(), 64);
+}
+
 #[test]
 fn test_sending_while_disconnected() {
     let mut fixture = Fixture::new();
@@ -304,3 +347,309 @@ fn test_handling_invalid_notification() {
         panic!("expected InvalidNotification error");
     }
 }
+
+#[test]
+fn test_batch_call() {
+    let mut fixture = Fixture::new();
+    let mut batch = fixture.client.handler.batch();
+    let mut fut1 = Box::pin(fixture.client.pow_in_batch(&mut batch, 2));
+    let mut fut2 = Box::pin(fixture.client.pow_in_batch(&mut batch, 3));
+    batch.add_notification(MockNotification::Meow { text: "meow!".into() }).unwrap();
+    assert_eq!(batch.len(), 3);
+    batch.send().unwrap();
+
+    let messages = fixture.transport.expect_json_message::<Vec<serde_json::Value>>();
+    assert_eq!(messages.len(), 3);
+    let req1 = serde_json::from_value::<MockRequestMessage>(messages[0].clone()).unwrap();
+    let req2 = serde_json::from_value::<MockRequestMessage>(messages[1].clone()).unwrap();
+    let notification = serde_json::from_value::<Message<MockNotification>>(messages[2].clone());
+    assert_eq!(notification.unwrap().payload, MockNotification::Meow { text: "meow!".into() });
+    fut1.expect_pending();
+    fut2.expect_pending();
+
+    // The peer may reply to a batch in any order.
+    fixture.transport.mock_peer_json_message(vec![pow_impl(req2), pow_impl(req1)]);
+    fixture.pool.run_until_stalled();
+    assert_eq!(fut1.expect_ok(), 4);
+    assert_eq!(fut2.expect_ok(), 9);
+}
+
+#[test]
+fn test_dropped_batch() {
+    let mut fixture = Fixture::new();
+    let mut batch = fixture.client.handler.batch();
+    let mut fut = Box::pin(fixture.client.pow_in_batch(&mut batch, 2));
+    drop(batch);
+    if let RpcError::LostConnection = fut.expect_err() {
+    } else {
+        panic!("Expected an error to be LostConnection");
+    }
+    fixture.client.handler.batch().send().unwrap();
+    assert!(fixture.transport.with_mut_data(|data| data.sent_text_msgs.is_empty()));
+}
+
+#[test]
+fn test_incoming_request() {
+    let mut fixture = Fixture::new();
+    fixture.client.handler.register_method(|input: MockRequest| -> MethodResult<MockResponse> {
+        Ok(MockResponse { result: input.i + 1 })
+    });
+    let request = Message::new_request(Id(7), MockRequest::NAME, MockRequest { i: 1 });
+    fixture.transport.mock_peer_json_message(request);
+    fixture.pool.run_until_stalled();
+    let reply = fixture.transport.expect_json_message::<MockResponseMessage>();
+    assert_eq!(reply, Message::new_success(Id(7), MockResponse { result: 2 }));
+
+    let request = Message::new_request(Id(8), "unknown", MockRequest { i: 1 });
+    fixture.transport.mock_peer_json_message(request);
+    fixture.pool.run_until_stalled();
+    let reply = fixture.transport.expect_json_message::<messages::ResponseMessage<()>>();
+    let code = json_rpc::messages::error_code::METHOD_NOT_FOUND;
+    assert!(matches!(&reply.result, messages::Result::Error { error } if error.code == code));
+}
+
+#[test]
+fn test_incoming_batch() {
+    let mut fixture = Fixture::new();
+    fixture.client.handler.register_method(|input: MockRequest| -> MethodResult<MockResponse> {
+        Ok(MockResponse { result: input.i + 1 })
+    });
+    let mut fut = Box::pin(fixture.client.pow(8));
+    let req_msg = fixture.transport.expect_json_message::<MockRequestMessage>();
+    let batch = serde_json::json!([
+        pow_impl(req_msg),
+        Message::new(MockNotification::Bark { text: "woof!".into() }),
+        Message::new_request(Id(1), MockRequest::NAME, MockRequest { i: 1 }),
+        Message::new_request(Id(2), MockRequest::NAME, MockRequest { i: 2 }),
+    ]);
+    fixture.transport.mock_peer_json_message(batch);
+    fixture.pool.run_until_stalled();
+
+    assert_eq!(fut.expect_ok(), 64);
+    let notification = fixture.client.expect_notification();
+    assert_eq!(notification, MockNotification::Bark { text: "woof!".into() });
+    let replies = fixture.transport.expect_json_message::<Vec<MockResponseMessage>>();
+    assert_eq!(replies, vec![
+        Message::new_success(Id(1), MockResponse { result: 2 }),
+        Message::new_success(Id(2), MockResponse { result: 3 }),
+    ]);
+}
+
+#[test]
+fn test_deadline() {
+    let mut fixture = Fixture::new();
+    let deadline = Duration::from_millis(10);
+    let mut fut =
+        Box::pin(fixture.client.pow_with_options(8, CallOptions::new().deadline(deadline)));
+    fixture.transport.expect_json_message::<MockRequestMessage>();
+    fut.expect_pending();
+    sleep(Duration::from_millis(100));
+
+    if let RpcError::TimeoutError { millis: 10 } = fut.expect_err() {
+    } else {
+        panic!("Expected an error to be TimeoutError");
+    }
+}
+
+#[test]
+fn test_cancel_on_drop() {
+    let mut fixture = Fixture::new();
+    let options = CallOptions::new().cancel_notification("$/cancelRequest");
+    let fut = fixture.client.pow_with_options(8, options);
+    let req_msg = fixture.transport.expect_json_message::<MockRequestMessage>();
+    drop(fut);
+
+    let cancel = fixture.transport.expect_json_message::<serde_json::Value>();
+    assert_eq!(cancel["method"], "$/cancelRequest");
+    assert_eq!(cancel["params"]["id"], req_msg.id.0);
+    fixture.transport.mock_peer_json_message(pow_impl(req_msg));
+    fixture.pool.run_until_stalled();
+    if let HandlingError::UnexpectedResponse(_) = fixture.client.expect_handling_error() {
+    } else {
+        panic!("Expected an error to be UnexpectedResponse");
+    }
+
+    // Cancelling after receiving the reply has no effect.
+    let mut fut = Box::pin(fixture.client.pow_with_options(2, CallOptions::new().cancel_on_drop()));
+    let req_msg = fixture.transport.expect_json_message::<MockRequestMessage>();
+    fixture.transport.mock_peer_json_message(pow_impl(req_msg));
+    fixture.pool.run_until_stalled();
+    assert_eq!(fut.expect_ok(), 4);
+    drop(fut);
+    assert!(fixture.transport.with_mut_data(|data| data.sent_text_msgs.is_empty()));
+}
+
+#[test]
+fn test_retry() {
+    let mut fixture = Fixture::new();
+    fixture.client.handler.set_timeout(Duration::from_millis(20));
+    let retry = RetryPolicy::new(2).initial_backoff(Duration::from_millis(1));
+    let mut fut = Box::pin(fixture.client.pow_with_options(8, CallOptions::new().retry(retry)));
+    let first_req = fixture.transport.expect_json_message::<MockRequestMessage>();
+    fut.expect_pending();
+    sleep(Duration::from_millis(100));
+    fut.expect_pending(); // the first attempt timed out, waiting for backoff
+    sleep(Duration::from_millis(50));
+    fut.expect_pending(); // the second attempt sent
+
+    let second_req = fixture.transport.expect_json_message::<MockRequestMessage>();
+    assert_ne!(first_req.id, second_req.id);
+    assert_eq!(second_req.i, first_req.i);
+    fixture.transport.mock_peer_json_message(pow_impl(second_req));
+    fixture.pool.run_until_stalled();
+    assert_eq!(fut.expect_ok(), 64);
+}
+
+#[test]
+fn test_retry_gives_up() {
+    let mut fixture = Fixture::new();
+    fixture.transport.mock_connection_closed();
+    let retry = RetryPolicy::new(2).initial_backoff(Duration::from_millis(1));
+    let mut fut = Box::pin(fixture.client.pow_with_options(8, CallOptions::new().retry(retry)));
+    fut.expect_pending(); // the first attempt failed, waiting for backoff
+    sleep(Duration::from_millis(50));
+    if let RpcError::LostConnection = fut.expect_err() {
+    } else {
+        panic!("Expected an error to be LostConnection");
+    }
+}
+
+
+// === Record and Replay ===
+
+/// A recording sink that can be read while being written to.
+#[derive(Clone, Debug, Default)]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl std::io::Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_record_and_replay() {
+    let buffer = SharedBuffer::default();
+    let mut transport = MockTransport::new();
+    let mut recording = RecordingTransport::new(transport.clone_ref(), buffer.clone());
+    let mut pool = futures::executor::LocalPool::new();
+    pool.spawner().spawn_local(recording.runner()).unwrap();
+    let mut client = Client::new(recording);
+    pool.spawner().spawn_local(client.events_processor()).unwrap();
+    let mut fut2 = Box::pin(client.pow(2));
+    let mut fut3 = Box::pin(client.pow(3));
+    let req2 = transport.expect_json_message::<MockRequestMessage>();
+    let req3 = transport.expect_json_message::<MockRequestMessage>();
+    transport.mock_peer_json_message(pow_impl(req3));
+    transport.mock_peer_json_message(pow_impl(req2));
+    pool.run_until_stalled();
+    assert_eq!(fut2.expect_ok(), 4);
+    assert_eq!(fut3.expect_ok(), 9);
+
+    // The requests are sent in a different order, so their ids differ from the recorded ones.
+    let recorded = String::from_utf8(buffer.0.borrow().clone()).unwrap();
+    let replay = ReplayTransport::from_recording(&recorded).unwrap();
+    let mut client = Client::new(replay.clone_ref());
+    let mut pool = futures::executor::LocalPool::new();
+    pool.spawner().spawn_local(client.events_processor()).unwrap();
+    let mut fut3 = Box::pin(client.pow(3));
+    let mut fut2 = Box::pin(client.pow(2));
+    pool.run_until_stalled();
+    assert_eq!(fut3.expect_ok(), 9);
+    assert_eq!(fut2.expect_ok(), 4);
+    assert!(replay.is_finished());
+
+    // The session has not recorded such request.
+    let mut fut4 = Box::pin(client.pow(4));
+    fut4.expect_err();
+}
+
+
+// === Generated Client ===
+
+mod generated {
+    use super::*;
+
+    pub type Notification = MockNotification;
+    pub type Event = MockEvent;
+
+    #[rpc_api(title = "Mock API")]
+    /// Mock API.
+    pub trait API {
+        /// Raise the number to the given power.
+        ///
+        /// The default exponent is 2.
+        #[rpc(name = "pow")]
+        fn pow(&self, base: &i64, exponent: Option<i64>) -> i64;
+
+        /// Greet the peer.
+        #[rpc(name = "greet", notification)]
+        fn greet(&self, name: String, title: &Option<String>);
+    }
+}
+
+#[test]
+fn test_generated_client() {
+    use generated::API;
+    let mut transport = MockTransport::new();
+    let client = generated::Client::new(transport.clone_ref());
+    let pool = futures::executor::LocalPool::new();
+    pool.spawner().spawn_local(client.runner()).unwrap();
+
+    let _fut = client.pow(&3, None);
+    let request = transport.expect_json_message::<serde_json::Value>();
+    assert_eq!(request["method"], "pow");
+    assert_eq!(request["params"], serde_json::json!({ "base": 3 }));
+    let _fut = client.pow(&3, Some(3));
+    let request = transport.expect_json_message::<serde_json::Value>();
+    assert_eq!(request["params"], serde_json::json!({ "base": 3, "exponent": 3 }));
+
+    client.greet("Bob".into(), &None).expect_ok();
+    let notification = transport.expect_json_message::<serde_json::Value>();
+    assert_eq!(notification["method"], "greet");
+    assert_eq!(notification["params"], serde_json::json!({ "name": "Bob" }));
+    assert!(notification.get("id").is_none());
+}
+
+#[test]
+fn test_generated_mock_client() {
+    use generated::API;
+    let mock = generated::MockClient::default();
+    expect_call!(mock.pow(base = 2, exponent = Some(3)) => Ok(8));
+    expect_call!(mock.greet(name = "Bob".to_owned(), title = None) => Ok(()));
+    mock.require_all_calls();
+    assert_eq!(mock.pow(&2, Some(3)).expect_ok(), 8);
+    mock.greet("Bob".into(), &None).expect_ok();
+}
+
+#[test]
+fn test_open_rpc_document() {
+    let document = generated::open_rpc_document();
+    assert_eq!(document["info"]["title"], "Mock API");
+    let pow = &document["methods"][0];
+    assert_eq!(pow["name"], "pow");
+    assert_eq!(pow["summary"], "Raise the number to the given power.");
+    assert_eq!(
+        pow["description"],
+        "Raise the number to the given power.\n\nThe default exponent is 2."
+    );
+    assert_eq!(
+        pow["params"][0],
+        serde_json::json!({
+            "name": "base", "required": true, "schema": { "type": "integer", "format": "int64" }
+        })
+    );
+    assert_eq!(pow["params"][1]["required"], false);
+    assert_eq!(pow["params"][1]["schema"]["format"], "int64");
+    assert_eq!(
+        pow["result"]["schema"],
+        serde_json::json!({ "type": "integer", "format": "int64" })
+    );
+    let greet = &document["methods"][1];
+    assert_eq!(greet["x-notification"], true);
+    assert!(greet.get("result").is_none());
+}
@@ -16,11 +16,31 @@ use std::collections;
 pub struct Aggregator {
     stack: Vec<ImString>,
     root:  Frame,
+    runs:  usize,
 }
 
 impl Aggregator {
     /// Add data from a profile to the tree.
     pub fn add_profile<Metadata>(&mut self, profile: &crate::Profile<Metadata>) {
+        self.visit_profile(profile);
+        self.runs += 1;
+    }
+
+    /// Add data from the profiles recorded by different processes during one run of the
+    /// application to the tree.
+    pub fn add_multiprocess_profile<Metadata>(&mut self, profiles: &[crate::Profile<Metadata>]) {
+        for profile in profiles {
+            self.visit_profile(profile);
+        }
+        self.runs += 1;
+    }
+
+    /// Return the number of runs of the application whose profiles were added to the tree.
+    pub fn run_count(&self) -> usize {
+        self.runs
+    }
+
+    fn visit_profile<Metadata>(&mut self, profile: &crate::Profile<Metadata>) {
         for child in &profile.root_interval().children {
             self.visit_interval(profile, *child);
         }
@@ -0,0 +1,68 @@
+//! Tool that converts event logs between the JSON and binary formats.
+//!
+//! # Usage
+//!
+//! The tool reads an event log from stdin, and writes it to stdout in the other format: A
+//! [JSON-formatted event log](https://github.com/enso-org/design/blob/main/epics/profiling/implementation.md#file-format)
+//! is converted to the binary format (see [`enso_profiler::format::binary`]), and a log in the
+//! binary format is converted to JSON. The log may contain the profiles of multiple processes. The
+//! conversion is lossless.
+//!
+//! For example:
+//!
+//! ```console
+//! ~/git/enso/data $ cargo run --bin convert < profile.json > profile.bin
+//! ~/git/enso/data $ cargo run --bin convert < profile.bin > profile.json
+//! ```
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+// === Non-Standard Linter Configuration ===
+#![deny(unconditional_recursion)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+
+use enso_profiler::format;
+use enso_profiler_data as data;
+use std::io::Write;
+
+
+
+// ============
+// === main ===
+// ============
+
+fn main() {
+    use std::io::Read;
+
+    let mut log = vec![];
+    std::io::stdin().read_to_end(&mut log).unwrap();
+    let mut out = std::io::BufWriter::new(std::io::stdout().lock());
+    if log.starts_with(format::binary::MAGIC) {
+        let mut rest = &log[..];
+        while !rest.is_empty() {
+            let mut reader = data::parse::binary::Reader::new(rest).unwrap();
+            let events: Result<Vec<_>, _> = (&mut reader).collect();
+            serde_json::to_writer(&mut out, &events.unwrap()).unwrap();
+            writeln!(out).unwrap();
+            rest = reader.remainder().unwrap();
+        }
+    } else {
+        let log = std::str::from_utf8(&log).expect("Input is neither JSON nor the binary format.");
+        let raw_parse_error = "Cannot parse input as sequence of JSON values!";
+        let logs = serde_json::Deserializer::from_str(log).into_iter::<Vec<format::Event>>();
+        for events in logs {
+            let mut writer = format::binary::Writer::new(&mut out);
+            events.expect(raw_parse_error).iter().for_each(|event| writer.write_event(event));
+            writer.finish().unwrap();
+        }
+    }
+    out.flush().unwrap();
+}
@@ -0,0 +1,103 @@
+//! Tool that compares the aggregated interval data of two sets of profiles, e.g. recorded by two
+//! builds of the application.
+//!
+//! # Usage
+//!
+//! The tool reads two sets of
+//! [JSON-formatted event logs](https://github.com/enso-org/design/blob/main/epics/profiling/implementation.md#file-format),
+//! separated by `--`, and writes a report to stdout. The profilers are identified by their label
+//! paths, and their durations and interval counts are averaged per file. The report lists the
+//! changes of the total and self durations and of the interval counts, sorted by the absolute
+//! change of the self duration.
+//!
+//! For example:
+//!
+//! ```console
+//! ~/git/enso/data $ cargo run --bin profile-diff -- before1.json before2.json -- after.json | less
+//! ```
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+// === Non-Standard Linter Configuration ===
+#![deny(unconditional_recursion)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+
+use data::diff::Change;
+use data::OpaqueMetadata;
+use enso_profiler_data as data;
+
+
+
+// ============
+// === main ===
+// ============
+
+fn main() {
+    let mut args = std::env::args();
+    let argv0 = args.next().unwrap();
+    let usage = format!("Usage: {} before1.json [before2.json ...] -- after1.json ...", argv0);
+    let args: Vec<_> = args.collect();
+    let separator = args.iter().position(|arg| arg == "--").expect(&usage);
+    let (before, after) = (&args[..separator], &args[separator + 1..]);
+    if before.is_empty() || after.is_empty() {
+        panic!("{}", usage);
+    }
+    let diff = data::diff::Frame::new(aggregate(before), aggregate(after));
+    let columns = ["total_before", "total_after", "total_change", "self_before", "self_after"];
+    let columns = columns.into_iter().chain(["self_change", "intervals_before", "intervals_after"]);
+    println!("{} profiler", columns.collect::<Vec<_>>().join(" "));
+    for (path, frame) in diff.by_impact() {
+        let total = frame.total_duration();
+        let self_ = frame.self_duration();
+        let intervals = frame.interval_count();
+        let path = path.iter().map(|label| label.to_string()).collect::<Vec<_>>().join(" > ");
+        println!(
+            "{:>6.1} {:>6.1} {} {:>6.1} {:>6.1} {} {:>6.1} {:>6.1} {}",
+            total.before,
+            total.after,
+            format_change(total),
+            self_.before,
+            self_.after,
+            format_change(self_),
+            intervals.before,
+            intervals.after,
+            path
+        );
+    }
+}
+
+/// Aggregate all the profiles in the given files.
+fn aggregate(paths: &[String]) -> data::aggregate::Aggregator {
+    let mut aggregator = data::aggregate::Aggregator::default();
+    for path in paths {
+        let log = std::fs::read_to_string(path).unwrap();
+        let profiles =
+            data::parse_multiprocess_profile::<OpaqueMetadata>(&log).map(|profile| match profile {
+                Ok(profile) => profile,
+                Err(data::Error::RecoverableFormatError { with_missing_data, .. }) =>
+                    with_missing_data,
+                Err(e) => panic!("{}: {}", path, e),
+            });
+        let profiles: Vec<_> = profiles.collect();
+        aggregator.add_multiprocess_profile(&profiles);
+    }
+    aggregator
+}
+
+/// Format the absolute and relative change of a duration.
+fn format_change(change: Change) -> String {
+    let relative = match change.relative() {
+        Some(relative) => format!("{:+.1}%", relative * 100.0),
+        None if change.after == 0.0 => "-".to_owned(),
+        None => "new".to_owned(),
+    };
+    format!("{:>+7.1} ({:>7})", change.absolute(), relative)
+}
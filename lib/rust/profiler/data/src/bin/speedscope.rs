@@ -0,0 +1,53 @@
+//! Tool that converts profiles to the [speedscope](https://www.speedscope.app) file format.
+//!
+//! # Usage
+//!
+//! The tool reads a
+//! [JSON-formatted event log](https://github.com/enso-org/design/blob/main/epics/profiling/implementation.md#file-format)
+//! from stdin, and writes the converted profile to stdout. The log may contain the profiles of
+//! multiple processes, which are aligned using their time offsets.
+//!
+//! For example:
+//!
+//! ```console
+//! ~/git/enso/data $ cargo run --bin speedscope < profile.json > speedscope.json
+//! ```
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+// === Non-Standard Linter Configuration ===
+#![deny(unconditional_recursion)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+
+use data::OpaqueMetadata;
+use enso_profiler_data as data;
+
+
+
+// ============
+// === main ===
+// ============
+
+fn main() {
+    use std::io::Read;
+
+    let mut log = String::new();
+    std::io::stdin().read_to_string(&mut log).unwrap();
+    let profiles =
+        data::parse_multiprocess_profile::<OpaqueMetadata>(&log).map(|profile| match profile {
+            Ok(profile) => profile,
+            Err(data::Error::RecoverableFormatError { with_missing_data, .. }) => with_missing_data,
+            Err(e) => panic!("{}", e),
+        });
+    let profiles: Vec<_> = profiles.collect();
+    let file = data::export::speedscope::export(&profiles);
+    serde_json::to_writer(std::io::stdout(), &file).unwrap();
+}
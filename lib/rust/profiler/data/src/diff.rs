@@ -0,0 +1,193 @@
+//! Supports comparing the aggregated interval data of two sets of profiles, e.g. recorded by two
+//! builds of the application.
+//!
+//! The [`aggregate::Frame`]s of both sets are aligned by their label paths. As the sets may consist
+//! of the profiles of different numbers of runs of the application, the durations and interval
+//! counts are averaged per run.
+
+use enso_prelude::*;
+
+use crate::aggregate;
+
+use std::collections;
+
+
+
+// =============
+// === Stats ===
+// =============
+
+/// Aggregated info about a stack of profilers, averaged per run of the application.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Stats {
+    /// The duration spent in the profiler's intervals.
+    pub total_duration: f64,
+    /// The duration spent in the profiler's intervals, exclusive of time in child intervals.
+    pub self_duration:  f64,
+    /// The number of the profiler's intervals.
+    pub intervals:      f64,
+}
+
+impl Stats {
+    fn new(frame: &aggregate::Frame, runs: usize) -> Self {
+        let runs = runs.max(1) as f64;
+        Self {
+            total_duration: frame.total_duration() / runs,
+            self_duration:  frame.self_duration() / runs,
+            intervals:      frame.interval_count() as f64 / runs,
+        }
+    }
+}
+
+
+
+// ==============
+// === Change ===
+// ==============
+
+/// The change of a value between the two sets of profiles.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Change {
+    /// The value in the first set of profiles.
+    pub before: f64,
+    /// The value in the second set of profiles.
+    pub after:  f64,
+}
+
+impl Change {
+    /// Return the difference between the values.
+    pub fn absolute(&self) -> f64 {
+        self.after - self.before
+    }
+
+    /// Return the difference between the values, relative to the first one. Returns `None` if the
+    /// first value is zero.
+    pub fn relative(&self) -> Option<f64> {
+        (self.before != 0.0).then(|| self.absolute() / self.before)
+    }
+}
+
+
+
+// =============
+// === Frame ===
+// =============
+
+/// Aggregated info about all occurrences of a particular stack of profilers in both sets of
+/// profiles. If the stack occurred in only one of the sets, the stats of the other are zero.
+#[derive(Default, Debug)]
+pub struct Frame {
+    /// The stats of the first set of profiles.
+    pub before:   Stats,
+    /// The stats of the second set of profiles.
+    pub after:    Stats,
+    /// Aggregated intervals that ran as children of this profiler, in either set.
+    pub children: collections::HashMap<ImString, Self>,
+}
+
+impl Frame {
+    /// Compare the aggregated profiles.
+    pub fn new(before: aggregate::Aggregator, after: aggregate::Aggregator) -> Self {
+        let before_runs = before.run_count();
+        let after_runs = after.run_count();
+        let before = aggregate::Frame::from(before);
+        let after = aggregate::Frame::from(after);
+        let mut root = Self::default();
+        root.add(&before, before_runs, |frame| &mut frame.before);
+        root.add(&after, after_runs, |frame| &mut frame.after);
+        root
+    }
+
+    /// Add the stats of the aggregated frame and its children to one side of the tree.
+    fn add(&mut self, frame: &aggregate::Frame, runs: usize, side: fn(&mut Self) -> &mut Stats) {
+        *side(self) = Stats::new(frame, runs);
+        for (label, child) in &frame.children {
+            self.children.entry(label.clone()).or_default().add(child, runs, side);
+        }
+    }
+
+    /// Return the change of the duration spent in this profiler's intervals.
+    pub fn total_duration(&self) -> Change {
+        Change { before: self.before.total_duration, after: self.after.total_duration }
+    }
+
+    /// Return the change of the duration spent in this profiler's intervals, exclusive of time in
+    /// child intervals.
+    pub fn self_duration(&self) -> Change {
+        Change { before: self.before.self_duration, after: self.after.self_duration }
+    }
+
+    /// Return the change of the number of this profiler's intervals.
+    pub fn interval_count(&self) -> Change {
+        Change { before: self.before.intervals, after: self.after.intervals }
+    }
+
+    /// Return all the descendant frames with their label paths, ordered by impact, i.e. by the
+    /// absolute change of their self duration, largest first.
+    pub fn by_impact(&self) -> Vec<(Vec<ImString>, &Self)> {
+        let mut frames = vec![];
+        let mut path = vec![];
+        self.collect_descendants(&mut path, &mut frames);
+        let impact = |frame: &Self| frame.self_duration().absolute().abs();
+        frames.sort_by(|(_, a), (_, b)| impact(b).total_cmp(&impact(a)));
+        frames
+    }
+
+    fn collect_descendants<'a>(
+        &'a self,
+        path: &mut Vec<ImString>,
+        frames: &mut Vec<(Vec<ImString>, &'a Self)>,
+    ) {
+        for (label, child) in &self.children {
+            path.push(label.clone());
+            frames.push((path.clone(), child));
+            child.collect_descendants(path, frames);
+            path.pop();
+        }
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use crate as profiler_data;
+    use enso_profiler::format;
+
+    /// A profile of a parent interval with a child interval, with the given durations.
+    fn profile(parent: f64, child: f64, child_label: &str) -> profiler_data::Profile<()> {
+        let mut log = format::Builder::new();
+        let parent_id = log.create(None, format::Parent::root(), "parent (lib.rs:1)");
+        let child_id = log.create(None, parent_id.into(), child_label);
+        log.start(format::Timestamp::from_ms(0.0), parent_id);
+        log.start(format::Timestamp::from_ms(0.0), child_id);
+        log.end(format::Timestamp::from_ms(child), child_id);
+        log.end(format::Timestamp::from_ms(parent), parent_id);
+        log.build_string().parse().unwrap()
+    }
+
+    #[test]
+    fn frames_are_aligned_by_label_path() {
+        let mut before = profiler_data::aggregate::Aggregator::default();
+        before.add_profile(&profile(10.0, 4.0, "child (lib.rs:2)"));
+        before.add_profile(&profile(20.0, 6.0, "child (lib.rs:2)"));
+        let mut after = profiler_data::aggregate::Aggregator::default();
+        after.add_profile(&profile(12.0, 8.0, "renamed (lib.rs:2)"));
+        let diff = super::Frame::new(before, after);
+        let parent = &diff.children["parent (lib.rs:1)"];
+        assert_eq!(parent.total_duration().absolute(), -3.0);
+        assert_eq!(parent.self_duration().relative(), Some(-0.6));
+        assert_eq!(parent.interval_count().absolute(), 0.0);
+        let frames = diff.by_impact();
+        let paths = frames.iter().map(|(path, _)| path.last().unwrap().to_string());
+        let paths = paths.collect::<Vec<_>>();
+        assert_eq!(paths, ["renamed (lib.rs:2)", "parent (lib.rs:1)", "child (lib.rs:2)"]);
+        let (_, removed) = frames[2];
+        assert_eq!(removed.after, super::Stats::default());
+        assert_eq!(removed.interval_count().relative(), Some(-1.0));
+    }
+}
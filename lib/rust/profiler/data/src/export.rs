@@ -0,0 +1,199 @@
+//! Exporting profiles to the formats of third-party profile viewers.
+//!
+//! Each exporter maps the hierarchy of [`crate::ActiveInterval`]s to call stacks, with the
+//! [`crate::Measurement`]s as the stack frames. The metadata is exported as instant markers.
+//! Profiles recorded by multiple processes are aligned using their time offset headers.
+
+use crate::IntervalId;
+use crate::Label;
+use crate::MeasurementId;
+use crate::Profile;
+
+
+// ==============
+// === Export ===
+// ==============
+
+pub mod firefox;
+pub mod speedscope;
+
+
+
+// =============
+// === Event ===
+// =============
+
+/// Change of the active stack of measurements.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum EventKind {
+    /// The measurement is pushed onto the stack.
+    Open,
+    /// The measurement is popped from the stack.
+    Close,
+}
+
+/// Change of the active stack of measurements, at a given time.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Event {
+    pub kind:        EventKind,
+    pub measurement: MeasurementId,
+    /// Time in milliseconds, relative to the time origin of the exported profiles.
+    pub time:        f64,
+}
+
+/// Opening and closing of the profile's intervals, properly nested and ordered by time. The
+/// intervals without a logged end are closed with their parents, or at the end of the profile if
+/// they are top-level. The times are shifted by the given offset.
+pub(crate) fn interval_events<M>(profile: &Profile<M>, offset: f64) -> Vec<Event> {
+    let mut events = vec![];
+    let end = end_time(profile);
+    for top_level in top_level_intervals(profile) {
+        visit_interval(profile, top_level, end, offset, &mut events);
+    }
+    events
+}
+
+/// The intervals without a parent interval, ordered by time. Besides the children of the root
+/// interval, these include the outermost intervals that were still active when the log ended, as
+/// they are not attached to the root.
+fn top_level_intervals<M>(profile: &Profile<M>) -> Vec<IntervalId> {
+    // We skip the root node APP_LIFETIME, which is not a real measurement.
+    let root = profile.intervals.len() - 1;
+    let intervals = &profile.intervals[..root];
+    let children = intervals.iter().flat_map(|active| active.children.iter().copied());
+    let children = children.collect::<std::collections::HashSet<_>>();
+    let top_level = (0..root).map(IntervalId).filter(|id| !children.contains(id));
+    let mut top_level = top_level.collect::<Vec<_>>();
+    top_level.sort_by_key(|id| profile[*id].interval.start);
+    top_level
+}
+
+fn visit_interval<M>(
+    profile: &Profile<M>,
+    id: IntervalId,
+    parent_end: f64,
+    offset: f64,
+    events: &mut Vec<Event>,
+) {
+    let active = &profile[id];
+    let measurement = active.measurement;
+    let start = active.interval.start.into_ms();
+    let end = active.interval.end.map_or(parent_end, |end| end.into_ms().min(parent_end));
+    events.push(Event { kind: EventKind::Open, measurement, time: start + offset });
+    for child in &active.children {
+        visit_interval(profile, *child, end, offset, events);
+    }
+    events.push(Event { kind: EventKind::Close, measurement, time: end + offset });
+}
+
+/// The time of the latest event in the profile, in milliseconds.
+pub(crate) fn end_time<M>(profile: &Profile<M>) -> f64 {
+    let interval_times = profile.intervals.iter().flat_map(|active| {
+        let interval = active.interval;
+        std::iter::once(interval.start).chain(interval.end)
+    });
+    let metadata_times = profile.metadata().map(|metadata| metadata.time);
+    interval_times.chain(metadata_times).map(|time| time.into_ms()).fold(0.0, f64::max)
+}
+
+
+
+// ===============
+// === Helpers ===
+// ===============
+
+/// Offsets of the profiles' time origins from the earliest one, in milliseconds. Profiles without
+/// a time offset header are assumed to share the time origin of the earliest profile.
+pub(crate) fn time_offsets<M>(profiles: &[Profile<M>]) -> Vec<f64> {
+    let offsets = profiles.iter().map(|profile| profile.headers.time_offset.map(|t| t.into_ms()));
+    let offsets = offsets.collect::<Vec<_>>();
+    let origin = offsets.iter().flatten().copied().fold(f64::INFINITY, f64::min);
+    offsets.into_iter().map(|offset| offset.map_or(0.0, |offset| offset - origin)).collect()
+}
+
+/// The name of the process that recorded the profile.
+pub(crate) fn process_name<M>(profile: &Profile<M>, index: usize) -> String {
+    profile.headers.process.clone().unwrap_or_else(|| format!("Process {}", index + 1))
+}
+
+/// The name and the contents of the metadata object, for displaying it as a marker.
+///
+/// Metadata is serialized as an enum variant (see [`crate`]), so the name is the variant's name.
+pub(crate) fn metadata_marker<M: serde::Serialize>(metadata: &M) -> (String, String) {
+    let value = serde_json::to_value(metadata).unwrap_or_default();
+    match value {
+        serde_json::Value::Object(fields) if fields.len() == 1 => {
+            let (name, data) = fields.into_iter().next().unwrap();
+            (name, data.to_string())
+        }
+        serde_json::Value::String(name) => (name, String::new()),
+        other => ("Metadata".to_owned(), other.to_string()),
+    }
+}
+
+/// The key identifying the stack frames of the measurements with the given label.
+pub(crate) fn frame_key(label: &Label) -> (String, Option<String>, Option<u32>) {
+    let pos = label.pos.as_ref();
+    (label.name.clone(), pos.map(|pos| pos.file.clone()), pos.map(|pos| pos.line))
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use crate as profiler_data;
+    use enso_profiler::format;
+
+    /// A profile of a finished top-level interval, followed by a parent with two children, with
+    /// the parent and the second child never ending. Both children log metadata.
+    pub(super) fn profile() -> profiler_data::Profile<format::AnyMetadata> {
+        let mut log = format::Builder::new();
+        log.time_offset(format::Timestamp::from_ms(100.0));
+        log.process("Ide");
+        let init = log.create(None, format::Parent::root(), "init (lib.rs:0)");
+        log.start(format::Timestamp::from_ms(0.0), init);
+        log.end(format::Timestamp::from_ms(0.5), init);
+        let parent = log.create(None, format::Parent::root(), "parent (lib.rs:1)");
+        let first = log.create(None, parent.into(), "child (lib.rs:2)");
+        let second = log.create(None, parent.into(), "child (lib.rs:2)");
+        log.start(format::Timestamp::from_ms(1.0), parent);
+        log.start(format::Timestamp::from_ms(2.0), first);
+        log.metadata(format::Timestamp::from_ms(2.5), "RpcEvent", "executionComplete");
+        log.end(format::Timestamp::from_ms(3.0), first);
+        log.start(format::Timestamp::from_ms(4.0), second);
+        log.metadata(format::Timestamp::from_ms(6.0), "RpcEvent", "executionFailed");
+        log.build_string().parse().unwrap()
+    }
+
+    #[test]
+    fn open_intervals_are_closed_with_parents() {
+        use super::EventKind::*;
+        let profile = profile();
+        let events = super::interval_events(&profile, 10.0);
+        let events = events.iter().map(|event| (event.kind, event.time)).collect::<Vec<_>>();
+        let expected = [
+            (Open, 10.0),
+            (Close, 10.5),
+            (Open, 11.0),
+            (Open, 12.0),
+            (Close, 13.0),
+            (Open, 14.0),
+            (Close, 16.0),
+            (Close, 16.0),
+        ];
+        assert_eq!(events, expected);
+    }
+
+    #[test]
+    fn metadata_markers() {
+        let profile = profile();
+        let metadata = profile.metadata().next().unwrap();
+        let (name, detail) = super::metadata_marker(&metadata.data);
+        assert_eq!(name, "RpcEvent");
+        assert_eq!(detail, "\"executionComplete\"");
+    }
+}
@@ -0,0 +1,434 @@
+//! Export to the processed profile format of the [Firefox Profiler](https://profiler.firefox.com).
+//!
+//! The format is documented in the
+//! [profiler's repository](https://github.com/firefox-devtools/profiler/blob/main/docs-developer/CHANGELOG-formats.md);
+//! the profiler upgrades the files of older format versions when loading them. Each process is
+//! exported as a thread. Its samples are derived from the intervals: every change of the active
+//! stack of measurements produces a sample, weighted by the time until the next change. The
+//! intervals are also exported as interval markers, and the metadata as instant markers.
+
+use crate::export;
+use crate::Profile;
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The version of the Gecko profile format the file is based on.
+const GECKO_VERSION: u32 = 24;
+
+/// The version of the processed profile format.
+const PROCESSED_VERSION: u32 = 41;
+
+/// The type of the markers of the metadata objects.
+const METADATA_MARKER_TYPE: &str = "EnsoMetadata";
+
+/// The index of the only category used in the profile.
+const CATEGORY: usize = 0;
+
+/// The index of the only subcategory of the category.
+const SUBCATEGORY: usize = 0;
+
+/// The phase of the instant markers.
+const INSTANT_PHASE: u8 = 0;
+
+/// The phase of the interval markers.
+const INTERVAL_PHASE: u8 = 1;
+
+
+
+// ============
+// === File ===
+// ============
+
+/// A processed Firefox Profiler profile.
+#[derive(Clone, Debug, Serialize)]
+pub struct File {
+    meta:     Meta,
+    libs:     Vec<()>,
+    pages:    Vec<()>,
+    counters: Vec<()>,
+    threads:  Vec<Thread>,
+}
+
+/// The information about the profile.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Meta {
+    interval: f64,
+    start_time: f64,
+    process_type: u32,
+    product: &'static str,
+    stackwalk: u32,
+    version: u32,
+    preprocessed_profile_version: u32,
+    symbolicated: bool,
+    categories: Vec<Category>,
+    marker_schema: Vec<MarkerSchema>,
+}
+
+/// A category of the stack frames and markers.
+#[derive(Clone, Debug, Serialize)]
+struct Category {
+    name:          &'static str,
+    color:         &'static str,
+    subcategories: Vec<&'static str>,
+}
+
+/// The description of a marker type, used for displaying the markers' data.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MarkerSchema {
+    name:          &'static str,
+    display:       Vec<&'static str>,
+    chart_label:   &'static str,
+    tooltip_label: &'static str,
+    table_label:   &'static str,
+    data:          Vec<MarkerField>,
+}
+
+/// The description of a field of a marker's data.
+#[derive(Clone, Debug, Serialize)]
+struct MarkerField {
+    key:    &'static str,
+    label:  &'static str,
+    format: &'static str,
+}
+
+impl Meta {
+    fn new() -> Self {
+        let categories = vec![Category {
+            name:          "Other",
+            color:         "grey",
+            subcategories: vec!["Other"],
+        }];
+        let marker_schema = vec![MarkerSchema {
+            name:          METADATA_MARKER_TYPE,
+            display:       vec!["marker-chart", "marker-table", "timeline-overview"],
+            chart_label:   "{marker.data.name}",
+            tooltip_label: "{marker.data.name}",
+            table_label:   "{marker.data.name} {marker.data.detail}",
+            data:          vec![
+                MarkerField { key: "name", label: "Name", format: "unique-string" },
+                MarkerField { key: "detail", label: "Data", format: "string" },
+            ],
+        }];
+        Self {
+            interval: 1.0,
+            start_time: 0.0,
+            process_type: 0,
+            product: "Enso",
+            stackwalk: 0,
+            version: GECKO_VERSION,
+            preprocessed_profile_version: PROCESSED_VERSION,
+            symbolicated: true,
+            categories,
+            marker_schema,
+        }
+    }
+}
+
+
+
+// ==============
+// === Thread ===
+// ==============
+
+/// The profile of a single thread; we export every process as a single thread.
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Thread {
+    process_type:          &'static str,
+    process_startup_time:  f64,
+    process_shutdown_time: Option<f64>,
+    register_time:         f64,
+    unregister_time:       Option<f64>,
+    paused_ranges:         Vec<()>,
+    name:                  String,
+    process_name:          String,
+    is_main_thread:        bool,
+    pid:                   String,
+    tid:                   usize,
+    samples:               Samples,
+    markers:               Markers,
+    stack_table:           StackTable,
+    frame_table:           FrameTable,
+    func_table:            FuncTable,
+    resource_table:        ResourceTable,
+    native_symbols:        NativeSymbols,
+    string_array:          Vec<String>,
+    #[serde(skip)]
+    strings:               HashMap<String, usize>,
+    #[serde(skip)]
+    funcs:                 HashMap<(String, Option<String>, Option<u32>), usize>,
+    #[serde(skip)]
+    stacks:                HashMap<(Option<usize>, usize), usize>,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Samples {
+    weight_type: &'static str,
+    weight:      Vec<f64>,
+    stack:       Vec<Option<usize>>,
+    time:        Vec<f64>,
+    length:      usize,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Markers {
+    data:       Vec<Option<serde_json::Value>>,
+    name:       Vec<usize>,
+    start_time: Vec<f64>,
+    end_time:   Vec<Option<f64>>,
+    phase:      Vec<u8>,
+    category:   Vec<usize>,
+    length:     usize,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+struct StackTable {
+    frame:       Vec<usize>,
+    prefix:      Vec<Option<usize>>,
+    category:    Vec<usize>,
+    subcategory: Vec<usize>,
+    length:      usize,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FrameTable {
+    address:         Vec<i64>,
+    inline_depth:    Vec<u32>,
+    category:        Vec<usize>,
+    subcategory:     Vec<usize>,
+    func:            Vec<usize>,
+    native_symbol:   Vec<Option<usize>>,
+    #[serde(rename = "innerWindowID")]
+    inner_window_id: Vec<Option<u64>>,
+    implementation:  Vec<Option<usize>>,
+    line:            Vec<Option<u32>>,
+    column:          Vec<Option<u32>>,
+    length:          usize,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FuncTable {
+    name:            Vec<usize>,
+    #[serde(rename = "isJS")]
+    is_js:           Vec<bool>,
+    #[serde(rename = "relevantForJS")]
+    relevant_for_js: Vec<bool>,
+    resource:        Vec<i64>,
+    file_name:       Vec<Option<usize>>,
+    line_number:     Vec<Option<u32>>,
+    column_number:   Vec<Option<u32>>,
+    length:          usize,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+struct ResourceTable {
+    lib:    Vec<usize>,
+    name:   Vec<usize>,
+    host:   Vec<Option<usize>>,
+    #[serde(rename = "type")]
+    type_:  Vec<u32>,
+    length: usize,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NativeSymbols {
+    lib_index:     Vec<usize>,
+    address:       Vec<u64>,
+    name:          Vec<usize>,
+    function_size: Vec<Option<u32>>,
+    length:        usize,
+}
+
+impl Thread {
+    /// The index of the string in the thread's string table.
+    fn string(&mut self, string: &str) -> usize {
+        if let Some(index) = self.strings.get(string) {
+            return *index;
+        }
+        self.string_array.push(string.to_owned());
+        let index = self.string_array.len() - 1;
+        self.strings.insert(string.to_owned(), index);
+        index
+    }
+
+    /// The index of the function (and of its only frame) of the measurement label.
+    fn func(&mut self, label: &crate::Label) -> usize {
+        let key = export::frame_key(label);
+        if let Some(index) = self.funcs.get(&key) {
+            return *index;
+        }
+        let (name, file, line) = key.clone();
+        let name = self.string(&name);
+        let file = file.map(|file| self.string(&file));
+        let funcs = &mut self.func_table;
+        funcs.name.push(name);
+        funcs.is_js.push(false);
+        funcs.relevant_for_js.push(false);
+        funcs.resource.push(-1);
+        funcs.file_name.push(file);
+        funcs.line_number.push(line);
+        funcs.column_number.push(None);
+        funcs.length += 1;
+        let frames = &mut self.frame_table;
+        frames.address.push(-1);
+        frames.inline_depth.push(0);
+        frames.category.push(CATEGORY);
+        frames.subcategory.push(SUBCATEGORY);
+        frames.func.push(funcs.length - 1);
+        frames.native_symbol.push(None);
+        frames.inner_window_id.push(None);
+        frames.implementation.push(None);
+        frames.line.push(line);
+        frames.column.push(None);
+        frames.length += 1;
+        self.funcs.insert(key, frames.length - 1);
+        frames.length - 1
+    }
+
+    /// The index of the stack consisting of the prefix stack and the frame.
+    fn stack(&mut self, prefix: Option<usize>, frame: usize) -> usize {
+        let stacks = &mut self.stack_table;
+        *self.stacks.entry((prefix, frame)).or_insert_with(|| {
+            stacks.frame.push(frame);
+            stacks.prefix.push(prefix);
+            stacks.category.push(CATEGORY);
+            stacks.subcategory.push(SUBCATEGORY);
+            stacks.length += 1;
+            stacks.length - 1
+        })
+    }
+
+    fn sample(&mut self, stack: usize, time: f64, weight: f64) {
+        self.samples.stack.push(Some(stack));
+        self.samples.time.push(time);
+        self.samples.weight.push(weight);
+        self.samples.length += 1;
+    }
+
+    fn marker(
+        &mut self,
+        name: &str,
+        start: f64,
+        end: Option<f64>,
+        data: Option<serde_json::Value>,
+    ) {
+        let name = self.string(name);
+        let markers = &mut self.markers;
+        markers.name.push(name);
+        markers.start_time.push(start);
+        markers.end_time.push(end);
+        markers.phase.push(if end.is_some() { INTERVAL_PHASE } else { INSTANT_PHASE });
+        markers.category.push(CATEGORY);
+        markers.data.push(data);
+        markers.length += 1;
+    }
+}
+
+
+
+// ==============
+// === Export ===
+// ==============
+
+/// Export the profiles, recorded by different processes of a single run of the application.
+pub fn export<M: Serialize>(profiles: &[Profile<M>]) -> File {
+    let offsets = export::time_offsets(profiles);
+    let threads = profiles.iter().zip(offsets).enumerate();
+    let threads = threads.map(|(index, (profile, offset))| export_thread(profile, index, offset));
+    File {
+        meta:     Meta::new(),
+        libs:     vec![],
+        pages:    vec![],
+        counters: vec![],
+        threads:  threads.collect(),
+    }
+}
+
+fn export_thread<M: Serialize>(profile: &Profile<M>, index: usize, offset: f64) -> Thread {
+    let name = export::process_name(profile, index);
+    let mut thread = Thread {
+        process_type: "default",
+        process_startup_time: offset,
+        register_time: offset,
+        name: name.clone(),
+        process_name: name,
+        is_main_thread: true,
+        pid: (index + 1).to_string(),
+        tid: index + 1,
+        ..Default::default()
+    };
+    thread.samples.weight_type = "tracing-ms";
+    let events = export::interval_events(profile, offset);
+    // The stack of the open measurements, with their stack indices and start times.
+    let mut stack: Vec<(usize, f64)> = vec![];
+    for (i, event) in events.iter().enumerate() {
+        let label = &profile[event.measurement].label;
+        match event.kind {
+            export::EventKind::Open => {
+                let frame = thread.func(label);
+                let prefix = stack.last().map(|(stack, _)| *stack);
+                stack.push((thread.stack(prefix, frame), event.time));
+            }
+            export::EventKind::Close => {
+                let (_, start) = stack.pop().expect("Interval events are properly nested.");
+                thread.marker(&label.to_string(), start, Some(event.time), None);
+            }
+        }
+        // No samples are recorded while no measurement is active.
+        if let (Some((current, _)), Some(next)) = (stack.last(), events.get(i + 1)) {
+            let weight = next.time - event.time;
+            if weight > 0.0 {
+                thread.sample(*current, event.time, weight);
+            }
+        }
+    }
+    for metadata in profile.metadata() {
+        let (name, detail) = export::metadata_marker(&metadata.data);
+        let data = serde_json::json!({
+            "type": METADATA_MARKER_TYPE,
+            "name": name,
+            "detail": detail,
+        });
+        thread.marker(&name, metadata.time.into_ms() + offset, None, Some(data));
+    }
+    thread
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn export() {
+        let profile = crate::export::tests::profile();
+        let file = super::export(&[profile]);
+        let thread = &file.threads[0];
+        assert_eq!(thread.name, "Ide");
+        // Both children share the function, but not the stack.
+        assert_eq!(thread.func_table.length, 3);
+        assert_eq!(thread.stack_table.prefix, [None, None, Some(1)]);
+        assert_eq!(thread.samples.stack, [Some(0), Some(1), Some(2), Some(1), Some(2)]);
+        assert_eq!(thread.samples.weight, [0.5, 1.0, 1.0, 1.0, 2.0]);
+        assert_eq!(thread.markers.phase, [1, 1, 1, 1, 0, 0]);
+        let metadata = thread.markers.data[4].as_ref().unwrap();
+        assert_eq!(metadata["detail"], "\"executionComplete\"");
+    }
+}
@@ -0,0 +1,198 @@
+//! Export to the [speedscope](https://www.speedscope.app) file format.
+//!
+//! The format is described by its
+//! [JSON schema](https://www.speedscope.app/file-format-schema.json). Each process is exported as
+//! an evented profile of the intervals. As the format has no notion of markers, the metadata of
+//! each process is exported as an additional profile, with a zero-length frame for every metadata
+//! object.
+
+use crate::export;
+use crate::Profile;
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The URL of the file format's schema, identifying the format.
+const SCHEMA: &str = "https://www.speedscope.app/file-format-schema.json";
+
+/// The name of the exporter, written to the file.
+const EXPORTER: &str = "enso-profiler-data";
+
+
+
+// ============
+// === File ===
+// ============
+
+/// A speedscope file.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct File {
+    #[serde(rename = "$schema")]
+    schema:               &'static str,
+    shared:               Shared,
+    profiles:             Vec<EventedProfile>,
+    name:                 String,
+    active_profile_index: usize,
+    exporter:             &'static str,
+}
+
+/// The data shared by all profiles of the file.
+#[derive(Clone, Debug, Default, Serialize)]
+struct Shared {
+    frames: Vec<Frame>,
+}
+
+/// A stack frame.
+#[derive(Clone, Debug, Serialize)]
+struct Frame {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<u32>,
+}
+
+/// A profile of the stack frames being opened and closed.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EventedProfile {
+    #[serde(rename = "type")]
+    type_:       &'static str,
+    name:        String,
+    unit:        &'static str,
+    start_value: f64,
+    end_value:   f64,
+    events:      Vec<FrameEvent>,
+}
+
+/// Opening or closing of a stack frame.
+#[derive(Clone, Copy, Debug, Serialize)]
+struct FrameEvent {
+    #[serde(rename = "type")]
+    type_: &'static str,
+    frame: usize,
+    at:    f64,
+}
+
+impl FrameEvent {
+    fn new(kind: export::EventKind, frame: usize, at: f64) -> Self {
+        let type_ = match kind {
+            export::EventKind::Open => "O",
+            export::EventKind::Close => "C",
+        };
+        Self { type_, frame, at }
+    }
+}
+
+
+
+// ==============
+// === Export ===
+// ==============
+
+/// Export the profiles, recorded by different processes of a single run of the application.
+pub fn export<M: Serialize>(profiles: &[Profile<M>]) -> File {
+    let mut frames = Frames::default();
+    let offsets = export::time_offsets(profiles);
+    let mut evented_profiles = vec![];
+    for (index, (profile, offset)) in profiles.iter().zip(offsets).enumerate() {
+        let name = export::process_name(profile, index);
+        let end_value = export::end_time(profile) + offset;
+        let events = export::interval_events(profile, offset);
+        let start_value = events.first().map_or(offset, |event| event.time);
+        let events = events.into_iter().map(|event| {
+            let frame = frames.measurement(profile, event.measurement);
+            FrameEvent::new(event.kind, frame, event.time)
+        });
+        let events = events.collect();
+        let unit = "milliseconds";
+        let type_ = "evented";
+        evented_profiles.push(EventedProfile { type_, name, unit, start_value, end_value, events });
+
+        let mut metadata = profile.metadata().collect::<Vec<_>>();
+        if metadata.is_empty() {
+            continue;
+        }
+        metadata.sort_by(|a, b| a.time.into_ms().total_cmp(&b.time.into_ms()));
+        let name = format!("{} (metadata)", export::process_name(profile, index));
+        let start_value = metadata[0].time.into_ms() + offset;
+        let mut events = vec![];
+        for metadata in metadata {
+            let (marker, detail) = export::metadata_marker(&metadata.data);
+            let frame = frames.marker(format!("{marker} {detail}"));
+            let at = metadata.time.into_ms() + offset;
+            events.push(FrameEvent::new(export::EventKind::Open, frame, at));
+            events.push(FrameEvent::new(export::EventKind::Close, frame, at));
+        }
+        let unit = "milliseconds";
+        let type_ = "evented";
+        evented_profiles.push(EventedProfile { type_, name, unit, start_value, end_value, events });
+    }
+    let shared = Shared { frames: frames.frames };
+    let profiles = evented_profiles;
+    let name = "Enso profile".to_owned();
+    File { schema: SCHEMA, shared, profiles, name, active_profile_index: 0, exporter: EXPORTER }
+}
+
+
+// === Frames ===
+
+/// The shared stack frames, deduplicated.
+#[derive(Debug, Default)]
+struct Frames {
+    frames:  Vec<Frame>,
+    indices: HashMap<(String, Option<String>, Option<u32>), usize>,
+}
+
+impl Frames {
+    /// The index of the frame of the measurement.
+    fn measurement<M>(&mut self, profile: &Profile<M>, id: crate::MeasurementId) -> usize {
+        self.get(export::frame_key(&profile[id].label))
+    }
+
+    /// The index of the frame of a metadata marker.
+    fn marker(&mut self, name: String) -> usize {
+        self.get((name, None, None))
+    }
+
+    fn get(&mut self, key: (String, Option<String>, Option<u32>)) -> usize {
+        let frames = &mut self.frames;
+        *self.indices.entry(key).or_insert_with_key(|(name, file, line)| {
+            frames.push(Frame { name: name.clone(), file: file.clone(), line: *line });
+            frames.len() - 1
+        })
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn export() {
+        let profile = crate::export::tests::profile();
+        let file = super::export(&[profile]);
+        let file = serde_json::to_value(file).unwrap();
+        let frames = &file["shared"]["frames"];
+        assert_eq!(frames.as_array().unwrap().len(), 5);
+        assert_eq!(frames[2], serde_json::json!({"name": "child", "file": "lib.rs", "line": 2}));
+        let profiles = file["profiles"].as_array().unwrap();
+        assert_eq!(profiles.len(), 2);
+        let events = profiles[0]["events"].as_array().unwrap();
+        let frames = events.iter().map(|event| event["frame"].as_u64().unwrap());
+        assert_eq!(frames.collect::<Vec<_>>(), [0, 0, 1, 2, 2, 2, 2, 1]);
+        assert_eq!(profiles[0]["endValue"], 6.0);
+        assert_eq!(profiles[1]["events"].as_array().unwrap().len(), 4);
+    }
+}
@@ -98,6 +98,8 @@ use std::rc::Rc;
 // ==============
 
 pub mod aggregate;
+pub mod diff;
+pub mod export;
 pub mod parse;
 
 
@@ -124,6 +126,9 @@ pub enum Error<M> {
     },
     /// Failed to interpret the event log data.
     DataError(EventError<parse::DataError>),
+    /// Failed to decode the event log in the binary format. The file is corrupt, or in an
+    /// incompatible version of the format.
+    BinaryFormatError(EventError<parse::binary::DecodeError>),
 }
 
 impl<M> fmt::Display for Error<M> {
@@ -140,6 +145,7 @@ impl<M> fmt::Debug for Error<M> {
             Error::FormatError(e) => e.fmt(f),
             Error::RecoverableFormatError { errors, .. } => errors.fmt(f),
             Error::DataError(e) => e.fmt(f),
+            Error::BinaryFormatError(e) => e.fmt(f),
         }
     }
 }
@@ -150,6 +156,7 @@ impl<M> error::Error for Error<M> {
             Error::FormatError(e) => e,
             Error::RecoverableFormatError { errors, .. } => &errors[0],
             Error::DataError(e) => e,
+            Error::BinaryFormatError(e) => e,
         })
     }
 }
@@ -195,6 +202,15 @@ pub fn parse_multiprocess_profile<M: serde::de::DeserializeOwned>(
     )
 }
 
+/// Parse data representing profiling information collected by multiple processes, in the binary
+/// format (see [`format::binary`]). If a log cannot be decoded, no further logs are returned.
+pub fn parse_multiprocess_binary_profile<M: serde::de::DeserializeOwned>(
+    data: &[u8],
+) -> impl Iterator<Item = Result<Profile<M>, Error<M>>> + '_ {
+    let mut data = data;
+    std::iter::from_fn(move || (!data.is_empty()).then(|| parse::parse_binary(&mut data)))
+}
+
 
 
 // ===============
@@ -665,4 +681,37 @@ mod tests {
         assert_eq!(root.root_interval().metadata.len(), 1);
         assert_eq!(root.root_interval().metadata[0].data, MyMetadata::MyDataA(MyDataA(23)));
     }
+    /// Ensure a log in the binary format has the same contents as in the JSON format, including
+    /// when some metadata cannot be deserialized.
+    #[test]
+    fn binary_log() {
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        enum MyMetadata {
+            MyDataA(u32),
+        }
+        profiler::metadata_logger!("MyDataA", log_data_a(u32));
+        profiler::metadata_logger!("MyDataB", log_data_b(String));
+        let _profiler = start_profiler("parent (?:?)");
+        log_data_a(23);
+        log_data_b("unexpected".into());
+        let log = profiler::internal::write_binary_log(vec![]).unwrap().repeat(2);
+        let profiles = profiler_data::parse_multiprocess_binary_profile(&log);
+        let profiles: Vec<_> = profiles.collect();
+        assert_eq!(profiles.len(), 2);
+        let profile: profiler_data::Profile<MyMetadata> = match profiles.into_iter().next() {
+            Some(Err(profiler_data::Error::RecoverableFormatError {
+                errors,
+                with_missing_data,
+            })) => {
+                assert_eq!(errors.len(), 1);
+                with_missing_data
+            }
+            other => panic!("Expected RecoverableFormatError, found: {:?}", other),
+        };
+        let roots = &profile.root_measurement().children;
+        assert_eq!(profile[roots[0]].label.name, "parent");
+        let metadata: Vec<_> = profile.metadata().map(|metadata| &metadata.data).collect();
+        assert_eq!(metadata, [&MyMetadata::MyDataA(23)]);
+        assert_eq!(profile.headers.process.as_deref(), Some("Ide"));
+    }
 }
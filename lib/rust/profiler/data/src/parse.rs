@@ -10,6 +10,13 @@ use std::rc::Rc;
 use std::str;
 
 
+// ==============
+// === Export ===
+// ==============
+
+pub mod binary;
+
+
 
 // ===========================
 // === Parse and interpret ===
@@ -21,14 +28,47 @@ impl<M: serde::de::DeserializeOwned> str::FromStr for crate::Profile<M> {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let events: Result<Vec<format::Event>, _> = serde_json::from_str(s);
         let events = events.map_err(crate::Error::FormatError)?;
-        let Interpreted { profile, metadata_errors } =
-            interpret(events).map_err(crate::Error::DataError)?;
-        if metadata_errors.is_empty() {
-            Ok(profile)
-        } else {
-            let errors = metadata_errors;
-            Err(crate::Error::RecoverableFormatError { errors, with_missing_data: profile })
-        }
+        from_events(events)
+    }
+}
+
+impl<M: serde::de::DeserializeOwned> crate::Profile<M> {
+    /// Parse a log in the binary format (see [`format::binary`]). Any data following the log is
+    /// ignored.
+    pub fn from_binary(mut data: &[u8]) -> Result<Self, crate::Error<M>> {
+        parse_binary(&mut data)
+    }
+}
+
+/// Parse a log in the binary format, advancing the data past it. If the log cannot be decoded, the
+/// data is exhausted.
+pub(crate) fn parse_binary<M: serde::de::DeserializeOwned>(
+    data: &mut &[u8],
+) -> Result<crate::Profile<M>, crate::Error<M>> {
+    let format_error =
+        |log_pos, error| crate::Error::BinaryFormatError(crate::EventError { log_pos, error });
+    let mut reader =
+        binary::Reader::new(mem::take(data)).map_err(|error| format_error(0, error))?;
+    let mut events = vec![];
+    for event in &mut reader {
+        events.push(event.map_err(|error| format_error(events.len(), error))?);
+    }
+    *data = reader.remainder().unwrap_or_default();
+    from_events(events)
+}
+
+/// Interpret a log of events. If only some metadata could not be deserialized, the profile is
+/// returned in a [`crate::Error::RecoverableFormatError`].
+fn from_events<M: serde::de::DeserializeOwned>(
+    events: Vec<format::Event>,
+) -> Result<crate::Profile<M>, crate::Error<M>> {
+    let Interpreted { profile, metadata_errors } =
+        interpret(events).map_err(crate::Error::DataError)?;
+    if metadata_errors.is_empty() {
+        Ok(profile)
+    } else {
+        let errors = metadata_errors;
+        Err(crate::Error::RecoverableFormatError { errors, with_missing_data: profile })
     }
 }
 
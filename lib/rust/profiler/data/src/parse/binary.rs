@@ -0,0 +1,240 @@
+//! Decoding of the binary event-log format (see [`format::binary`]).
+
+use enso_profiler as profiler;
+use profiler::format;
+use profiler::format::binary::tag;
+use std::borrow::Cow;
+use std::error;
+use std::fmt;
+use std::str;
+
+
+
+// ===================
+// === DecodeError ===
+// ===================
+
+/// A problem decoding a log in the binary format.
+#[derive(Debug)]
+pub enum DecodeError {
+    /// The data does not start with the [`format::binary::MAGIC`] bytes.
+    NotBinary,
+    /// The log was written by a newer version of the encoding.
+    UnsupportedVersion(u64),
+    /// The data ended before the end of the log.
+    UnexpectedEnd,
+    /// A record had an unknown tag.
+    UnknownTag(u8),
+    /// A varint was longer than the encoding of any 64-bit value.
+    VarintOverflow,
+    /// A label or name was not valid UTF-8.
+    InvalidText(str::Utf8Error),
+    /// A metadata record referred to a type that had not been interned.
+    UndefinedMetadataType(usize),
+    /// The data of a metadata record was not valid JSON.
+    InvalidMetadata(serde_json::Error),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl error::Error for DecodeError {}
+
+
+
+// ==============
+// === Reader ===
+// ==============
+
+/// Decodes a log in the binary format, event by event.
+///
+/// After an error, or the end of the log, no more events are returned.
+#[derive(Debug)]
+pub struct Reader<'a> {
+    data:           &'a [u8],
+    pos:            usize,
+    last_timestamp: u64,
+    metadata_types: Vec<&'a str>,
+    state:          State,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum State {
+    Reading,
+    Ended,
+    Failed,
+}
+
+impl<'a> Reader<'a> {
+    /// Start decoding the log at the beginning of the data.
+    pub fn new(data: &'a [u8]) -> Result<Self, DecodeError> {
+        let data = data.strip_prefix(format::binary::MAGIC.as_slice());
+        let data = data.ok_or(DecodeError::NotBinary)?;
+        let last_timestamp = Default::default();
+        let metadata_types = Default::default();
+        let state = State::Reading;
+        let mut reader = Self { data, pos: 0, last_timestamp, metadata_types, state };
+        let version = reader.varint()?;
+        if version > format::binary::VERSION {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+        Ok(reader)
+    }
+
+    /// Return the data following the log, if all of it has been read.
+    pub fn remainder(&self) -> Option<&'a [u8]> {
+        (self.state == State::Ended).then(|| &self.data[self.pos..])
+    }
+
+    fn read_event(&mut self) -> Result<Option<format::Event<'a>>, DecodeError> {
+        loop {
+            let tag = self.byte()?;
+            let event = match tag {
+                tag::END => return Ok(None),
+                tag::LABEL => format::Event::Label { label: Cow::Borrowed(self.text()?) },
+                tag::METADATA_TYPE => {
+                    let name = self.text()?;
+                    self.metadata_types.push(name);
+                    continue;
+                }
+                tag::CREATE | tag::CREATE_INHERIT => {
+                    let parent = match self.varint()? {
+                        0 => format::Parent::implicit(),
+                        1 => format::Parent::root(),
+                        id => format::MeasurementId(id as usize - 2).into(),
+                    };
+                    let start = match tag {
+                        tag::CREATE => Some(self.timestamp()?),
+                        _ => None,
+                    };
+                    let label = format::Label::from_id(self.varint()? as usize);
+                    format::Event::Create(format::Start { parent, start, label })
+                }
+                tag::START | tag::END_MEASUREMENT | tag::PAUSE => {
+                    let id = format::MeasurementId(self.varint()? as usize);
+                    let timestamp = self.timestamp()?;
+                    match tag {
+                        tag::START => format::Event::Start { id, timestamp },
+                        tag::END_MEASUREMENT => format::Event::End { id, timestamp },
+                        _ => format::Event::Pause { id, timestamp },
+                    }
+                }
+                tag::METADATA => {
+                    let type_id = self.varint()? as usize;
+                    let name = self.metadata_types.get(type_id);
+                    let name = *name.ok_or(DecodeError::UndefinedMetadataType(type_id))?;
+                    let time = self.timestamp()?;
+                    // Restore the representation as a variant of an enum.
+                    let name = serde_json::to_string(name).unwrap();
+                    let data = format!("{{{}:{}}}", name, self.text()?);
+                    let data = serde_json::value::RawValue::from_string(data);
+                    let data = data.map_err(DecodeError::InvalidMetadata)?;
+                    format::Event::Metadata(format::Timestamped { time, data })
+                }
+                tag::UNTYPED_METADATA => {
+                    let time = self.timestamp()?;
+                    let data = serde_json::value::RawValue::from_string(self.text()?.to_owned());
+                    let data = data.map_err(DecodeError::InvalidMetadata)?;
+                    format::Event::Metadata(format::Timestamped { time, data })
+                }
+                tag => return Err(DecodeError::UnknownTag(tag)),
+            };
+            return Ok(Some(event));
+        }
+    }
+
+    fn timestamp(&mut self) -> Result<format::Timestamp, DecodeError> {
+        let zigzag = self.varint()?;
+        let delta = (zigzag >> 1) as i64 ^ -((zigzag & 1) as i64);
+        self.last_timestamp = self.last_timestamp.wrapping_add(delta as u64);
+        Ok(format::Timestamp::from_micros(self.last_timestamp))
+    }
+
+    fn text(&mut self) -> Result<&'a str, DecodeError> {
+        let len = self.varint()? as usize;
+        let end = self.pos.checked_add(len).ok_or(DecodeError::UnexpectedEnd)?;
+        let bytes = self.data.get(self.pos..end).ok_or(DecodeError::UnexpectedEnd)?;
+        self.pos = end;
+        str::from_utf8(bytes).map_err(DecodeError::InvalidText)
+    }
+
+    fn varint(&mut self) -> Result<u64, DecodeError> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(DecodeError::VarintOverflow)
+    }
+
+    fn byte(&mut self) -> Result<u8, DecodeError> {
+        let byte = *self.data.get(self.pos).ok_or(DecodeError::UnexpectedEnd)?;
+        self.pos += 1;
+        Ok(byte)
+    }
+}
+
+impl<'a> Iterator for Reader<'a> {
+    type Item = Result<format::Event<'a>, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.state != State::Reading {
+            return None;
+        }
+        match self.read_event() {
+            Ok(Some(event)) => Some(Ok(event)),
+            Ok(None) => {
+                self.state = State::Ended;
+                None
+            }
+            Err(error) => {
+                self.state = State::Failed;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut log = format::Builder::new();
+        log.time_offset(format::Timestamp::from_ms(1_650_900_741_301.3));
+        log.process("Ide");
+        let parent = log.create(Some(format::Timestamp::from_ms(5.0)), format::Parent::root(), "a");
+        let child = log.create(None, parent.into(), "b (lib.rs:1)");
+        log.start(format::Timestamp::from_ms(5.0), parent);
+        log.start(format::Timestamp::from_ms(4.5), child);
+        log.metadata(format::Timestamp::from_ms(6.0), "RpcEvent", "executionComplete");
+        log.pause(format::Timestamp::from_ms(7.25), child);
+        log.create(None, format::Parent::implicit(), "a");
+        log.end(format::Timestamp::from_ms(8.0), parent);
+        let events: Vec<format::Event> = serde_json::from_str(&log.build_string()).unwrap();
+        let mut writer = format::binary::Writer::new(vec![]);
+        events.iter().for_each(|event| writer.write_event(event));
+        let mut binary = writer.finish().unwrap();
+        binary.extend_from_slice(format::binary::MAGIC);
+        let mut reader = Reader::new(&binary).unwrap();
+        let decoded: Result<Vec<_>, _> = (&mut reader).collect();
+        let json = serde_json::to_string(&events).unwrap();
+        assert_eq!(serde_json::to_string(&decoded.unwrap()).unwrap(), json);
+        assert_eq!(reader.remainder(), Some(format::binary::MAGIC.as_slice()));
+        let truncated = Reader::new(&binary[..binary.len() / 2]).unwrap().last();
+        assert!(matches!(truncated, Some(Err(DecodeError::UnexpectedEnd))));
+    }
+}
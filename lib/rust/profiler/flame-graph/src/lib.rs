@@ -388,6 +388,105 @@ impl FlamegraphGrapher {
 
 
 
+// ================================
+// === Differential Flamegraphs ===
+// ================================
+
+/// The smallest relative change of a profiler's self duration that is marked as an improvement or
+/// a regression in a differential flamegraph.
+const DIFFERENTIAL_THRESHOLD: f64 = 0.1;
+
+/// Build a graph that illustrates the changes of aggregate time spent in different functions
+/// between two sets of profiles, e.g. recorded by two builds of the application.
+///
+/// The activity blocks form a flamegraph of the second set of profiles. Below it, a mirrored row of
+/// performance blocks for each activity block indicates whether its self duration improved
+/// ([`Performance::Good`]), regressed ([`Performance::Bad`]), or did not change significantly
+/// ([`Performance::Medium`]).
+#[derive(Default)]
+pub struct DifferentialFlamegraphBuilder {
+    before: data::aggregate::Aggregator,
+    after:  data::aggregate::Aggregator,
+}
+
+impl DifferentialFlamegraphBuilder {
+    /// Add data from a profile of the first set to the graph.
+    pub fn add_profile_before<Metadata>(&mut self, profile: &data::Profile<Metadata>) {
+        self.before.add_profile(profile);
+    }
+
+    /// Add data from a profile of the second set to the graph.
+    pub fn add_profile_after<Metadata>(&mut self, profile: &data::Profile<Metadata>) {
+        self.after.add_profile(profile);
+    }
+}
+
+impl From<DifferentialFlamegraphBuilder> for Graph {
+    fn from(builder: DifferentialFlamegraphBuilder) -> Self {
+        let root = data::diff::Frame::new(builder.before, builder.after);
+        Self::new_differential_flamegraph(&root)
+    }
+}
+
+impl Graph {
+    /// Create a differential flamegraph from the compared aggregate data; see
+    /// [`DifferentialFlamegraphBuilder`].
+    pub fn new_differential_flamegraph(root: &data::diff::Frame) -> Self {
+        let mut grapher = DifferentialFlamegraphGrapher::default();
+        for (label, frame) in &root.children {
+            grapher.visit_frame(frame, label.to_string(), 0);
+        }
+        let DifferentialFlamegraphGrapher { activity_blocks, performance_blocks, .. } = grapher;
+        Graph { activity_blocks, performance_blocks, marks: Vec::default() }
+    }
+}
+
+/// Builds a differential flamegraph [`Graph`] from [`data::diff::Frame`]s.
+#[derive(Default)]
+struct DifferentialFlamegraphGrapher {
+    activity_blocks:    Vec<Block<Activity>>,
+    performance_blocks: Vec<Block<Performance>>,
+    time:               f64,
+}
+
+impl DifferentialFlamegraphGrapher {
+    fn visit_frame(&mut self, frame: &data::diff::Frame, label: String, row: RowNumber) {
+        let start = self.time;
+        let end = self.time + frame.after.total_duration;
+        // Profilers that didn't run in the second set of profiles can't be drawn.
+        if end == start {
+            return;
+        }
+        let change = frame.self_duration();
+        let block_type = match change.relative() {
+            Some(relative) if relative <= -DIFFERENTIAL_THRESHOLD => Performance::Good,
+            Some(relative) if relative >= DIFFERENTIAL_THRESHOLD => Performance::Bad,
+            Some(_) => Performance::Medium,
+            None => Performance::Bad,
+        };
+        let change_label = match change.relative() {
+            Some(relative) => format!("{:+.1}%", relative * 100.0),
+            None => "new".to_owned(),
+        };
+        let performance_label =
+            format!("{label}\n[self {:+.2} ({change_label})]", change.absolute());
+        let performance_row = -row - 1;
+        self.performance_blocks.push(Block {
+            start,
+            end,
+            label: performance_label,
+            row: performance_row,
+            block_type,
+        });
+        self.activity_blocks.push(Block { start, end, label, row, block_type: Activity::Active });
+        for (label, frame) in &frame.children {
+            self.visit_frame(frame, label.to_string(), row + 1);
+        }
+        self.time = end;
+    }
+}
+
+
 // =============
 // === Tests ===
 // =============
@@ -418,4 +517,26 @@ mod tests {
         assert_eq!(flame_graph.activity_blocks[0].row, 0);
         assert!(flame_graph.activity_blocks[0].label.contains("profiled_a"));
     }
+
+    #[test]
+    fn check_differential_flame_graph_creation() {
+        use profiler::format;
+        let profile = |duration| {
+            let mut log = format::Builder::new();
+            let id = log.create(None, format::Parent::root(), "task (lib.rs:1)");
+            log.start(format::Timestamp::from_ms(0.0), id);
+            log.end(format::Timestamp::from_ms(duration), id);
+            log.build_string().parse::<data::Profile<data::OpaqueMetadata>>().unwrap()
+        };
+        let mut builder = DifferentialFlamegraphBuilder::default();
+        builder.add_profile_before(&profile(10.0));
+        builder.add_profile_after(&profile(15.0));
+        let flame_graph = Graph::from(builder);
+        assert_eq!(flame_graph.activity_blocks.len(), 1);
+        assert_eq!(flame_graph.activity_blocks[0].width(), 15.0);
+        assert_eq!(flame_graph.performance_blocks.len(), 1);
+        assert_eq!(flame_graph.performance_blocks[0].row, -1);
+        assert!(matches!(flame_graph.performance_blocks[0].block_type, Performance::Bad));
+        assert!(flame_graph.performance_blocks[0].label.contains("+50.0%"));
+    }
 }
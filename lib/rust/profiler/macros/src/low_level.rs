@@ -27,7 +27,13 @@ pub fn define_profiling_levels(ts: proc_macro::TokenStream) -> proc_macro::Token
         }
     }
     let ident = syn::Ident::new("ProfilingLevel", proc_macro2::Span::call_site());
-    out.extend(level::make_enum(ident, levels.iter().map(|level| &level.obj_ident)));
+    out.extend(level::make_enum(ident.clone(), levels.iter().map(|level| &level.obj_ident)));
+    let enabled = levels.iter().filter(|level| level.enabled);
+    let max_enabled = &enabled.last().expect("The coarsest level is always enabled.").obj_ident;
+    out.extend(proc_macro::TokenStream::from(quote::quote! {
+        /// The finest profiling level enabled at compile time.
+        pub const MAX_PROFILING_LEVEL: #ident = #ident::#max_enabled;
+    }));
     out
 }
 
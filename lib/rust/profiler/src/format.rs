@@ -1,6 +1,8 @@
 //! Defines the JSON-based profile event-log format.
 //!
 //! See: https://github.com/enso-org/design/blob/main/epics/profiling/implementation.md#profiling-data
+//!
+//! The same events can also be encoded in a compact binary format; see [`binary`].
 
 use serde;
 use serde::Deserialize;
@@ -12,6 +14,7 @@ use std::borrow::Cow;
 // === Export ===
 // ==============
 
+pub mod binary;
 pub mod builder;
 
 pub use builder::Builder;
@@ -106,6 +109,11 @@ pub struct Start {
 pub struct Label(usize);
 
 impl Label {
+    /// Return the label at the given index into the label table.
+    pub fn from_id(id: usize) -> Self {
+        Self(id)
+    }
+
     /// Return an index into the label table.
     pub fn id(self) -> usize {
         self.0
@@ -232,6 +240,16 @@ impl Timestamp {
     pub fn into_ms(self) -> f64 {
         self.0 as f64 / 1000.0
     }
+
+    /// Return the timestamp corresponding to an offset from the time origin, in μs.
+    pub fn from_micros(micros: u64) -> Self {
+        Self(micros)
+    }
+
+    /// Convert to an offset from the time origin, in μs.
+    pub fn into_micros(self) -> u64 {
+        self.0
+    }
 }
 
 
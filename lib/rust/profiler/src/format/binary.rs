@@ -0,0 +1,213 @@
+//! Defines a compact binary encoding of the profile event-log format.
+//!
+//! The binary format represents the same sequence of [`format::Event`]s as the JSON format, so a
+//! log can be converted between the formats without loss. A log consists of:
+//! - The [`MAGIC`] bytes, and the [`VERSION`] of the encoding as a varint.
+//! - A sequence of records, each consisting of a [`tag`] byte and its fields.
+//! - A [`tag::END`] byte. A multi-process profile is a concatenation of logs.
+//!
+//! Integers are encoded as unsigned LEB128 varints. Every timestamp is encoded as the difference
+//! from the previous timestamp in the log, zigzag-encoded. Labels are interned as in the JSON
+//! format, by [`tag::LABEL`] records. The names of metadata types are interned likewise, by
+//! [`tag::METADATA_TYPE`] records; the data of a metadata record is the JSON representation of the
+//! value, without the type name.
+
+use crate::format;
+
+use std::collections::HashMap;
+use std::io;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The bytes identifying a log in the binary format.
+pub const MAGIC: &[u8; 8] = b"ENSOPROF";
+
+/// The version of the encoding written by [`Writer`].
+pub const VERSION: u64 = 1;
+
+/// The tags identifying the types of records.
+pub mod tag {
+    /// The end of the log.
+    pub const END: u8 = 0;
+    /// Interns a label: the length of the text, and the UTF-8 text.
+    pub const LABEL: u8 = 1;
+    /// Interns the name of a metadata type: the length of the name, and the UTF-8 name.
+    pub const METADATA_TYPE: u8 = 2;
+    /// A [`format::Event::Create`] with a start time: the parent, the timestamp, the label.
+    ///
+    /// The parent is encoded as 0 if implicit, 1 if the root, or else 2 plus the measurement ID.
+    pub const CREATE: u8 = 3;
+    /// A [`format::Event::Create`] inheriting its start time: the parent, the label.
+    pub const CREATE_INHERIT: u8 = 4;
+    /// A [`format::Event::Start`]: the measurement ID, the timestamp.
+    pub const START: u8 = 5;
+    /// A [`format::Event::End`]: the measurement ID, the timestamp.
+    pub const END_MEASUREMENT: u8 = 6;
+    /// A [`format::Event::Pause`]: the measurement ID, the timestamp.
+    pub const PAUSE: u8 = 7;
+    /// A [`format::Event::Metadata`] of an interned type: the type ID, the timestamp, the length
+    /// of the data, and the JSON data.
+    pub const METADATA: u8 = 8;
+    /// A [`format::Event::Metadata`] that is not tagged with a type name: the timestamp, the
+    /// length of the data, and the JSON data.
+    pub const UNTYPED_METADATA: u8 = 9;
+}
+
+
+
+// ==============
+// === Writer ===
+// ==============
+
+/// Writes a log in the binary format, event by event.
+///
+/// Output errors are reported by [`Writer::finish`]; after an error, the following events are
+/// ignored.
+#[derive(Debug)]
+pub struct Writer<W: io::Write> {
+    out:            W,
+    error:          Option<io::Error>,
+    last_timestamp: u64,
+    metadata_types: HashMap<String, usize>,
+}
+
+impl<W: io::Write> Writer<W> {
+    /// Start a log, written to the given output. As records are written separately, the output
+    /// should be buffered.
+    pub fn new(out: W) -> Self {
+        let mut writer = Self {
+            out,
+            error: None,
+            last_timestamp: Default::default(),
+            metadata_types: Default::default(),
+        };
+        writer.write(MAGIC);
+        writer.varint(VERSION);
+        writer
+    }
+
+    /// Write an event to the log.
+    pub fn write_event(&mut self, event: &format::Event) {
+        match event {
+            format::Event::Label { label } => {
+                self.write(&[tag::LABEL]);
+                self.bytes(label.as_bytes());
+            }
+            format::Event::Create(format::Start { parent, start, label }) => {
+                let parent = match parent {
+                    format::Parent::Implicit => 0,
+                    format::Parent::Explicit(format::ParentId::Root) => 1,
+                    format::Parent::Explicit(format::ParentId::Measurement(id)) => id.0 as u64 + 2,
+                };
+                match start {
+                    Some(start) => {
+                        self.write(&[tag::CREATE]);
+                        self.varint(parent);
+                        self.timestamp(*start);
+                    }
+                    None => {
+                        self.write(&[tag::CREATE_INHERIT]);
+                        self.varint(parent);
+                    }
+                }
+                self.varint(label.0 as u64);
+            }
+            format::Event::Start { id, timestamp } => self.transition(tag::START, *id, *timestamp),
+            format::Event::End { id, timestamp } =>
+                self.transition(tag::END_MEASUREMENT, *id, *timestamp),
+            format::Event::Pause { id, timestamp } => self.transition(tag::PAUSE, *id, *timestamp),
+            format::Event::Metadata(format::Timestamped { time, data }) =>
+                self.metadata(*time, data.get()),
+        }
+    }
+
+    /// Finish the log, and return the output.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write(&[tag::END]);
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.out),
+        }
+    }
+
+    fn transition(&mut self, tag: u8, id: format::MeasurementId, timestamp: format::Timestamp) {
+        self.write(&[tag]);
+        self.varint(id.0 as u64);
+        self.timestamp(timestamp);
+    }
+
+    fn metadata(&mut self, time: format::Timestamp, data: &str) {
+        // Metadata is serialized as a variant of an enum, i.e. an object with a single field named
+        // after the type.
+        let variant: Option<HashMap<&str, &serde_json::value::RawValue>> =
+            serde_json::from_str(data).ok();
+        let variant = variant.filter(|variant| variant.len() == 1);
+        match variant.and_then(|variant| variant.into_iter().next()) {
+            Some((name, data)) => {
+                let next_type = self.metadata_types.len();
+                let type_id = match self.metadata_types.get(name) {
+                    Some(type_id) => *type_id,
+                    None => {
+                        self.metadata_types.insert(name.to_owned(), next_type);
+                        self.write(&[tag::METADATA_TYPE]);
+                        self.bytes(name.as_bytes());
+                        next_type
+                    }
+                };
+                self.write(&[tag::METADATA]);
+                self.varint(type_id as u64);
+                self.timestamp(time);
+                self.bytes(data.get().as_bytes());
+            }
+            None => {
+                self.write(&[tag::UNTYPED_METADATA]);
+                self.timestamp(time);
+                self.bytes(data.as_bytes());
+            }
+        }
+    }
+
+    fn timestamp(&mut self, timestamp: format::Timestamp) {
+        let delta = timestamp.0.wrapping_sub(self.last_timestamp) as i64;
+        self.last_timestamp = timestamp.0;
+        self.varint(((delta << 1) ^ (delta >> 63)) as u64);
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.varint(bytes.len() as u64);
+        self.write(bytes);
+    }
+
+    fn varint(&mut self, mut value: u64) {
+        let mut buffer = [0; 10];
+        let mut len = 0;
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                buffer[len] = byte;
+                len += 1;
+                break;
+            }
+            buffer[len] = byte | 0x80;
+            len += 1;
+        }
+        self.write(&buffer[..len]);
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        if self.error.is_none() {
+            self.error = self.out.write_all(bytes).err();
+        }
+    }
+}
+
+impl<'a, W: io::Write> format::builder::Sink<'a> for Writer<W> {
+    fn event(&mut self, event: format::Event<'a>) {
+        self.write_event(&event);
+    }
+}
@@ -1,4 +1,5 @@
-//! Supports constructing a document in the JSON format (see [`crate::format`]).
+//! Supports constructing a document in the JSON format (see [`crate::format`]), or in the binary
+//! format (see [`crate::format::binary`]).
 
 use crate::format;
 
@@ -11,9 +12,12 @@ use std::collections::HashMap;
 // ===============
 
 /// Constructs a profile document for serialization into the JSON format (see [`crate::format`]).
+///
+/// The events are passed to a [`Sink`] as they are built; by default, they are collected to be
+/// serialized when the document is complete.
 #[derive(Debug, Default)]
-pub struct Builder<'a> {
-    events:           Vec<format::Event<'a>>,
+pub struct Builder<'a, S = Vec<format::Event<'a>>> {
+    events:           S,
     next_measurement: usize,
     labels:           HashMap<&'a str, format::Label>,
 }
@@ -24,13 +28,37 @@ impl<'a> Builder<'a> {
         Default::default()
     }
 
+    /// Render the profile to a file.
+    pub fn build_string(self) -> String {
+        serde_json::to_string(&self.events).unwrap()
+    }
+
+    /// Render the profile in the binary format.
+    pub fn build_binary(self) -> Vec<u8> {
+        let mut writer = format::binary::Writer::new(vec![]);
+        self.events.into_iter().for_each(|event| writer.event(event));
+        writer.finish().unwrap()
+    }
+}
+
+impl<'a, S: Sink<'a>> Builder<'a, S> {
+    /// Construct a profile document, passing its events to the given sink.
+    pub fn with_sink(events: S) -> Self {
+        Self { events, next_measurement: Default::default(), labels: Default::default() }
+    }
+
+    /// Return the sink the events have been passed to.
+    pub fn into_sink(self) -> S {
+        self.events
+    }
+
     /// Log a metadata event to the profile.
     pub fn metadata<M>(&mut self, time: format::Timestamp, name: &'static str, data: M)
     where M: serde::Serialize {
         let data = Variant { name, data };
         let data = serde_json::value::to_raw_value(&data).unwrap();
         let event = format::Timestamped { time, data };
-        self.events.push(format::Event::Metadata(event));
+        self.events.event(format::Event::Metadata(event));
     }
 
     /// Log a profiler-creation event to the profile.
@@ -44,13 +72,13 @@ impl<'a> Builder<'a> {
         let next_label_id = self.labels.len();
         let label = *self.labels.entry(label).or_insert_with(|| {
             let label = label.into();
-            self.events.push(format::Event::Label { label });
+            self.events.event(format::Event::Label { label });
             format::Label(next_label_id)
         });
         // Create event.
         let start = time;
         let event = format::Start { parent, start, label };
-        self.events.push(format::Event::Create(event));
+        self.events.event(format::Event::Create(event));
         let id = self.next_measurement;
         self.next_measurement += 1;
         format::MeasurementId(id)
@@ -58,17 +86,17 @@ impl<'a> Builder<'a> {
 
     /// Log a profiler-start event to the profile.
     pub fn start(&mut self, timestamp: format::Timestamp, id: format::MeasurementId) {
-        self.events.push(format::Event::Start { id, timestamp });
+        self.events.event(format::Event::Start { id, timestamp });
     }
 
     /// Log a profiler-end event to the profile.
     pub fn end(&mut self, timestamp: format::Timestamp, id: format::MeasurementId) {
-        self.events.push(format::Event::End { id, timestamp });
+        self.events.event(format::Event::End { id, timestamp });
     }
 
     /// Log a profiler-pause event to the profile.
     pub fn pause(&mut self, timestamp: format::Timestamp, id: format::MeasurementId) {
-        self.events.push(format::Event::Pause { id, timestamp });
+        self.events.event(format::Event::Pause { id, timestamp });
     }
 
     /// Attach a header to the profile indicating the offset of the file's timestamps from system
@@ -86,12 +114,25 @@ impl<'a> Builder<'a> {
         let data = serde_json::value::to_raw_value(&data).unwrap();
         let time = format::Timestamp::default();
         let event = format::Timestamped { time, data };
-        self.events.push(format::Event::Metadata(event));
+        self.events.event(format::Event::Metadata(event));
     }
+}
 
-    /// Render the profile to a file.
-    pub fn build_string(self) -> String {
-        serde_json::to_string(&self.events).unwrap()
+
+
+// ============
+// === Sink ===
+// ============
+
+/// Receives the events of a profile document as they are built.
+pub trait Sink<'a> {
+    /// Add an event to the document.
+    fn event(&mut self, event: format::Event<'a>);
+}
+
+impl<'a> Sink<'a> for Vec<format::Event<'a>> {
+    fn event(&mut self, event: format::Event<'a>) {
+        self.push(event);
     }
 }
 
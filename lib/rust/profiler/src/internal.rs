@@ -8,7 +8,10 @@
 use crate::format;
 use crate::log;
 use crate::ProfilingLevel;
+use crate::Retention;
 
+use std::cell;
+use std::collections;
 use std::fmt;
 use std::rc;
 
@@ -31,6 +34,11 @@ thread_local! {
 pub(crate) static METADATA_LOGS: log::ThreadLocalLog<rc::Rc<dyn MetadataSource>> =
     log::ThreadLocalLog::new(METADATA_LOG_LOG);
 
+thread_local! {
+    /// Summary of the events discarded from [`EVENTS`].
+    static DISCARDED: cell::RefCell<Discarded> = Default::default();
+}
+
 
 
 // =========================
@@ -39,10 +47,30 @@ pub(crate) static METADATA_LOGS: log::ThreadLocalLog<rc::Rc<dyn MetadataSource>>
 
 /// Produce a JSON-formatted event log from the internal event logs.
 pub fn get_log() -> String {
-    let LogData { events, metadata_names, mut metadata_entries } = get_raw_log();
-    let mut out = LogTranslator::new();
-    for (id, event) in events.into_iter().enumerate() {
-        let id = EventId(id as u32);
+    translate_log(vec![]).build_string()
+}
+
+/// Write an event log in the binary format (see [`format::binary`]) from the internal event logs.
+/// The events are written as they are translated, so the output should be buffered.
+pub fn write_binary_log<W: std::io::Write>(out: W) -> std::io::Result<W> {
+    translate_log(format::binary::Writer::new(out)).into_sink().finish()
+}
+
+/// Translate the internal event logs to a profile document, passing the events to the given sink.
+fn translate_log<S: format::builder::Sink<'static>>(sink: S) -> format::Builder<'static, S> {
+    let LogData { events, first_event, discarded, metadata_names, mut metadata_entries } =
+        get_raw_log();
+    let mut out = LogTranslator::new(sink);
+    // Recreate the profilers that were started by the discarded events, and had not ended by then.
+    for (id, open) in &discarded.open {
+        out.create(Some(open.created), open.parent, open.label, *id);
+    }
+    for id in &discarded.stack {
+        let open = &discarded.open[id];
+        out.start(open.running_since.unwrap_or(open.created), *id);
+    }
+    for (i, event) in events.into_iter().enumerate() {
+        let id = EventId((first_event + i) as u32);
         match event {
             Event::Metadata { timestamp, data } => {
                 let ExternalMetadata { type_id } = data;
@@ -62,7 +90,7 @@ pub fn get_log() -> String {
             Event::Resume { id, timestamp } => out.start(timestamp, id),
         }
     }
-    out.finish()
+    out.profile
 }
 
 
@@ -71,55 +99,194 @@ pub fn get_log() -> String {
 /// Obtain the data from the internal event log.
 pub(crate) fn get_raw_log() -> LogData {
     let events = EVENTS.clone_all();
+    let first_event = EVENTS.first_retained();
+    let discarded = DISCARDED.with_borrow(|discarded| discarded.clone());
     let metadatas: Vec<_> = METADATA_LOGS.clone_all();
     let metadata_names: Vec<_> = metadatas.iter().map(|metadata| metadata.name()).collect();
-    let metadata_entries: Vec<_> =
-        metadatas.into_iter().map(|metadata| metadata.get_all()).collect();
-    LogData { events, metadata_names, metadata_entries }
+    let metadata_entries: Vec<_> = metadatas
+        .into_iter()
+        .enumerate()
+        .map(|(i, metadata)| {
+            // Skip the retained entries that were logged by discarded events.
+            let discarded_entries = discarded.metadata.get(i).copied().unwrap_or_default();
+            let skip = discarded_entries - metadata.first_retained();
+            let entries: Box<dyn Iterator<Item = _>> = Box::new(metadata.get_all().skip(skip));
+            entries
+        })
+        .collect();
+    LogData { events, first_event, discarded, metadata_names, metadata_entries }
 }
 
 /// A snapshot of the internal event log.
 /// Contains all the information necessary to produce a profile.
 pub(crate) struct LogData {
     pub events:       Vec<Event>,
+    /// The ID of the first event in `events`.
+    first_event:      usize,
+    discarded:        Discarded,
     metadata_names:   Vec<&'static str>,
     metadata_entries: Vec<Box<dyn Iterator<Item = Box<serde_json::value::RawValue>>>>,
 }
 
 
 
+// ==================
+// === Discarding ===
+// ==================
+
+/// Summary of the events discarded from the log, allowing a profile to be produced from the
+/// retained events.
+#[derive(Clone, Debug, Default)]
+struct Discarded {
+    /// The profilers started by the discarded events, that had not ended by the last of them.
+    open:     collections::BTreeMap<EventId, OpenProfiler>,
+    /// The profilers running after the last discarded event, innermost last.
+    stack:    Vec<EventId>,
+    /// For each type of metadata, the number of entries logged by the discarded events.
+    metadata: Vec<usize>,
+}
+
+/// A profiler started by a discarded event.
+#[derive(Clone, Copy, Debug)]
+struct OpenProfiler {
+    /// The explicit parent of the profiler.
+    parent:        EventId,
+    label:         Label,
+    created:       Timestamp,
+    /// The start of the profiler's current active interval, if it is running.
+    running_since: Option<Timestamp>,
+}
+
+impl Discarded {
+    /// Update the summary with a discarded event.
+    fn visit(&mut self, id: EventId, event: &Event) {
+        match *event {
+            Event::Start(start) => {
+                let created = self.add_open(id, start);
+                self.open.get_mut(&id).unwrap().running_since = Some(created);
+                self.stack.push(id);
+            }
+            Event::StartPaused(start) => {
+                self.add_open(id, start);
+            }
+            Event::Resume { id, timestamp } =>
+                if let Some(open) = self.open.get_mut(&id) {
+                    open.running_since = Some(timestamp);
+                    self.stack.push(id);
+                },
+            Event::Pause { id, .. } =>
+                if let Some(open) = self.open.get_mut(&id) {
+                    open.running_since = None;
+                    self.stack.retain(|running| *running != id);
+                },
+            Event::End { id, .. } => {
+                self.open.remove(&id);
+                self.stack.retain(|running| *running != id);
+            }
+            Event::Metadata { data: ExternalMetadata { type_id }, .. } => {
+                let type_id = type_id as usize;
+                if self.metadata.len() <= type_id {
+                    self.metadata.resize(type_id + 1, 0);
+                }
+                self.metadata[type_id] += 1;
+            }
+        }
+    }
+
+    /// Add an open profiler; return its creation time.
+    fn add_open(&mut self, id: EventId, start: Start) -> Timestamp {
+        let parent = match start.parent.explicit() {
+            Some(parent) => parent,
+            None => self.stack.last().copied().unwrap_or(EventId::APP_LIFETIME),
+        };
+        let parent_created = self.open.get(&parent).map(|parent| parent.created);
+        let created = start.start.or(parent_created).unwrap_or_default();
+        let label = start.label;
+        self.open.insert(id, OpenProfiler { parent, label, created, running_since: None });
+        created
+    }
+}
+
+/// Discard the oldest blocks of events, if they are not to be retained.
+fn discard_expired_events() {
+    let retention = crate::retention();
+    while is_first_block_expired(retention) {
+        let first = EVENTS.first_retained();
+        let mut i = first;
+        let discarded = DISCARDED.with_borrow_mut(|discarded| {
+            let visit = |event: &Event| {
+                discarded.visit(EventId(i as u32), event);
+                i += 1;
+            };
+            // Safety: References obtained by indexing `EVENTS` are never kept outside the
+            // implementation of `log::Log`.
+            #[allow(unsafe_code)]
+            unsafe {
+                EVENTS.discard_first_block(visit)
+            }
+        });
+        if !discarded {
+            return;
+        }
+    }
+    let metadatas: Vec<_> = METADATA_LOGS.clone_all();
+    DISCARDED.with_borrow(|discarded| {
+        for (metadata, discarded_entries) in metadatas.iter().zip(&discarded.metadata) {
+            metadata.discard_until(*discarded_entries);
+        }
+    });
+}
+
+/// Return whether the oldest block of events can be discarded, according to the retention policy.
+fn is_first_block_expired(retention: Retention) -> bool {
+    let first = EVENTS.first_retained();
+    let completed = first + log::BLOCK <= EVENTS.len();
+    match retention {
+        _ if !completed => false,
+        Retention::All => false,
+        Retention::Events(count) => EVENTS.len() - (first + log::BLOCK) >= count,
+        Retention::Duration(duration) => {
+            let block = first..first + log::BLOCK;
+            let latest = block.rev().find_map(|i| EVENTS.get(i, |event| event.timestamp()));
+            let threshold = Timestamp::now().into_ms() - duration.as_secs_f64() * 1000.0;
+            latest.map_or(false, |latest| latest.into_ms() < threshold)
+        }
+    }
+}
+
+
+
 // =====================
 // === LogTranslator ===
 // =====================
 
 /// Translates [`profiler::internal`] types and IDs to [`profiler::format`] equivalents.
 #[derive(Debug)]
-struct LogTranslator<'a> {
-    profile: format::Builder<'a>,
+struct LogTranslator<'a, S> {
+    profile: format::Builder<'a, S>,
     ids:     std::collections::HashMap<EventId, format::MeasurementId>,
 }
 
 macro_rules! translate_transition {
     ($name:ident) => {
         fn $name(&mut self, time: Timestamp, id: EventId) {
-            self.profile.$name(time.into(), self.ids[&id]);
+            // The profiler's start event may have been discarded.
+            if let Some(&id) = self.ids.get(&id) {
+                self.profile.$name(time.into(), id);
+            }
         }
     };
 }
 
-impl<'a> LogTranslator<'a> {
-    fn new() -> Self {
-        let mut profile = format::Builder::new();
+impl<'a, S: format::builder::Sink<'a>> LogTranslator<'a, S> {
+    fn new(sink: S) -> Self {
+        let mut profile = format::Builder::with_sink(sink);
         profile.time_offset(Timestamp::time_offset().into());
         profile.process("Ide");
         let ids = Default::default();
         Self { profile, ids }
     }
 
-    fn finish(self) -> String {
-        self.profile.build_string()
-    }
-
     fn metadata(&mut self, time: Timestamp, name: &'static str, data: format::AnyMetadata) {
         self.profile.metadata(time.into(), name, data);
     }
@@ -128,7 +295,8 @@ impl<'a> LogTranslator<'a> {
         let parent = match parent {
             EventId::IMPLICIT => format::Parent::implicit(),
             EventId::APP_LIFETIME => format::Parent::root(),
-            id => self.ids[&id].into(),
+            // The parent's events may have been discarded.
+            id => self.ids.get(&id).map_or(format::Parent::root(), |&parent| parent.into()),
         };
         let time = time.map(|t| t.into());
         let interval = self.profile.create(time, parent, label.0);
@@ -154,6 +322,10 @@ pub(crate) struct MetadataLog<T> {
 pub(crate) trait MetadataSource {
     fn name(&self) -> &'static str;
     fn get_all(&self) -> Box<dyn Iterator<Item = Box<serde_json::value::RawValue>>>;
+    /// Return the index of the first entry returned by [`get_all`].
+    fn first_retained(&self) -> usize;
+    /// Discard the blocks of entries preceding the given index.
+    fn discard_until(&self, index: usize);
 }
 
 impl<T: 'static + serde::Serialize> MetadataSource for MetadataLog<T> {
@@ -161,6 +333,22 @@ impl<T: 'static + serde::Serialize> MetadataSource for MetadataLog<T> {
         self.name
     }
 
+    fn first_retained(&self) -> usize {
+        self.entries.first_retained()
+    }
+
+    fn discard_until(&self, index: usize) {
+        while self.entries.first_retained() + log::BLOCK <= index {
+            // Safety: References obtained by indexing the entries are never kept outside the
+            // implementation of `log::Log`.
+            #[allow(unsafe_code)]
+            let discarded = unsafe { self.entries.discard_first_block(|_| ()) };
+            if !discarded {
+                return;
+            }
+        }
+    }
+
     fn get_all(&self) -> Box<dyn Iterator<Item = Box<serde_json::value::RawValue>>> {
         let mut entries = Vec::with_capacity(self.entries.len());
         self.entries.for_each(|x| entries.push(serde_json::value::to_raw_value(&x).unwrap()));
@@ -213,6 +401,9 @@ pub struct EventLog;
 
 impl EventLog {
     /// Log the beginning of a measurement.
+    ///
+    /// Returns [`EventId::DISABLED`] without logging anything, if the measurement's level is not
+    /// active at runtime, or if its parent is disabled.
     #[inline]
     pub fn start(
         self,
@@ -222,6 +413,9 @@ impl EventLog {
         state: StartState,
         level: ProfilingLevel,
     ) -> EventId {
+        if parent == EventId::DISABLED || level > crate::active_level() {
+            return EventId::DISABLED;
+        }
         let m = Start { parent, label, start, level };
         let event = match state {
             StartState::Active => Event::Start(m),
@@ -233,19 +427,25 @@ impl EventLog {
     /// Log the end of a measurement.
     #[inline]
     pub fn end(self, id: EventId, timestamp: Timestamp) {
-        self.log_event(Event::End { id, timestamp });
+        if id != EventId::DISABLED {
+            self.log_event(Event::End { id, timestamp });
+        }
     }
 
     /// Log the beginning of an interval in which the measurement is not active.
     #[inline]
     pub fn pause(self, id: EventId, timestamp: Timestamp) {
-        self.log_event(Event::Pause { id, timestamp });
+        if id != EventId::DISABLED {
+            self.log_event(Event::Pause { id, timestamp });
+        }
     }
 
     /// Log the end of an interval in which the measurement is not active.
     #[inline]
     pub fn resume(self, id: EventId, timestamp: Timestamp) {
-        self.log_event(Event::Resume { id, timestamp });
+        if id != EventId::DISABLED {
+            self.log_event(Event::Resume { id, timestamp });
+        }
     }
 
     /// Log metadata.
@@ -256,10 +456,24 @@ impl EventLog {
         self.log_event(Event::Metadata { timestamp, data })
     }
 
+    /// Log the event, returning its ID.
+    ///
+    /// The ID is the position of the event in the log, which counts the events discarded by the
+    /// [`Retention`] policy too; so in a long session it could reach the special values of
+    /// [`EventId`]. Before that happens logging stops: the event is dropped, and
+    /// [`EventId::DISABLED`] is returned, so the measurements started afterwards are treated as
+    /// disabled.
     #[inline(always)]
     fn log_event(self, event: Event) -> EventId {
-        let id = EventId(EVENTS.len() as u32);
+        let position = EVENTS.len();
+        if position >= EventId::DISABLED.0 as usize {
+            return EventId::DISABLED;
+        }
+        let id = EventId(position as u32);
         EVENTS.push(event);
+        if (id.0 as usize + 1) % log::BLOCK == 0 {
+            discard_expired_events();
+        }
         id
     }
 }
@@ -327,6 +541,17 @@ impl Event {
             _ => None,
         }
     }
+
+    /// Return the time of the event, if it is known without referring to other events.
+    pub fn timestamp(&self) -> Option<Timestamp> {
+        match self {
+            Event::Start(start) | Event::StartPaused(start) => start.start,
+            Event::End { timestamp, .. }
+            | Event::Pause { timestamp, .. }
+            | Event::Resume { timestamp, .. }
+            | Event::Metadata { timestamp, .. } => Some(*timestamp),
+        }
+    }
 }
 
 
@@ -470,6 +695,10 @@ impl EventId {
     /// Special value indicating the root pseudo-profiler (the parent of runtime root profilers).
     pub const APP_LIFETIME: EventId = EventId(u32::MAX - 1);
 
+    /// Special value identifying the profilers whose level was disabled at runtime when they were
+    /// started; nothing is logged for them.
+    pub const DISABLED: EventId = EventId(u32::MAX - 2);
+
     /// Special value indicating that no explicit prior event is associated.
     ///
     /// When used to identify a parent, this indicates that the parent can be inferred to be the
@@ -42,6 +42,17 @@
 //! If the environment variable is not set, the level will default to the minimum supported,
 //! *objective*.
 //!
+//! ## Runtime configuration
+//!
+//! Within the levels enabled at compile time, the finest level of the measurements that are logged
+//! can be changed at runtime with [`set_active_level`]. Measurements of the disabled levels still
+//! have a small runtime cost, unlike the levels disabled at compile time.
+//!
+//! By default, all events are kept in memory until the end of the program. To keep profiling
+//! enabled in long-running sessions, the log can be limited to the most recent events with
+//! [`set_retention`]. The profilers that were running when the older events were discarded are
+//! still present in the captured profile, so that it remains properly nested.
+//!
 //! # Structured measurement
 //!
 //! This API can be used to make arbitrary measurements; in order to ensure measurements are easy to
@@ -337,6 +348,58 @@ pub const APP_LIFETIME: Objective = Objective(EventId::APP_LIFETIME);
 
 
 
+// =============================
+// === Runtime configuration ===
+// =============================
+
+thread_local! {
+    static ACTIVE_LEVEL: std::cell::Cell<ProfilingLevel> =
+        std::cell::Cell::new(MAX_PROFILING_LEVEL);
+    static RETENTION: std::cell::Cell<Retention> = std::cell::Cell::new(Retention::All);
+}
+
+/// Set the finest profiling level of the measurements to log. Levels finer than
+/// [`MAX_PROFILING_LEVEL`] are disabled at compile time, and cannot be enabled at runtime.
+///
+/// The change affects the measurements started afterwards. Returns the level that was set.
+pub fn set_active_level(level: ProfilingLevel) -> ProfilingLevel {
+    let level = level.min(MAX_PROFILING_LEVEL);
+    ACTIVE_LEVEL.set(level);
+    level
+}
+
+/// Return the finest profiling level of the measurements that are logged.
+pub fn active_level() -> ProfilingLevel {
+    ACTIVE_LEVEL.get()
+}
+
+/// Set which of the logged events are kept in memory. Events are discarded in blocks of
+/// [`log::BLOCK`] events, so slightly more events than requested may be kept.
+pub fn set_retention(retention: Retention) {
+    RETENTION.set(retention);
+}
+
+/// Return which of the logged events are kept in memory.
+pub fn retention() -> Retention {
+    RETENTION.get()
+}
+
+
+// === Retention ===
+
+/// Specifies which of the logged events are kept in memory.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Retention {
+    /// All events are kept.
+    All,
+    /// At least the given number of the most recent events are kept.
+    Events(usize),
+    /// At least the events logged within the given duration before now are kept.
+    Duration(std::time::Duration),
+}
+
+
+
 // ===================
 // === EventStream ===
 // ===================
@@ -349,12 +412,21 @@ pub struct EventStream {
     next_i: usize,
 }
 
+impl EventStream {
+    fn next_with_id(&mut self) -> Option<(EventId, Event)> {
+        // Skip the events that were discarded since the last call.
+        self.next_i = self.next_i.max(EVENTS.first_retained());
+        let id = EventId(self.next_i as u32);
+        let result = EVENTS.try_get(self.next_i, |e| (id, *e));
+        result.inspect(|_| self.next_i += 1);
+        result
+    }
+}
+
 impl Iterator for EventStream {
     type Item = Event;
     fn next(&mut self) -> Option<Self::Item> {
-        let result = EVENTS.try_get(self.next_i, |e| *e);
-        result.inspect(|_| self.next_i += 1);
-        result
+        self.next_with_id().map(|(_, event)| event)
     }
 }
 
@@ -374,7 +446,7 @@ pub fn event_stream() -> EventStream {
 /// logged since the last it was used.
 pub fn interval_stream() -> IntervalStream {
     IntervalStream {
-        events: EventStream::default().enumerate(),
+        events: EventStream::default(),
         resume: Default::default(),
         parent: Default::default(),
         stack:  Default::default(),
@@ -384,7 +456,7 @@ pub fn interval_stream() -> IntervalStream {
 /// A stream that yields all logged events.
 #[derive(Debug, Clone)]
 pub struct IntervalStream {
-    events: std::iter::Enumerate<EventStream>,
+    events: EventStream,
     // [`Timestamp`]s of events that have been paused (or started paused) and resumed. If a running
     // event is not found in this collection, it has been running since its start event.
     resume: std::collections::BTreeMap<EventId, Timestamp>,
@@ -413,15 +485,16 @@ impl IntervalStream {
         let parent_was_recorded =
             "If the event ID is implicit, we must have resolved its parent when we encountered it.";
         let id = id.explicit().unwrap_or_else(|| *self.parent.get(&id).expect(parent_was_recorded));
-        EVENTS.get(id.0 as usize, |event| self.resolve_start_time(&event.as_start().unwrap()))
+        // If the parent's start event was discarded, its start time is unknown.
+        let start = EVENTS.try_get(id.0 as usize, |event| event.as_start().unwrap());
+        start.map(|start| self.resolve_start_time(&start)).unwrap_or_default()
     }
 }
 
 impl Iterator for IntervalStream {
     type Item = Interval;
     fn next(&mut self) -> Option<Self::Item> {
-        while let Some((i, event)) = self.events.next() {
-            let id = EventId(i as u32);
+        while let Some((id, event)) = self.events.next_with_id() {
             match event {
                 Event::Start(start) => {
                     self.stack.push(id);
@@ -435,14 +508,16 @@ impl Iterator for IntervalStream {
                 Event::End { id, timestamp } | Event::Pause { id, timestamp } => {
                     let start = self.resume.remove(&id).or_else(|| {
                         let id = self.resolve_start_id(id);
-                        EVENTS.get(id, |e| match e {
+                        let start = EVENTS.try_get(id, |e| match e {
                             Event::Start(start) => Some(self.resolve_start_time(start)),
                             Event::StartPaused(_) => None,
                             _ => unreachable!(),
-                        })
+                        });
+                        start.flatten()
                     });
-                    if let Some(start) = start {
-                        let label = EVENTS.get(id.0 as usize, |e| e.as_start().unwrap().label);
+                    // The interval is skipped if its profiler's start event was discarded.
+                    let label = EVENTS.try_get(id.0 as usize, |e| e.as_start().unwrap().label);
+                    if let (Some(start), Some(label)) = (start, label) {
                         let end = timestamp;
                         return Some(Interval { label, start, end });
                     }
@@ -603,6 +678,76 @@ mod log_tests {
             _ => panic!("log: {:#?}", log),
         };
     }
+
+    #[test]
+    fn runtime_level() {
+        let max = profiler::MAX_PROFILING_LEVEL;
+        assert_eq!(profiler::set_active_level(profiler::ProfilingLevel::Debug), max);
+        assert_eq!(
+            profiler::set_active_level(profiler::ProfilingLevel::Objective),
+            max.min(profiler::ProfilingLevel::Objective)
+        );
+        let log = profiler::internal::EventLog;
+        let label = profiler::internal::Label("disabled (lib.rs:1)");
+        let state = profiler::internal::StartState::Active;
+        let task = profiler::ProfilingLevel::Task;
+        let id = log.start(profiler::EventId::APP_LIFETIME, label, None, state, task);
+        assert_eq!(id, profiler::EventId::DISABLED);
+        let child = log.start(id, label, None, state, profiler::ProfilingLevel::Objective);
+        assert_eq!(child, profiler::EventId::DISABLED);
+        log.end(child, Default::default());
+        log.end(id, Default::default());
+        assert!(get_log().is_empty());
+    }
+
+    #[test]
+    fn bounded_log() {
+        profiler::set_retention(profiler::Retention::Events(100));
+        let metadata = profiler::internal::MetadataLogger::<usize>::new("Counter");
+        let outer = start_objective!(profiler::APP_LIFETIME, "outer");
+        for i in 0..3000 {
+            let _profiler = start_objective!(outer, "inner");
+            metadata.log(i);
+        }
+        let retained = crate::EVENTS.len() - crate::EVENTS.first_retained();
+        assert!(crate::EVENTS.first_retained() > 0);
+        assert!(retained < 100 + 2 * crate::log::BLOCK);
+        let log = profiler::internal::get_log();
+        let events: Vec<profiler::format::Event> = serde_json::from_str(&log).unwrap();
+        let (labels, mut events): (Vec<_>, Vec<_>) = events
+            .into_iter()
+            .partition(|event| matches!(event, profiler::format::Event::Label { .. }));
+        let labels: Vec<_> = labels
+            .into_iter()
+            .filter_map(|event| match event {
+                profiler::format::Event::Label { label } => Some(label.to_string()),
+                _ => None,
+            })
+            .collect();
+        // Skip the header of the log.
+        let first = events.iter().position(|e| !matches!(e, profiler::format::Event::Metadata(_)));
+        let mut events = events.drain(first.unwrap()..);
+        // The outer profiler, started by a discarded event, is recreated.
+        match (events.next(), events.next()) {
+            (
+                Some(profiler::format::Event::Create(start)),
+                Some(profiler::format::Event::Start { id, .. }),
+            ) => {
+                assert!(labels[start.label.id()].starts_with("outer "));
+                assert_eq!(id.0, 0);
+            }
+            other => panic!("events: {:?}", other),
+        }
+        // Only the metadata logged by the retained events is included.
+        let metadata = events.filter_map(|event| match event {
+            profiler::format::Event::Metadata(metadata) => Some(metadata.data.get().to_owned()),
+            _ => None,
+        });
+        let metadata: Vec<_> = metadata.collect();
+        assert_eq!(metadata.len(), retained / 3);
+        assert!(metadata.last().unwrap().contains("2999"));
+        drop(outer);
+    }
 }
 
 // Performance analysis [KW]
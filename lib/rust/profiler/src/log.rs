@@ -2,6 +2,7 @@
 //! - O(1) append (amortized), with low overhead.
 //! - O(1) random-access reads.
 //! - Single-threaded shared mutability.
+//! - Discarding the oldest entries, a block at a time, to bound memory usage.
 //!
 //! # Implementation
 //!
@@ -12,14 +13,16 @@
 //! - No public interface of [`Log`] allows keeping a reference with lifetime derived from the data.
 //! - References taken within [`Log`]'s implementation don't overlap with other references in the
 //!   scope.
+//! - Blocks are only discarded when no reader is active (see [`Log::discard_first_block`]).
 
 use std::cell;
+use std::collections::VecDeque;
 use std::mem;
 
 
 
 /// Allocation unit of events within a [`Log`].
-const BLOCK: usize = 1024;
+pub const BLOCK: usize = 1024;
 
 
 
@@ -31,8 +34,12 @@ const BLOCK: usize = 1024;
 #[derive(Debug)]
 pub struct Log<T> {
     current:   cell::UnsafeCell<Box<[mem::MaybeUninit<T>; BLOCK]>>,
-    completed: cell::UnsafeCell<Vec<Box<[T; BLOCK]>>>,
+    completed: cell::UnsafeCell<VecDeque<Box<[T; BLOCK]>>>,
     len:       cell::Cell<usize>,
+    /// The number of blocks discarded from the beginning of the log.
+    discarded: cell::Cell<usize>,
+    /// The number of active borrows of the log's data.
+    readers:   cell::Cell<usize>,
 }
 
 #[allow(unsafe_code)]
@@ -43,6 +50,8 @@ impl<T> Log<T> {
             current:   cell::UnsafeCell::new(Box::new(mem::MaybeUninit::uninit_array())),
             completed: cell::UnsafeCell::new(Default::default()),
             len:       Default::default(),
+            discarded: Default::default(),
+            readers:   Default::default(),
         }
     }
 
@@ -62,35 +71,43 @@ impl<T> Log<T> {
                 let block =
                     mem::transmute::<Box<[mem::MaybeUninit<T>; BLOCK]>, Box<[T; BLOCK]>>(block);
                 // Add the old block to our collection of completed blocks.
-                (*self.completed.get()).push(block);
+                (*self.completed.get()).push_back(block);
             }
             self.len.set(i1);
         }
     }
 
-    /// Returns the number of entries in the log.
+    /// Returns the number of entries in the log, including the discarded entries.
     #[inline]
     pub fn len(&self) -> usize {
         self.len.get()
     }
 
+    /// Returns the index of the first entry that has not been discarded.
+    #[inline]
+    pub fn first_retained(&self) -> usize {
+        self.discarded.get() * BLOCK
+    }
+
     /// Returns true if the log contains no entries.
     #[inline]
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
 
-    /// Applies a function to each entry in the log.
+    /// Applies a function to each entry in the log that has not been discarded.
     #[allow(unsafe_code)] // Note [Log Safety]
     pub fn for_each<F>(&self, mut f: F)
     where F: FnMut(&T) {
+        let _reader = self.reader();
         unsafe {
-            let blocks = self.len() / BLOCK;
+            let blocks = self.len() / BLOCK - self.discarded.get();
             let n = self.len() % BLOCK;
             for i in 0..blocks {
-                // Safety: The contents of a completed block are never modified, so we can hold a
-                // borrow while calling the function (which may append to the log).
-                let block = &(*self.completed.get())[i];
+                // Safety: The contents of a completed block are never modified, and blocks are not
+                // discarded while a reader is active, so we can hold a borrow while calling the
+                // function (which may append to the log).
+                let block: &[T; BLOCK] = &(*self.completed.get())[i];
                 block.iter().for_each(&mut f);
             }
             // Safety: The elements in the completed portion of the block are never modified, so we
@@ -100,11 +117,50 @@ impl<T> Log<T> {
         }
     }
 
+    /// Applies a function to the entry at the given index, if it exists and has not been
+    /// discarded.
+    pub fn with<U>(&self, index: usize, f: impl FnOnce(&T) -> U) -> Option<U> {
+        let _reader = self.reader();
+        self.get(index).map(f)
+    }
+
+    /// Discard the oldest completed block of entries, after applying a function to each of them.
+    ///
+    /// Returns `false` if there is no completed block to discard, or if the log is being read (in
+    /// which case nothing is discarded).
+    ///
+    /// # Safety
+    ///
+    /// No references to the log's entries obtained through the [`Index`](core::ops::Index)
+    /// implementation may exist.
+    #[allow(unsafe_code)] // Note [Log Safety]
+    pub unsafe fn discard_first_block<F>(&self, f: F) -> bool
+    where F: FnMut(&T) {
+        if self.readers.get() > 0 {
+            return false;
+        }
+        {
+            let _reader = self.reader();
+            match (*self.completed.get()).front() {
+                Some(block) => block.iter().for_each(f),
+                None => return false,
+            }
+        }
+        (*self.completed.get()).pop_front();
+        self.discarded.set(self.discarded.get() + 1);
+        true
+    }
+
+    fn reader(&self) -> Reader {
+        self.readers.set(self.readers.get() + 1);
+        Reader(&self.readers)
+    }
+
     #[inline]
     #[allow(unsafe_code)] // Note [Log Safety]
     fn get(&self, index: usize) -> Option<&T> {
         unsafe {
-            let block_i = index / BLOCK;
+            let block_i = (index / BLOCK).checked_sub(self.discarded.get())?;
             let i = index % BLOCK;
             let blocks = &*self.completed.get();
             if let Some(block) = blocks.get(block_i) {
@@ -118,6 +174,18 @@ impl<T> Log<T> {
     }
 }
 
+
+// === Reader ===
+
+/// Marks the log as being read while alive.
+struct Reader<'a>(&'a cell::Cell<usize>);
+
+impl<'a> Drop for Reader<'a> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() - 1);
+    }
+}
+
 impl<T: Clone> Log<T> {
     /// Return a collection of all entries currently in the log.
     pub fn clone_all<C>(&self) -> C
@@ -177,20 +245,36 @@ impl<T: 'static> ThreadLocalLog<T> {
         self.0.with(|this| this.is_empty())
     }
 
+    /// Return the index of the first entry that has not been discarded.
+    pub fn first_retained(&'static self) -> usize {
+        self.0.with(|this| this.first_retained())
+    }
+
     /// Get the entry at the given index, and pass it to a function; return the result of the
     /// function.
     ///
-    /// Panics if the index is not less than [`len`].
+    /// Panics if the index is not less than [`len`], or the entry has been discarded.
     pub fn get<U>(&'static self, i: usize, f: impl FnOnce(&T) -> U) -> U {
-        self.0.with(|this| f(&this[i]))
+        self.try_get(i, f).unwrap()
     }
 
     /// Get the entry at the given index, and pass it to a function; return the result of the
     /// function.
     ///
-    /// Returns [`None`] if the index is not less than [`len`].
+    /// Returns [`None`] if the index is not less than [`len`], or the entry has been discarded.
     pub fn try_get<U>(&'static self, i: usize, f: impl FnOnce(&T) -> U) -> Option<U> {
-        self.0.with(|this| this.get(i).map(f))
+        self.0.with(|this| this.with(i, f))
+    }
+
+    /// Discard the oldest completed block of entries, after applying a function to each of them.
+    /// See [`Log::discard_first_block`].
+    ///
+    /// # Safety
+    ///
+    /// See [`Log::discard_first_block`].
+    #[allow(unsafe_code)]
+    pub unsafe fn discard_first_block(&'static self, f: impl FnMut(&T)) -> bool {
+        self.0.with(|this| this.discard_first_block(f))
     }
 }
 
@@ -0,0 +1,174 @@
+//! Baselines: the stored results of the previous runs, used for detecting the regressions.
+
+use enso_prelude::*;
+
+use crate::stats::Estimate;
+use crate::stats::Statistic;
+
+use serde::Deserialize;
+use serde::Serialize;
+use std::fmt;
+
+
+
+// ================
+// === Baseline ===
+// ================
+
+/// The estimated durations of all operations.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Baseline {
+    /// The statistic used for the estimates.
+    pub statistic:  Statistic,
+    /// The estimates, in the order of the specification lines.
+    pub operations: Vec<Entry>,
+}
+
+/// The estimated duration of a single operation.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Entry {
+    /// The specification line of the operation.
+    pub line:     String,
+    /// The estimated duration in milliseconds.
+    pub value_ms: f64,
+    /// The lower bound of the confidence interval in milliseconds.
+    pub low_ms:   f64,
+    /// The upper bound of the confidence interval in milliseconds.
+    pub high_ms:  f64,
+    /// The number of the measurements.
+    pub samples:  usize,
+}
+
+impl Entry {
+    /// Constructor.
+    pub fn new(line: impl Into<String>, estimate: &Estimate, samples: usize) -> Self {
+        let milliseconds = |duration: time::Duration| duration.as_seconds_f64() * 1000.0;
+        Self {
+            line: line.into(),
+            value_ms: milliseconds(estimate.value),
+            low_ms: milliseconds(estimate.low),
+            high_ms: milliseconds(estimate.high),
+            samples,
+        }
+    }
+}
+
+
+
+// ==================
+// === Comparison ===
+// ==================
+
+/// The outcome of comparing an operation with its baseline.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// The operation is significantly slower than the baseline, beyond the threshold.
+    Regression,
+    /// The operation is significantly faster than the baseline, beyond the threshold.
+    Improvement,
+    /// The difference is not significant, or it is within the threshold.
+    Unchanged,
+}
+
+/// The comparison of an operation with its baseline.
+#[derive(Clone, Debug)]
+pub struct Comparison {
+    /// The specification line of the operation.
+    pub line:     String,
+    /// The baseline estimate.
+    pub baseline: Entry,
+    /// The current estimate.
+    pub current:  Entry,
+    /// The relative change of the estimated duration.
+    pub change:   f64,
+    /// The outcome of the comparison.
+    pub verdict:  Verdict,
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let baseline = self.baseline.value_ms;
+        let current = self.current.value_ms;
+        let change = self.change * 100.0;
+        let verdict = match self.verdict {
+            Verdict::Regression => "REGRESSION",
+            Verdict::Improvement => "improvement",
+            Verdict::Unchanged => "unchanged",
+        };
+        let truncated_line = self.line.chars().take(80).collect::<String>();
+        write!(f, "{baseline:.1}ms -> {current:.1}ms ({change:+.1}%) {verdict} {truncated_line}")
+    }
+}
+
+/// Compare the operations with the baseline. A change is significant if the confidence intervals
+/// do not overlap. The `threshold` is the smallest relative change reported as a regression or an
+/// improvement. The operations missing in the baseline are skipped.
+pub fn compare(baseline: &Baseline, current: &Baseline, threshold: f64) -> Vec<Comparison> {
+    let mut unmatched = baseline.operations.iter().collect_vec();
+    let mut comparisons = vec![];
+    for entry in &current.operations {
+        let Some(index) = unmatched.iter().position(|base| base.line == entry.line) else {
+            eprintln!("[WARN] No baseline for [{}]", entry.line);
+            continue;
+        };
+        let base = unmatched.remove(index);
+        let change = if base.value_ms > 0.0 {
+            (entry.value_ms - base.value_ms) / base.value_ms
+        } else {
+            0.0
+        };
+        let verdict = if entry.low_ms > base.high_ms && change > threshold {
+            Verdict::Regression
+        } else if entry.high_ms < base.low_ms && change < -threshold {
+            Verdict::Improvement
+        } else {
+            Verdict::Unchanged
+        };
+        let line = entry.line.clone();
+        let (baseline, current) = (base.clone(), entry.clone());
+        comparisons.push(Comparison { line, baseline, current, change, verdict });
+    }
+    comparisons
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(line: &str, low_ms: f64, value_ms: f64, high_ms: f64) -> Entry {
+        Entry { line: line.into(), value_ms, low_ms, high_ms, samples: 10 }
+    }
+
+    #[test]
+    fn detecting_regressions() {
+        let statistic = Statistic::Mean;
+        let operations = vec![
+            entry("a", 9.0, 10.0, 11.0),
+            entry("b", 9.0, 10.0, 11.0),
+            entry("c", 9.0, 10.0, 11.0),
+            entry("d", 9.0, 10.0, 11.0),
+        ];
+        let baseline = Baseline { statistic, operations };
+        let operations = vec![
+            // Significant, beyond the threshold.
+            entry("a", 11.5, 12.0, 12.5),
+            // Beyond the threshold, but the intervals overlap.
+            entry("b", 10.5, 12.0, 13.5),
+            // Significant, but within the threshold.
+            entry("c", 11.1, 11.2, 11.3),
+            entry("d", 7.0, 7.5, 8.0),
+            entry("e", 1.0, 1.0, 1.0),
+        ];
+        let current = Baseline { statistic, operations };
+        let verdicts = compare(&baseline, &current, 0.15).into_iter().map(|c| c.verdict);
+        let expected = [Verdict::Regression, Verdict::Unchanged, Verdict::Unchanged];
+        let expected = expected.into_iter().chain([Verdict::Improvement]);
+        assert_eq!(verdicts.collect_vec(), expected.collect_vec());
+    }
+}
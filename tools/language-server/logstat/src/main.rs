@@ -15,12 +15,19 @@
 #![warn(missing_copy_implementations)]
 #![warn(missing_debug_implementations)]
 
+mod baseline;
+mod stats;
+
 use enso_prelude::*;
 
+use crate::baseline::Baseline;
+use crate::stats::Statistic;
+
 use clap::Parser;
 use clap::ValueHint;
 use lazy_static::lazy_static;
 use regex::Regex;
+use serde_json::Value;
 use std::fmt;
 use std::io::Result;
 use std::path::PathBuf;
@@ -43,17 +50,18 @@ use tokio_stream::StreamExt;
 #[derive(Parser, Debug)]
 #[clap(version, about)]
 struct Args {
-    /// Logfile to analyze.
-    #[clap(value_name = "FILE", value_hint = ValueHint::FilePath)]
-    log: PathBuf,
+    /// Logfiles to analyze, one per benchmark run. The iterations of all runs are analyzed
+    /// together.
+    #[clap(value_name = "FILE", value_hint = ValueHint::FilePath, required = true)]
+    log: Vec<PathBuf>,
 
     /// Specification file.
     #[clap(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
     spec: PathBuf,
 
-    /// Wstest log file.
-    #[clap(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
-    wstest_log: Option<PathBuf>,
+    /// Wstest log files, one per each of the analyzed logfiles.
+    #[clap(long, value_name = "FILE", value_hint = ValueHint::FilePath, multiple_occurrences = true)]
+    wstest_log: Vec<PathBuf>,
 
     /// Number of iterations to skip.
     #[clap(long, default_value = "0")]
@@ -62,6 +70,20 @@ struct Args {
     /// Calculate median instead of mean.
     #[clap(long)]
     median: bool,
+
+    /// Baseline file to compare the results with. The program exits with an error if any
+    /// operation regressed.
+    #[clap(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    baseline: Option<PathBuf>,
+
+    /// File to store the results to, to be used as a baseline of the future runs.
+    #[clap(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    save_baseline: Option<PathBuf>,
+
+    /// The smallest relative slowdown in percents reported as a regression. Smaller changes are
+    /// ignored even when statistically significant.
+    #[clap(long, value_name = "PERCENT", default_value = "5")]
+    threshold: f64,
 }
 
 
@@ -131,20 +153,39 @@ impl Iteration {
 /// Final statistics about benchmarked operation.
 #[derive(Debug)]
 struct Stats {
-    min:  Duration,
-    max:  Duration,
-    avg:  Duration,
-    line: String,
+    min:     Duration,
+    max:     Duration,
+    avg:     stats::Estimate,
+    samples: usize,
+    line:    String,
+}
+
+impl Stats {
+    /// Compute the statistics of the operation's durations in all iterations.
+    fn new(durations: &[Duration], statistic: Statistic, line: String) -> Self {
+        let min = durations.iter().copied().min().unwrap_or(Duration::ZERO);
+        let max = durations.iter().copied().max().unwrap_or(Duration::ZERO);
+        let zero =
+            stats::Estimate { value: Duration::ZERO, low: Duration::ZERO, high: Duration::ZERO };
+        let avg = statistic.estimate(durations).unwrap_or(zero);
+        Stats { min, max, avg, samples: durations.len(), line }
+    }
 }
 
 impl Display for Stats {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let avg_millis = self.avg.whole_milliseconds();
+        let avg_millis = self.avg.value.whole_milliseconds();
+        let low_millis = self.avg.low.whole_milliseconds();
+        let high_millis = self.avg.high.whole_milliseconds();
         let min_millis = self.min.whole_milliseconds();
         let max_millis = self.max.whole_milliseconds();
         let truncated_line = self.line.chars().take(80).collect::<String>();
 
-        write!(f, "{avg_millis}ms [{min_millis}..{max_millis}] {truncated_line}")
+        write!(
+            f,
+            "{avg_millis}ms [{min_millis}..{max_millis}] ({low_millis}..{high_millis}) \
+             {truncated_line}"
+        )
     }
 }
 
@@ -163,6 +204,15 @@ static RE_LOGLINE_TIMESTAMP_CAPTURE_GROUP: usize = 2;
 /// Capture group containing the message part of the log line
 static RE_LOGLINE_MESSAGE_CAPTURE_GROUP: usize = 3;
 
+/// Fields of the JSON log line that may contain the timestamp.
+static JSON_TIMESTAMP_FIELDS: [&str; 3] = ["timestamp", "@timestamp", "time"];
+
+/// Fields of the JSON log line that may contain the message.
+static JSON_MESSAGE_FIELDS: [&str; 2] = ["message", "msg"];
+
+/// Fields of the JSON log line that may contain the logger name.
+static JSON_LOGGER_FIELDS: [&str; 3] = ["logger", "logger_name", "loggerName"];
+
 lazy_static! {
     /// Regex for parsing the log line.
     static ref RE_LOGLINE: Regex = Regex::new(r"\[([\w]+)\] \[([\w\d:.-]+)\] (.*)").unwrap();
@@ -202,18 +252,12 @@ async fn read_logfile(path: &PathBuf, spec: &Spec) -> Result<Vec<Iteration>> {
     let mut current_match = matches.next().expect("Empty spec!");
 
     while let Some(line) = lines.next_line().await? {
-        if let Some(cap) = RE_LOGLINE.captures(line.as_str()) {
-            let groups = (
-                cap.get(RE_LOGLINE_TIMESTAMP_CAPTURE_GROUP),
-                cap.get(RE_LOGLINE_MESSAGE_CAPTURE_GROUP),
-            );
-            match groups {
-                (Some(timestamp), Some(message)) =>
-                    if message.as_str().contains(current_match) {
+        if let Some(entry) = parse_logline(line.as_str()) {
+            match entry {
+                Some((timestamp, message)) =>
+                    if message.contains(current_match) {
                         let duration = Duration::ZERO;
-                        let timestamp =
-                            OffsetDateTime::parse(timestamp.as_str(), &Rfc3339).unwrap();
-                        let line = message.as_str().to_string();
+                        let line = message;
 
                         current_operations.push(Operation { duration, timestamp, line });
 
@@ -226,7 +270,7 @@ async fn read_logfile(path: &PathBuf, spec: &Spec) -> Result<Vec<Iteration>> {
                             current_operations = vec![];
                         }
                     },
-                _ => {
+                None => {
                     eprintln!("[ERR] Invalid log line [{}]", line);
                 }
             }
@@ -236,18 +280,44 @@ async fn read_logfile(path: &PathBuf, spec: &Spec) -> Result<Vec<Iteration>> {
     Ok(iterations)
 }
 
-/// Calcualte median of values.
-fn median<I>(durations_iter: I) -> Duration
-where I: Iterator<Item = Duration> {
-    let mut durations = durations_iter.collect::<Vec<_>>();
-    durations.sort();
-
-    let mid = durations.len() / 2;
-    if durations.len() % 2 == 0 {
-        (durations[mid - 1] + durations[mid]) / 2
-    } else {
-        durations[mid]
+/// Extract the timestamp and the message from the log line in either the text or the JSON format.
+///
+/// Returns `None` for the lines which are not log entries, and `Some(None)` for the invalid ones.
+fn parse_logline(line: &str) -> Option<Option<(OffsetDateTime, String)>> {
+    if line.trim_start().starts_with('{') {
+        let entry = serde_json::from_str::<serde_json::Map<String, Value>>(line).ok()?;
+        return Some(parse_json_logline(&entry));
     }
+    let cap = RE_LOGLINE.captures(line)?;
+    let groups =
+        (cap.get(RE_LOGLINE_TIMESTAMP_CAPTURE_GROUP), cap.get(RE_LOGLINE_MESSAGE_CAPTURE_GROUP));
+    Some(match groups {
+        (Some(timestamp), Some(message)) => OffsetDateTime::parse(timestamp.as_str(), &Rfc3339)
+            .ok()
+            .map(|timestamp| (timestamp, message.as_str().to_string())),
+        _ => None,
+    })
+}
+
+/// Extract the timestamp and the message from the JSON-structured log entry. The timestamp is
+/// either an RFC 3339 string or a number of milliseconds since the Unix epoch. The logger name, if
+/// present, is prepended to the message in the same way as in the text format.
+fn parse_json_logline(entry: &serde_json::Map<String, Value>) -> Option<(OffsetDateTime, String)> {
+    let field = |names: &[&str]| names.iter().find_map(|name| entry.get(*name));
+    let timestamp = match field(&JSON_TIMESTAMP_FIELDS)? {
+        Value::String(timestamp) => OffsetDateTime::parse(timestamp, &Rfc3339).ok()?,
+        Value::Number(millis) => {
+            let nanos = (millis.as_f64()? * 1_000_000.0) as i128;
+            OffsetDateTime::from_unix_timestamp_nanos(nanos).ok()?
+        }
+        _ => return None,
+    };
+    let message = field(&JSON_MESSAGE_FIELDS)?.as_str()?;
+    let message = match field(&JSON_LOGGER_FIELDS).and_then(Value::as_str) {
+        Some(logger) => format!("[{logger}] {message}"),
+        None => message.to_string(),
+    };
+    Some((timestamp, message))
 }
 
 /// Merge iterations from two log files.
@@ -277,62 +347,45 @@ fn calculate_durations(iterations: &mut Vec<Iteration>) {
 }
 
 /// Analyze benchmark results.
-fn analyze_iterations(iterations: &[Iteration], use_median: bool) -> Vec<Stats> {
-    let iterations_len = iterations.len();
+fn analyze_iterations(iterations: &[Iteration], statistic: Statistic) -> Vec<Stats> {
     let operations_len = iterations.first().unwrap().operations.len();
 
     let mut stats = (0..operations_len)
         .map(|operation_index| {
             let current_line = &iterations[0].operations[operation_index].line;
-            let durations = iterations.iter().map(|it| it.operations[operation_index].duration);
-
-            let min = durations.clone().min().unwrap_or(Duration::ZERO);
-            let max = durations.clone().max().unwrap_or(Duration::ZERO);
-            let avg = if use_median {
-                median(durations)
-            } else {
-                durations.sum::<Duration>() / iterations_len as u32
-            };
+            let durations =
+                iterations.iter().map(|it| it.operations[operation_index].duration).collect_vec();
             let line = current_line.to_string();
 
-            Stats { min, max, avg, line }
+            Stats::new(&durations, statistic, line)
         })
         .collect_vec();
 
-    let overall_stats = iterations_average(iterations, &stats);
+    let overall_stats = iterations_average(iterations, statistic);
     stats.push(overall_stats);
 
     stats
 }
 
-/// Calculate the average of all iterations.
-fn iterations_average(ops: &[Iteration], stats: &[Stats]) -> Stats {
-    let min_opt = ops.iter().map(|o| o.total_time()).min();
-    let max_opt = ops.iter().map(|o| o.total_time()).max();
-
-    let min = min_opt.unwrap_or(Duration::ZERO);
-    let max = max_opt.unwrap_or(Duration::ZERO);
-    let avg = stats.iter().map(|s| s.avg).sum();
+/// Calculate the statistics of the total time of all iterations.
+fn iterations_average(ops: &[Iteration], statistic: Statistic) -> Stats {
+    let total_times = ops.iter().map(|o| o.total_time()).collect_vec();
     let line = String::from("Total");
 
-    Stats { min, max, avg, line }
+    Stats::new(&total_times, statistic, line)
 }
 
+/// Read the iterations of a single benchmark run, optionally merged with the wstest iterations.
+async fn read_run(
+    log: &PathBuf,
+    wstest_log: Option<&PathBuf>,
+    spec: &Spec,
+    skip_iterations: usize,
+) -> Result<Vec<Iteration>> {
+    let log_iterations = read_logfile(log, spec).await?;
 
-
-// ============
-// === Main ===
-// ============
-
-#[tokio::main]
-async fn main() -> Result<()> {
-    let args = Args::parse();
-    let spec = read_specs(&args.spec).await?;
-
-    let log_iterations = read_logfile(&args.log, &spec).await?;
-
-    let mut iterations = if let Some(path_buf) = args.wstest_log {
-        let mut ws_iterations = read_logfile(&path_buf, &WSTEST_SPEC).await?;
+    let mut iterations = if let Some(path_buf) = wstest_log {
+        let mut ws_iterations = read_logfile(path_buf, &WSTEST_SPEC).await?;
 
         // skip warmup iterations
         let start_time = &ws_iterations[0].operations[0].timestamp;
@@ -361,15 +414,95 @@ async fn main() -> Result<()> {
     };
 
     // cleanup iterations info before analyzing
-    iterations.drain(..args.skip_iterations);
+    iterations.drain(..skip_iterations);
     calculate_durations(&mut iterations);
 
-    let stats = analyze_iterations(&iterations, args.median);
+    Ok(iterations)
+}
+
+/// Compare the results with the baseline, and report the regressions.
+fn check_baseline(baseline: &Baseline, current: &Baseline, threshold_percent: f64) -> bool {
+    if baseline.statistic != current.statistic {
+        eprintln!(
+            "[ERR] The baseline uses the {:?} statistic, but the results use the {:?} one!",
+            baseline.statistic, current.statistic
+        );
+        process::exit(1);
+    }
+    let comparisons = baseline::compare(baseline, current, threshold_percent / 100.0);
+
+    println!();
+    println!("baseline -> current (change) verdict");
+    for comparison in &comparisons {
+        println!("{}", comparison);
+    }
+
+    comparisons.iter().any(|comparison| comparison.verdict == baseline::Verdict::Regression)
+}
+
+
+
+// ============
+// === Main ===
+// ============
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    let spec = read_specs(&args.spec).await?;
+    let statistic = if args.median { Statistic::Median } else { Statistic::Mean };
+
+    if !args.wstest_log.is_empty() && args.wstest_log.len() != args.log.len() {
+        eprintln!(
+            "[ERR] Unequal number of log files and wstest log files! [{}] vs. [{}]",
+            args.log.len(),
+            args.wstest_log.len()
+        );
+        process::exit(1);
+    }
+
+    let mut iterations = vec![];
+    for (index, log) in args.log.iter().enumerate() {
+        let wstest_log = args.wstest_log.get(index);
+        let run_iterations = read_run(log, wstest_log, &spec, args.skip_iterations).await?;
+        let operations_len = |its: &[Iteration]| its.first().map(|it| it.operations.len());
+        let (expected, actual) = (operations_len(&iterations), operations_len(&run_iterations));
+        if expected.is_some() && actual.is_some() && expected != actual {
+            eprintln!("[ERR] Unequal number of operations in the runs! [{log:?}]");
+            process::exit(1);
+        }
+        iterations.extend(run_iterations);
+    }
 
-    println!("avg [min..max] (of {} records)", iterations.len());
+    if iterations.is_empty() {
+        eprintln!("[ERR] No benchmark iterations found in log files!");
+        process::exit(1);
+    }
+
+    let stats = analyze_iterations(&iterations, statistic);
+
+    println!(
+        "avg [min..max] ({}% confidence interval) (of {} records from {} runs)",
+        stats::CONFIDENCE_LEVEL,
+        iterations.len(),
+        args.log.len()
+    );
     for s in &stats {
         println!("{}", s);
     }
 
+    let entries = stats.iter().map(|s| baseline::Entry::new(&s.line, &s.avg, s.samples));
+    let results = Baseline { statistic, operations: entries.collect() };
+    if let Some(path_buf) = &args.save_baseline {
+        tokio::fs::write(path_buf, serde_json::to_string_pretty(&results)?).await?;
+    }
+    if let Some(path_buf) = &args.baseline {
+        let baseline: Baseline = serde_json::from_str(&tokio::fs::read_to_string(path_buf).await?)?;
+        if check_baseline(&baseline, &results, args.threshold) {
+            eprintln!("[ERR] Performance regression detected!");
+            process::exit(1);
+        }
+    }
+
     Ok(())
 }
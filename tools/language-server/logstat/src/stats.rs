@@ -0,0 +1,144 @@
+//! Estimates of the typical duration of an operation, with their confidence intervals.
+
+use serde::Deserialize;
+use serde::Serialize;
+use time::Duration;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The confidence level of the computed intervals, in percents.
+pub const CONFIDENCE_LEVEL: u32 = 95;
+
+/// The two-sided critical value of the normal distribution for the confidence level.
+const NORMAL_CRITICAL_VALUE: f64 = 1.96;
+
+/// The two-sided critical values of the Student's t-distribution for the confidence level, indexed
+/// by the degrees of freedom minus one. For more degrees of freedom, the normal distribution is a
+/// close approximation.
+const T_CRITICAL_VALUES: [f64; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160,
+    2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056,
+    2.052, 2.048, 2.045, 2.042,
+];
+
+
+
+// =================
+// === Statistic ===
+// =================
+
+/// The statistic estimating the typical duration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Statistic {
+    /// The arithmetic mean, with the confidence interval based on the t-distribution.
+    Mean,
+    /// The median, with the distribution-free confidence interval based on the order statistics.
+    Median,
+}
+
+impl Statistic {
+    /// Estimate the statistic of the samples. Returns `None` if there are no samples.
+    pub fn estimate(self, samples: &[Duration]) -> Option<Estimate> {
+        let mut samples = samples.to_vec();
+        samples.sort();
+        match self {
+            _ if samples.is_empty() => None,
+            Statistic::Mean => Some(mean(&samples)),
+            Statistic::Median => Some(median(&samples)),
+        }
+    }
+}
+
+
+
+// ================
+// === Estimate ===
+// ================
+
+/// The estimated value with its confidence interval.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Estimate {
+    /// The estimated value.
+    pub value: Duration,
+    /// The lower bound of the confidence interval.
+    pub low:   Duration,
+    /// The upper bound of the confidence interval.
+    pub high:  Duration,
+}
+
+fn mean(samples: &[Duration]) -> Estimate {
+    let count = samples.len();
+    let seconds = samples.iter().map(|sample| sample.as_seconds_f64()).collect::<Vec<_>>();
+    let mean = seconds.iter().sum::<f64>() / count as f64;
+    let value = samples.iter().copied().sum::<Duration>() / count as u32;
+    if count < 2 {
+        return Estimate { value, low: value, high: value };
+    }
+    let variance = seconds.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / (count - 1) as f64;
+    let critical_value = T_CRITICAL_VALUES.get(count - 2).copied().unwrap_or(NORMAL_CRITICAL_VALUE);
+    let margin = critical_value * (variance / count as f64).sqrt();
+    let low = Duration::seconds_f64(mean - margin);
+    let high = Duration::seconds_f64(mean + margin);
+    Estimate { value, low, high }
+}
+
+fn median(sorted_samples: &[Duration]) -> Estimate {
+    let count = sorted_samples.len();
+    let mid = count / 2;
+    let value = if count % 2 == 0 {
+        (sorted_samples[mid - 1] + sorted_samples[mid]) / 2
+    } else {
+        sorted_samples[mid]
+    };
+    // The ranks of the order statistics bounding the interval, from the normal approximation of
+    // the binomial distribution of the number of samples below the median.
+    let half_width = NORMAL_CRITICAL_VALUE * (count as f64).sqrt() / 2.0;
+    let low_rank = (count as f64 / 2.0 - half_width).floor().max(1.0) as usize;
+    let high_rank = (1.0 + count as f64 / 2.0 + half_width).ceil().min(count as f64) as usize;
+    let low = sorted_samples[low_rank - 1].min(value);
+    let high = sorted_samples[high_rank - 1].max(value);
+    Estimate { value, low, high }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(values: &[i64]) -> Vec<Duration> {
+        values.iter().map(|value| Duration::milliseconds(*value)).collect()
+    }
+
+    #[test]
+    fn mean_confidence_interval() {
+        let estimate = Statistic::Mean.estimate(&millis(&[10, 12, 14])).unwrap();
+        assert_eq!(estimate.value, Duration::milliseconds(12));
+        // The standard error is 2 / sqrt(3) ms, with 2 degrees of freedom.
+        let margin = 4.303 * 2.0 / 3.0_f64.sqrt();
+        assert!((estimate.low.as_seconds_f64() * 1000.0 - (12.0 - margin)).abs() < 1e-6);
+        assert!((estimate.high.as_seconds_f64() * 1000.0 - (12.0 + margin)).abs() < 1e-6);
+
+        let single = Statistic::Mean.estimate(&millis(&[5])).unwrap();
+        assert_eq!((single.low, single.high), (single.value, single.value));
+        assert!(Statistic::Mean.estimate(&[]).is_none());
+    }
+
+    #[test]
+    fn median_confidence_interval() {
+        let samples = millis(&(1..=100).rev().collect::<Vec<_>>());
+        let estimate = Statistic::Median.estimate(&samples).unwrap();
+        assert_eq!(estimate.value, Duration::microseconds(50_500));
+        assert_eq!(estimate.low, Duration::milliseconds(40));
+        assert_eq!(estimate.high, Duration::milliseconds(61));
+    }
+}
@@ -0,0 +1,360 @@
+//! An in-memory file system backing the content root of a mocked project.
+//!
+//! The file system objects are addressed by their path segments relative to the content root. The
+//! root itself, addressed by an empty path, is always present.
+
+use enso_prelude::*;
+
+use std::collections::BTreeMap;
+use std::time::SystemTime;
+
+
+
+// ==============
+// === Errors ===
+// ==============
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail, PartialEq, Eq)]
+pub enum Error {
+    #[fail(display = "File {} does not exist.", _0)]
+    FileNotFound(String),
+    #[fail(display = "File {} already exists.", _0)]
+    FileExists(String),
+    #[fail(display = "File {} is not a directory.", _0)]
+    NotDirectory(String),
+    #[fail(display = "File {} is not a regular file.", _0)]
+    NotFile(String),
+    #[fail(display = "Cannot overwrite the contents of {}.", _0)]
+    CannotOverwrite(String),
+    #[fail(display = "Cannot move {} into itself.", _0)]
+    MoveIntoItself(String),
+    #[fail(
+        display = "Cannot read beyond the end of {}, which is {} bytes long.",
+        path, file_length
+    )]
+    ReadOutOfBounds { path: String, file_length: u64 },
+}
+
+/// The result of the file system operations.
+pub type Result<T = ()> = std::result::Result<T, Error>;
+
+fn display(path: &[String]) -> String {
+    path.join("/")
+}
+
+
+
+// =============
+// === Entry ===
+// =============
+
+/// The contents of a file system object.
+#[allow(missing_docs)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Contents {
+    Directory,
+    File(Vec<u8>),
+}
+
+/// A file system object.
+#[derive(Clone, Debug)]
+pub struct Entry {
+    /// The file contents, if the entry is a regular file.
+    pub contents:      Contents,
+    /// When the entry was created.
+    pub creation_time: SystemTime,
+    /// When the entry was last modified.
+    pub modified_time: SystemTime,
+}
+
+impl Entry {
+    fn new(contents: Contents) -> Self {
+        let creation_time = SystemTime::now();
+        Self { contents, creation_time, modified_time: creation_time }
+    }
+
+    /// Check if the entry is a directory.
+    pub fn is_directory(&self) -> bool {
+        self.contents == Contents::Directory
+    }
+
+    /// The size of the regular file in bytes. Zero for directories.
+    pub fn byte_size(&self) -> u64 {
+        match &self.contents {
+            Contents::Directory => 0,
+            Contents::File(bytes) => bytes.len() as u64,
+        }
+    }
+}
+
+
+
+// ==================
+// === FileSystem ===
+// ==================
+
+/// An in-memory file system. See the [module documentation](self).
+#[derive(Clone, Debug)]
+pub struct FileSystem {
+    entries: BTreeMap<Vec<String>, Entry>,
+}
+
+impl Default for FileSystem {
+    fn default() -> Self {
+        let entries = iter::once((default(), Entry::new(Contents::Directory))).collect();
+        Self { entries }
+    }
+}
+
+impl FileSystem {
+    /// Create a file system containing only the root directory.
+    pub fn new() -> Self {
+        default()
+    }
+
+    /// Get the file system object under the given path.
+    pub fn entry(&self, path: &[String]) -> Result<&Entry> {
+        self.entries.get(path).ok_or_else(|| Error::FileNotFound(display(path)))
+    }
+
+    /// Check if there is a file system object under the given path.
+    pub fn exists(&self, path: &[String]) -> bool {
+        self.entries.contains_key(path)
+    }
+
+    /// Create a directory, together with all its missing parents. Succeeds if the directory already
+    /// exists.
+    pub fn create_directory(&mut self, path: &[String]) -> Result {
+        self.create_parents(path)?;
+        match self.entries.get(path) {
+            Some(entry) if entry.is_directory() => Ok(()),
+            Some(_) => Err(Error::FileExists(display(path))),
+            None => {
+                self.entries.insert(path.to_vec(), Entry::new(Contents::Directory));
+                Ok(())
+            }
+        }
+    }
+
+    /// Replace the contents of a regular file, creating it and its missing parents if needed.
+    pub fn write(&mut self, path: &[String], contents: Vec<u8>) -> Result {
+        *self.file_mut(path)? = contents;
+        Ok(())
+    }
+
+    /// Read the contents of a regular file.
+    pub fn read(&self, path: &[String]) -> Result<&[u8]> {
+        match &self.entry(path)?.contents {
+            Contents::File(bytes) => Ok(bytes),
+            Contents::Directory => Err(Error::NotFile(display(path))),
+        }
+    }
+
+    /// Write the bytes to a regular file at the given offset, creating the file if needed.
+    ///
+    /// The existing contents after the offset may be replaced only if `overwrite` is set. In such
+    /// case, the file is truncated at the end of the written bytes. If the offset is beyond the end
+    /// of the file, the gap is filled with zeros.
+    pub fn write_bytes(
+        &mut self,
+        path: &[String],
+        byte_offset: u64,
+        overwrite: bool,
+        bytes: &[u8],
+    ) -> Result {
+        let file = self.file_mut(path)?;
+        let offset = byte_offset as usize;
+        if offset < file.len() && !overwrite {
+            Err(Error::CannotOverwrite(display(path)))
+        } else {
+            file.resize(offset, 0);
+            file.extend_from_slice(bytes);
+            Ok(())
+        }
+    }
+
+    /// Read at most `length` bytes of a regular file, starting at the given offset. Fails if the
+    /// offset is beyond the end of the file.
+    pub fn read_bytes(&self, path: &[String], byte_offset: u64, length: u64) -> Result<&[u8]> {
+        let bytes = self.read(path)?;
+        let file_length = bytes.len() as u64;
+        if byte_offset > file_length {
+            Err(Error::ReadOutOfBounds { path: display(path), file_length })
+        } else {
+            let end = byte_offset.saturating_add(length).min(file_length);
+            Ok(&bytes[byte_offset as usize..end as usize])
+        }
+    }
+
+    /// Remove the file system object, together with all its contents. Removing the root clears
+    /// the whole file system.
+    pub fn delete(&mut self, path: &[String]) -> Result {
+        self.entry(path)?;
+        let removed = self.subtree(path).map(|(path, _)| path.clone()).collect_vec();
+        for removed in removed {
+            if !removed.is_empty() {
+                self.entries.remove(&removed);
+            }
+        }
+        Ok(())
+    }
+
+    /// Copy the file system object, together with all its contents. Overwrites the target.
+    pub fn copy(&mut self, from: &[String], to: &[String]) -> Result {
+        self.entry(from)?;
+        if to.starts_with(from) {
+            return Err(Error::MoveIntoItself(display(from)));
+        }
+        self.create_parents(to)?;
+        if self.exists(to) {
+            self.delete(to)?;
+        }
+        let copied = self
+            .subtree(from)
+            .map(|(path, entry)| {
+                let target = to.iter().chain(&path[from.len()..]).cloned().collect_vec();
+                (target, entry.clone())
+            })
+            .collect_vec();
+        self.entries.extend(copied);
+        Ok(())
+    }
+
+    /// Move the file system object to the path which is not yet occupied.
+    pub fn move_to(&mut self, from: &[String], to: &[String]) -> Result {
+        if self.exists(to) {
+            Err(Error::FileExists(display(to)))
+        } else {
+            self.copy(from, to)?;
+            self.delete(from)
+        }
+    }
+
+    /// The paths of the objects placed directly in the directory.
+    pub fn list(&self, path: &[String]) -> Result<Vec<Vec<String>>> {
+        if !self.entry(path)?.is_directory() {
+            return Err(Error::NotDirectory(display(path)));
+        }
+        let children = self.subtree(path).filter(|(child, _)| child.len() == path.len() + 1);
+        Ok(children.map(|(child, _)| child.clone()).collect())
+    }
+
+    /// All the objects in the subtree rooted in the given path, including the root, ordered by
+    /// their paths.
+    pub fn subtree<'a>(
+        &'a self,
+        path: &'a [String],
+    ) -> impl Iterator<Item = (&'a Vec<String>, &'a Entry)> + 'a {
+        let descendants = self.entries.range(path.to_vec()..);
+        descendants.take_while(move |(descendant, _)| descendant.starts_with(path))
+    }
+
+    fn create_parents(&mut self, path: &[String]) -> Result {
+        for length in 0..path.len() {
+            let parent = &path[..length];
+            match self.entries.get(parent) {
+                Some(entry) if !entry.is_directory() =>
+                    return Err(Error::NotDirectory(display(parent))),
+                Some(_) => {}
+                None => {
+                    self.entries.insert(parent.to_vec(), Entry::new(Contents::Directory));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn file_mut(&mut self, path: &[String]) -> Result<&mut Vec<u8>> {
+        self.create_parents(path)?;
+        let entry = self.entries.entry(path.to_vec());
+        let entry = entry.or_insert_with(|| Entry::new(Contents::File(default())));
+        entry.modified_time = SystemTime::now();
+        match &mut entry.contents {
+            Contents::File(bytes) => Ok(bytes),
+            Contents::Directory => Err(Error::NotFile(display(path))),
+        }
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(path: &str) -> Vec<String> {
+        path.split('/').filter(|segment| !segment.is_empty()).map(Into::into).collect()
+    }
+
+    #[test]
+    fn writing_and_reading_files() {
+        let mut fs = FileSystem::new();
+        fs.write(&path("src/Main.enso"), b"main = 42".to_vec()).unwrap();
+        assert!(fs.entry(&path("src")).unwrap().is_directory());
+        assert_eq!(fs.read(&path("src/Main.enso")).unwrap(), b"main = 42");
+        assert_eq!(fs.read(&path("src")), Err(Error::NotFile("src".into())));
+        assert_eq!(fs.read(&path("Main.enso")), Err(Error::FileNotFound("Main.enso".into())));
+        let error = fs.write(&path("src/Main.enso/file"), default());
+        assert_eq!(error, Err(Error::NotDirectory("src/Main.enso".into())));
+        assert_eq!(fs.list(&path("")).unwrap(), vec![path("src")]);
+        assert_eq!(fs.list(&path("src")).unwrap(), vec![path("src/Main.enso")]);
+    }
+
+    #[test]
+    fn copying_moving_and_deleting() {
+        let mut fs = FileSystem::new();
+        fs.write(&path("a/b/file"), b"contents".to_vec()).unwrap();
+        fs.create_directory(&path("a/c")).unwrap();
+        fs.write(&path("ab"), default()).unwrap();
+
+        fs.copy(&path("a"), &path("x/y")).unwrap();
+        assert_eq!(fs.read(&path("x/y/b/file")).unwrap(), b"contents");
+        assert!(fs.entry(&path("x/y/c")).unwrap().is_directory());
+        assert_eq!(fs.copy(&path("a"), &path("a/d")), Err(Error::MoveIntoItself("a".into())));
+
+        assert_eq!(fs.move_to(&path("a"), &path("ab")), Err(Error::FileExists("ab".into())));
+        fs.move_to(&path("a"), &path("z")).unwrap();
+        assert!(!fs.exists(&path("a")));
+        assert!(!fs.exists(&path("a/b/file")));
+        assert_eq!(fs.read(&path("z/b/file")).unwrap(), b"contents");
+        assert!(fs.exists(&path("ab")));
+
+        fs.delete(&path("")).unwrap();
+        assert!(fs.list(&path("")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn writing_and_reading_bytes() {
+        let mut fs = FileSystem::new();
+        let file = path("data/file");
+        fs.write_bytes(&file, 0, false, b"Hello").unwrap();
+        fs.write_bytes(&file, 5, false, b", world!").unwrap();
+        assert_eq!(fs.read(&file).unwrap(), b"Hello, world!");
+        let error = fs.write_bytes(&file, 5, false, b"!");
+        assert_eq!(error, Err(Error::CannotOverwrite("data/file".into())));
+        fs.write_bytes(&file, 5, true, b"!").unwrap();
+        assert_eq!(fs.read(&file).unwrap(), b"Hello!");
+
+        assert_eq!(fs.read_bytes(&file, 1, 3).unwrap(), b"ell");
+        assert_eq!(fs.read_bytes(&file, 4, 10).unwrap(), b"o!");
+        assert_eq!(fs.read_bytes(&file, 6, 0).unwrap(), b"");
+        assert_eq!(fs.read_bytes(&file, 6, 1).unwrap(), b"");
+    }
+
+    #[test]
+    fn reading_bytes_out_of_bounds() {
+        let mut fs = FileSystem::new();
+        let file = path("data/file");
+        fs.write(&file, b"Hello!".to_vec()).unwrap();
+        let out_of_bounds =
+            || Err(Error::ReadOutOfBounds { path: "data/file".into(), file_length: 6 });
+        assert_eq!(fs.read_bytes(&file, 7, 0), out_of_bounds());
+        assert_eq!(fs.read_bytes(&file, 7, 1), out_of_bounds());
+        assert_eq!(fs.read_bytes(&file, u64::MAX, u64::MAX), out_of_bounds());
+    }
+}
@@ -0,0 +1,125 @@
+//! Fixtures describing the contents of the created projects and the scripted results of the
+//! program execution.
+//!
+//! The fixtures are loaded from a directory with the following optional entries:
+//! - `project/` - the files of every created project. If missing, the projects contain only the
+//!   `src/Main.enso` module.
+//! - `expression-updates.json` - an array of batches of `executionContext/expressionUpdates`
+//!   notification parameters, sent after every change of an execution context stack.
+//! - `visualisations.json` - an object mapping the expression ids to the JSON values sent as the
+//!   visualisation data of these expressions.
+
+use enso_prelude::*;
+
+use crate::file_system::FileSystem;
+
+use engine_protocol::language_server::ExpressionId;
+use engine_protocol::language_server::ExpressionUpdate;
+use serde::de::DeserializeOwned;
+use std::fs;
+use std::path::Path;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The directory with the files of every created project.
+pub const PROJECT_DIRECTORY: &str = "project";
+
+/// The file with the batches of expression updates.
+pub const EXPRESSION_UPDATES_FILE: &str = "expression-updates.json";
+
+/// The file with the visualisation data.
+pub const VISUALISATIONS_FILE: &str = "visualisations.json";
+
+/// The path of the main module of the project.
+pub const MAIN_MODULE_PATH: [&str; 2] = ["src", "Main.enso"];
+
+/// The contents of the main module of the project if no project files are given.
+pub const DEFAULT_MAIN_MODULE: &str = r#"from Standard.Base import all
+
+main = IO.println "Hello, World!"
+"#;
+
+
+
+// ================
+// === Fixtures ===
+// ================
+
+/// The fixtures of the mock backend. See the [module documentation](self).
+#[derive(Clone, Debug)]
+pub struct Fixtures {
+    /// The paths relative to the project root, and the contents of the project files.
+    pub project_files:      Vec<(Vec<String>, Vec<u8>)>,
+    /// The batches of the expression updates.
+    pub expression_updates: Vec<Vec<ExpressionUpdate>>,
+    /// The serialized visualisation data of the expressions.
+    pub visualisations:     HashMap<ExpressionId, Vec<u8>>,
+}
+
+impl Default for Fixtures {
+    fn default() -> Self {
+        let main_path = MAIN_MODULE_PATH.iter().map(|segment| segment.to_string()).collect();
+        let project_files = vec![(main_path, DEFAULT_MAIN_MODULE.as_bytes().to_vec())];
+        Self { project_files, expression_updates: default(), visualisations: default() }
+    }
+}
+
+impl Fixtures {
+    /// Load the fixtures from the directory.
+    pub fn load(directory: &Path) -> FallibleResult<Self> {
+        let defaults = Self::default();
+        let project = directory.join(PROJECT_DIRECTORY);
+        let project_files =
+            if project.is_dir() { read_tree(&project, &[])? } else { defaults.project_files };
+        let expression_updates = read_json(&directory.join(EXPRESSION_UPDATES_FILE))?;
+        let visualisations: HashMap<ExpressionId, serde_json::Value> =
+            read_json(&directory.join(VISUALISATIONS_FILE))?;
+        let visualisations = visualisations
+            .into_iter()
+            .map(|(expression_id, data)| (expression_id, data.to_string().into_bytes()))
+            .collect();
+        Ok(Self { project_files, expression_updates, visualisations })
+    }
+
+    /// Create the file system of a new project.
+    pub fn project_file_system(&self) -> FileSystem {
+        let mut file_system = FileSystem::new();
+        for (path, contents) in &self.project_files {
+            if let Err(error) = file_system.write(path, contents.clone()) {
+                warn!("Cannot create the project file: {error}");
+            }
+        }
+        file_system
+    }
+}
+
+/// Read all the files in the directory, recursively. The returned paths are prefixed with
+/// `prefix`.
+fn read_tree(directory: &Path, prefix: &[String]) -> FallibleResult<Vec<(Vec<String>, Vec<u8>)>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let path = prefix.iter().cloned().chain(iter::once(name)).collect_vec();
+        if entry.file_type()?.is_dir() {
+            files.extend(read_tree(&entry.path(), &path)?);
+        } else {
+            files.push((path, fs::read(entry.path())?));
+        }
+    }
+    Ok(files)
+}
+
+/// Read the JSON file. Returns the default value if the file does not exist.
+fn read_json<T: DeserializeOwned + Default>(path: &Path) -> FallibleResult<T> {
+    if path.exists() {
+        let contents = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    } else {
+        Ok(default())
+    }
+}
@@ -0,0 +1,918 @@
+//! A mocked language server serving a single project, with its files kept in memory.
+//!
+//! The execution of the program is scripted: after every change of an execution context stack,
+//! the client receives the expression updates and the visualisation data defined in the
+//! [fixtures](crate::fixtures).
+
+use enso_prelude::*;
+
+use crate::file_system::FileSystem;
+use crate::fixtures::Fixtures;
+use crate::rpc::code;
+use crate::rpc::error;
+use crate::rpc::MethodResult;
+use crate::server;
+
+use engine_protocol::binary::message::VisualisationContext;
+use engine_protocol::language_server::response;
+use engine_protocol::language_server::CapabilityRegistration;
+use engine_protocol::language_server::ContentRoot;
+use engine_protocol::language_server::ContextId;
+use engine_protocol::language_server::ExpressionId;
+use engine_protocol::language_server::ExpressionUpdates;
+use engine_protocol::language_server::FileAttributes;
+use engine_protocol::language_server::FileEdit;
+use engine_protocol::language_server::FileSystemObject;
+use engine_protocol::language_server::Notification;
+use engine_protocol::language_server::Path;
+use engine_protocol::language_server::Position;
+use engine_protocol::language_server::RegisterOptions;
+use engine_protocol::language_server::StackItem;
+use engine_protocol::language_server::SuggestionEntryType;
+use engine_protocol::language_server::TextEdit;
+use engine_protocol::language_server::VisualisationConfiguration;
+use engine_protocol::project_manager::IpWithSocket;
+use engine_protocol::types::Sha3_224;
+use engine_protocol::types::UTCDateTime;
+use json_rpc::dispatcher::WeakDispatcher;
+use json_rpc::Dispatcher;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::hash_map;
+use std::time::SystemTime;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+
+// ==============
+// === Export ===
+// ==============
+
+pub mod binary;
+
+
+
+// ===============
+// === Methods ===
+// ===============
+
+methods! {
+    InitProtocolConnection = "session/initProtocolConnection" (client_id: Uuid)
+        -> response::InitProtocolConnection;
+    AcquireCapability = "capability/acquire" (method: String, register_options: RegisterOptions)
+        -> ();
+    ReleaseCapability = "capability/release" (registration: CapabilityRegistration) -> ();
+
+    WriteFile = "file/write" (path: Path, contents: String) -> ();
+    ReadFile = "file/read" (path: Path) -> response::Read;
+    CreateFile = "file/create" (object: FileSystemObject) -> ();
+    DeleteFile = "file/delete" (path: Path) -> ();
+    CopyFile = "file/copy" (from: Path, to: Path) -> ();
+    MoveFile = "file/move" (from: Path, to: Path) -> ();
+    FileExists = "file/exists" (path: Path) -> response::FileExists;
+    FileList = "file/list" (path: Path) -> response::FileList;
+    FileInfo = "file/info" (path: Path) -> response::FileInfo;
+    FileChecksum = "file/checksum" (path: Path) -> response::FileChecksum;
+
+    OpenTextFile = "text/openFile" (path: Path) -> response::OpenTextFile;
+    CloseTextFile = "text/closeFile" (path: Path) -> ();
+    SaveTextFile = "text/save" (path: Path, current_version: Sha3_224) -> ();
+    ApplyTextFileEdit = "text/applyEdit" (edit: FileEdit) -> ();
+
+    CreateExecutionContext = "executionContext/create" (context_id: Option<ContextId>)
+        -> response::CreateExecutionContext;
+    DestroyExecutionContext = "executionContext/destroy" (context_id: ContextId) -> ();
+    PushToExecutionContext = "executionContext/push" (context_id: ContextId, stack_item: StackItem)
+        -> ();
+    PopFromExecutionContext = "executionContext/pop" (context_id: ContextId) -> ();
+    Recompute = "executionContext/recompute" (
+        context_id: ContextId,
+        invalidated_expressions: Option<serde_json::Value>,
+    ) -> ();
+    Interrupt = "executionContext/interrupt" (context_id: ContextId) -> ();
+    AttachVisualisation = "executionContext/attachVisualisation" (
+        visualisation_id: Uuid,
+        expression_id: ExpressionId,
+        visualisation_config: VisualisationConfiguration,
+    ) -> ();
+    DetachVisualisation = "executionContext/detachVisualisation" (
+        context_id: ContextId,
+        visualisation_id: Uuid,
+        expression_id: ExpressionId,
+    ) -> ();
+    ModifyVisualisation = "executionContext/modifyVisualisation" (
+        visualisation_id: Uuid,
+        visualisation_config: VisualisationConfiguration,
+    ) -> ();
+    ExecuteExpression = "executionContext/executeExpression" (
+        visualisation_id: Uuid,
+        expression_id: ExpressionId,
+        visualisation_config: VisualisationConfiguration,
+    ) -> ();
+    GetComponentGroups = "executionContext/getComponentGroups" (context_id: ContextId)
+        -> response::GetComponentGroups;
+
+    GetSuggestionsDatabase = "search/getSuggestionsDatabase" () -> response::GetSuggestionDatabase;
+    GetSuggestionsDatabaseVersion = "search/getSuggestionsDatabaseVersion" ()
+        -> response::GetSuggestionDatabaseVersion;
+    Completion = "search/completion" (
+        file: Path,
+        position: Position,
+        self_type: Option<String>,
+        return_type: Option<String>,
+        tags: Option<Vec<SuggestionEntryType>>,
+    ) -> response::Completion;
+}
+
+
+
+// =============
+// === Model ===
+// =============
+
+/// A text file opened by the clients.
+#[derive(Clone, Debug)]
+struct OpenFile {
+    /// The file contents, including the unsaved edits.
+    buffer:  String,
+    clients: HashSet<Uuid>,
+    /// The client holding the `text/canEdit` capability.
+    writer:  Option<Uuid>,
+}
+
+impl OpenFile {
+    fn version(&self) -> Sha3_224 {
+        Sha3_224::new(self.buffer.as_bytes())
+    }
+
+    fn check_writer(&self, client_id: Uuid, path: &Path) -> MethodResult<()> {
+        if self.writer == Some(client_id) {
+            Ok(())
+        } else {
+            Err(error(code::WRITE_DENIED, format!("The client cannot edit {path}.")))
+        }
+    }
+
+    fn check_version(&self, version: &Sha3_224) -> MethodResult<()> {
+        let current = self.version();
+        if &current == version {
+            Ok(())
+        } else {
+            let message = format!("The version is {version}, while {current} was expected.");
+            Err(error(code::INVALID_VERSION, message))
+        }
+    }
+}
+
+/// A visualisation attached to an expression.
+#[derive(Clone, Copy, Debug)]
+struct Visualisation {
+    /// The client receiving the visualisation updates.
+    client_id:     Uuid,
+    expression_id: ExpressionId,
+}
+
+#[derive(Debug)]
+struct ExecutionContext {
+    /// The connection of the client which created the context, receiving the notifications.
+    notifier:       WeakDispatcher,
+    stack:          Vec<StackItem>,
+    visualisations: HashMap<Uuid, Visualisation>,
+}
+
+#[derive(Debug, Default)]
+struct Model {
+    open_files:         HashMap<Vec<String>, OpenFile>,
+    contexts:           HashMap<ContextId, ExecutionContext>,
+    /// The binary connections by the ids of the clients.
+    binary_connections: HashMap<Uuid, server::Sender>,
+}
+
+
+
+// ======================
+// === LanguageServer ===
+// ======================
+
+/// The state of the language server, shared by all its connections.
+#[derive(Clone, Debug)]
+pub struct LanguageServer {
+    root_id:     Uuid,
+    file_system: Rc<RefCell<FileSystem>>,
+    fixtures:    Rc<Fixtures>,
+    model:       Rc<RefCell<Model>>,
+}
+
+impl LanguageServer {
+    /// Create the language server of the project with the given files.
+    pub fn new(file_system: Rc<RefCell<FileSystem>>, fixtures: Rc<Fixtures>) -> Self {
+        let root_id = Uuid::new_v4();
+        Self { root_id, file_system, fixtures, model: default() }
+    }
+
+    /// Start listening for the connections on free ports of the given host.
+    pub async fn start(&self, host: &str) -> FallibleResult<Endpoints> {
+        let json_listener = server::bind(host, 0).await?;
+        let binary_listener = server::bind(host, 0).await?;
+        let json_address = address(host, &json_listener)?;
+        let binary_address = address(host, &binary_listener)?;
+        let json_server = self.clone();
+        let binary_server = self.clone();
+        let serve_json = server::serve(json_listener, move |connection| {
+            json_server.connect(connection);
+        });
+        let serve_binary = server::serve(binary_listener, move |connection| {
+            binary::connect(&binary_server, connection);
+        });
+        let tasks =
+            vec![tokio::task::spawn_local(serve_json), tokio::task::spawn_local(serve_binary)];
+        Ok(Endpoints { json_address, binary_address, tasks })
+    }
+
+    /// Serve the JSON-RPC protocol on the connection.
+    pub fn connect(&self, connection: server::Connection) {
+        info!("Language server text connection from {}.", connection.peer());
+        let dispatcher = Dispatcher::new(connection);
+        let session = Rc::new(Session::new(self.clone(), dispatcher.downgrade()));
+        register(&dispatcher, &session, Session::init_protocol_connection);
+        register(&dispatcher, &session, Session::acquire_capability);
+        register(&dispatcher, &session, Session::release_capability);
+        register(&dispatcher, &session, Session::write_file);
+        register(&dispatcher, &session, Session::read_file);
+        register(&dispatcher, &session, Session::create_file);
+        register(&dispatcher, &session, Session::delete_file);
+        register(&dispatcher, &session, Session::copy_file);
+        register(&dispatcher, &session, Session::move_file);
+        register(&dispatcher, &session, Session::file_exists);
+        register(&dispatcher, &session, Session::file_list);
+        register(&dispatcher, &session, Session::file_info);
+        register(&dispatcher, &session, Session::file_checksum);
+        register(&dispatcher, &session, Session::open_text_file);
+        register(&dispatcher, &session, Session::close_text_file);
+        register(&dispatcher, &session, Session::save_text_file);
+        register(&dispatcher, &session, Session::apply_text_file_edit);
+        register(&dispatcher, &session, Session::create_execution_context);
+        register(&dispatcher, &session, Session::destroy_execution_context);
+        register(&dispatcher, &session, Session::push_to_execution_context);
+        register(&dispatcher, &session, Session::pop_from_execution_context);
+        register(&dispatcher, &session, Session::recompute);
+        register(&dispatcher, &session, Session::interrupt);
+        register(&dispatcher, &session, Session::attach_visualisation);
+        register(&dispatcher, &session, Session::detach_visualisation);
+        register(&dispatcher, &session, Session::modify_visualisation);
+        register(&dispatcher, &session, Session::execute_expression);
+        register(&dispatcher, &session, Session::get_component_groups);
+        register(&dispatcher, &session, Session::get_suggestions_database);
+        register(&dispatcher, &session, Session::get_suggestions_database_version);
+        register(&dispatcher, &session, Session::completion);
+        let runner = dispatcher.runner();
+        tokio::task::spawn_local(async move {
+            runner.await;
+            session.disconnect();
+            drop(dispatcher);
+        });
+    }
+
+    fn content_root(&self) -> ContentRoot {
+        ContentRoot::Project { id: self.root_id }
+    }
+
+    /// The path segments relative to the project root.
+    fn segments<'a>(&self, path: &'a Path) -> MethodResult<&'a [String]> {
+        if path.root_id == self.root_id {
+            Ok(&path.segments)
+        } else {
+            let message = format!("Content root {} does not exist.", path.root_id);
+            Err(error(code::CONTENT_ROOT_NOT_FOUND, message))
+        }
+    }
+
+    fn file_system_object(&self, segments: &[String]) -> MethodResult<FileSystemObject> {
+        let is_directory = self.file_system.borrow().entry(segments)?.is_directory();
+        let (name, parent) = match segments.split_last() {
+            Some((name, parent)) => (name.clone(), parent),
+            None => (default(), segments),
+        };
+        let path = Path::new(self.root_id, parent);
+        Ok(if is_directory {
+            FileSystemObject::Directory { name, path }
+        } else {
+            FileSystemObject::File { name, path }
+        })
+    }
+
+    /// Send the scripted results of the execution: the expression updates followed by the data of
+    /// all visualisations attached in the context. The results are sent after the reply to the
+    /// currently handled request.
+    fn execute(&self, context_id: ContextId) {
+        let server = self.clone();
+        tokio::task::spawn_local(async move {
+            let model = server.model.borrow();
+            if let Some(context) = model.contexts.get(&context_id) {
+                if let Some(notifier) = context.notifier.upgrade() {
+                    for updates in &server.fixtures.expression_updates {
+                        let updates = updates.clone();
+                        let updates = ExpressionUpdates { context_id, updates };
+                        notify(&notifier, Notification::ExpressionUpdates(updates));
+                    }
+                    notify(&notifier, Notification::ExecutionComplete { context_id });
+                }
+                for (visualisation_id, visualisation) in &context.visualisations {
+                    let context =
+                        visualisation_context(context_id, *visualisation_id, visualisation);
+                    server.send_visualisation_update(visualisation.client_id, context);
+                }
+            }
+        });
+    }
+
+    /// Send the visualisation data after the reply to the currently handled request.
+    fn update_visualisation(&self, client_id: Uuid, context: VisualisationContext) {
+        let server = self.clone();
+        tokio::task::spawn_local(async move {
+            server.send_visualisation_update(client_id, context);
+        });
+    }
+
+    fn send_visualisation_update(&self, client_id: Uuid, context: VisualisationContext) {
+        match self.fixtures.visualisations.get(&context.expression_id) {
+            Some(data) => match self.model.borrow().binary_connections.get(&client_id) {
+                Some(sender) => binary::send_visualisation_update(sender, context, data.clone()),
+                None =>
+                    warn!("Client {client_id} has no binary connection for visualisation data."),
+            },
+            None => info!("No visualisation data for expression {}.", context.expression_id),
+        }
+    }
+}
+
+fn register<M>(
+    dispatcher: &Dispatcher,
+    session: &Rc<Session>,
+    handler: fn(&Session, M) -> MethodResult<M::Returned>,
+) where
+    M: json_rpc::RemoteMethodCall + DeserializeOwned + 'static,
+    M::Returned: Serialize + 'static,
+{
+    let session = session.clone();
+    dispatcher.register_method(move |params| handler(&session, params));
+}
+
+fn notify(notifier: &Dispatcher, notification: Notification) {
+    if let Err(error) = notifier.send_notification(notification) {
+        warn!("Failed to send a notification: {error}");
+    }
+}
+
+fn visualisation_context(
+    context_id: ContextId,
+    visualization_id: Uuid,
+    visualisation: &Visualisation,
+) -> VisualisationContext {
+    let expression_id = visualisation.expression_id;
+    VisualisationContext { visualization_id, context_id, expression_id }
+}
+
+fn address(host: &str, listener: &TcpListener) -> FallibleResult<IpWithSocket> {
+    let port = listener.local_addr()?.port();
+    Ok(IpWithSocket { host: host.into(), port })
+}
+
+fn time(time: SystemTime) -> UTCDateTime {
+    chrono::DateTime::<chrono::Utc>::from(time).into()
+}
+
+
+
+// =================
+// === Endpoints ===
+// =================
+
+/// The endpoints of a running language server. The server stops accepting new connections once
+/// the endpoints are dropped.
+#[derive(Debug)]
+pub struct Endpoints {
+    /// The address of the JSON-RPC endpoint.
+    pub json_address:   IpWithSocket,
+    /// The address of the binary protocol endpoint.
+    pub binary_address: IpWithSocket,
+    tasks:              Vec<JoinHandle<()>>,
+}
+
+impl Drop for Endpoints {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+
+
+// ===============
+// === Session ===
+// ===============
+
+/// The state of a single JSON-RPC connection.
+#[derive(Debug)]
+struct Session {
+    server:    LanguageServer,
+    notifier:  WeakDispatcher,
+    client_id: Cell<Option<Uuid>>,
+}
+
+impl Session {
+    fn new(server: LanguageServer, notifier: WeakDispatcher) -> Self {
+        Self { server, notifier, client_id: default() }
+    }
+
+    fn client_id(&self) -> MethodResult<Uuid> {
+        let not_initialised = || error(code::SESSION_NOT_INITIALISED, "Session not initialised.");
+        self.client_id.get().ok_or_else(not_initialised)
+    }
+
+    /// Release the files opened by the client.
+    fn disconnect(&self) {
+        if let Some(client_id) = self.client_id.get() {
+            info!("Client {client_id} disconnected.");
+            self.server.model.borrow_mut().open_files.retain(|_, file| {
+                file.clients.remove(&client_id);
+                if file.writer == Some(client_id) {
+                    file.writer = None;
+                }
+                !file.clients.is_empty()
+            });
+        }
+    }
+
+    fn with_open_file<R>(
+        &self,
+        path: &Path,
+        f: impl FnOnce(&mut OpenFile, Uuid) -> MethodResult<R>,
+    ) -> MethodResult<R> {
+        let client_id = self.client_id()?;
+        let segments = self.server.segments(path)?;
+        let mut model = self.server.model.borrow_mut();
+        let file = model.open_files.get_mut(segments);
+        match file.filter(|file| file.clients.contains(&client_id)) {
+            Some(file) => f(file, client_id),
+            None => Err(error(code::FILE_NOT_OPENED, format!("File {path} is not opened."))),
+        }
+    }
+
+    fn with_context<R>(
+        &self,
+        context_id: ContextId,
+        f: impl FnOnce(&mut ExecutionContext) -> MethodResult<R>,
+    ) -> MethodResult<R> {
+        self.client_id()?;
+        let mut model = self.server.model.borrow_mut();
+        match model.contexts.get_mut(&context_id) {
+            Some(context) => f(context),
+            None => {
+                let message = format!("Execution context {context_id} does not exist.");
+                Err(error(code::CONTEXT_NOT_FOUND, message))
+            }
+        }
+    }
+
+
+    // === Session and Capabilities ===
+
+    fn init_protocol_connection(
+        &self,
+        params: InitProtocolConnection,
+    ) -> MethodResult<response::InitProtocolConnection> {
+        if self.client_id.get().is_some() {
+            Err(error(code::SESSION_ALREADY_INITIALISED, "Session already initialised."))
+        } else {
+            info!("Client {} initialised the session.", params.client_id);
+            self.client_id.set(Some(params.client_id));
+            Ok(response::InitProtocolConnection { content_roots: vec![self.server.content_root()] })
+        }
+    }
+
+    fn acquire_capability(&self, params: AcquireCapability) -> MethodResult<()> {
+        self.client_id()?;
+        match (params.method.as_str(), &params.register_options) {
+            ("text/canEdit", RegisterOptions::Path { path }) =>
+                self.with_open_file(path, |file, client_id| match file.writer {
+                    Some(writer) if writer != client_id => {
+                        let message = format!("Another client is editing {path}.");
+                        Err(error(code::ACCESS_DENIED, message))
+                    }
+                    _ => {
+                        file.writer = Some(client_id);
+                        Ok(())
+                    }
+                }),
+            _ => Ok(()),
+        }
+    }
+
+    fn release_capability(&self, params: ReleaseCapability) -> MethodResult<()> {
+        self.client_id()?;
+        let registration = &params.registration;
+        match (registration.method.as_str(), &registration.register_options) {
+            ("text/canEdit", RegisterOptions::Path { path }) =>
+                self.with_open_file(path, |file, client_id| {
+                    if file.writer == Some(client_id) {
+                        file.writer = None;
+                    }
+                    Ok(())
+                }),
+            _ => Ok(()),
+        }
+    }
+
+
+    // === File System ===
+
+    fn write_file(&self, params: WriteFile) -> MethodResult<()> {
+        self.client_id()?;
+        let segments = self.server.segments(&params.path)?;
+        let contents = params.contents.into_bytes();
+        Ok(self.server.file_system.borrow_mut().write(segments, contents)?)
+    }
+
+    fn read_file(&self, params: ReadFile) -> MethodResult<response::Read> {
+        self.client_id()?;
+        let segments = self.server.segments(&params.path)?;
+        let contents = self.server.file_system.borrow().read(segments)?.to_vec();
+        let contents = String::from_utf8(contents).map_err(|e| error(code::CANNOT_DECODE, e))?;
+        Ok(response::Read { contents })
+    }
+
+    fn create_file(&self, params: CreateFile) -> MethodResult<()> {
+        self.client_id()?;
+        let path = Path::from(&params.object);
+        let segments = self.server.segments(&path)?;
+        let mut file_system = self.server.file_system.borrow_mut();
+        match params.object {
+            FileSystemObject::Directory { .. } => Ok(file_system.create_directory(segments)?),
+            FileSystemObject::File { .. } if file_system.exists(segments) =>
+                Err(error(code::FILE_EXISTS, format!("File {path} already exists."))),
+            FileSystemObject::File { .. } => Ok(file_system.write(segments, default())?),
+            _ => Err(error(code::FILE_SYSTEM_ERROR, format!("Cannot create {path}."))),
+        }
+    }
+
+    fn delete_file(&self, params: DeleteFile) -> MethodResult<()> {
+        self.client_id()?;
+        let segments = self.server.segments(&params.path)?;
+        Ok(self.server.file_system.borrow_mut().delete(segments)?)
+    }
+
+    fn copy_file(&self, params: CopyFile) -> MethodResult<()> {
+        self.client_id()?;
+        let from = self.server.segments(&params.from)?;
+        let to = self.server.segments(&params.to)?;
+        Ok(self.server.file_system.borrow_mut().copy(from, to)?)
+    }
+
+    fn move_file(&self, params: MoveFile) -> MethodResult<()> {
+        self.client_id()?;
+        let from = self.server.segments(&params.from)?;
+        let to = self.server.segments(&params.to)?;
+        Ok(self.server.file_system.borrow_mut().move_to(from, to)?)
+    }
+
+    fn file_exists(&self, params: FileExists) -> MethodResult<response::FileExists> {
+        self.client_id()?;
+        let segments = self.server.segments(&params.path)?;
+        let exists = self.server.file_system.borrow().exists(segments);
+        Ok(response::FileExists { exists })
+    }
+
+    fn file_list(&self, params: FileList) -> MethodResult<response::FileList> {
+        self.client_id()?;
+        let segments = self.server.segments(&params.path)?;
+        let children = self.server.file_system.borrow().list(segments)?;
+        let paths = children.iter().map(|child| self.server.file_system_object(child));
+        let paths = paths.collect::<MethodResult<_>>()?;
+        Ok(response::FileList { paths })
+    }
+
+    fn file_info(&self, params: FileInfo) -> MethodResult<response::FileInfo> {
+        self.client_id()?;
+        let segments = self.server.segments(&params.path)?;
+        let kind = self.server.file_system_object(segments)?;
+        let file_system = self.server.file_system.borrow();
+        let entry = file_system.entry(segments)?;
+        let attributes = FileAttributes {
+            creation_time: time(entry.creation_time),
+            last_access_time: time(entry.modified_time),
+            last_modified_time: time(entry.modified_time),
+            kind,
+            byte_size: entry.byte_size(),
+        };
+        Ok(response::FileInfo { attributes })
+    }
+
+    fn file_checksum(&self, params: FileChecksum) -> MethodResult<response::FileChecksum> {
+        self.client_id()?;
+        let segments = self.server.segments(&params.path)?;
+        let checksum = Sha3_224::new(self.server.file_system.borrow().read(segments)?);
+        Ok(response::FileChecksum { checksum })
+    }
+
+
+    // === Text ===
+
+    fn open_text_file(&self, params: OpenTextFile) -> MethodResult<response::OpenTextFile> {
+        let client_id = self.client_id()?;
+        let segments = self.server.segments(&params.path)?.to_vec();
+        let mut model = self.server.model.borrow_mut();
+        let file = match model.open_files.entry(segments) {
+            hash_map::Entry::Occupied(entry) => entry.into_mut(),
+            hash_map::Entry::Vacant(entry) => {
+                let contents = self.server.file_system.borrow().read(entry.key())?.to_vec();
+                let buffer =
+                    String::from_utf8(contents).map_err(|e| error(code::CANNOT_DECODE, e))?;
+                entry.insert(OpenFile { buffer, clients: default(), writer: None })
+            }
+        };
+        file.clients.insert(client_id);
+        let is_writer = *file.writer.get_or_insert(client_id) == client_id;
+        let path = params.path;
+        let write_capability =
+            is_writer.then(|| CapabilityRegistration::create_can_edit_text_file(path));
+        let content = file.buffer.clone();
+        let current_version = file.version();
+        Ok(response::OpenTextFile { write_capability, content, current_version })
+    }
+
+    fn close_text_file(&self, params: CloseTextFile) -> MethodResult<()> {
+        let is_closed = self.with_open_file(&params.path, |file, client_id| {
+            file.clients.remove(&client_id);
+            if file.writer == Some(client_id) {
+                file.writer = None;
+            }
+            Ok(file.clients.is_empty())
+        })?;
+        if is_closed {
+            let segments = self.server.segments(&params.path)?;
+            self.server.model.borrow_mut().open_files.remove(segments);
+        }
+        Ok(())
+    }
+
+    fn save_text_file(&self, params: SaveTextFile) -> MethodResult<()> {
+        self.with_open_file(&params.path, |file, client_id| {
+            file.check_writer(client_id, &params.path)?;
+            file.check_version(&params.current_version)?;
+            let segments = self.server.segments(&params.path)?;
+            let contents = file.buffer.clone().into_bytes();
+            Ok(self.server.file_system.borrow_mut().write(segments, contents)?)
+        })
+    }
+
+    fn apply_text_file_edit(&self, params: ApplyTextFileEdit) -> MethodResult<()> {
+        let edit = params.edit;
+        self.with_open_file(&edit.path, |file, client_id| {
+            file.check_writer(client_id, &edit.path)?;
+            file.check_version(&edit.old_version)?;
+            let mut buffer = file.buffer.clone();
+            for text_edit in &edit.edits {
+                apply_text_edit(&mut buffer, text_edit)?;
+            }
+            let new_version = Sha3_224::new(buffer.as_bytes());
+            if new_version == edit.new_version {
+                file.buffer = buffer;
+                Ok(())
+            } else {
+                let message = format!("The version after the edit is {new_version}.");
+                Err(error(code::INVALID_VERSION, message))
+            }
+        })
+    }
+
+
+    // === Execution Contexts ===
+
+    fn create_execution_context(
+        &self,
+        params: CreateExecutionContext,
+    ) -> MethodResult<response::CreateExecutionContext> {
+        self.client_id()?;
+        let context_id = params.context_id.unwrap_or_else(Uuid::new_v4);
+        let notifier = self.notifier.clone();
+        let context = ExecutionContext { notifier, stack: default(), visualisations: default() };
+        self.server.model.borrow_mut().contexts.insert(context_id, context);
+        Ok(response::CreateExecutionContext {
+            context_id,
+            can_modify: CapabilityRegistration::create_can_modify_execution_context(context_id),
+            receives_updates: CapabilityRegistration::create_receives_execution_context_updates(
+                context_id,
+            ),
+        })
+    }
+
+    fn destroy_execution_context(&self, params: DestroyExecutionContext) -> MethodResult<()> {
+        self.with_context(params.context_id, |_| Ok(()))?;
+        self.server.model.borrow_mut().contexts.remove(&params.context_id);
+        Ok(())
+    }
+
+    fn push_to_execution_context(&self, params: PushToExecutionContext) -> MethodResult<()> {
+        self.with_context(params.context_id, |context| {
+            let is_valid = match &params.stack_item {
+                StackItem::ExplicitCall(_) => context.stack.is_empty(),
+                StackItem::LocalCall(_) => !context.stack.is_empty(),
+            };
+            if is_valid {
+                context.stack.push(params.stack_item);
+                Ok(())
+            } else {
+                Err(error(code::INVALID_STACK_ITEM, "Invalid stack item."))
+            }
+        })?;
+        self.server.execute(params.context_id);
+        Ok(())
+    }
+
+    fn pop_from_execution_context(&self, params: PopFromExecutionContext) -> MethodResult<()> {
+        let is_empty =
+            self.with_context(params.context_id, |context| match context.stack.pop() {
+                Some(_) => Ok(context.stack.is_empty()),
+                None => Err(error(code::EMPTY_STACK, "The execution context stack is empty.")),
+            })?;
+        if !is_empty {
+            self.server.execute(params.context_id);
+        }
+        Ok(())
+    }
+
+    fn recompute(&self, params: Recompute) -> MethodResult<()> {
+        let is_empty =
+            self.with_context(params.context_id, |context| Ok(context.stack.is_empty()))?;
+        if !is_empty {
+            self.server.execute(params.context_id);
+        }
+        Ok(())
+    }
+
+    fn interrupt(&self, params: Interrupt) -> MethodResult<()> {
+        self.with_context(params.context_id, |_| Ok(()))
+    }
+
+    fn attach_visualisation(&self, params: AttachVisualisation) -> MethodResult<()> {
+        let client_id = self.client_id()?;
+        let context_id = params.visualisation_config.execution_context_id;
+        let expression_id = params.expression_id;
+        let visualisation = Visualisation { client_id, expression_id };
+        self.with_context(context_id, |context| {
+            context.visualisations.insert(params.visualisation_id, visualisation);
+            Ok(())
+        })?;
+        let context = visualisation_context(context_id, params.visualisation_id, &visualisation);
+        self.server.update_visualisation(client_id, context);
+        Ok(())
+    }
+
+    fn detach_visualisation(&self, params: DetachVisualisation) -> MethodResult<()> {
+        self.with_context(params.context_id, |context| {
+            match context.visualisations.remove(&params.visualisation_id) {
+                Some(_) => Ok(()),
+                None => Err(visualisation_not_found(params.visualisation_id)),
+            }
+        })
+    }
+
+    fn modify_visualisation(&self, params: ModifyVisualisation) -> MethodResult<()> {
+        let context_id = params.visualisation_config.execution_context_id;
+        let visualisation = self.with_context(context_id, |context| {
+            let visualisation = context.visualisations.get(&params.visualisation_id);
+            visualisation.copied().ok_or_else(|| visualisation_not_found(params.visualisation_id))
+        })?;
+        let context = visualisation_context(context_id, params.visualisation_id, &visualisation);
+        self.server.update_visualisation(visualisation.client_id, context);
+        Ok(())
+    }
+
+    fn execute_expression(&self, params: ExecuteExpression) -> MethodResult<()> {
+        let client_id = self.client_id()?;
+        let context_id = params.visualisation_config.execution_context_id;
+        self.with_context(context_id, |_| Ok(()))?;
+        let expression_id = params.expression_id;
+        let visualisation = Visualisation { client_id, expression_id };
+        let context = visualisation_context(context_id, params.visualisation_id, &visualisation);
+        self.server.update_visualisation(client_id, context);
+        Ok(())
+    }
+
+    fn get_component_groups(
+        &self,
+        params: GetComponentGroups,
+    ) -> MethodResult<response::GetComponentGroups> {
+        self.with_context(params.context_id, |_| Ok(()))?;
+        Ok(response::GetComponentGroups { component_groups: default() })
+    }
+
+
+    // === Suggestions ===
+
+    fn get_suggestions_database(
+        &self,
+        _params: GetSuggestionsDatabase,
+    ) -> MethodResult<response::GetSuggestionDatabase> {
+        self.client_id()?;
+        Ok(response::GetSuggestionDatabase {
+            entries:         default(),
+            current_version: default(),
+        })
+    }
+
+    fn get_suggestions_database_version(
+        &self,
+        _params: GetSuggestionsDatabaseVersion,
+    ) -> MethodResult<response::GetSuggestionDatabaseVersion> {
+        self.client_id()?;
+        Ok(response::GetSuggestionDatabaseVersion { current_version: default() })
+    }
+
+    fn completion(&self, _params: Completion) -> MethodResult<response::Completion> {
+        self.client_id()?;
+        Ok(response::Completion { results: default(), current_version: default() })
+    }
+}
+
+fn visualisation_not_found(visualisation_id: Uuid) -> json_rpc::messages::Error {
+    let message = format!("Visualisation {visualisation_id} does not exist.");
+    error(code::VISUALISATION_NOT_FOUND, message)
+}
+
+
+
+// =================
+// === Text Edit ===
+// =================
+
+/// Apply the edit to the text. Fails if the edited range is not within the text.
+fn apply_text_edit(text: &mut String, edit: &TextEdit) -> MethodResult<()> {
+    let start = byte_offset(text, edit.range.start);
+    let end = byte_offset(text, edit.range.end);
+    match (start, end) {
+        (Some(start), Some(end)) if start <= end => {
+            text.replace_range(start..end, &edit.text);
+            Ok(())
+        }
+        _ => {
+            let message = format!("Invalid range of the edit: {:?}.", edit.range);
+            Err(error(code::TEXT_EDIT_VALIDATION, message))
+        }
+    }
+}
+
+/// Convert the position, with the column counted in UTF-16 code units, to a byte offset.
+fn byte_offset(text: &str, position: Position) -> Option<usize> {
+    let line_start = match position.line {
+        0 => 0,
+        line => text.match_indices('\n').nth(line - 1)?.0 + 1,
+    };
+    let line = text[line_start..].split('\n').next().unwrap_or_default();
+    let mut code_units = 0;
+    for (index, char) in line.char_indices() {
+        if code_units == position.character {
+            return Some(line_start + index);
+        }
+        code_units += char.len_utf16();
+    }
+    (code_units == position.character).then_some(line_start + line.len())
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(start: (usize, usize), end: (usize, usize), text: &str) -> TextEdit {
+        let start = Position { line: start.0, character: start.1 };
+        let end = Position { line: end.0, character: end.1 };
+        let range = engine_protocol::language_server::TextRange { start, end };
+        TextEdit { range, text: text.into() }
+    }
+
+    #[test]
+    fn applying_text_edits() {
+        let mut text = "main =\n    🌊 = 2\n    🌊 + 2".to_string();
+        apply_text_edit(&mut text, &edit((1, 4), (1, 6), "x")).unwrap();
+        assert_eq!(text, "main =\n    x = 2\n    🌊 + 2");
+        apply_text_edit(&mut text, &edit((2, 7), (2, 10), "* 3")).unwrap();
+        assert_eq!(text, "main =\n    x = 2\n    🌊 * 3");
+        apply_text_edit(&mut text, &edit((0, 6), (1, 0), " 42\n")).unwrap();
+        assert_eq!(text, "main = 42\n    x = 2\n    🌊 * 3");
+
+        // The position inside a surrogate pair, beyond the end of line, and beyond the last line.
+        assert!(apply_text_edit(&mut text, &edit((2, 5), (2, 6), "")).is_err());
+        assert!(apply_text_edit(&mut text, &edit((0, 10), (0, 10), "")).is_err());
+        assert!(apply_text_edit(&mut text, &edit((3, 0), (3, 0), "")).is_err());
+        assert!(apply_text_edit(&mut text, &edit((1, 2), (1, 1), "")).is_err());
+    }
+}
@@ -0,0 +1,204 @@
+//! The binary protocol endpoint of the mocked language server, transferring the file contents and
+//! the visualisation data.
+
+use enso_prelude::*;
+
+use crate::file_system;
+use crate::language_server::LanguageServer;
+use crate::rpc::code;
+use crate::rpc::error;
+use crate::rpc::file_system_error_code;
+use crate::server;
+
+use engine_protocol::binary::message::EnsoDigest;
+use engine_protocol::binary::message::ErrorPayload;
+use engine_protocol::binary::message::FileSegment;
+use engine_protocol::binary::message::FromServerPayloadOwned;
+use engine_protocol::binary::message::MessageFromServer;
+use engine_protocol::binary::message::MessageToServerOwned;
+use engine_protocol::binary::message::ToServerPayloadOwned;
+use engine_protocol::binary::message::VisualisationContext;
+use engine_protocol::binary::serialization::DeserializableRoot;
+use engine_protocol::binary::serialization::SerializableRoot;
+use engine_protocol::language_server::Path;
+use futures::StreamExt;
+use json_rpc::messages;
+use json_rpc::Transport;
+use json_rpc::TransportEvent;
+use sha3::Digest;
+use uuid::Uuid;
+
+
+
+// ==================
+// === ErrorReply ===
+// ==================
+
+/// The error reported to the client in the `Error` payload.
+#[derive(Clone, Debug)]
+struct ErrorReply {
+    code:    i64,
+    message: String,
+    data:    Option<ErrorPayload>,
+}
+
+impl From<messages::Error> for ErrorReply {
+    fn from(error: messages::Error) -> Self {
+        Self { code: error.code, message: error.message, data: None }
+    }
+}
+
+impl From<file_system::Error> for ErrorReply {
+    fn from(cause: file_system::Error) -> Self {
+        let data = match &cause {
+            file_system::Error::ReadOutOfBounds { file_length, .. } =>
+                Some(ErrorPayload::ReadOOB { file_length: *file_length }),
+            _ => None,
+        };
+        Self { code: file_system_error_code(&cause), message: cause.to_string(), data }
+    }
+}
+
+impl From<ErrorReply> for FromServerPayloadOwned {
+    fn from(error: ErrorReply) -> Self {
+        let ErrorReply { code, message, data } = error;
+        FromServerPayloadOwned::Error { code: code as i32, message, data }
+    }
+}
+
+type Result<T> = std::result::Result<T, ErrorReply>;
+
+
+
+// ==================
+// === Connection ===
+// ==================
+
+/// Serve the binary protocol on the connection.
+pub fn connect(server: &LanguageServer, mut connection: server::Connection) {
+    info!("Language server binary connection from {}.", connection.peer());
+    let mut session =
+        Session { server: server.clone(), sender: connection.sender(), client_id: None };
+    let mut events = connection.establish_event_stream();
+    tokio::task::spawn_local(async move {
+        while let Some(event) = events.next().await {
+            match event {
+                TransportEvent::BinaryMessage(data) => session.handle(&data),
+                TransportEvent::TextMessage(_) =>
+                    warn!("Binary endpoint received an unexpected text message."),
+                TransportEvent::Closed => break,
+                _ => {}
+            }
+        }
+        session.disconnect();
+        drop(connection);
+    });
+}
+
+/// Send the visualisation data to the client.
+pub fn send_visualisation_update(
+    sender: &server::Sender,
+    context: VisualisationContext,
+    data: Vec<u8>,
+) {
+    let payload = FromServerPayloadOwned::VisualizationUpdate { context, data };
+    send(sender, MessageFromServer::new(payload));
+}
+
+fn send(sender: &server::Sender, message: MessageFromServer<FromServerPayloadOwned>) {
+    server::send_binary(sender, message.with_serialized(|data| data.to_vec()));
+}
+
+fn digest(bytes: &[u8]) -> EnsoDigest {
+    EnsoDigest { bytes: sha3::Sha3_224::digest(bytes).to_vec() }
+}
+
+
+
+// ===============
+// === Session ===
+// ===============
+
+/// The state of a single binary connection.
+#[derive(Debug)]
+struct Session {
+    server:    LanguageServer,
+    sender:    server::Sender,
+    client_id: Option<Uuid>,
+}
+
+impl Session {
+    fn handle(&mut self, data: &[u8]) {
+        match MessageToServerOwned::deserialize(data) {
+            Ok(request) => {
+                let payload =
+                    self.handle_request(request.payload.clone()).unwrap_or_else(Into::into);
+                let mut reply = MessageFromServer::new(payload);
+                reply.correlation_id = Some(request.message_id);
+                send(&self.sender, reply);
+            }
+            Err(error) => warn!("Cannot handle a binary message: {error}"),
+        }
+    }
+
+    fn handle_request(&mut self, payload: ToServerPayloadOwned) -> Result<FromServerPayloadOwned> {
+        use ToServerPayloadOwned::*;
+        let file_system = &self.server.file_system;
+        match payload {
+            InitSession { client_id } => self.init_session(client_id),
+            _ if self.client_id.is_none() => {
+                let message = "Session not initialised.";
+                Err(error(code::SESSION_NOT_INITIALISED, message).into())
+            }
+            WriteFile { path, contents } => {
+                file_system.borrow_mut().write(self.segments(&path)?, contents)?;
+                Ok(FromServerPayloadOwned::Success {})
+            }
+            ReadFile { path } => {
+                let contents = file_system.borrow().read(self.segments(&path)?)?.to_vec();
+                Ok(FromServerPayloadOwned::FileContentsReply { contents })
+            }
+            WriteBytes { path, byte_offset, overwrite, bytes } => {
+                let segments = self.segments(&path)?;
+                file_system.borrow_mut().write_bytes(segments, byte_offset, overwrite, &bytes)?;
+                Ok(FromServerPayloadOwned::WriteBytesReply { checksum: digest(&bytes) })
+            }
+            ReadBytes { segment } => {
+                let bytes = self.read_segment(&segment)?;
+                Ok(FromServerPayloadOwned::ReadBytesReply { checksum: digest(&bytes), bytes })
+            }
+            ChecksumBytes { segment } => {
+                let bytes = self.read_segment(&segment)?;
+                Ok(FromServerPayloadOwned::ChecksumBytesReply { checksum: digest(&bytes) })
+            }
+        }
+    }
+
+    fn init_session(&mut self, client_id: Uuid) -> Result<FromServerPayloadOwned> {
+        if self.client_id.is_some() {
+            let message = "Session already initialised.";
+            Err(error(code::SESSION_ALREADY_INITIALISED, message).into())
+        } else {
+            self.client_id = Some(client_id);
+            let mut model = self.server.model.borrow_mut();
+            model.binary_connections.insert(client_id, self.sender.clone());
+            Ok(FromServerPayloadOwned::Success {})
+        }
+    }
+
+    fn segments<'a>(&self, path: &'a Path) -> Result<&'a [String]> {
+        Ok(self.server.segments(path)?)
+    }
+
+    fn read_segment(&self, segment: &FileSegment) -> Result<Vec<u8>> {
+        let segments = self.segments(&segment.path)?;
+        let file_system = self.server.file_system.borrow();
+        Ok(file_system.read_bytes(segments, segment.byte_offset, segment.length)?.to_vec())
+    }
+
+    fn disconnect(&self) {
+        if let Some(client_id) = self.client_id {
+            self.server.model.borrow_mut().binary_connections.remove(&client_id);
+        }
+    }
+}
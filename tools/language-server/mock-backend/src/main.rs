@@ -0,0 +1,96 @@
+//! The crate provides an executable serving an in-memory mock of the project manager and the
+//! language server, allowing to run the IDE without the Enso engine.
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+// === Non-Standard Linter Configuration ===
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+#![warn(unused_qualifications)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+
+
+
+#[macro_use]
+mod rpc;
+
+mod file_system;
+mod fixtures;
+mod language_server;
+mod project_manager;
+mod server;
+
+#[cfg(test)]
+mod tests;
+
+use enso_prelude::*;
+
+use crate::fixtures::Fixtures;
+use crate::project_manager::ProjectManager;
+
+use clap::Parser;
+use clap::ValueHint;
+use engine_protocol::project_manager::ProjectName;
+use std::path::PathBuf;
+use tokio::task::LocalSet;
+
+
+
+// =====================
+// === CLI Arguments ===
+// =====================
+
+#[derive(Parser, Debug)]
+#[clap(version, about)]
+struct Args {
+    /// Interface on which the project manager and the language servers listen.
+    #[clap(long, default_value = "127.0.0.1")]
+    interface: String,
+
+    /// Port of the project manager.
+    #[clap(long, default_value_t = 30535)]
+    port: u16,
+
+    /// Directory with the project files and the scripted execution results.
+    #[clap(long, value_name = "DIR", value_hint = ValueHint::DirPath)]
+    fixtures: Option<PathBuf>,
+
+    /// Name of a project created on startup. May be given multiple times.
+    #[clap(long = "project", value_name = "NAME")]
+    projects: Vec<String>,
+}
+
+
+
+// ============
+// === Main ===
+// ============
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> FallibleResult {
+    let args = Args::parse();
+    let fixtures = match &args.fixtures {
+        Some(directory) => Fixtures::load(directory)?,
+        None => default(),
+    };
+    let manager = ProjectManager::new(&args.interface, fixtures);
+    for name in args.projects {
+        if let Err(error) = manager.create_project(ProjectName::new_unchecked(name)) {
+            warn!("Cannot create the project: {}", error.message);
+        }
+    }
+    LocalSet::new()
+        .run_until(async move {
+            let listener = server::bind(&args.interface, args.port).await?;
+            println!("Project manager listening at ws://{}.", listener.local_addr()?);
+            server::serve(listener, move |connection| manager.connect(connection)).await;
+            Ok(())
+        })
+        .await
+}
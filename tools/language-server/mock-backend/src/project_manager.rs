@@ -0,0 +1,279 @@
+//! A mocked project manager, keeping the projects in memory. Opening a project starts a mocked
+//! language server serving it.
+
+use enso_prelude::*;
+
+use crate::file_system::FileSystem;
+use crate::fixtures::Fixtures;
+use crate::language_server::Endpoints;
+use crate::language_server::LanguageServer;
+use crate::rpc::code;
+use crate::rpc::error;
+use crate::rpc::MethodResult;
+use crate::server;
+
+use engine_protocol::project_manager::response;
+use engine_protocol::project_manager::MissingComponentAction;
+use engine_protocol::project_manager::ProjectMetadata;
+use engine_protocol::project_manager::ProjectName;
+use json_rpc::Dispatcher;
+use uuid::Uuid;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The engine version reported for all projects.
+pub const ENGINE_VERSION: &str = "0.0.0-dev";
+
+/// The namespace of all projects.
+pub const NAMESPACE: &str = "local";
+
+
+
+// ===============
+// === Methods ===
+// ===============
+
+methods! {
+    OpenProject = "project/open" (
+        project_id: Uuid,
+        missing_component_action: MissingComponentAction,
+    ) -> response::OpenProject;
+    CloseProject = "project/close" (project_id: Uuid) -> ();
+    ListProjects = "project/list" (number_of_projects: Option<u32>) -> response::ProjectList;
+    CreateProject = "project/create" (
+        name: ProjectName,
+        project_template: Option<String>,
+        version: Option<String>,
+        missing_component_action: MissingComponentAction,
+    ) -> response::CreateProject;
+    RenameProject = "project/rename" (project_id: Uuid, name: ProjectName) -> ();
+    DeleteProject = "project/delete" (project_id: Uuid) -> ();
+    ListSamples = "project/listSample" (num_projects: u32) -> response::ProjectList;
+}
+
+
+
+// ===============
+// === Project ===
+// ===============
+
+#[derive(Debug)]
+struct Project {
+    metadata:        ProjectMetadata,
+    file_system:     Rc<RefCell<FileSystem>>,
+    /// The language server serving the project, if it is opened.
+    language_server: Option<Endpoints>,
+}
+
+
+
+// ======================
+// === ProjectManager ===
+// ======================
+
+/// The state of the project manager, shared by all its connections.
+#[derive(Clone, Debug)]
+pub struct ProjectManager {
+    /// The host on which the language servers listen.
+    host:     String,
+    fixtures: Rc<Fixtures>,
+    projects: Rc<RefCell<Vec<Project>>>,
+}
+
+impl ProjectManager {
+    /// Create a project manager without any projects. The language servers will listen on the
+    /// given host.
+    pub fn new(host: impl Into<String>, fixtures: Fixtures) -> Self {
+        let fixtures = Rc::new(fixtures);
+        Self { host: host.into(), fixtures, projects: default() }
+    }
+
+    /// Create a new project with the files defined in the fixtures.
+    pub fn create_project(&self, name: ProjectName) -> MethodResult<Uuid> {
+        let mut projects = self.projects.borrow_mut();
+        if name.as_ref().is_empty() {
+            return Err(error(code::PROJECT_NAME_VALIDATION, "Project name cannot be empty."));
+        }
+        if projects.iter().any(|project| project.metadata.name == name) {
+            return Err(error(code::PROJECT_EXISTS, format!("Project {name} already exists.")));
+        }
+        let id = Uuid::new_v4();
+        let metadata = ProjectMetadata {
+            name,
+            namespace: NAMESPACE.into(),
+            id,
+            engine_version: Some(ENGINE_VERSION.into()),
+            last_opened: None,
+        };
+        let file_system = Rc::new(RefCell::new(self.fixtures.project_file_system()));
+        projects.push(Project { metadata, file_system, language_server: None });
+        Ok(id)
+    }
+
+    /// Serve the JSON-RPC protocol on the connection.
+    pub fn connect(&self, connection: server::Connection) {
+        info!("Project manager connection from {}.", connection.peer());
+        let dispatcher = Dispatcher::new(connection);
+        let manager = self.clone();
+        dispatcher.register_async_method(move |params| manager.clone().open_project(params));
+        let manager = self.clone();
+        dispatcher.register_method(move |params| manager.close_project(params));
+        let manager = self.clone();
+        dispatcher.register_method(move |params| manager.list_projects(params));
+        let manager = self.clone();
+        dispatcher.register_method(move |params: CreateProject| {
+            let project_id = manager.create_project(params.name)?;
+            Ok(response::CreateProject { project_id })
+        });
+        let manager = self.clone();
+        dispatcher.register_method(move |params| manager.rename_project(params));
+        let manager = self.clone();
+        dispatcher.register_method(move |params| manager.delete_project(params));
+        dispatcher
+            .register_method(|_: ListSamples| Ok(response::ProjectList { projects: default() }));
+        let runner = dispatcher.runner();
+        tokio::task::spawn_local(async move {
+            runner.await;
+            drop(dispatcher);
+        });
+    }
+
+    fn with_project<R>(
+        &self,
+        project_id: Uuid,
+        f: impl FnOnce(&mut Project) -> MethodResult<R>,
+    ) -> MethodResult<R> {
+        let mut projects = self.projects.borrow_mut();
+        match projects.iter_mut().find(|project| project.metadata.id == project_id) {
+            Some(project) => f(project),
+            None => {
+                let message = format!("Project {project_id} does not exist.");
+                Err(error(code::PROJECT_NOT_FOUND, message))
+            }
+        }
+    }
+
+    async fn open_project(self, params: OpenProject) -> MethodResult<response::OpenProject> {
+        let file_system = self.with_project(params.project_id, |project| {
+            project.metadata.last_opened = Some(chrono::Utc::now().into());
+            let is_opened = project.language_server.is_some();
+            Ok((!is_opened).then(|| project.file_system.clone()))
+        })?;
+        if let Some(file_system) = file_system {
+            let server = LanguageServer::new(file_system, self.fixtures.clone());
+            let endpoints = server.start(&self.host).await;
+            let endpoints = endpoints.map_err(|e| error(code::LANGUAGE_SERVER_ERROR, e))?;
+            info!("Started the language server at {}.", endpoints.json_address);
+            self.with_project(params.project_id, |project| {
+                // The project might have been opened by a concurrent call in the meantime.
+                project.language_server.get_or_insert(endpoints);
+                Ok(())
+            })?;
+        }
+        self.with_project(params.project_id, |project| {
+            let endpoints = project.language_server.as_ref().expect("The project is opened.");
+            Ok(response::OpenProject {
+                engine_version:                 ENGINE_VERSION.into(),
+                language_server_json_address:   endpoints.json_address.clone(),
+                language_server_binary_address: endpoints.binary_address.clone(),
+                project_name:                   project.metadata.name.clone(),
+                project_namespace:              project.metadata.namespace.clone(),
+            })
+        })
+    }
+
+    fn close_project(&self, params: CloseProject) -> MethodResult<()> {
+        self.with_project(params.project_id, |project| {
+            project.language_server = None;
+            Ok(())
+        })
+    }
+
+    fn list_projects(&self, params: ListProjects) -> MethodResult<response::ProjectList> {
+        let projects = self.projects.borrow();
+        let mut projects = projects.iter().map(|project| project.metadata.clone()).collect_vec();
+        projects.sort_by(|a, b| b.last_opened.cmp(&a.last_opened));
+        if let Some(limit) = params.number_of_projects {
+            projects.truncate(limit as usize);
+        }
+        Ok(response::ProjectList { projects })
+    }
+
+    fn rename_project(&self, params: RenameProject) -> MethodResult<()> {
+        let name = params.name;
+        let is_taken = self.projects.borrow().iter().any(|project| {
+            project.metadata.name == name && project.metadata.id != params.project_id
+        });
+        if is_taken {
+            return Err(error(code::PROJECT_EXISTS, format!("Project {name} already exists.")));
+        }
+        self.with_project(params.project_id, |project| {
+            project.metadata.name = name;
+            Ok(())
+        })
+    }
+
+    fn delete_project(&self, params: DeleteProject) -> MethodResult<()> {
+        self.with_project(params.project_id, |project| match project.language_server {
+            Some(_) => Err(error(code::CANNOT_REMOVE_OPEN_PROJECT, "Cannot remove open project.")),
+            None => Ok(()),
+        })?;
+        self.projects.borrow_mut().retain(|project| project.metadata.id != params.project_id);
+        Ok(())
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project_manager() -> ProjectManager {
+        ProjectManager::new("127.0.0.1", default())
+    }
+
+    #[test]
+    fn creating_and_deleting_projects() {
+        let manager = project_manager();
+        let first = manager.create_project(ProjectName::new_unchecked("First")).unwrap();
+        let second = manager.create_project(ProjectName::new_unchecked("Second")).unwrap();
+        let error = manager.create_project(ProjectName::new_unchecked("First")).unwrap_err();
+        assert_eq!(error.code, code::PROJECT_EXISTS);
+
+        let list = |number_of_projects| {
+            let projects = manager.list_projects(ListProjects { number_of_projects }).unwrap();
+            projects.projects.into_iter().map(|project| project.id).collect_vec()
+        };
+        assert_eq!(list(None), vec![first, second]);
+        assert_eq!(list(Some(1)), vec![first]);
+
+        let name = ProjectName::new_unchecked("Second");
+        let error = manager.rename_project(RenameProject { project_id: first, name }).unwrap_err();
+        assert_eq!(error.code, code::PROJECT_EXISTS);
+        manager.delete_project(DeleteProject { project_id: first }).unwrap();
+        assert_eq!(list(None), vec![second]);
+        let error = manager.delete_project(DeleteProject { project_id: first }).unwrap_err();
+        assert_eq!(error.code, code::PROJECT_NOT_FOUND);
+    }
+
+    #[test]
+    fn created_projects_contain_fixture_files() {
+        let manager = project_manager();
+        let id = manager.create_project(ProjectName::new_unchecked("Project")).unwrap();
+        let projects = manager.projects.borrow();
+        let file_system =
+            projects.iter().find(|p| p.metadata.id == id).unwrap().file_system.borrow();
+        let main_path = crate::fixtures::MAIN_MODULE_PATH.map(String::from);
+        let main = file_system.read(&main_path).unwrap();
+        assert_eq!(main, crate::fixtures::DEFAULT_MAIN_MODULE.as_bytes());
+    }
+}
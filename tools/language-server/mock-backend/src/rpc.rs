@@ -0,0 +1,116 @@
+//! Utilities for defining the JSON-RPC methods served by the mock backend and reporting their
+//! errors.
+
+use enso_prelude::*;
+
+use crate::file_system;
+
+use json_rpc::messages;
+
+
+// ==============
+// === Export ===
+// ==============
+
+pub use json_rpc::dispatcher::MethodResult;
+
+
+
+// ===============
+// === Methods ===
+// ===============
+
+/// Define the parameters of the served methods. Every method is described by a structure with
+/// the method's parameters as fields, which implements [`json_rpc::RemoteMethodCall`], so its
+/// handler can be registered in a [`json_rpc::Dispatcher`].
+///
+/// ```text
+/// methods! {
+///     /// Documentation.
+///     FileExists = "file/exists" (path: Path) -> response::FileExists;
+/// }
+/// ```
+macro_rules! methods {
+    ($(
+        $(#[doc = $doc:literal])*
+        $name:ident = $method:literal ($($field:ident : $field_type:ty),* $(,)?) -> $returned:ty;
+    )*) => {$(
+        $(#[doc = $doc])*
+        // Some of the parameters are ignored by the mock.
+        #[allow(missing_docs, dead_code)]
+        #[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        pub struct $name {
+            $(pub $field: $field_type),*
+        }
+
+        impl json_rpc::RemoteMethodCall for $name {
+            const NAME: &'static str = $method;
+            type Returned = $returned;
+        }
+    )*};
+}
+
+
+
+// ==============
+// === Errors ===
+// ==============
+
+/// The error codes defined by the Enso protocol, see
+/// https://enso.org/docs/developer/enso/language-server/protocol-language-server.html#errors-1
+/// and
+/// https://enso.org/docs/developer/enso/language-server/protocol-project-manager.html#errors
+#[allow(missing_docs)]
+pub mod code {
+    pub const ACCESS_DENIED: i64 = 100;
+    pub const FILE_SYSTEM_ERROR: i64 = 1000;
+    pub const CONTENT_ROOT_NOT_FOUND: i64 = 1001;
+    pub const FILE_NOT_FOUND: i64 = 1003;
+    pub const FILE_EXISTS: i64 = 1004;
+    pub const NOT_DIRECTORY: i64 = 1006;
+    pub const NOT_FILE: i64 = 1007;
+    pub const CANNOT_OVERWRITE: i64 = 1008;
+    pub const READ_OUT_OF_BOUNDS: i64 = 1009;
+    pub const CANNOT_DECODE: i64 = 1010;
+    pub const CONTEXT_NOT_FOUND: i64 = 2002;
+    pub const EMPTY_STACK: i64 = 2003;
+    pub const INVALID_STACK_ITEM: i64 = 2004;
+    pub const VISUALISATION_NOT_FOUND: i64 = 2006;
+    pub const FILE_NOT_OPENED: i64 = 3001;
+    pub const TEXT_EDIT_VALIDATION: i64 = 3002;
+    pub const INVALID_VERSION: i64 = 3003;
+    pub const WRITE_DENIED: i64 = 3004;
+    pub const PROJECT_NAME_VALIDATION: i64 = 4001;
+    pub const PROJECT_EXISTS: i64 = 4003;
+    pub const PROJECT_NOT_FOUND: i64 = 4004;
+    pub const CANNOT_REMOVE_OPEN_PROJECT: i64 = 4008;
+    pub const LANGUAGE_SERVER_ERROR: i64 = 4010;
+    pub const SESSION_NOT_INITIALISED: i64 = 6001;
+    pub const SESSION_ALREADY_INITIALISED: i64 = 6002;
+}
+
+/// Construct the error reported to the client.
+pub fn error(code: i64, message: impl Display) -> messages::Error {
+    messages::Error::new(code, message.to_string())
+}
+
+/// The error code reported for the file system error.
+pub fn file_system_error_code(error: &file_system::Error) -> i64 {
+    use file_system::Error::*;
+    match error {
+        FileNotFound(_) => code::FILE_NOT_FOUND,
+        FileExists(_) => code::FILE_EXISTS,
+        NotDirectory(_) => code::NOT_DIRECTORY,
+        NotFile(_) => code::NOT_FILE,
+        CannotOverwrite(_) => code::CANNOT_OVERWRITE,
+        MoveIntoItself(_) => code::FILE_SYSTEM_ERROR,
+        ReadOutOfBounds { .. } => code::READ_OUT_OF_BOUNDS,
+    }
+}
+
+impl From<file_system::Error> for messages::Error {
+    fn from(cause: file_system::Error) -> Self {
+        error(file_system_error_code(&cause), cause)
+    }
+}
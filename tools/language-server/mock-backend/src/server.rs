@@ -0,0 +1,180 @@
+//! WebSocket endpoints of the mock backend.
+
+use enso_prelude::*;
+
+use futures::channel::mpsc;
+use futures::stream::SplitStream;
+use futures::StreamExt;
+use json_rpc::Transport;
+use json_rpc::TransportEvent;
+use std::net::SocketAddr;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::Error;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+
+
+
+// ==============
+// === Sender ===
+// ==============
+
+/// A handle sending messages through the connection. It can be freely cloned and used after the
+/// connection has been passed to the message handler.
+pub type Sender = mpsc::UnboundedSender<Message>;
+
+/// Send the binary message, logging the failure.
+pub fn send_binary(sender: &Sender, data: Vec<u8>) {
+    if sender.unbounded_send(Message::Binary(data)).is_err() {
+        warn!("Cannot send a binary message, as the connection has been closed.");
+    }
+}
+
+
+
+// ==================
+// === Connection ===
+// ==================
+
+/// A WebSocket connection, accepted by the server or opened by a test client. It serves as a
+/// JSON-RPC transport.
+///
+/// The incoming messages are read only after the event transmitter has been set, so no message is
+/// lost before the connection is passed to the message handler.
+pub struct Connection {
+    peer:     SocketAddr,
+    sender:   Sender,
+    receiver: Option<SplitStream<WebSocketStream<TcpStream>>>,
+}
+
+impl Connection {
+    /// Perform the WebSocket handshake on the accepted TCP stream.
+    pub async fn accept(stream: TcpStream) -> FallibleResult<Self> {
+        let peer = stream.peer_addr()?;
+        let socket = tokio_tungstenite::accept_async(stream).await?;
+        Ok(Self::new(peer, socket))
+    }
+
+    /// Connect to the WebSocket server at the given address. Used by the tests to connect the
+    /// clients to the mocked services.
+    #[cfg(test)]
+    pub async fn connect(
+        address: &engine_protocol::project_manager::IpWithSocket,
+    ) -> FallibleResult<Self> {
+        let stream = TcpStream::connect((address.host.as_str(), address.port)).await?;
+        let peer = stream.peer_addr()?;
+        let (socket, _) = tokio_tungstenite::client_async(address.to_string(), stream).await?;
+        Ok(Self::new(peer, socket))
+    }
+
+    /// Wrap the socket after the handshake. The messages are sent by a spawned task.
+    fn new(peer: SocketAddr, socket: WebSocketStream<TcpStream>) -> Self {
+        let (sink, receiver) = socket.split();
+        let (sender, outgoing) = mpsc::unbounded();
+        tokio::task::spawn_local(async move {
+            match outgoing.map(Ok).forward(sink).await {
+                Ok(()) | Err(Error::ConnectionClosed | Error::AlreadyClosed) => {}
+                Err(error) => warn!("Failed to send a message to {peer}: {error}."),
+            }
+        });
+        Self { peer, sender, receiver: Some(receiver) }
+    }
+
+    /// The address of the connected client.
+    pub fn peer(&self) -> SocketAddr {
+        self.peer
+    }
+
+    /// A handle sending messages through this connection.
+    pub fn sender(&self) -> Sender {
+        self.sender.clone()
+    }
+
+    fn send(&self, message: Message) -> FallibleResult {
+        Ok(self.sender.unbounded_send(message)?)
+    }
+}
+
+impl Debug for Connection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Connection").field("peer", &self.peer).finish()
+    }
+}
+
+impl Transport for Connection {
+    fn send_text(&mut self, message: &str) -> FallibleResult {
+        self.send(Message::Text(message.into()))
+    }
+
+    fn send_binary(&mut self, message: &[u8]) -> FallibleResult {
+        self.send(Message::Binary(message.into()))
+    }
+
+    fn set_event_transmitter(&mut self, transmitter: mpsc::UnboundedSender<TransportEvent>) {
+        match self.receiver.take() {
+            Some(receiver) => {
+                tokio::task::spawn_local(forward_events(self.peer, receiver, transmitter));
+            }
+            None => warn!("The messages from {} are already being received.", self.peer),
+        }
+    }
+}
+
+async fn forward_events(
+    peer: SocketAddr,
+    mut receiver: SplitStream<WebSocketStream<TcpStream>>,
+    transmitter: mpsc::UnboundedSender<TransportEvent>,
+) {
+    while let Some(message) = receiver.next().await {
+        let event = match message {
+            Ok(Message::Text(text)) => TransportEvent::TextMessage(text),
+            Ok(Message::Binary(data)) => TransportEvent::BinaryMessage(data),
+            Ok(Message::Close(_)) => break,
+            Ok(_) => continue,
+            Err(error) => {
+                warn!("Connection with {peer} failed: {error}.");
+                break;
+            }
+        };
+        if transmitter.unbounded_send(event).is_err() {
+            break;
+        }
+    }
+    transmitter.unbounded_send(TransportEvent::Closed).ok();
+}
+
+
+
+// ================
+// === Listener ===
+// ================
+
+/// Bind a listener to the given address. The port `0` picks any free port.
+pub async fn bind(host: &str, port: u16) -> FallibleResult<TcpListener> {
+    Ok(TcpListener::bind((host, port)).await?)
+}
+
+/// Accept the WebSocket connections until the listener fails, and pass them to the handler.
+///
+/// Must be run within a [`tokio::task::LocalSet`].
+pub async fn serve(listener: TcpListener, on_connection: impl Fn(Connection) + 'static) {
+    let on_connection = Rc::new(on_connection);
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                let on_connection = on_connection.clone();
+                tokio::task::spawn_local(async move {
+                    match Connection::accept(stream).await {
+                        Ok(connection) => on_connection(connection),
+                        Err(error) => warn!("WebSocket handshake failed: {error}."),
+                    }
+                });
+            }
+            Err(error) => {
+                warn!("Cannot accept connections: {error}.");
+                break;
+            }
+        }
+    }
+}
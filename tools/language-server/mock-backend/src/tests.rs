@@ -0,0 +1,159 @@
+//! End-to-end tests of the mock backend, connecting the clients of the IDE to the services over
+//! WebSocket.
+
+use enso_prelude::*;
+
+use crate::fixtures::Fixtures;
+use crate::project_manager::ProjectManager;
+use crate::server;
+
+use engine_protocol::binary;
+use engine_protocol::binary::message::FileSegment;
+use engine_protocol::language_server;
+use engine_protocol::language_server::ExplicitCall;
+use engine_protocol::language_server::MethodPointer;
+use engine_protocol::language_server::Notification;
+use engine_protocol::language_server::Path;
+use engine_protocol::language_server::StackItem;
+use engine_protocol::language_server::API as _;
+use engine_protocol::prelude::AnyLogger;
+use engine_protocol::prelude::Logger;
+use engine_protocol::project_manager;
+use engine_protocol::project_manager::IpWithSocket;
+use engine_protocol::project_manager::MissingComponentAction;
+use engine_protocol::project_manager::ProjectName;
+use engine_protocol::project_manager::API as _;
+use engine_protocol::types::Sha3_224;
+use futures::Stream;
+use futures::StreamExt;
+use std::time::Duration;
+use tokio::task::LocalSet;
+use uuid::Uuid;
+
+
+
+// =================
+// === Utilities ===
+// =================
+
+/// The time after which a test waiting for a notification fails.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The fixtures checked in along this crate.
+fn fixtures() -> FallibleResult<Fixtures> {
+    Fixtures::load(&std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures"))
+}
+
+/// Start the project manager on a free port, with a single project named `Test`. Returns the
+/// address of the project manager and the project id.
+async fn start_project_manager() -> FallibleResult<(IpWithSocket, Uuid)> {
+    let host = "127.0.0.1";
+    let manager = ProjectManager::new(host, fixtures()?);
+    let project_id = manager.create_project(ProjectName::new_unchecked("Test"));
+    let project_id = project_id.map_err(|error| failure::err_msg(error.message))?;
+    let listener = server::bind(host, 0).await?;
+    let port = listener.local_addr()?.port();
+    tokio::task::spawn_local(server::serve(listener, move |connection| {
+        manager.connect(connection)
+    }));
+    Ok((IpWithSocket { host: host.into(), port }, project_id))
+}
+
+/// Wait for the next notification received by the language server client.
+async fn next_notification(
+    events: &mut (impl Stream<Item = language_server::Event> + Unpin),
+) -> FallibleResult<Notification> {
+    loop {
+        match tokio::time::timeout(TIMEOUT, events.next()).await? {
+            Some(language_server::Event::Notification(notification)) => return Ok(notification),
+            Some(_) => continue,
+            None => return Err(failure::err_msg("The language server connection was closed.")),
+        }
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[tokio::test]
+async fn serving_the_clients_of_the_ide() -> FallibleResult {
+    LocalSet::new().run_until(open_project_and_execute()).await
+}
+
+async fn open_project_and_execute() -> FallibleResult {
+    let (address, project_id) = start_project_manager().await?;
+
+    // === Opening the Project ===
+
+    let project_manager =
+        project_manager::Client::new(server::Connection::connect(&address).await?);
+    tokio::task::spawn_local(project_manager.runner());
+    let opened = project_manager.open_project(&project_id, &MissingComponentAction::Fail).await?;
+    assert_eq!(opened.project_name, ProjectName::new_unchecked("Test"));
+
+    // === Session Initialisation ===
+
+    let client_id = Uuid::new_v4();
+    let json_transport = server::Connection::connect(&opened.language_server_json_address).await?;
+    let json_client = language_server::Client::new(json_transport);
+    tokio::task::spawn_local(json_client.runner());
+    let mut events = json_client.events();
+    let json = language_server::Connection::new(json_client, client_id).await?;
+    let binary_transport =
+        server::Connection::connect(&opened.language_server_binary_address).await?;
+    let binary_client = binary::Client::new(Logger::new("Test"), binary_transport);
+    tokio::task::spawn_local(binary_client.runner());
+    let binary = binary::Connection::new(binary_client, client_id).await?;
+    let root_id = json.project_root().id();
+
+    // === Files ===
+
+    let path = Path::new(root_id, ["src", "Notes.txt"]);
+    let contents = "Hello, World!".to_string();
+    json.write_file(&path, &contents).await?;
+    assert_eq!(json.read_file(&path).await?.contents, contents);
+    assert_eq!(binary.read_file(&path).await?, contents.as_bytes());
+
+    let segment = FileSegment { path: path.clone(), byte_offset: 7, length: 5 };
+    let read = binary.read_bytes(&segment).await?;
+    assert_eq!(read.bytes, b"World");
+    assert_eq!(read.checksum, Sha3_224::new(b"World"));
+    assert_eq!(binary.checksum_bytes(&segment).await?, Sha3_224::new(b"World"));
+    let beyond_end = FileSegment { path: path.clone(), byte_offset: 14, length: 1 };
+    assert!(binary.read_bytes(&beyond_end).await.is_err());
+    let checksum = json.file_checksum(&path).await?.checksum;
+    assert_eq!(checksum, Sha3_224::new(contents.as_bytes()));
+
+    // === Execution Context ===
+
+    let context = json.create_execution_context(None).await?;
+    let context_id = context.context_id;
+    let method_pointer = MethodPointer {
+        module:          "local.Test.Main".into(),
+        defined_on_type: "local.Test.Main".into(),
+        name:            "main".into(),
+    };
+    let stack_item = StackItem::ExplicitCall(ExplicitCall {
+        method_pointer,
+        this_argument_expression: None,
+        positional_arguments_expressions: default(),
+    });
+    json.push_to_execution_context(&context_id, &stack_item).await?;
+    let expression_updates = fixtures()?.expression_updates;
+    assert!(!expression_updates.is_empty());
+    for expected in expression_updates {
+        match next_notification(&mut events).await? {
+            Notification::ExpressionUpdates(updates) => {
+                assert_eq!(updates.context_id, context_id);
+                assert_eq!(updates.updates, expected);
+            }
+            other => panic!("Expected the expression updates, got {other:?}."),
+        }
+    }
+    let complete = next_notification(&mut events).await?;
+    assert_eq!(complete, Notification::ExecutionComplete { context_id });
+    Ok(())
+}
@@ -0,0 +1,145 @@
+//! Latency histograms summarizing the responses received in the load mode.
+
+use serde::Serialize;
+use std::time::Duration;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The upper bound of the first histogram bucket. Every next bucket is twice as wide.
+const FIRST_BUCKET_BOUND: Duration = Duration::from_millis(1);
+
+
+
+// =================
+// === Histogram ===
+// =================
+
+/// The recorded latencies of the responses.
+#[derive(Clone, Debug, Default)]
+pub struct Histogram {
+    samples: Vec<Duration>,
+}
+
+impl Histogram {
+    /// Record the latency of a single response.
+    pub fn record(&mut self, latency: Duration) {
+        self.samples.push(latency);
+    }
+
+    /// The number of the recorded latencies.
+    pub fn count(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Compute the statistics of the recorded latencies. Returns `None` if nothing was recorded.
+    pub fn summary(&self) -> Option<Summary> {
+        let mut samples = self.samples.clone();
+        samples.sort_unstable();
+        let min = *samples.first()?;
+        let max = *samples.last()?;
+        let total: Duration = samples.iter().sum();
+        let mean = total / samples.len() as u32;
+        let percentile = |p: f64| {
+            // The nearest-rank method.
+            let rank = (p / 100.0 * samples.len() as f64).ceil() as usize;
+            samples[rank.clamp(1, samples.len()) - 1]
+        };
+        let p50 = percentile(50.0);
+        let p90 = percentile(90.0);
+        let p99 = percentile(99.0);
+        let buckets = buckets(&samples);
+        Some(Summary { min, mean, p50, p90, p99, max, buckets })
+    }
+}
+
+/// Count the sorted samples in the buckets of exponentially growing width. The empty buckets
+/// after the last sample are omitted.
+fn buckets(samples: &[Duration]) -> Vec<Bucket> {
+    let mut buckets = Vec::new();
+    let mut bound = FIRST_BUCKET_BOUND;
+    let mut remaining = samples;
+    while !remaining.is_empty() {
+        let count = remaining.partition_point(|latency| *latency <= bound);
+        buckets.push(Bucket { upper_bound: bound, count });
+        remaining = &remaining[count..];
+        bound *= 2;
+    }
+    buckets
+}
+
+
+
+// ===============
+// === Summary ===
+// ===============
+
+/// The statistics of the recorded latencies.
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Serialize)]
+pub struct Summary {
+    #[serde(serialize_with = "milliseconds")]
+    pub min:     Duration,
+    #[serde(serialize_with = "milliseconds")]
+    pub mean:    Duration,
+    #[serde(serialize_with = "milliseconds")]
+    pub p50:     Duration,
+    #[serde(serialize_with = "milliseconds")]
+    pub p90:     Duration,
+    #[serde(serialize_with = "milliseconds")]
+    pub p99:     Duration,
+    #[serde(serialize_with = "milliseconds")]
+    pub max:     Duration,
+    pub buckets: Vec<Bucket>,
+}
+
+/// The number of latencies not greater than the bound, and greater than the bound of the
+/// previous bucket.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct Bucket {
+    #[serde(rename = "upper_bound_ms", serialize_with = "milliseconds")]
+    pub upper_bound: Duration,
+    pub count:       usize,
+}
+
+/// Serialize the duration as a fractional number of milliseconds.
+fn milliseconds<S: serde::Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64() * 1000.0)
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles_and_buckets() {
+        let mut histogram = Histogram::default();
+        assert!(histogram.summary().is_none());
+        for ms in (1..=100).rev() {
+            histogram.record(Duration::from_millis(ms));
+        }
+        let summary = histogram.summary().unwrap();
+        assert_eq!(summary.min, Duration::from_millis(1));
+        assert_eq!(summary.p50, Duration::from_millis(50));
+        assert_eq!(summary.p90, Duration::from_millis(90));
+        assert_eq!(summary.p99, Duration::from_millis(99));
+        assert_eq!(summary.max, Duration::from_millis(100));
+        assert_eq!(summary.mean, Duration::from_micros(50_500));
+        let counts = summary.buckets.iter().map(|bucket| bucket.count).collect::<Vec<_>>();
+        assert_eq!(counts, vec![1, 1, 2, 4, 8, 16, 32, 36]);
+        assert_eq!(summary.buckets.last().unwrap().upper_bound, Duration::from_millis(128));
+    }
+}
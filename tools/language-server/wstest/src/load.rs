@@ -0,0 +1,575 @@
+//! The load-generation mode.
+//!
+//! Opens multiple pairs of text and binary connections and runs the init sequence on each of them.
+//! Then issues the input requests, either keeping the given number of requests in flight on every
+//! connection, or at the given total rate. The responses are correlated with the requests by their
+//! JSON-RPC `id`, which is rewritten to be unique across all the issued requests. The input
+//! messages without an `id` are notifications: they are sent as they are, and are not reported. The
+//! latencies of the responses are summarized in a [`Report`].
+
+use enso_prelude::*;
+
+use crate::histogram::Histogram;
+use crate::histogram::Summary;
+use crate::EXPECT_BINARY_RESPONSE;
+use crate::EXPECT_TEXT_RESPONSE;
+
+use futures::Sink;
+use futures::SinkExt;
+use futures::Stream;
+use futures::StreamExt;
+use regex::RegexSet;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::sync::Barrier;
+use tokio::time::Instant;
+use url::Url;
+use websocket_lite::ClientBuilder;
+use websocket_lite::Message;
+use websocket_lite::Opcode;
+use websocket_lite::Result;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The method reported for the requests without the `method` field.
+const UNKNOWN_METHOD: &str = "<unknown>";
+
+/// The row label of the statistics of all requests.
+const TOTAL: &str = "total";
+
+
+
+// ==============
+// === Config ===
+// ==============
+
+/// The way of issuing the requests.
+#[derive(Clone, Copy, Debug)]
+pub enum Load {
+    /// Keep the given number of requests in flight on every connection.
+    Concurrency(usize),
+    /// Issue the given number of requests per second, distributed over all connections.
+    Rate(f64),
+}
+
+/// The parameters of the load-generation run.
+#[derive(Debug)]
+pub struct Config {
+    /// Text websocket to connect to.
+    pub text_socket:            Url,
+    /// Binary socket to connect to.
+    pub binary_socket:          Option<Url>,
+    /// Messages initializing each text socket, prefixed with the expected response kind.
+    pub init_text_socket:       Vec<String>,
+    /// Messages initializing each binary socket.
+    pub init_binary_socket:     Vec<Vec<u8>>,
+    /// Responses not awaited by the init sequence.
+    pub ignored_text_responses: RegexSet,
+    /// The requests to send, in order. Each of them must be a JSON object.
+    pub input:                  Vec<Value>,
+    /// Number of the opened connections.
+    pub connections:            usize,
+    /// The way of issuing the requests.
+    pub load:                   Load,
+    /// Number of the first requests excluded from the report.
+    pub warmup_iterations:      usize,
+    /// Number of the reported requests.
+    pub benchmark_iterations:   usize,
+    /// Time to wait before sending the requests, after all connections are initialized.
+    pub wait_after_init:        Duration,
+    /// Time after which a request without a response is reported as timed out.
+    pub response_timeout:       Duration,
+}
+
+impl Config {
+    /// The method and the serialized text of the message with the given sequence number, and
+    /// whether it is a request. Only the ids of the requests are rewritten, as adding an id to a
+    /// notification would turn it into a request.
+    fn request(&self, sequence: usize) -> (String, String, bool) {
+        let mut request = self.input[sequence % self.input.len()].clone();
+        let method = request.get("method").and_then(Value::as_str).unwrap_or(UNKNOWN_METHOD);
+        let method = method.to_owned();
+        let is_request = request.get("id").is_some();
+        if is_request {
+            request["id"] = sequence.into();
+        }
+        (method, request.to_string(), is_request)
+    }
+}
+
+
+
+// ================
+// === Schedule ===
+// ================
+
+/// Hands out the sequence numbers of the requests to the connections.
+#[derive(Debug)]
+struct Schedule {
+    next:  AtomicUsize,
+    total: usize,
+}
+
+impl Schedule {
+    fn new(total: usize) -> Self {
+        Self { next: default(), total }
+    }
+
+    /// Get the sequence number of the next request to issue, if any.
+    fn claim(&self) -> Option<usize> {
+        let sequence = self.next.fetch_add(1, Ordering::Relaxed);
+        (sequence < self.total).then_some(sequence)
+    }
+}
+
+
+
+// ==============
+// === Sample ===
+// ==============
+
+#[derive(Clone, Copy, Debug)]
+enum Outcome {
+    Response {
+        received: Instant,
+        is_error: bool,
+    },
+    /// No response was received in time, or the connection was closed before the response.
+    Timeout,
+}
+
+/// The outcome of a single request.
+#[derive(Clone, Debug)]
+struct Sample {
+    method:    String,
+    is_warmup: bool,
+    sent:      Instant,
+    outcome:   Outcome,
+}
+
+/// A request awaiting the response.
+#[derive(Clone, Debug)]
+struct InFlight {
+    method:    String,
+    is_warmup: bool,
+    sent:      Instant,
+}
+
+impl InFlight {
+    fn into_sample(self, outcome: Outcome) -> Sample {
+        let InFlight { method, is_warmup, sent } = self;
+        Sample { method, is_warmup, sent, outcome }
+    }
+}
+
+
+
+// ==================
+// === Connection ===
+// ==================
+
+/// The `id` and the error flag of the JSON-RPC response. Returns `None` for other messages.
+fn parse_response(text: &str) -> Option<(Value, bool)> {
+    let mut message: Value = serde_json::from_str(text).ok()?;
+    let is_error = message.get("error").is_some();
+    let is_response = is_error || message.get("result").is_some();
+    let id = message.get_mut("id").map(Value::take);
+    id.filter(|_| is_response).map(|id| (id, is_error))
+}
+
+async fn next_message<S>(stream: &mut S) -> Result<Message>
+where S: Stream<Item = Result<Message>> + Unpin {
+    match stream.next().await {
+        Some(message) => message,
+        None => Err("The connection has been closed.".into()),
+    }
+}
+
+/// Wait for the response to the init request. The request's `id` is used for matching the
+/// response if present, otherwise the first not ignored text message is the response.
+async fn wait_for_text_response<S>(
+    stream: &mut S,
+    request: &str,
+    ignored: &RegexSet,
+) -> Result<()>
+where
+    S: Stream<Item = Result<Message>> + Unpin,
+{
+    let request: Option<Value> = serde_json::from_str(request).ok();
+    let id = request.as_ref().and_then(|request| request.get("id"));
+    loop {
+        let message = next_message(stream).await?;
+        if let Some(text) = message.as_text() {
+            let is_response = match id {
+                Some(id) =>
+                    parse_response(text).map_or(false, |(response_id, _)| &response_id == id),
+                None => !ignored.is_match(text),
+            };
+            if is_response {
+                return Ok(());
+            }
+        }
+    }
+}
+
+async fn wait_for_binary_response<S>(stream: &mut S) -> Result<()>
+where S: Stream<Item = Result<Message>> + Unpin {
+    loop {
+        if let Opcode::Binary = next_message(stream).await?.opcode() {
+            return Ok(());
+        }
+    }
+}
+
+/// Open the connections and run the init sequence on them. The binary messages received after
+/// the init sequence are discarded.
+async fn connect(
+    config: &Config,
+) -> Result<(impl Sink<Message, Error = websocket_lite::Error>, impl Stream<Item = Result<Message>>)>
+{
+    let text_client = ClientBuilder::from_url(config.text_socket.clone()).async_connect().await?;
+    let (mut text_sink, mut text_stream) = text_client.split::<Message>();
+    let mut binary = None;
+    if let Some(binary_socket) = &config.binary_socket {
+        let binary_client = ClientBuilder::from_url(binary_socket.clone()).async_connect().await?;
+        let (mut sink, mut stream) = binary_client.split::<Message>();
+        for bytes in &config.init_binary_socket {
+            sink.send(Message::binary(bytes.clone())).await?;
+            wait_for_binary_response(&mut stream).await?;
+        }
+        binary = Some((sink, stream));
+    }
+    for line in &config.init_text_socket {
+        let (prefix, message_text) = line.split_at(2);
+        text_sink.send(Message::text(message_text)).await?;
+        if prefix == EXPECT_TEXT_RESPONSE {
+            let ignored = &config.ignored_text_responses;
+            wait_for_text_response(&mut text_stream, message_text, ignored).await?;
+        } else if prefix == EXPECT_BINARY_RESPONSE {
+            if let Some((_, stream)) = &mut binary {
+                wait_for_binary_response(stream).await?;
+            }
+        }
+    }
+    if let Some((sink, mut stream)) = binary {
+        tokio::spawn(async move {
+            let _sink = sink;
+            while let Some(Ok(_)) = stream.next().await {}
+        });
+    }
+    Ok((text_sink, text_stream))
+}
+
+/// Send the next request, if there is any left, preceded by the notifications scheduled before it.
+/// Returns whether a request was sent.
+async fn issue<S>(
+    sink: &mut S,
+    config: &Config,
+    schedule: &Schedule,
+    in_flight: &mut HashMap<u64, InFlight>,
+) -> Result<bool>
+where
+    S: Sink<Message, Error = websocket_lite::Error> + Unpin,
+{
+    while let Some(sequence) = schedule.claim() {
+        let (method, text, is_request) = config.request(sequence);
+        if is_request {
+            let is_warmup = sequence < config.warmup_iterations;
+            in_flight.insert(sequence as u64, InFlight { method, is_warmup, sent: Instant::now() });
+        }
+        sink.send(Message::text(text)).await?;
+        if is_request {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Open the connection, and issue the requests until there are no more left. In the rate mode,
+/// a request is issued on every received tick.
+async fn run_connection(
+    config: Arc<Config>,
+    schedule: Arc<Schedule>,
+    ready: Arc<Barrier>,
+    mut ticks: Option<mpsc::UnboundedReceiver<()>>,
+    samples: mpsc::UnboundedSender<Sample>,
+) -> Result<()> {
+    let connection = connect(&config).await;
+    ready.wait().await;
+    let (sink, stream) = connection?;
+    let (mut sink, mut stream) = (Box::pin(sink), Box::pin(stream));
+    tokio::time::sleep(config.wait_after_init).await;
+
+    let mut in_flight = HashMap::new();
+    let mut may_issue = true;
+    let is_closed_loop = matches!(config.load, Load::Concurrency(_));
+    if let Load::Concurrency(concurrency) = config.load {
+        for _ in 0..concurrency {
+            may_issue = issue(&mut sink, &config, &schedule, &mut in_flight).await?;
+            if !may_issue {
+                break;
+            }
+        }
+    }
+    let record = |request: InFlight, outcome| samples.send(request.into_sample(outcome)).ok();
+    while may_issue || !in_flight.is_empty() {
+        let oldest = in_flight.values().map(|request: &InFlight| request.sent).min();
+        let deadline = oldest.map(|sent| sent + config.response_timeout);
+        let mut completed = 0;
+        tokio::select! {
+            message = stream.next() => {
+                let message = match message {
+                    Some(message) => message?,
+                    None => break,
+                };
+                let response = message.as_text().and_then(parse_response);
+                let id = response.as_ref().and_then(|(id, _)| id.as_u64());
+                if let (Some(id), Some((_, is_error))) = (id, response) {
+                    if let Some(request) = in_flight.remove(&id) {
+                        record(request, Outcome::Response { received: Instant::now(), is_error });
+                        completed += 1;
+                    }
+                }
+            }
+            tick = async { ticks.as_mut()?.recv().await }, if !is_closed_loop && may_issue => {
+                match tick {
+                    Some(()) => { issue(&mut sink, &config, &schedule, &mut in_flight).await?; }
+                    None => may_issue = false,
+                }
+            }
+            _ = tokio::time::sleep_until(deadline.unwrap_or_else(Instant::now)), if deadline.is_some() => {
+                let now = Instant::now();
+                let expired = in_flight
+                    .iter()
+                    .filter(|(_, request)| request.sent + config.response_timeout <= now)
+                    .map(|(id, _)| *id)
+                    .collect_vec();
+                for id in expired {
+                    if let Some(request) = in_flight.remove(&id) {
+                        record(request, Outcome::Timeout);
+                        completed += 1;
+                    }
+                }
+            }
+        }
+        if is_closed_loop {
+            for _ in 0..completed {
+                may_issue =
+                    may_issue && issue(&mut sink, &config, &schedule, &mut in_flight).await?;
+            }
+        }
+    }
+    for (_, request) in in_flight.drain() {
+        record(request, Outcome::Timeout);
+    }
+    Ok(())
+}
+
+/// Send the ticks to the connections in turns, at the given rate. The closed connections are
+/// skipped.
+async fn tick(rate: f64, total: usize, connections: Vec<mpsc::UnboundedSender<()>>) {
+    let mut interval = tokio::time::interval(Duration::from_secs_f64(1.0 / rate));
+    let mut targets = connections.iter().cycle();
+    for _ in 0..total {
+        interval.tick().await;
+        let mut open_targets = targets.by_ref().take(connections.len());
+        match open_targets.find(|target| !target.is_closed()) {
+            Some(target) => target.send(()).ok(),
+            None => break,
+        };
+    }
+}
+
+
+
+// ===========
+// === Run ===
+// ===========
+
+/// Run the load generation and summarize the results.
+pub async fn run(config: Config) -> Result<Report> {
+    let config = Arc::new(config);
+    let total = config.warmup_iterations + config.benchmark_iterations;
+    let schedule = Arc::new(Schedule::new(total));
+    let ready = Arc::new(Barrier::new(config.connections + 1));
+    let (sample_sender, mut sample_receiver) = mpsc::unbounded_channel();
+    let mut tick_senders = Vec::new();
+    let mut tasks = Vec::new();
+    for _ in 0..config.connections {
+        let ticks = match config.load {
+            Load::Rate(_) => {
+                let (sender, receiver) = mpsc::unbounded_channel();
+                tick_senders.push(sender);
+                Some(receiver)
+            }
+            Load::Concurrency(_) => None,
+        };
+        let connection = run_connection(
+            config.clone(),
+            schedule.clone(),
+            ready.clone(),
+            ticks,
+            sample_sender.clone(),
+        );
+        tasks.push(tokio::spawn(connection));
+    }
+    drop(sample_sender);
+
+    ready.wait().await;
+    if let Load::Rate(rate) = config.load {
+        tokio::time::sleep(config.wait_after_init).await;
+        tick(rate, total, tick_senders).await;
+    }
+    let mut failed_connections = 0;
+    for task in tasks {
+        if let Err(error) = task.await? {
+            eprintln!("Connection failed: {error}");
+            failed_connections += 1;
+        }
+    }
+    let mut samples = Vec::new();
+    while let Some(sample) = sample_receiver.recv().await {
+        samples.push(sample);
+    }
+    Ok(Report::new(config.connections, failed_connections, &samples))
+}
+
+
+
+// ==============
+// === Report ===
+// ==============
+
+/// The statistics of the requests of a single method, or of all requests.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Stats {
+    /// Number of the issued requests.
+    pub requests: usize,
+    /// Number of the error responses.
+    pub errors:   usize,
+    /// Number of the requests without a response.
+    pub timeouts: usize,
+    /// The latencies of all responses, including the errors.
+    pub latency:  Option<Summary>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct StatsBuilder {
+    histogram: Histogram,
+    errors:    usize,
+    timeouts:  usize,
+}
+
+impl StatsBuilder {
+    fn add(&mut self, sample: &Sample) {
+        match sample.outcome {
+            Outcome::Response { received, is_error } => {
+                self.histogram.record(received - sample.sent);
+                self.errors += is_error as usize;
+            }
+            Outcome::Timeout => self.timeouts += 1,
+        }
+    }
+
+    fn build(&self) -> Stats {
+        let requests = self.histogram.count() + self.timeouts;
+        let latency = self.histogram.summary();
+        Stats { requests, errors: self.errors, timeouts: self.timeouts, latency }
+    }
+}
+
+/// The summary of the benchmarked requests, excluding the warmup ones.
+#[derive(Clone, Debug, Serialize)]
+pub struct Report {
+    /// Number of the opened connections.
+    pub connections:        usize,
+    /// Number of the connections which failed to connect, or were closed with an error.
+    pub failed_connections: usize,
+    /// Time from sending the first request to receiving the last response.
+    pub duration_ms:        f64,
+    /// Number of the responses per second.
+    pub throughput:         f64,
+    /// The statistics of all requests.
+    pub total:              Stats,
+    /// The statistics of the requests by their methods.
+    pub methods:            BTreeMap<String, Stats>,
+}
+
+impl Report {
+    fn new(connections: usize, failed_connections: usize, samples: &[Sample]) -> Self {
+        let samples = samples.iter().filter(|sample| !sample.is_warmup).collect_vec();
+        let mut total = StatsBuilder::default();
+        let mut methods = BTreeMap::<_, StatsBuilder>::new();
+        for sample in &samples {
+            total.add(sample);
+            methods.entry(sample.method.clone()).or_default().add(sample);
+        }
+        let start = samples.iter().map(|sample| sample.sent).min();
+        let end = samples
+            .iter()
+            .filter_map(|sample| match sample.outcome {
+                Outcome::Response { received, .. } => Some(received),
+                Outcome::Timeout => None,
+            })
+            .max();
+        let duration = match (start, end) {
+            (Some(start), Some(end)) => end - start,
+            _ => Duration::ZERO,
+        };
+        let responses = total.histogram.count() as f64;
+        let seconds = duration.as_secs_f64();
+        let throughput = if seconds > 0.0 { responses / seconds } else { 0.0 };
+        let duration_ms = seconds * 1000.0;
+        let total = total.build();
+        let methods = methods.into_iter().map(|(method, stats)| (method, stats.build())).collect();
+        Self { connections, failed_connections, duration_ms, throughput, total, methods }
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let connections = self.connections;
+        let failed = self.failed_connections;
+        writeln!(f, "connections: {connections} ({failed} failed)")?;
+        let seconds = self.duration_ms / 1000.0;
+        writeln!(f, "duration: {seconds:.3} s, throughput: {:.1} req/s", self.throughput)?;
+        writeln!(f)?;
+        let header = ["requests", "errors", "timeouts", "p50", "p90", "p99", "max"];
+        let [requests, errors, timeouts, p50, p90, p99, max] = header;
+        writeln!(
+            f,
+            "{:<40} {requests:>8} {errors:>8} {timeouts:>8} {p50:>10} {p90:>10} {p99:>10} {max:>10}",
+            "method"
+        )?;
+        for (method, stats) in &self.methods {
+            write_stats_row(f, method, stats)?;
+        }
+        write_stats_row(f, TOTAL, &self.total)
+    }
+}
+
+fn write_stats_row(f: &mut fmt::Formatter, label: &str, stats: &Stats) -> fmt::Result {
+    let Stats { requests, errors, timeouts, latency } = stats;
+    let format_latency = |field: fn(&Summary) -> Duration| match latency {
+        Some(summary) => format!("{:.2} ms", field(summary).as_secs_f64() * 1000.0),
+        None => "-".to_owned(),
+    };
+    let p50 = format_latency(|summary| summary.p50);
+    let p90 = format_latency(|summary| summary.p90);
+    let p99 = format_latency(|summary| summary.p99);
+    let max = format_latency(|summary| summary.max);
+    writeln!(
+        f,
+        "{label:<40} {requests:>8} {errors:>8} {timeouts:>8} {p50:>10} {p90:>10} {p99:>10} {max:>10}"
+    )
+}
@@ -17,9 +17,13 @@
 
 
 mod format;
+mod histogram;
+mod load;
+mod scenario;
 
 use enso_prelude::*;
 
+use std::path::Path;
 use std::path::PathBuf;
 use std::time::Duration;
 
@@ -103,6 +107,38 @@ struct Args {
     /// Time in milliseconds to wait before sending the next request from the `input` file.
     #[clap(long, value_name = "MILLISECONDS", default_value = "0")]
     wait_after_response: u64,
+
+    /// Generate load over multiple connections, and report the latencies of the responses instead
+    /// of logging the messages.
+    #[clap(long, requires = "input")]
+    load: bool,
+
+    /// Number of connections opened in the load mode.
+    #[clap(long, default_value = "1", requires = "load")]
+    connections: usize,
+
+    /// Number of requests kept in flight on every connection in the load mode.
+    #[clap(long, default_value = "1", requires = "load")]
+    concurrency: usize,
+
+    /// Total number of requests per second sent in the load mode, regardless of the responses.
+    /// Overrides `concurrency`.
+    #[clap(long, value_name = "REQUESTS", requires = "load")]
+    rate: Option<f64>,
+
+    /// Time in milliseconds after which a request without a response is reported as timed out in
+    /// the load mode.
+    #[clap(long, value_name = "MILLISECONDS", default_value = "10000", requires = "load")]
+    response_timeout: u64,
+
+    /// Path to a YAML or JSON scenario to run instead of sending the input commands.
+    #[clap(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    #[clap(conflicts_with_all = &["input", "load"])]
+    scenario: Option<PathBuf>,
+
+    /// Path to write the load mode report in the JSON format to.
+    #[clap(long, value_name = "FILE", value_hint = ValueHint::FilePath, requires = "load")]
+    report_json: Option<PathBuf>,
 }
 
 
@@ -131,6 +167,94 @@ async fn read_lines(path_buf: PathBuf) -> std::io::Result<Vec<String>> {
     tokio_stream::StreamExt::collect(lines_stream).await
 }
 
+/// Read the set of the ignored responses.
+async fn read_ignored_text_responses(path_buf: Option<PathBuf>) -> Result<regex::RegexSet> {
+    match path_buf {
+        Some(path_buf) => Ok(regex::RegexSet::new(read_lines(path_buf).await?)?),
+        None => Ok(regex::RegexSet::empty()),
+    }
+}
+
+
+
+// =====================
+// === Scenario Mode ===
+// =====================
+
+/// Run the scenario on a new connection.
+async fn run_scenario(text_socket: Url, path: &Path) -> Result<()> {
+    let scenario = scenario::Scenario::read(path).await?;
+    let client = ClientBuilder::from_url(text_socket).async_connect().await?;
+    let (sink, stream) = client.split::<Message>();
+    scenario::Runner::new(sink, stream).run(&scenario).await
+}
+
+
+
+// =================
+// === Load Mode ===
+// =================
+
+/// Run the load generation and report the results.
+async fn run_load(args: Args) -> Result<()> {
+    if args.input_expects_binary_responses {
+        let message = "The load mode does not support the binary responses to the input commands.";
+        return Err(message.into());
+    }
+    let load = match args.rate {
+        Some(rate) if rate > 0.0 => load::Load::Rate(rate),
+        Some(_) => return Err("The request rate must be positive.".into()),
+        None if args.concurrency > 0 => load::Load::Concurrency(args.concurrency),
+        None => return Err("The concurrency must be positive.".into()),
+    };
+    let input_lines = match args.input {
+        Some(path_buf) => read_lines(path_buf).await?,
+        None => default(),
+    };
+    let mut input = Vec::new();
+    for line in input_lines.iter().filter(|line| !line.trim().is_empty()) {
+        let request: serde_json::Value = serde_json::from_str(line)?;
+        if !request.is_object() {
+            return Err(format!("The input command is not a JSON object: {line}").into());
+        }
+        input.push(request);
+    }
+    if input.is_empty() {
+        return Err("The input file contains no commands.".into());
+    }
+    let init_text_socket = match args.init_text_socket {
+        Some(path_buf) => read_lines(path_buf).await?,
+        None => default(),
+    };
+    let mut init_binary_socket = Vec::new();
+    if let Some(path_buf) = args.init_binary_socket {
+        for line in read_lines(path_buf).await? {
+            init_binary_socket.push(base64::decode(line)?);
+        }
+    }
+    let config = load::Config {
+        text_socket: args.text_socket,
+        binary_socket: args.binary_socket,
+        init_text_socket,
+        init_binary_socket,
+        ignored_text_responses: read_ignored_text_responses(args.ignore_text_socket_responses)
+            .await?,
+        input,
+        connections: args.connections,
+        load,
+        warmup_iterations: args.warmup_iterations,
+        benchmark_iterations: args.benchmark_iterations,
+        wait_after_init: Duration::from_millis(args.wait_after_init),
+        response_timeout: Duration::from_millis(args.response_timeout),
+    };
+    let report = load::run(config).await?;
+    println!("{report}");
+    if let Some(path_buf) = args.report_json {
+        tokio::fs::write(path_buf, serde_json::to_string_pretty(&report)?).await?;
+    }
+    Ok(())
+}
+
 
 
 // ============
@@ -140,6 +264,12 @@ async fn read_lines(path_buf: PathBuf) -> std::io::Result<Vec<String>> {
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
+    if args.load {
+        return run_load(args).await;
+    }
+    if let Some(path_buf) = args.scenario {
+        return run_scenario(args.text_socket, &path_buf).await;
+    }
 
     // text socket connection
     let text_socket_client = ClientBuilder::from_url(args.text_socket).async_connect().await?;
@@ -155,11 +285,8 @@ async fn main() -> Result<()> {
     };
 
     // ignored responses
-    let mut ignored_text_responses = regex::RegexSet::empty();
-    if let Some(path_buf) = args.ignore_text_socket_responses {
-        let ignored_lines = read_lines(path_buf).await?;
-        ignored_text_responses = regex::RegexSet::new(ignored_lines)?;
-    }
+    let ignored_text_responses =
+        read_ignored_text_responses(args.ignore_text_socket_responses).await?;
 
     // synchronization channels between requests and responses
     let (text_tx, mut text_rx) = mpsc::channel::<SyncMessage>(1);
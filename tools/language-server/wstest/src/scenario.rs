@@ -0,0 +1,414 @@
+//! Scenarios: scripted sequences of named steps, sending the messages and checking the received
+//! ones.
+//!
+//! A scenario is read from a YAML file (or a JSON one, as YAML is its superset). Every step may:
+//! - `send` a JSON message on the text socket;
+//! - `expect` a message matching the JSON pattern. If omitted, a step sending a request with an
+//!   `id` waits for the response to that request;
+//! - `capture` the values of the expected message, addressed by JSON pointers, into variables;
+//! - `assert` that the values of the expected message, addressed by JSON pointers, match the
+//!   patterns.
+//!
+//! The strings of the sent messages and the patterns may refer to the variables as `${name}`. A
+//! string consisting of a single reference is replaced with the variable's value of any type.
+//!
+//! A pattern matches a value if they are equal, except that a pattern object matches any object
+//! with matching values of the pattern's fields, ignoring the other fields.
+
+use enso_prelude::*;
+
+use futures::Sink;
+use futures::SinkExt;
+use futures::Stream;
+use futures::StreamExt;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::time::Duration;
+use tokio::time::Instant;
+use websocket_lite::Message;
+use websocket_lite::Result;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The default time in milliseconds to wait for the expected message.
+const DEFAULT_TIMEOUT_MS: u64 = 5000;
+
+fn default_timeout_ms() -> u64 {
+    DEFAULT_TIMEOUT_MS
+}
+
+
+
+// ================
+// === Scenario ===
+// ================
+
+/// A scripted sequence of steps.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    /// The name printed before running the scenario.
+    #[serde(default)]
+    pub name:       Option<String>,
+    /// Time in milliseconds to wait for the expected message, unless overridden by the step.
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    /// The initial values of the variables.
+    #[serde(default)]
+    pub variables:  HashMap<String, Value>,
+    /// The steps, run in order.
+    pub steps:      Vec<Step>,
+}
+
+/// A single step of the scenario.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Step {
+    /// The name printed when running the step.
+    pub name:       String,
+    /// The message to send.
+    #[serde(default)]
+    pub send:       Option<Value>,
+    /// The pattern of the message to wait for.
+    #[serde(default)]
+    pub expect:     Option<Value>,
+    /// The variables to set, mapped to the JSON pointers into the expected message.
+    #[serde(default)]
+    pub capture:    BTreeMap<String, String>,
+    /// The JSON pointers into the expected message, mapped to the patterns of their values.
+    #[serde(default, rename = "assert")]
+    pub assertions: BTreeMap<String, Value>,
+    /// Time in milliseconds to wait for the expected message.
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+impl Scenario {
+    /// Read the scenario from the YAML or JSON file.
+    pub async fn read(path: &Path) -> Result<Self> {
+        let text = tokio::fs::read_to_string(path).await?;
+        let scenario: Self = serde_yaml::from_str(&text)?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    fn validate(&self) -> Result<()> {
+        for step in &self.steps {
+            let checks_message = !step.capture.is_empty() || !step.assertions.is_empty();
+            if checks_message && step.expected_pattern().is_none() {
+                let name = &step.name;
+                return Err(format!("Step \"{name}\" checks a message it does not expect.").into());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Step {
+    /// The pattern of the message to wait for, defaulting to the response to the sent request.
+    fn expected_pattern(&self) -> Option<Value> {
+        let response_id = || self.send.as_ref()?.get("id").cloned();
+        let response_pattern = || response_id().map(|id| serde_json::json!({ "id": id }));
+        self.expect.clone().or_else(response_pattern)
+    }
+}
+
+
+
+// ===============
+// === Failure ===
+// ===============
+
+/// The reason of the step's failure.
+#[derive(Clone, Debug)]
+pub enum Failure {
+    /// No message matching the pattern was received in time.
+    Timeout { pattern: Value },
+    /// The connection was closed while waiting for the message matching the pattern.
+    ConnectionClosed { pattern: Value },
+    /// The value pointed by the assertion did not match the pattern.
+    AssertionFailed { pointer: String, expected: Value, actual: Option<Value> },
+    /// The value pointed by the capture is missing in the message.
+    MissingCapture { variable: String, pointer: String },
+    /// The variable was referenced before being set.
+    UndefinedVariable { variable: String },
+}
+
+impl Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Failure::Timeout { pattern } =>
+                write!(f, "No message matching {pattern} was received in time."),
+            Failure::ConnectionClosed { pattern } =>
+                write!(f, "The connection was closed while waiting for {pattern}."),
+            Failure::AssertionFailed { pointer, expected, actual: Some(actual) } =>
+                write!(f, "The value at {pointer} is {actual}, which does not match {expected}."),
+            Failure::AssertionFailed { pointer, expected, actual: None } =>
+                write!(f, "There is no value at {pointer}, expected {expected}."),
+            Failure::MissingCapture { variable, pointer } =>
+                write!(f, "Cannot capture {variable}: there is no value at {pointer}."),
+            Failure::UndefinedVariable { variable } =>
+                write!(f, "The variable {variable} is not defined."),
+        }
+    }
+}
+
+impl std::error::Error for Failure {}
+
+
+
+// ================
+// === Patterns ===
+// ================
+
+/// Check whether the value matches the pattern.
+pub fn matches(pattern: &Value, value: &Value) -> bool {
+    match (pattern, value) {
+        (Value::Object(pattern), Value::Object(value)) =>
+            pattern.iter().all(|(key, field)| value.get(key).map_or(false, |v| matches(field, v))),
+        (Value::Array(pattern), Value::Array(value)) =>
+            pattern.len() == value.len() && pattern.iter().zip(value).all(|(p, v)| matches(p, v)),
+        _ => pattern == value,
+    }
+}
+
+/// Replace the references to the variables in all the strings of the value.
+fn substitute(
+    value: &Value,
+    variables: &HashMap<String, Value>,
+) -> std::result::Result<Value, Failure> {
+    let lookup = |variable: &str| {
+        let undefined = || Failure::UndefinedVariable { variable: variable.to_owned() };
+        variables.get(variable).ok_or_else(undefined)
+    };
+    Ok(match value {
+        Value::String(text) => {
+            let whole_reference = text.strip_prefix("${").and_then(|t| t.strip_suffix('}'));
+            match whole_reference.filter(|name| !name.contains('}')) {
+                Some(variable) => lookup(variable)?.clone(),
+                None => Value::String(substitute_in_text(text, lookup)?),
+            }
+        }
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| substitute(item, variables))
+                .collect::<std::result::Result<_, _>>()?,
+        ),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, field)| Ok((key.clone(), substitute(field, variables)?)))
+                .collect::<std::result::Result<_, _>>()?,
+        ),
+        other => other.clone(),
+    })
+}
+
+fn substitute_in_text<'a>(
+    text: &str,
+    lookup: impl Fn(&str) -> std::result::Result<&'a Value, Failure>,
+) -> std::result::Result<String, Failure> {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        let Some(length) = rest[start..].find('}') else { break };
+        result.push_str(&rest[..start]);
+        match lookup(&rest[start + 2..start + length])? {
+            Value::String(value) => result.push_str(value),
+            value => result.push_str(&value.to_string()),
+        }
+        rest = &rest[start + length + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+
+
+// ==============
+// === Runner ===
+// ==============
+
+/// Runs the scenario's steps on a single text connection.
+#[derive(Debug)]
+pub struct Runner<Si, St> {
+    sink:      Si,
+    stream:    St,
+    variables: HashMap<String, Value>,
+    /// The received messages not matched by any step yet.
+    pending:   VecDeque<Value>,
+}
+
+impl<Si, St> Runner<Si, St>
+where
+    Si: Sink<Message, Error = websocket_lite::Error> + Unpin,
+    St: Stream<Item = Result<Message>> + Unpin,
+{
+    /// Constructor.
+    pub fn new(sink: Si, stream: St) -> Self {
+        Self { sink, stream, variables: default(), pending: default() }
+    }
+
+    /// Run all steps of the scenario, stopping at the first failed one.
+    pub async fn run(&mut self, scenario: &Scenario) -> Result<()> {
+        if let Some(name) = &scenario.name {
+            println!("scenario: {name}");
+        }
+        self.variables.extend(scenario.variables.clone());
+        for step in &scenario.steps {
+            let start = Instant::now();
+            let timeout = Duration::from_millis(step.timeout_ms.unwrap_or(scenario.timeout_ms));
+            let result = self.run_step(step, timeout).await;
+            let elapsed_ms = start.elapsed().as_secs_f64() * 1000.0;
+            match &result {
+                Ok(()) => println!("ok     {} ({elapsed_ms:.2} ms)", step.name),
+                Err(error) => println!("FAILED {} ({elapsed_ms:.2} ms): {error}", step.name),
+            }
+            result?;
+        }
+        Ok(())
+    }
+
+    async fn run_step(&mut self, step: &Step, timeout: Duration) -> Result<()> {
+        let deadline = Instant::now() + timeout;
+        if let Some(message) = &step.send {
+            let message = substitute(message, &self.variables)?;
+            self.sink.send(Message::text(message.to_string())).await?;
+        }
+        if let Some(pattern) = step.expected_pattern() {
+            let pattern = substitute(&pattern, &self.variables)?;
+            let message = self.receive(pattern, deadline).await?;
+            for (pointer, expected) in &step.assertions {
+                let expected = substitute(expected, &self.variables)?;
+                let actual = message.pointer(pointer);
+                if !actual.map_or(false, |actual| matches(&expected, actual)) {
+                    let pointer = pointer.clone();
+                    let actual = actual.cloned();
+                    return Err(Failure::AssertionFailed { pointer, expected, actual }.into());
+                }
+            }
+            for (variable, pointer) in &step.capture {
+                let missing = || {
+                    let variable = variable.clone();
+                    Failure::MissingCapture { variable, pointer: pointer.clone() }
+                };
+                let value = message.pointer(pointer).ok_or_else(missing)?;
+                self.variables.insert(variable.clone(), value.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the first received message matching the pattern, waiting for it until the deadline.
+    async fn receive(&mut self, pattern: Value, deadline: Instant) -> Result<Value> {
+        if let Some(index) = self.pending.iter().position(|message| matches(&pattern, message)) {
+            return Ok(self.pending.remove(index).expect("The index is in bounds."));
+        }
+        loop {
+            let message = match tokio::time::timeout_at(deadline, self.stream.next()).await {
+                Ok(Some(message)) => message?,
+                Ok(None) => return Err(Failure::ConnectionClosed { pattern }.into()),
+                Err(_) => return Err(Failure::Timeout { pattern }.into()),
+            };
+            if let Some(text) = message.as_text() {
+                let message: Value = serde_json::from_str(text)?;
+                if matches(&pattern, &message) {
+                    return Ok(message);
+                }
+                self.pending.push_back(message);
+            }
+        }
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn matching_patterns() {
+        let value = json!({"id": 1, "result": {"items": [1, {"a": 2, "b": 3}]}});
+        assert!(matches(&json!({"id": 1}), &value));
+        assert!(matches(&json!({"result": {"items": [1, {"b": 3}]}}), &value));
+        assert!(!matches(&json!({"result": {"items": [1]}}), &value));
+        assert!(!matches(&json!({"id": 2}), &value));
+        assert!(!matches(&json!({ "error": null }), &value));
+    }
+
+    #[test]
+    fn substituting_variables() {
+        let variables =
+            HashMap::from_iter([("id".to_owned(), json!(7)), ("name".to_owned(), json!("Main"))]);
+        let value = json!({"id": "${id}", "path": ["src", "${name}.enso"], "tag": "${id}-${id}"});
+        let expected = json!({"id": 7, "path": ["src", "Main.enso"], "tag": "7-7"});
+        assert_eq!(substitute(&value, &variables).unwrap(), expected);
+        let error = substitute(&json!("${missing}"), &variables).unwrap_err();
+        assert!(matches!(error, Failure::UndefinedVariable { variable } if variable == "missing"));
+    }
+
+    const SCENARIO: &str = r#"
+steps:
+  - name: Create context
+    send: { jsonrpc: "2.0", method: executionContext/create, id: 1, params: {} }
+    capture: { context: /result/contextId }
+  - name: Push frame
+    send: { jsonrpc: "2.0", method: executionContext/push, id: 2, params: { contextId: "${context}" } }
+    assert: { /result: null }
+  - name: Execution complete
+    expect: { method: executionContext/executionComplete }
+    assert: { /params/contextId: "${context}" }
+"#;
+
+    async fn run_scenario(responses: &[Value]) -> (Result<()>, Vec<Value>) {
+        let scenario: Scenario = serde_yaml::from_str(SCENARIO).unwrap();
+        scenario.validate().unwrap();
+        let (sink, sent) = futures::channel::mpsc::unbounded::<Message>();
+        let sink = sink.sink_map_err(|error| -> websocket_lite::Error { error.into() });
+        let messages = responses.iter().map(|response| Ok(Message::text(response.to_string())));
+        let stream = futures::stream::iter(messages.collect_vec());
+        let result = Runner::new(sink, stream).run(&scenario).await;
+        let sent = sent.map(|message| serde_json::from_str(message.as_text().unwrap()).unwrap());
+        (result, sent.collect().await)
+    }
+
+    #[tokio::test]
+    async fn running_scenario() {
+        let complete =
+            json!({"method": "executionContext/executionComplete", "params": {"contextId": "c"}});
+        let responses = [
+            json!({"id": 1, "result": {"contextId": "c"}}),
+            // A notification received before the step expecting it.
+            complete,
+            json!({"id": 2, "result": null}),
+        ];
+        let (result, sent) = run_scenario(&responses).await;
+        result.unwrap();
+        assert_eq!(sent[1]["params"]["contextId"], json!("c"));
+    }
+
+    #[tokio::test]
+    async fn failing_assertion() {
+        let responses = [
+            json!({"id": 1, "result": {"contextId": "c"}}),
+            json!({"id": 2, "error": {"code": 2002, "message": "Stack item not found."}}),
+        ];
+        let (result, _) = run_scenario(&responses).await;
+        let error = result.unwrap_err().to_string();
+        assert_eq!(error, "There is no value at /result, expected null.");
+    }
+}